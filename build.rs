@@ -115,7 +115,11 @@ fn main() {
     for entry in &config.components.entry {
         generated.push_str(&format!("        {}::get_metadata(),\n", entry.struct_name));
     }
-    generated.push_str("    ])))\n");
+    generated.push_str("    ]).with_types(vec![\n");
+    for entry in &config.components.entry {
+        generated.push_str(&format!("        {}::port_types(),\n", entry.struct_name));
+    }
+    generated.push_str("    ].concat())))\n");
     generated.push_str("}\n\n");
 
     // component factory function
@@ -143,17 +147,12 @@ fn main() {
     generated.push_str("    }\n");
     generated.push_str("}\n\n");
 
-    // component log filters function
-    generated.push_str(
-        "pub fn register_component_log_filters(_logger: &mut simplelog::ConfigBuilder) {\n",
-    );
+    // component log filters, log targets starting with one of them are not logged
+    generated.push_str("pub const COMPONENT_LOG_IGNORE: &[&str] = &[\n");
     for filter in &log_filters {
-        generated.push_str(&format!(
-            "    _logger.add_filter_ignore_str(\"{}\");\n",
-            escape_rust_string(filter)
-        ));
+        generated.push_str(&format!("    \"{}\",\n", escape_rust_string(filter)));
     }
-    generated.push_str("}\n\n");
+    generated.push_str("];\n\n");
 
     // component budget class function
     generated.push_str("pub fn get_component_budget_class(component_name: &str) -> flowd_component_api::BudgetClass {\n");
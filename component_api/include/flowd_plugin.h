@@ -0,0 +1,50 @@
+/*
+ * flowd plugin components, C ABI version 1 (ADR-023)
+ *
+ * A plugin is a shared library in the plugin directory (flowd.plugins below the graph directory),
+ * loaded at startup and added to the component library next to the compiled-in components.
+ *
+ * Packets cross the boundary as buffers in the canonical packet encoding of FbpMessage
+ * (MessagePack array [version, message], see component_api/src/wire.rs), for example the text
+ * packet "hi" is 0x92 0x01 0x81 0xa4 'T' 'e' 'x' 't' 0xa2 'h' 'i'.
+ *
+ * No memory changes owners: buffers passed into the plugin are valid for the duration of the call
+ * only, and flowd copies every buffer given to emit before emit returns.
+ * process() and drop() are called from varying threads, but never concurrently for one handle.
+ */
+
+#ifndef FLOWD_PLUGIN_H
+#define FLOWD_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define FLOWD_PLUGIN_ABI_VERSION 1
+
+/* send an encoded packet out of the given outport */
+typedef void (*flowd_emit_fn)(void *emit_ctx, const char *port, const uint8_t *data, size_t len);
+
+/* must return FLOWD_PLUGIN_ABI_VERSION */
+uint32_t flowd_plugin_abi_version(void);
+
+/*
+ * Component metadata as NUL-terminated JSON, static for the lifetime of the library:
+ *   {"name": "Upper", "description": "...", "icon": "font",
+ *    "inports": [{"name": "IN", "type": "string", "description": "...", "required": true}],
+ *    "outports": [{"name": "OUT"}]}
+ * Only name is required, ports default to type "any" and required true. At least one inport.
+ * Port names are uppercase, like the ports of built-in components.
+ */
+const char *flowd_plugin_metadata(void);
+
+/* create a component instance, NULL on failure */
+void *flowd_plugin_init(void);
+
+/* process one packet received on the given inport, 0 on success, negative to finish the node */
+int32_t flowd_plugin_process(void *handle, const char *port, const uint8_t *data, size_t len,
+                             flowd_emit_fn emit, void *emit_ctx);
+
+/* release the component instance */
+void flowd_plugin_drop(void *handle);
+
+#endif /* FLOWD_PLUGIN_H */
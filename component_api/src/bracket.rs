@@ -0,0 +1,109 @@
+// Substreams delimited by bracket and group control events
+//
+// BeginBracket/EndBracket and BeginGroup/EndGroup open and close one nesting level each,
+// Discontinuity drops all open levels. Components producing substreams wrap them in a bracket,
+// components transforming single packets forward the control events unchanged.
+
+use log::warn;
+
+use crate::{ControlEvent, FbpMessage};
+
+/// What a message did to the nesting of the stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BracketChange {
+    /// a bracket or group was opened, the new depth
+    Opened(usize),
+    /// a bracket or group was closed, the depth it had
+    Closed(usize),
+    /// the stream was reset to the top level
+    Reset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LevelKind {
+    Bracket,
+    Group,
+}
+
+/// Tracks the bracket and group nesting of a stream as its messages pass by
+#[derive(Debug, Clone, Default)]
+pub struct BracketTracker {
+    // innermost level last
+    open: Vec<(LevelKind, String)>,
+}
+
+impl BracketTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the nesting from a message, None for data and for unmatched closing events
+    pub fn observe(&mut self, msg: &FbpMessage) -> Option<BracketChange> {
+        let (kind, name, opening) = match msg.as_control()? {
+            ControlEvent::BeginBracket(name) => (LevelKind::Bracket, name, true),
+            ControlEvent::EndBracket(name) => (LevelKind::Bracket, name, false),
+            ControlEvent::BeginGroup(name) => (LevelKind::Group, name, true),
+            ControlEvent::EndGroup(name) => (LevelKind::Group, name, false),
+            ControlEvent::Discontinuity => {
+                self.open.clear();
+                return Some(BracketChange::Reset);
+            }
+            _ => return None,
+        };
+        if opening {
+            self.open.push((kind, name.clone()));
+            return Some(BracketChange::Opened(self.open.len()));
+        }
+        let depth = self.open.len();
+        let Some((open_kind, open_name)) = self.open.pop() else {
+            warn!(
+                "closing {:?} {} without matching opening, ignoring",
+                kind, name
+            );
+            return None;
+        };
+        // an empty closing name closes whatever is innermost
+        if open_kind != kind || (!name.is_empty() && *name != open_name) {
+            warn!(
+                "closing {:?} {} while {:?} {} is innermost",
+                kind, name, open_kind, open_name
+            );
+        }
+        Some(BracketChange::Closed(depth))
+    }
+
+    /// Number of open brackets and groups, 0 on the top level
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Name of the innermost open bracket or group
+    pub fn innermost(&self) -> Option<&str> {
+        self.open.last().map(|(_, name)| name.as_str())
+    }
+}
+
+impl FbpMessage {
+    /// Opening bracket of a substream
+    pub fn begin_bracket(name: &str) -> Self {
+        Self::Control(ControlEvent::BeginBracket(name.to_owned()))
+    }
+
+    /// Closing bracket of a substream
+    pub fn end_bracket(name: &str) -> Self {
+        Self::Control(ControlEvent::EndBracket(name.to_owned()))
+    }
+
+    /// Whether the message opens or closes a bracket or group
+    pub fn is_bracket(&self) -> bool {
+        matches!(
+            self,
+            Self::Control(
+                ControlEvent::BeginBracket(_)
+                    | ControlEvent::EndBracket(_)
+                    | ControlEvent::BeginGroup(_)
+                    | ControlEvent::EndGroup(_)
+            )
+        )
+    }
+}
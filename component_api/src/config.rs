@@ -0,0 +1,459 @@
+// ADR-012: typed component configuration
+//
+// A component declares the fields of its configuration IIP as ConfigSchema, which is published as
+// JSON Schema in ComponentPort.schema. The runtime checks IIPs against that schema when validating
+// the graph, so invalid configuration is rejected before the network starts.
+//
+// Accepted forms of the IIP, all checked against the same schema:
+//   JSON object    {"mode": "packets", "limit": 3}
+//   TOML table     mode = "packets"
+//   URL query      ?mode=packets&limit=3  (leading ? optional), values converted to the field type
+//   shorthand      50us  the value of the schema's shorthand field, if it has one
+
+use std::io::{Error, ErrorKind, Result};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::ProcessEdgeSource;
+
+/// Type of a configuration field
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// string like `50us`, `1ms`, `2s`, `5m`, `1h`, a bare number is microseconds
+    Duration,
+    /// one of the given strings
+    Enum(Vec<String>),
+    /// JSON array
+    List,
+    /// any JSON value
+    Any,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ConfigField {
+    name: String,
+    kind: ConfigType,
+    required: bool,
+    default: Option<JsonValue>,
+    description: String,
+}
+
+/// Fields of a component configuration
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigSchema {
+    fields: Vec<ConfigField>,
+    shorthand: Option<String>,
+}
+
+/// Configuration struct deserialized from a configuration IIP
+pub trait ComponentConfig: DeserializeOwned {
+    fn schema() -> ConfigSchema;
+}
+
+impl ConfigSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Field that must be given
+    pub fn required(self, name: &str, kind: ConfigType, description: &str) -> Self {
+        self.with_field(name, kind, true, None, description)
+    }
+
+    /// Field that may be left out
+    pub fn optional(self, name: &str, kind: ConfigType, description: &str) -> Self {
+        self.with_field(name, kind, false, None, description)
+    }
+
+    /// Field taking `default` if left out
+    pub fn with_default(
+        self,
+        name: &str,
+        kind: ConfigType,
+        default: impl Into<JsonValue>,
+        description: &str,
+    ) -> Self {
+        self.with_field(name, kind, false, Some(default.into()), description)
+    }
+
+    /// IIP which is none of the structured forms is the value of field `name`
+    pub fn shorthand(mut self, name: &str) -> Self {
+        self.shorthand = Some(name.to_owned());
+        self
+    }
+
+    fn with_field(
+        mut self,
+        name: &str,
+        kind: ConfigType,
+        required: bool,
+        default: Option<JsonValue>,
+        description: &str,
+    ) -> Self {
+        self.fields.push(ConfigField {
+            name: name.to_owned(),
+            kind,
+            required,
+            default,
+            description: description.to_owned(),
+        });
+        self
+    }
+
+    fn field(&self, name: &str) -> Option<&ConfigField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// JSON Schema of the configuration, for ComponentPort.schema
+    pub fn to_json_schema(&self) -> JsonValue {
+        let mut properties = JsonMap::new();
+        for field in self.fields.iter() {
+            let mut property = JsonMap::new();
+            let mut set = |key: &str, value: JsonValue| property.insert(key.to_owned(), value);
+            match &field.kind {
+                ConfigType::String => set("type", "string".into()),
+                ConfigType::Integer => set("type", "integer".into()),
+                ConfigType::Number => set("type", "number".into()),
+                ConfigType::Boolean => set("type", "boolean".into()),
+                ConfigType::Duration => {
+                    set("type", "string".into());
+                    set("format", "duration".into())
+                }
+                ConfigType::Enum(values) => {
+                    set("type", "string".into());
+                    set("enum", values.clone().into())
+                }
+                ConfigType::List => set("type", "array".into()),
+                ConfigType::Any => None,
+            };
+            if let Some(default) = &field.default {
+                set("default", default.clone());
+            }
+            if !field.description.is_empty() {
+                set("description", field.description.clone().into());
+            }
+            properties.insert(field.name.clone(), JsonValue::Object(property));
+        }
+        let required: Vec<JsonValue> = self
+            .fields
+            .iter()
+            .filter(|field| field.required)
+            .map(|field| field.name.clone().into())
+            .collect();
+
+        let mut schema = JsonMap::new();
+        schema.insert("type".to_owned(), "object".into());
+        schema.insert("properties".to_owned(), JsonValue::Object(properties));
+        schema.insert("required".to_owned(), JsonValue::Array(required));
+        schema.insert("additionalProperties".to_owned(), false.into());
+        if let Some(shorthand) = &self.shorthand {
+            schema.insert("x-flowd-shorthand".to_owned(), shorthand.clone().into());
+        }
+        JsonValue::Object(schema)
+    }
+
+    /// Read back a schema written by to_json_schema(), for example from ComponentPort.schema
+    pub fn from_json_schema(schema: &JsonValue) -> Result<Self> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_owned());
+        let properties = schema
+            .get("properties")
+            .and_then(JsonValue::as_object)
+            .ok_or_else(|| invalid("configuration schema has no properties"))?;
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(JsonValue::as_array)
+            .map(|required| required.iter().filter_map(JsonValue::as_str).collect())
+            .unwrap_or_default();
+        let mut parsed = ConfigSchema::new();
+        for (name, property) in properties.iter() {
+            let text = |key: &str| property.get(key).and_then(JsonValue::as_str);
+            let kind = match (text("type"), text("format"), property.get("enum")) {
+                (Some("string"), _, Some(JsonValue::Array(values))) => ConfigType::Enum(
+                    values
+                        .iter()
+                        .filter_map(|value| value.as_str().map(str::to_owned))
+                        .collect(),
+                ),
+                (Some("string"), Some("duration"), _) => ConfigType::Duration,
+                (Some("string"), _, _) => ConfigType::String,
+                (Some("integer"), _, _) => ConfigType::Integer,
+                (Some("number"), _, _) => ConfigType::Number,
+                (Some("boolean"), _, _) => ConfigType::Boolean,
+                (Some("array"), _, _) => ConfigType::List,
+                (None, _, _) => ConfigType::Any,
+                (Some(other), _, _) => {
+                    return Err(invalid(&format!(
+                        "unsupported type {} of configuration field {}",
+                        other, name
+                    )))
+                }
+            };
+            parsed = parsed.with_field(
+                name,
+                kind,
+                required.contains(&name.as_str()),
+                property.get("default").cloned(),
+                text("description").unwrap_or(""),
+            );
+        }
+        parsed.shorthand = schema
+            .get("x-flowd-shorthand")
+            .and_then(JsonValue::as_str)
+            .map(str::to_owned);
+        Ok(parsed)
+    }
+
+    /// Parse and check a configuration IIP, returns all fields with defaults filled in
+    pub fn parse(&self, iip: &str) -> Result<JsonMap<String, JsonValue>> {
+        let mut config = self.parse_form(iip)?;
+        let mut problems = Vec::new();
+        for (name, value) in config.iter() {
+            match self.field(name) {
+                Some(field) => {
+                    if let Err(problem) = check_value(field, value) {
+                        problems.push(problem);
+                    }
+                }
+                None => problems.push(format!(
+                    "unknown field {}, expected one of: {}",
+                    name,
+                    self.fields
+                        .iter()
+                        .map(|field| field.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            }
+        }
+        for field in self.fields.iter() {
+            if config.contains_key(&field.name) {
+                continue;
+            }
+            if field.required {
+                problems.push(format!("missing field {}", field.name));
+            } else if let Some(default) = &field.default {
+                config.insert(field.name.clone(), default.clone());
+            }
+        }
+        if !problems.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, problems.join(", ")));
+        }
+        Ok(config)
+    }
+
+    /// Parse and check a configuration IIP and deserialize it into `T`
+    pub fn deserialize<T: DeserializeOwned>(&self, iip: &str) -> Result<T> {
+        let config = self.parse(iip)?;
+        serde_json::from_value(JsonValue::Object(config))
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err.to_string()))
+    }
+
+    fn parse_form(&self, iip: &str) -> Result<JsonMap<String, JsonValue>> {
+        let iip = iip.trim();
+        if iip.starts_with('{') {
+            return serde_json::from_str(iip).map_err(|err| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid JSON configuration: {}", err),
+                )
+            });
+        }
+        if let Some(query) = iip.strip_prefix('?') {
+            return self.parse_query(query);
+        }
+        if let Ok(table) = iip.parse::<toml::Table>() {
+            return match serde_json::to_value(table) {
+                Ok(JsonValue::Object(config)) => Ok(config),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "TOML configuration cannot be represented as JSON",
+                )),
+            };
+        }
+        if iip.contains('=') {
+            return self.parse_query(iip);
+        }
+        match self.shorthand.as_deref().and_then(|name| self.field(name)) {
+            Some(field) => Ok(JsonMap::from_iter([(
+                field.name.clone(),
+                string_to_value(&field.kind, iip),
+            )])),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "expected configuration as JSON object, TOML table or URL query",
+            )),
+        }
+    }
+
+    fn parse_query(&self, query: &str) -> Result<JsonMap<String, JsonValue>> {
+        let pairs: Vec<(String, String)> = serde_urlencoded::from_str(query).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid URL query configuration: {}", err),
+            )
+        })?;
+        Ok(pairs
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match self.field(&name) {
+                    Some(field) => string_to_value(&field.kind, &value),
+                    None => JsonValue::String(value),
+                };
+                (name, value)
+            })
+            .collect())
+    }
+}
+
+// URL query and shorthand values are strings, convert them to the declared type where possible
+fn string_to_value(kind: &ConfigType, value: &str) -> JsonValue {
+    let converted = match kind {
+        ConfigType::Integer => value.parse::<i64>().ok().map(JsonValue::from),
+        ConfigType::Number => value.parse::<f64>().ok().map(JsonValue::from),
+        ConfigType::Boolean => match value {
+            "true" | "1" | "yes" | "on" => Some(JsonValue::Bool(true)),
+            "false" | "0" | "no" | "off" => Some(JsonValue::Bool(false)),
+            _ => None,
+        },
+        ConfigType::List | ConfigType::Any => serde_json::from_str(value).ok(),
+        ConfigType::String | ConfigType::Duration | ConfigType::Enum(_) => None,
+    };
+    converted.unwrap_or_else(|| JsonValue::String(value.to_owned()))
+}
+
+fn check_value(field: &ConfigField, value: &JsonValue) -> std::result::Result<(), String> {
+    let expected = match &field.kind {
+        ConfigType::String if !value.is_string() => "a string",
+        ConfigType::Integer if !(value.is_i64() || value.is_u64()) => "an integer",
+        ConfigType::Number if !value.is_number() => "a number",
+        ConfigType::Boolean if !value.is_boolean() => "true or false",
+        ConfigType::List if !value.is_array() => "a list",
+        ConfigType::Duration => match value.as_str().map(parse_duration) {
+            Some(Ok(_)) => return Ok(()),
+            Some(Err(err)) => return Err(format!("field {}: {}", field.name, err)),
+            None => "a duration like 50us, 1ms, 2s",
+        },
+        ConfigType::Enum(values) => match value.as_str() {
+            Some(value) if values.iter().any(|allowed| allowed == value) => return Ok(()),
+            _ => {
+                return Err(format!(
+                    "field {} must be one of {}, got {}",
+                    field.name,
+                    values.join("|"),
+                    value
+                ))
+            }
+        },
+        _ => return Ok(()),
+    };
+    Err(format!(
+        "field {} must be {}, got {}",
+        field.name, expected, value
+    ))
+}
+
+/// Parse a duration like `50us`, `1.5ms`, `2s`, `5m` or `1h`, a bare number is microseconds
+pub fn parse_duration(text: &str) -> std::result::Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration {:?}", text))?;
+    let micros = match unit.trim() {
+        "" | "us" => 1.0,
+        "ms" => 1_000.0,
+        "s" => 1_000_000.0,
+        "m" => 60_000_000.0,
+        "h" => 3_600_000_000.0,
+        other => {
+            return Err(format!(
+                "unknown unit {:?} in duration {:?}, use us, ms, s, m or h",
+                other, text
+            ))
+        }
+    };
+    Ok(Duration::from_micros((number * micros) as u64))
+}
+
+/// For `#[serde(deserialize_with = "flowd_component_api::config::deserialize_duration")]`
+pub fn deserialize_duration<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+    parse_duration(&text).map_err(serde::de::Error::custom)
+}
+
+/// Check a configuration IIP against the JSON Schema of its port
+pub fn validate_config(schema: &str, iip: &str) -> Result<()> {
+    let schema: JsonValue = serde_json::from_str(schema).map_err(|err| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid port schema: {}", err),
+        )
+    })?;
+    ConfigSchema::from_json_schema(&schema)?
+        .parse(iip)
+        .map(|_| ())
+}
+
+enum ConfigState<T> {
+    Pending,
+    Valid(T),
+    Invalid(String),
+}
+
+/// Configuration inport whose IIP is checked against `T::schema()` and deserialized into `T`
+pub struct Config<T> {
+    source: Option<ProcessEdgeSource>,
+    state: ConfigState<T>,
+}
+
+impl<T: ComponentConfig> Config<T> {
+    pub fn new(source: Option<ProcessEdgeSource>) -> Self {
+        Config {
+            source,
+            state: ConfigState::Pending,
+        }
+    }
+
+    /// The configuration, None as long as its IIP has not arrived, an error if the IIP is invalid
+    pub fn get(&mut self) -> Result<Option<&T>> {
+        if let ConfigState::Pending = self.state {
+            if let Some(iip) = self.source.as_mut().and_then(|source| source.pop().ok()) {
+                let text = iip.as_text().or_else(|| {
+                    iip.as_bytes()
+                        .and_then(|bytes| std::str::from_utf8(bytes).ok())
+                });
+                self.state = match text.map(|text| T::schema().deserialize(text)) {
+                    Some(Ok(config)) => ConfigState::Valid(config),
+                    Some(Err(err)) => ConfigState::Invalid(err.to_string()),
+                    None => ConfigState::Invalid(String::from("configuration IIP is not text")),
+                };
+            }
+        }
+        match &self.state {
+            ConfigState::Pending => Ok(None),
+            ConfigState::Valid(config) => Ok(Some(config)),
+            ConfigState::Invalid(err) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid configuration: {}", err),
+            )),
+        }
+    }
+
+    /// Replace the connection, returning the previous one. A configuration already read stays
+    /// in effect.
+    pub fn reconnect(&mut self, source: Option<ProcessEdgeSource>) -> Option<ProcessEdgeSource> {
+        std::mem::replace(&mut self.source, source)
+    }
+}
@@ -0,0 +1,138 @@
+// Support for components declared with #[derive(FlowdComponent)]
+//
+// The derive macro generates new(), get_metadata() and the signal handling of process(),
+// the component author implements only ProcessData:
+//
+//     #[derive(FlowdComponent)]
+//     #[component(description = "Copies data as-is from IN port to OUT port.", icon = "copy")]
+//     pub struct RepeatComponent {
+//         #[inport(name = "IN", description = "data to be repeated on outport")]
+//         inn: ProcessEdgeSource,
+//         #[outport(name = "OUT", description = "repeated data from IN port")]
+//         out: ProcessEdgeSink,
+//         signals_in: ProcessSignalSource,
+//         signals_out: ProcessSignalSink,
+//     }
+//
+//     impl ProcessData for RepeatComponent {
+//         fn process_data(&mut self, context: &mut NodeContext) -> ProcessResult { ... }
+//     }
+
+use std::io::Result;
+
+use log::{error, info, trace};
+
+use crate::{
+    FbpMessage, FbpValue, NodeContext, ProcessEdge, ProcessEdgeSink, ProcessEdgeSource,
+    ProcessInports, ProcessOutports, ProcessResult, ProcessSignalSink, ProcessSignalSource,
+};
+
+/// Data-handling part of a component declared with `#[derive(FlowdComponent)]`
+pub trait ProcessData {
+    /// Called by the derived process() after signals were handled
+    fn process_data(&mut self, context: &mut NodeContext) -> ProcessResult;
+
+    /// See Component::snapshot()
+    fn snapshot(&self) -> Option<FbpValue> {
+        None
+    }
+
+    /// See Component::restore()
+    fn restore(&mut self, _state: FbpValue) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Answer a pending ping signal, returns true if the component was told to stop.
+/// Also useful inside long-running loops of process_data() to stay responsive.
+pub fn handle_signals(signals_in: &ProcessSignalSource, signals_out: &ProcessSignalSink) -> bool {
+    let Ok(signal) = signals_in.try_recv() else {
+        return false;
+    };
+    let signal_text = signal
+        .as_text()
+        .or_else(|| {
+            signal
+                .as_bytes()
+                .and_then(|bytes| std::str::from_utf8(bytes).ok())
+        })
+        .unwrap_or("");
+    trace!("received signal: {}", signal_text);
+    match signal_text {
+        "stop" => {
+            info!("got stop signal, finishing");
+            true
+        }
+        "ping" => {
+            trace!("got ping signal, responding");
+            let _ = signals_out.try_send(FbpMessage::from_str("pong"));
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Inport carrying the configuration IIP, which is taken out of the edge on first use
+pub struct ConfigInport {
+    source: Option<ProcessEdgeSource>,
+    value: Option<FbpMessage>,
+}
+
+impl ConfigInport {
+    pub fn new(source: Option<ProcessEdgeSource>) -> Self {
+        ConfigInport {
+            source,
+            value: None,
+        }
+    }
+
+    /// The configuration IIP, None as long as it has not arrived
+    pub fn get(&mut self) -> Option<&FbpMessage> {
+        if self.value.is_none() {
+            self.value = self.source.as_mut().and_then(|source| source.pop().ok());
+        }
+        self.value.as_ref()
+    }
+
+    /// Replace the connection, returning the previous one. An IIP already taken stays in effect.
+    pub fn reconnect(&mut self, source: Option<ProcessEdgeSource>) -> Option<ProcessEdgeSource> {
+        std::mem::replace(&mut self.source, source)
+    }
+
+    /// The configuration IIP as text, also if it was sent as UTF-8 bytes
+    pub fn text(&mut self) -> Option<&str> {
+        let value = self.get()?;
+        value.as_text().or_else(|| {
+            value
+                .as_bytes()
+                .and_then(|bytes| std::str::from_utf8(bytes).ok())
+        })
+    }
+}
+
+/// Take the connection of a single inport, an unconnected port reads as closed
+pub fn take_inport(inports: &mut ProcessInports, component: &str, port: &str) -> ProcessEdgeSource {
+    if let Some(source) = inports.remove(port).and_then(|mut sources| sources.pop()) {
+        return source;
+    }
+    error!(
+        "{}: inport {} is not connected, treating it as closed",
+        component, port
+    );
+    let (_sink, source) = ProcessEdge::new(1);
+    source.into()
+}
+
+/// Take the connection of a single outport, an unconnected port is_abandoned() and fills up
+pub fn take_outport(
+    outports: &mut ProcessOutports,
+    component: &str,
+    port: &str,
+) -> ProcessEdgeSink {
+    if let Some(sink) = outports.remove(port).and_then(|mut sinks| sinks.pop()) {
+        return sink;
+    }
+    error!("{}: outport {} is not connected", component, port);
+    let (sink, _source) = ProcessEdge::new(1);
+    ProcessEdgeSink::new(sink, None, None, None)
+}
@@ -0,0 +1,236 @@
+// ADR-010: durable file-backed edges
+//
+// Append-only segment file per persistent edge. Records:
+//   'M' u32 length (LE) + packet    message pushed into the edge, see FbpMessage::encode()
+//   'A' u64 count (LE)                number of messages of this segment taken out of the edge
+// On open, unacknowledged messages are compacted into a fresh segment and handed out for replay.
+// A torn record at the end of the file (crash during write) is ignored.
+// Messages are acknowledged once the target process returned from the process() call which
+// took them out of the edge, see EdgeJournalAck; whatever is left when the target process
+// finishes is acknowledged then, see ack_all().
+// Every record is synced to disk before the push returns.
+
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Result, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::FbpMessage;
+
+const RECORD_MESSAGE: u8 = b'M';
+const RECORD_ACK: u8 = b'A';
+
+pub struct EdgeJournal {
+    path: PathBuf,
+    file: File,
+    written: u64,      // messages journaled since open, including the replayed ones
+    acked: u64,        // messages processed since open
+    segment_base: u64, // value of written when the current segment was started
+    pending: Vec<FbpMessage>,
+}
+
+impl EdgeJournal {
+    /// Open or create the journal at `path`, keeping unacknowledged messages for replay
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let pending = match std::fs::read(path) {
+            Ok(contents) => read_unacked(&contents),
+            Err(err) if err.kind() == ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err),
+        };
+
+        // compact: write a fresh segment with only the pending messages, then replace the old one
+        let mut compacted_path = path.as_os_str().to_owned();
+        compacted_path.push(".compact");
+        let compacted_path = PathBuf::from(compacted_path);
+        let mut compacted = File::create(&compacted_path)?;
+        for message in pending.iter() {
+            compacted.write_all(&message_record(message)?)?;
+        }
+        compacted.sync_all()?;
+        drop(compacted);
+        std::fs::rename(&compacted_path, path)?;
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(EdgeJournal {
+            path: path.to_owned(),
+            file,
+            written: pending.len() as u64,
+            acked: 0,
+            segment_base: 0,
+            pending,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of messages left over from the previous run
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Take the messages left over from the previous run, in push order
+    pub fn take_pending(&mut self) -> Vec<FbpMessage> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Journal a message pushed into the edge
+    pub fn append(&mut self, message: &FbpMessage) -> Result<()> {
+        if message.is_trace() {
+            // trace events are runtime-internal and never replayed
+            return Ok(());
+        }
+        let record = message_record(message)?;
+        self.file.write_all(&record)?;
+        self.file.sync_data()?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Record that the first `count` journaled messages have been processed
+    pub fn ack(&mut self, count: u64) -> Result<()> {
+        let count = count.min(self.written);
+        if count <= self.acked {
+            return Ok(());
+        }
+        self.acked = count;
+        if self.acked == self.written {
+            // everything delivered, start a new empty segment
+            self.file.set_len(0)?;
+            self.file.sync_data()?;
+            self.segment_base = self.written;
+            return Ok(());
+        }
+        let mut record = Vec::with_capacity(9);
+        record.push(RECORD_ACK);
+        record.extend_from_slice(&(self.acked - self.segment_base).to_le_bytes());
+        self.file.write_all(&record)?;
+        self.file.sync_data()
+    }
+
+    /// Record that every journaled message has been processed, used when the target process
+    /// finished and therefore took out and handled everything it is going to
+    pub fn ack_all(&mut self) -> Result<()> {
+        self.ack(self.written)
+    }
+}
+
+impl std::fmt::Debug for EdgeJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EdgeJournal")
+            .field("path", &self.path)
+            .field("written", &self.written)
+            .field("acked", &self.acked)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+/// Acknowledges the messages of a persistent edge on behalf of its receiving side,
+/// see `ProcessEdgeSink::enable_journal()`
+#[derive(Debug)]
+pub struct EdgeJournalAck {
+    journal: Arc<Mutex<EdgeJournal>>,
+    taken: Arc<AtomicU64>, // counted by the receiving side, see ProcessEdgeSource
+    unjournaled_ahead: u64, // messages pushed before the journal was enabled (IIPs)
+}
+
+impl EdgeJournalAck {
+    pub(crate) fn new(
+        journal: Arc<Mutex<EdgeJournal>>,
+        taken: Arc<AtomicU64>,
+        unjournaled_ahead: u64,
+    ) -> Self {
+        EdgeJournalAck {
+            journal,
+            taken,
+            unjournaled_ahead,
+        }
+    }
+
+    /// Acknowledge what the receiving process has taken out of the edge so far,
+    /// to be called after its process() returned
+    pub fn ack_taken(&self) {
+        let taken = self.taken.load(Ordering::Relaxed);
+        let count = taken.saturating_sub(self.unjournaled_ahead);
+        self.with_journal(|journal| journal.ack(count));
+    }
+
+    /// Acknowledge every journaled message, once the receiving process finished
+    pub fn ack_all(&self) {
+        self.with_journal(EdgeJournal::ack_all);
+    }
+
+    fn with_journal(&self, f: impl FnOnce(&mut EdgeJournal) -> Result<()>) {
+        let mut journal = self.journal.lock().expect("edge journal lock poisoned");
+        if let Err(err) = f(&mut journal) {
+            log::error!(
+                "failed to write edge journal {}: {}",
+                journal.path().display(),
+                err
+            );
+        }
+    }
+}
+
+// parse a segment and return the messages after the last acknowledgement
+fn read_unacked(mut contents: &[u8]) -> Vec<FbpMessage> {
+    let mut messages = vec![];
+    let mut acked: usize = 0;
+    loop {
+        let mut kind = [0u8; 1];
+        if contents.read_exact(&mut kind).is_err() {
+            break;
+        }
+        match kind[0] {
+            RECORD_MESSAGE => {
+                let Ok(len) = read_u32(&mut contents) else {
+                    break;
+                };
+                if contents.len() < len as usize {
+                    break;
+                }
+                let (payload, rest) = contents.split_at(len as usize);
+                contents = rest;
+                match FbpMessage::decode(payload) {
+                    Ok(message) => messages.push(message),
+                    Err(_) => break,
+                }
+            }
+            RECORD_ACK => {
+                let Ok(count) = read_u64(&mut contents) else {
+                    break;
+                };
+                acked = acked.max(count as usize);
+            }
+            _ => break,
+        }
+    }
+    messages.split_off(acked.min(messages.len()))
+}
+
+fn message_record(message: &FbpMessage) -> Result<Vec<u8>> {
+    let payload = message.encode()?;
+    let mut record = Vec::with_capacity(payload.len() + 5);
+    record.push(RECORD_MESSAGE);
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(&payload);
+    Ok(record)
+}
+
+fn read_u32(input: &mut &[u8]) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(input: &mut &[u8]) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
@@ -1,21 +1,130 @@
 use std::fmt::{self, Debug, Formatter};
-use std::sync::Arc;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread::Thread;
 use std::time::{Duration, Instant};
 
 use multimap::MultiMap;
 use serde::{Deserialize, Serialize};
 
+mod bracket;
+pub mod config;
+mod derive;
+mod journal;
+pub mod types;
+pub mod value;
+mod wire;
+pub use bracket::{BracketChange, BracketTracker};
+pub use config::{ComponentConfig, Config, ConfigSchema, ConfigType};
+pub use derive::{handle_signals, take_inport, take_outport, ConfigInport, ProcessData};
+pub use flowd_component_derive::FlowdComponent;
+pub use journal::{EdgeJournal, EdgeJournalAck};
+pub use wire::WIRE_FORMAT_VERSION;
+
 // ports
+// addressable ports: the position in the Vec of a port is the connection index, so IN[3] is
+// element 3; unused indices in between hold a disconnected edge which is_abandoned()
 pub type ProcessInports = MultiMap<String, ProcessEdgeSource>;
 pub type ProcessOutports = MultiMap<String, ProcessEdgeSink>;
 
 // edges
 pub type ProcessEdge = rtrb::RingBuffer<FbpMessage>;
-pub type ProcessEdgeSource = rtrb::Consumer<FbpMessage>;
+pub type ProcessEdgeSourceConnection = rtrb::Consumer<FbpMessage>;
 pub type ProcessEdgeSinkConnection = rtrb::Producer<FbpMessage>;
 pub use rtrb::PushError; // re-eport for abstraction
 
+/// Receiving side of an edge, dereferences to the rtrb consumer.
+/// Taking messages out through pop() or read_chunk() updates the edge metrics and the count
+/// for acknowledging persistent edges.
+pub struct ProcessEdgeSource {
+    source: ProcessEdgeSourceConnection,
+    metrics: Option<Arc<EdgeMetrics>>,
+    taken: Option<Arc<AtomicU64>>, // ADR-010: messages taken out, acknowledged once processed
+}
+
+impl ProcessEdgeSource {
+    pub fn new(source: ProcessEdgeSourceConnection) -> Self {
+        Self {
+            source,
+            metrics: None,
+            taken: None,
+        }
+    }
+
+    /// Take the next message out of the edge
+    pub fn pop(&mut self) -> Result<FbpMessage, rtrb::PopError> {
+        let result = self.source.pop();
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .occupancy
+                .store(self.source.slots(), Ordering::Relaxed);
+        }
+        if let (Ok(_), Some(taken)) = (&result, &self.taken) {
+            taken.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Take `n` messages out of the edge at once, they count as taken out right away
+    pub fn read_chunk(
+        &mut self,
+        n: usize,
+    ) -> Result<rtrb::chunks::ReadChunk<'_, FbpMessage>, rtrb::chunks::ChunkError> {
+        let available = self.source.slots();
+        let chunk = self.source.read_chunk(n)?;
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .occupancy
+                .store(available - chunk.len(), Ordering::Relaxed);
+        }
+        if let Some(taken) = &self.taken {
+            taken.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        }
+        Ok(chunk)
+    }
+
+    /// Report the occupancy of the edge into `metrics` when taking messages out
+    pub fn enable_metrics(&mut self, metrics: Arc<EdgeMetrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    // count of the messages taken out of the edge from now on, for EdgeJournalAck
+    fn count_taken(&mut self) -> Arc<AtomicU64> {
+        self.taken
+            .get_or_insert_with(|| Arc::new(AtomicU64::new(0)))
+            .clone()
+    }
+}
+
+impl From<ProcessEdgeSourceConnection> for ProcessEdgeSource {
+    fn from(source: ProcessEdgeSourceConnection) -> Self {
+        Self::new(source)
+    }
+}
+
+impl Deref for ProcessEdgeSource {
+    type Target = ProcessEdgeSourceConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.source
+    }
+}
+
+impl DerefMut for ProcessEdgeSource {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.source
+    }
+}
+
+impl Debug for ProcessEdgeSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessEdgeSource")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
 pub struct ProcessEdgeSink {
     sink: ProcessEdgeSinkConnection,
     wakeup: Option<WakeupNotify>,
@@ -28,8 +137,56 @@ pub struct ProcessEdgeSink {
     src_port: Option<String>,
     tgt_node: Option<String>,
     tgt_port: Option<String>,
+    src_index: Option<String>,
+    tgt_index: Option<String>,
     graph_name: Option<String>,
     trace_sender: Option<std::sync::mpsc::Sender<FbpMessage>>,
+    // ADR-010: persistent edge journal
+    journal: Option<Arc<Mutex<EdgeJournal>>>, // shared with the acknowledging receiving side
+    pushed: u64,            // messages pushed into the edge in total
+    metrics: Option<Arc<EdgeMetrics>>,
+    // ADR-029: declared type of the sending port, checked in debug builds
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    contract: Option<Arc<types::PortContract>>,
+}
+
+/// Counters of one edge for the metrics exporter, updated by the sending side.
+/// Occupancy is sampled by both sides, on every push attempt and every message taken out.
+#[derive(Debug, Default)]
+pub struct EdgeMetrics {
+    pushes: AtomicU64,
+    full: AtomicU64,
+    occupancy: AtomicUsize,
+    capacity: AtomicUsize,
+}
+
+impl EdgeMetrics {
+    /// Messages pushed into the edge
+    pub fn pushes(&self) -> u64 {
+        self.pushes.load(Ordering::Relaxed)
+    }
+
+    /// Push attempts rejected because the edge was full
+    pub fn full(&self) -> u64 {
+        self.full.load(Ordering::Relaxed)
+    }
+
+    /// Slots in use as of the last push attempt or message taken out
+    pub fn occupancy(&self) -> usize {
+        self.occupancy.load(Ordering::Relaxed)
+    }
+
+    /// Slots of the edge
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    fn record(&self, sink: &ProcessEdgeSinkConnection, pushed: bool) {
+        let counter = if pushed { &self.pushes } else { &self.full };
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.occupancy
+            .store(sink.buffer().capacity() - sink.slots(), Ordering::Relaxed);
+    }
 }
 
 impl ProcessEdgeSink {
@@ -50,39 +207,56 @@ impl ProcessEdgeSink {
             src_port: None,
             tgt_node: None,
             tgt_port: None,
+            src_index: None,
+            tgt_index: None,
             graph_name: None,
             trace_sender: None,
+            journal: None,
+            pushed: 0,
+            metrics: None,
+            contract: None,
         }
     }
 
     /// Push data into the edge and signal readiness if configured
     pub fn push(&mut self, data: MessageBuf) -> Result<(), PushError<MessageBuf>> {
-        match self.sink.push(data.clone()) {
+        #[cfg(debug_assertions)]
+        if let Some(contract) = &self.contract {
+            if let Err(err) = contract.check(&data) {
+                log::error!(
+                    "edge to {}: {}",
+                    self.proc_name.as_deref().unwrap_or("unknown process"),
+                    err
+                );
+            }
+        }
+        let result = self.sink.push(data.clone());
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&self.sink, result.is_ok());
+        }
+        match result {
             Ok(()) => {
+                self.pushed += 1;
+                if self.journal.is_some() {
+                    self.journal_push(&data);
+                }
+
                 // Emit trace:data event if tracing is enabled
                 if self.tracing_enabled {
                     if let (Some(edge_id), Some(src_node), Some(src_port), Some(tgt_node), Some(tgt_port), Some(graph_name), Some(trace_sender)) =
                         (&self.edge_id, &self.src_node, &self.src_port, &self.tgt_node, &self.tgt_port, &self.graph_name, &self.trace_sender) {
-                        let data_str = match &data {
-                            FbpMessage::Bytes(bytes) => format!("{} bytes", bytes.len()),
-                            FbpMessage::Text(text) => text.to_string(),
-                            FbpMessage::Value(_) => "structured data".to_string(),
-                            FbpMessage::Control(_) => "control message".to_string(),
-                            FbpMessage::TraceData(_) => "trace data".to_string(),
-                            FbpMessage::TraceConnect(_) => "trace connect".to_string(),
-                            FbpMessage::TraceDisconnect(_) => "trace disconnect".to_string(),
-                        };
+                        let data_str = data.to_trace_data();
                         let trace_payload = TraceDataEventPayload {
                             id: edge_id.clone(),
                             src: GraphNodeSpecNetwork {
                                 node: src_node.clone(),
                                 port: src_port.clone(),
-                                index: None,
+                                index: self.src_index.clone(),
                             },
                             tgt: GraphNodeSpecNetwork {
                                 node: tgt_node.clone(),
                                 port: tgt_port.clone(),
-                                index: None,
+                                index: self.tgt_index.clone(),
                             },
                             graph: graph_name.clone(),
                             data: data_str,
@@ -106,10 +280,49 @@ impl ProcessEdgeSink {
         }
     }
 
+    /// Disconnect and wake up the receiving side, so that it notices is_abandoned() right away.
+    /// Components which finish while their receivers wait for a wakeup call this on finishing.
+    pub fn close(self) {
+        let signal_ready = self.signal_ready.clone();
+        let wakeup = self.wakeup.clone();
+        drop(self);
+        if let Some(signal) = signal_ready {
+            signal();
+        }
+        if let Some(wakeup) = wakeup {
+            wakeup.unpark();
+        }
+    }
+
     pub fn proc_name(&self) -> Option<&str> {
         self.proc_name.as_deref()
     }
 
+    /// Count pushes and full edges into `metrics`
+    pub fn enable_metrics(&mut self, metrics: Arc<EdgeMetrics>) {
+        let capacity = self.sink.buffer().capacity();
+        metrics.capacity.store(capacity, Ordering::Relaxed);
+        metrics
+            .occupancy
+            .store(capacity - self.sink.slots(), Ordering::Relaxed);
+        self.metrics = Some(metrics);
+    }
+
+    /// Check the pushed messages against the declared type of the port, only in debug builds
+    pub fn check_contract(&mut self, contract: Arc<types::PortContract>) {
+        self.contract = Some(contract);
+    }
+
+    /// True if the receiving side has taken all packets out of the edge
+    pub fn is_drained(&self) -> bool {
+        self.sink.slots() == self.sink.buffer().capacity()
+    }
+
+    /// True if the receiving side was dropped
+    pub fn is_abandoned(&self) -> bool {
+        self.sink.is_abandoned()
+    }
+
     /// Enable tracing for this edge sink
     pub fn enable_tracing(
         &mut self,
@@ -130,6 +343,76 @@ impl ProcessEdgeSink {
         self.graph_name = Some(graph_name);
         self.trace_sender = Some(trace_sender);
     }
+
+    /// Set the connection indices reported in trace data for edges on addressable ports
+    pub fn set_trace_index(&mut self, src_index: Option<String>, tgt_index: Option<String>) {
+        self.src_index = src_index;
+        self.tgt_index = tgt_index;
+    }
+
+    /// Make this edge persistent: messages left over from the previous run are replayed
+    /// into the edge, and from now on every pushed message is journaled until the
+    /// receiving side has processed it.
+    /// `source` is the receiving side of this edge, before anything was taken out of it.
+    /// The edge needs free capacity for the replayed messages, see `EdgeJournal::pending_len()`.
+    /// Returns the acknowledgement to run after each process() call of the receiving process.
+    pub fn enable_journal(
+        &mut self,
+        mut journal: EdgeJournal,
+        source: &mut ProcessEdgeSource,
+    ) -> std::io::Result<EdgeJournalAck> {
+        let pending = journal.take_pending();
+        if pending.len() > self.sink.slots() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "edge has not enough capacity to replay {} journaled messages",
+                    pending.len()
+                ),
+            ));
+        }
+        let unjournaled_ahead = self.pushed;
+        for message in pending {
+            // already journaled, so bypass push()
+            if self.sink.push(message).is_ok() {
+                self.pushed += 1;
+            }
+        }
+        let journal = Arc::new(Mutex::new(journal));
+        self.journal = Some(journal.clone());
+        if self.pushed > unjournaled_ahead {
+            if let Some(signal) = &self.signal_ready {
+                signal();
+            }
+            if let Some(wakeup) = &self.wakeup {
+                wakeup.unpark();
+            }
+        }
+        Ok(EdgeJournalAck::new(
+            journal,
+            source.count_taken(),
+            unjournaled_ahead,
+        ))
+    }
+
+    /// True if pushed messages are journaled
+    pub fn is_persistent(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    // journal the pushed message
+    fn journal_push(&mut self, data: &MessageBuf) {
+        if let Some(journal) = self.journal.as_ref() {
+            let mut journal = journal.lock().expect("edge journal lock poisoned");
+            if let Err(err) = journal.append(data) {
+                log::error!(
+                    "failed to write edge journal {}: {}",
+                    journal.path().display(),
+                    err
+                );
+            }
+        }
+    }
 }
 
 impl Debug for ProcessEdgeSink {
@@ -138,6 +421,7 @@ impl Debug for ProcessEdgeSink {
             .field("sink", &self.sink)
             .field("wakeup", &self.wakeup)
             .field("proc_name", &self.proc_name)
+            .field("journal", &self.journal)
             .finish()
     }
 }
@@ -168,7 +452,7 @@ pub enum FbpValue {
 }
 
 // Control events for stream boundaries and lifecycle
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ControlEvent {
     // Stream boundaries
     BeginGroup(String),      // start of named group
@@ -186,14 +470,17 @@ pub enum ControlEvent {
 }
 
 // ADR-003: Typed message format
-#[derive(Debug, Clone, PartialEq)]
+// serde representation is the canonical packet encoding, see FbpMessage::encode()
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum FbpMessage {
-    Bytes(Arc<[u8]>),        // Raw binary data with sharing
-    Text(Arc<str>),          // UTF-8 text with sharing
-    Value(FbpValue),         // Structured data
-    Control(ControlEvent),   // Control messages
-    TraceData(TraceDataEventPayload), // Trace data events
-    TraceConnect(TraceConnectEventPayload), // Trace connect events
+    #[serde(with = "wire::arc_bytes")]
+    Bytes(Arc<[u8]>), // Raw binary data with sharing
+    #[serde(with = "wire::arc_str")]
+    Text(Arc<str>), // UTF-8 text with sharing
+    Value(FbpValue),                              // Structured data
+    Control(ControlEvent),                        // Control messages
+    TraceData(TraceDataEventPayload),             // Trace data events
+    TraceConnect(TraceConnectEventPayload),       // Trace connect events
     TraceDisconnect(TraceDisconnectEventPayload), // Trace disconnect events
 }
 
@@ -213,13 +500,6 @@ impl FbpMessage {
         Self::Text(text.into())
     }
 
-    /// Create a message from a JSON value (via serde)
-    /// Note: Requires serde_json dependency to be added to Cargo.toml
-    // pub fn from_json<T: serde::Serialize>(value: T) -> Result<Self, serde_json::Error> {
-    //     let json_str = serde_json::to_string(&value)?;
-    //     Ok(Self::from_text(json_str))
-    // }
-
     /// Try to extract bytes from the message
     pub fn as_bytes(&self) -> Option<&[u8]> {
         match self {
@@ -266,6 +546,88 @@ impl FbpMessage {
     pub fn is_trace(&self) -> bool {
         matches!(self, Self::TraceData(_) | Self::TraceConnect(_) | Self::TraceDisconnect(_))
     }
+
+    /// Render the full message content for trace:data, network:data and Flowtrace dumps.
+    /// Bytes are shown as text if they are valid UTF-8, otherwise as lowercase hex.
+    /// Values are rendered as JSON.
+    pub fn to_trace_data(&self) -> String {
+        match self {
+            Self::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_owned(),
+                Err(_) => bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            },
+            Self::Text(text) => text.to_string(),
+            Self::Value(value) => value.to_string(),
+            Self::Control(event) => format!("{:?}", event),
+            Self::TraceData(_) => "trace data".to_string(),
+            Self::TraceConnect(_) => "trace connect".to_string(),
+            Self::TraceDisconnect(_) => "trace disconnect".to_string(),
+        }
+    }
+}
+
+// JSON rendering, used for tracing and debug output
+impl fmt::Display for FbpValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fn write_json_string(f: &mut Formatter<'_>, text: &str) -> fmt::Result {
+            f.write_str("\"")?;
+            for c in text.chars() {
+                match c {
+                    '"' => f.write_str("\\\"")?,
+                    '\\' => f.write_str("\\\\")?,
+                    '\n' => f.write_str("\\n")?,
+                    '\r' => f.write_str("\\r")?,
+                    '\t' => f.write_str("\\t")?,
+                    c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                    c => write!(f, "{}", c)?,
+                }
+            }
+            f.write_str("\"")
+        }
+
+        match self {
+            FbpValue::Null => f.write_str("null"),
+            FbpValue::Bool(value) => write!(f, "{}", value),
+            FbpValue::Int(value) => write!(f, "{}", value),
+            FbpValue::Float(value) if value.is_finite() => write!(f, "{}", value),
+            FbpValue::Float(_) => f.write_str("null"), // JSON has no NaN or infinity
+            FbpValue::Text(text) => write_json_string(f, text),
+            FbpValue::Bytes(bytes) => {
+                f.write_str("[")?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", byte)?;
+                }
+                f.write_str("]")
+            }
+            FbpValue::List(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            FbpValue::Map(entries) => {
+                // sorted keys for stable output
+                let mut keys: Vec<&String> = entries.keys().collect();
+                keys.sort();
+                f.write_str("{")?;
+                for (i, key) in keys.into_iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_json_string(f, key)?;
+                    write!(f, ":{}", entries[key])?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
 }
 
 // Convenience conversions
@@ -400,6 +762,13 @@ impl NodeContext {
 }
 
 // component
+fn reconnect_unsupported() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        String::from("component does not support reconnecting ports"),
+    )
+}
+
 pub trait Component: Send {
     fn new(
         inports: ProcessInports,
@@ -434,27 +803,46 @@ pub trait Component: Send {
     where
         Self: Sized;
 
-    /*// to support reconnecting of inports and outports
-    fn reconfigure_connection(
+    // ADR-029: types the ports of the component refer to besides the core types,
+    // registered in the type registry of the runtime
+    fn port_types() -> Vec<types::RegistryEntry>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    // Hot graph mutation: swap the connection of an inport or outport while the network runs.
+    // Only called by the runtime if the component metadata has support_reconnect set.
+    // `None` disconnects the port. Returns the connection that was replaced, so that the runtime can drain it.
+    //TODO arrayports: add index to address a single connection of an array port
+    fn reconnect_inport(
         &mut self,
-        source_port: &str,
-        target_component: &mut dyn Component,   //TODO optimize - is dyn fast?
-        target_port: &str,
-    ) -> std::result::Result<(), std::io::Error> {
-        // remove the current connection from the source process
-        self.get_outports_mut().retain(|port, _| port != source_port);
+        _port: &str,
+        _source: Option<ProcessEdgeSource>,
+    ) -> std::result::Result<Option<ProcessEdgeSource>, std::io::Error> {
+        Err(reconnect_unsupported())
+    }
 
-        // remove the current connection from the target process
-        target_component.get_inports_mut().retain(|port, _| port != target_port);
+    fn reconnect_outport(
+        &mut self,
+        _port: &str,
+        _sink: Option<ProcessEdgeSink>,
+    ) -> std::result::Result<Option<ProcessEdgeSink>, std::io::Error> {
+        Err(reconnect_unsupported())
+    }
 
-        // add the new connection
-        self.get_outports_mut().insert(source_port.to_string(), target_port.to_string());
-        target_component.get_inports_mut().insert(target_port.to_string(), source_port.to_string());
+    // ADR-005: checkpointing of node-local state.
+    // Called by the runtime while the scheduler is quiesced, so no process() call is running.
+    // Returns None if the component has no state worth saving (default).
+    fn snapshot(&self) -> Option<FbpValue> {
+        None
+    }
 
+    // Called with the state saved by snapshot(), once after new() and before the first process().
+    fn restore(&mut self, _state: FbpValue) -> std::result::Result<(), std::io::Error> {
         Ok(())
     }
-    fn get_inports_mut(&mut self) -> &mut ProcessInports;
-    fn get_outports_mut(&mut self) -> &mut ProcessOutports;*/
 }
 
 #[derive(Serialize, Debug)]
@@ -471,7 +859,7 @@ pub struct ComponentComponentPayload {
     #[serde(skip)]
     pub support_perfdata: bool,
     #[serde(skip)]
-    pub support_reconnect: bool, //TODO should this belong to the ports (in_ports, out_ports fields)?
+    pub support_reconnect: bool, // component implements reconnect_inport/outport() and can be instantiated without connected ports //TODO should this belong to the ports (in_ports, out_ports fields)?
 }
 
 impl Default for ComponentComponentPayload {
@@ -0,0 +1,396 @@
+// ADR-028/029: typed ports and the type registry
+//
+// A port type is a TypeId like core/String@1 naming an entry of the TypeRegistry, which may carry
+// a JSON Schema of the packets on the port. The noflo port types (any, string, int, ...) are
+// aliases of core types, other free-form port types keep being compared by name.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value as JsonValue};
+
+use crate::{FbpMessage, FbpValue};
+
+/// Type of ports taking packets of every type
+pub const ANY_TYPE: &str = "core/Any@1";
+
+/// Type name in the canonical form `<namespace>/<Type>@<major>`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeId {
+    pub namespace: String,
+    pub name: String,
+    pub major: u32,
+}
+
+impl TypeId {
+    pub fn new(namespace: &str, name: &str, major: u32) -> Self {
+        TypeId {
+            namespace: namespace.to_owned(),
+            name: name.to_owned(),
+            major,
+        }
+    }
+
+    /// Parse and check a TypeId, surrounding whitespace is ignored
+    pub fn parse(text: &str) -> Result<TypeId, String> {
+        let invalid = || {
+            format!(
+                "invalid type {:?}, expected <namespace>/<Type>@<major>",
+                text
+            )
+        };
+        let (namespace, rest) = text.trim().split_once('/').ok_or_else(invalid)?;
+        let (name, major) = rest.split_once('@').ok_or_else(invalid)?;
+        let namespace_ok = namespace.len() <= 64
+            && namespace.starts_with(|c: char| c.is_ascii_lowercase())
+            && namespace
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        let name_ok = name.len() <= 64
+            && name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name.chars().all(|c| c.is_ascii_alphanumeric());
+        let major = match major.parse::<u32>() {
+            Ok(number) if number > 0 && !major.starts_with('0') => number,
+            _ => return Err(invalid()),
+        };
+        if !namespace_ok || !name_ok {
+            return Err(invalid());
+        }
+        Ok(TypeId::new(namespace, name, major))
+    }
+}
+
+impl fmt::Display for TypeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}@{}", self.namespace, self.name, self.major)
+    }
+}
+
+impl Serialize for TypeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A named type known to the runtime
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryEntry {
+    #[serde(rename = "id")]
+    pub type_id: TypeId,
+    pub description: String,
+    /// JSON Schema the packets conform to, None for no structural contract
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<JsonValue>,
+    /// producer types this type accepts as well, e.g. integers for numbers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub compatible_from: Vec<TypeId>,
+    /// port type names resolving to this type
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl RegistryEntry {
+    pub fn new(type_id: TypeId, description: &str) -> Self {
+        RegistryEntry {
+            type_id,
+            description: description.to_owned(),
+            schema: None,
+            compatible_from: Vec::new(),
+            aliases: Vec::new(),
+        }
+    }
+
+    /// Schema of the packets, for object types e.g. `ConfigSchema::to_json_schema()`
+    pub fn with_schema(mut self, schema: JsonValue) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub fn compatible_from(mut self, producer: TypeId) -> Self {
+        self.compatible_from.push(producer);
+        self
+    }
+
+    fn alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_owned());
+        self
+    }
+}
+
+/// How packets of a producer type fit a consumer type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatibilityResult {
+    CompatibleExact,
+    CompatibleDeclared,
+    IncompatibleType,
+    IncompatibleVersion,
+}
+
+impl CompatibilityResult {
+    pub fn is_compatible(self) -> bool {
+        matches!(self, Self::CompatibleExact | Self::CompatibleDeclared)
+    }
+}
+
+/// Types known to the runtime, by canonical TypeId
+#[derive(Clone, Debug)]
+pub struct TypeRegistry {
+    entries: BTreeMap<String, RegistryEntry>,
+    aliases: BTreeMap<String, String>, // lowercase alias -> canonical TypeId
+}
+
+impl Default for TypeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeRegistry {
+    /// Registry with the core types, whose schemas follow the variants of FbpValue
+    pub fn new() -> Self {
+        let mut registry = TypeRegistry {
+            entries: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+        };
+        let core = |name: &str, description: &str| {
+            RegistryEntry::new(TypeId::new("core", name, 1), description)
+        };
+        let integer = TypeId::new("core", "Integer", 1);
+        for entry in [
+            core("Any", "packets of every type")
+                .alias("")
+                .alias("any")
+                .alias("all"),
+            core("String", "UTF-8 text")
+                .with_schema(json!({"type": "string"}))
+                .alias("string"),
+            core("Bytes", "binary data")
+                .with_schema(json!({"type": "string", "contentEncoding": "binary"}))
+                .alias("buffer")
+                .alias("bytes"),
+            core("Boolean", "true or false")
+                .with_schema(json!({"type": "boolean"}))
+                .alias("boolean"),
+            core("Integer", "64-bit signed integer")
+                .with_schema(json!({"type": "integer"}))
+                .alias("int")
+                .alias("integer"),
+            core("Number", "integer or floating-point number")
+                .with_schema(json!({"type": "number"}))
+                .compatible_from(integer)
+                .alias("number"),
+            core("Object", "map of named values")
+                .with_schema(json!({"type": "object"}))
+                .alias("object"),
+            core("Array", "list of values")
+                .with_schema(json!({"type": "array"}))
+                .alias("array"),
+            core("Bang", "a packet without content")
+                .with_schema(json!({"type": "null"}))
+                .alias("bang"),
+        ] {
+            registry
+                .register(entry)
+                .expect("core types are registered once");
+        }
+        registry
+    }
+
+    /// Add a type, registering the same TypeId again with different content is an error
+    pub fn register(&mut self, entry: RegistryEntry) -> Result<(), String> {
+        let id = entry.type_id.to_string();
+        TypeId::parse(&id)?;
+        if let Some(existing) = self.entries.get(&id) {
+            if *existing == entry {
+                return Ok(());
+            }
+            return Err(format!("type {} is already registered differently", id));
+        }
+        for alias in entry.aliases.iter() {
+            if let Some(other) = self.aliases.get(&alias.to_ascii_lowercase()) {
+                return Err(format!("port type {:?} already names {}", alias, other));
+            }
+        }
+        for alias in entry.aliases.iter() {
+            self.aliases.insert(alias.to_ascii_lowercase(), id.clone());
+        }
+        self.entries.insert(id, entry);
+        Ok(())
+    }
+
+    pub fn get(&self, type_id: &TypeId) -> Option<&RegistryEntry> {
+        self.entries.get(&type_id.to_string())
+    }
+
+    /// All types, ordered by TypeId
+    pub fn entries(&self) -> impl Iterator<Item = &RegistryEntry> {
+        self.entries.values()
+    }
+
+    /// Registry entry of a port type, None for a free-form port type that is no TypeId
+    /// like `image/png`, an error for a TypeId that is not registered
+    pub fn resolve(&self, port_type: &str) -> Result<Option<&RegistryEntry>, String> {
+        let port_type = port_type.trim();
+        if let Some(id) = self.aliases.get(&port_type.to_ascii_lowercase()) {
+            return Ok(self.entries.get(id));
+        }
+        let Ok(type_id) = TypeId::parse(port_type) else {
+            return Ok(None);
+        };
+        match self.get(&type_id) {
+            Some(entry) => Ok(Some(entry)),
+            None => Err(format!("unknown type {}", type_id)),
+        }
+    }
+
+    /// Whether packets of the `producer` type can go to a port of the `consumer` type
+    pub fn compatibility(&self, producer: &TypeId, consumer: &TypeId) -> CompatibilityResult {
+        if producer == consumer {
+            return CompatibilityResult::CompatibleExact;
+        }
+        if producer.to_string() == ANY_TYPE || consumer.to_string() == ANY_TYPE {
+            return CompatibilityResult::CompatibleDeclared;
+        }
+        let declared = self
+            .get(consumer)
+            .is_some_and(|entry| entry.compatible_from.contains(producer));
+        if declared {
+            CompatibilityResult::CompatibleDeclared
+        } else if producer.namespace == consumer.namespace && producer.name == consumer.name {
+            CompatibilityResult::IncompatibleVersion
+        } else {
+            CompatibilityResult::IncompatibleType
+        }
+    }
+
+    /// Contract to check the packets of a port of this type against, None if it has no schema
+    pub fn contract(&self, port_type: &str) -> Option<PortContract> {
+        let entry = self.resolve(port_type).ok().flatten()?;
+        Some(PortContract {
+            type_id: entry.type_id.clone(),
+            schema: entry.schema.clone()?,
+        })
+    }
+}
+
+/// Declared type of a port, checked on the packets sent in debug builds
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortContract {
+    pub type_id: TypeId,
+    pub schema: JsonValue,
+}
+
+impl PortContract {
+    /// Check a message against the schema, control and trace messages always conform
+    pub fn check(&self, msg: &FbpMessage) -> Result<(), String> {
+        let value = match msg {
+            FbpMessage::Text(text) => Packet::Text(text),
+            FbpMessage::Bytes(_) => Packet::Bytes,
+            FbpMessage::Value(value) => Packet::Value(value),
+            _ => return Ok(()),
+        };
+        conforms(&value, &self.schema, "")
+            .map_err(|err| format!("packet is no {}: {}", self.type_id, err))
+    }
+}
+
+enum Packet<'a> {
+    Text(&'a str),
+    Bytes,
+    Value(&'a FbpValue),
+}
+
+// subset of JSON Schema: type, enum, properties, required, additionalProperties and items
+fn conforms(packet: &Packet, schema: &JsonValue, path: &str) -> Result<(), String> {
+    let at = |path: &str| {
+        if path.is_empty() {
+            String::new()
+        } else {
+            format!(" at {}", path)
+        }
+    };
+    let json_type = match packet {
+        Packet::Text(_) | Packet::Bytes => "string",
+        Packet::Value(value) => match value {
+            FbpValue::Null => "null",
+            FbpValue::Bool(_) => "boolean",
+            FbpValue::Int(_) => "integer",
+            FbpValue::Float(_) => "number",
+            FbpValue::Text(_) | FbpValue::Bytes(_) => "string",
+            FbpValue::List(_) => "array",
+            FbpValue::Map(_) => "object",
+        },
+    };
+    if let Some(expected) = schema.get("type") {
+        let allowed = |typ: &str| typ == json_type || (typ == "number" && json_type == "integer");
+        let matches = match expected {
+            JsonValue::String(typ) => allowed(typ),
+            JsonValue::Array(types) => types.iter().filter_map(JsonValue::as_str).any(allowed),
+            _ => true,
+        };
+        if !matches {
+            return Err(format!(
+                "expected {}, got {}{}",
+                expected,
+                json_type,
+                at(path)
+            ));
+        }
+    }
+    if let Some(JsonValue::Array(options)) = schema.get("enum") {
+        let json = match packet {
+            Packet::Text(text) => Some(JsonValue::from(*text)),
+            Packet::Bytes => None,
+            Packet::Value(value) => JsonValue::try_from(*value).ok(),
+        };
+        if !json.is_some_and(|json| options.contains(&json)) {
+            return Err(format!("value not one of {:?}{}", options, at(path)));
+        }
+    }
+    let Packet::Value(value) = packet else {
+        return Ok(());
+    };
+    match value {
+        FbpValue::Map(entries) => {
+            let properties = schema.get("properties").and_then(JsonValue::as_object);
+            if let Some(JsonValue::Array(required)) = schema.get("required") {
+                for field in required.iter().filter_map(JsonValue::as_str) {
+                    if !entries.contains_key(field) {
+                        return Err(format!("missing field {}{}", field, at(path)));
+                    }
+                }
+            }
+            // sorted for deterministic error messages
+            let mut fields: Vec<_> = entries.iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            for (field, value) in fields {
+                let field_path = if path.is_empty() {
+                    field.clone()
+                } else {
+                    format!("{}.{}", path, field)
+                };
+                match properties.and_then(|properties| properties.get(field)) {
+                    Some(field_schema) => {
+                        conforms(&Packet::Value(value), field_schema, &field_path)?
+                    }
+                    None if schema.get("additionalProperties") == Some(&JsonValue::Bool(false)) => {
+                        return Err(format!("unknown field {}{}", field, at(path)));
+                    }
+                    None => {}
+                }
+            }
+        }
+        FbpValue::List(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    let item_path = format!("{}[{}]", path, index);
+                    conforms(&Packet::Value(item), item_schema, &item_path)?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,686 @@
+// Serde bridge between FbpValue and Rust types
+//
+// FbpValue is used as a serde data format of its own, so any #[derive(Serialize, Deserialize)] type
+// can travel as FbpMessage::Value without a round-trip through JSON text:
+//
+//     let msg = FbpMessage::from_serialize(&Reading { sensor: "t1".into(), celsius: 21.5 })?;
+//     let reading: Reading = msg.deserialize()?;
+//
+// The mapping follows serde_json: structs and maps become Map, sequences and tuples become List,
+// unit variants become Text and other enum variants a Map with the variant name as the only key.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize};
+use serde_json::{Map as JsonMap, Number as JsonNumber, Value as JsonValue};
+
+use crate::{FbpMessage, FbpValue};
+
+/// Error of converting between FbpValue and a Rust type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err.0)
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Convert any serializable value into an FbpValue
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> std::io::Result<FbpValue> {
+    Ok(value.serialize(ValueSerializer)?)
+}
+
+/// Convert an FbpValue into any deserializable type
+pub fn from_value<T: DeserializeOwned>(value: &FbpValue) -> std::io::Result<T> {
+    Ok(T::deserialize(value)?)
+}
+
+impl FbpMessage {
+    /// Create a Value message from any serializable type
+    pub fn from_serialize<T: Serialize + ?Sized>(value: &T) -> std::io::Result<Self> {
+        to_value(value).map(FbpMessage::Value)
+    }
+
+    /// Read the message as `T`, from a Value message or from JSON sent as Text or Bytes
+    pub fn deserialize<T: DeserializeOwned>(&self) -> std::io::Result<T> {
+        match self {
+            FbpMessage::Value(value) => from_value(value),
+            FbpMessage::Text(text) => Ok(serde_json::from_str(text)?),
+            FbpMessage::Bytes(bytes) => Ok(serde_json::from_slice(bytes)?),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "message carries no data",
+            )),
+        }
+    }
+}
+
+// lossless conversion to and from JSON, values JSON cannot represent exactly are refused
+
+impl TryFrom<JsonValue> for FbpValue {
+    type Error = std::io::Error;
+
+    fn try_from(json: JsonValue) -> std::io::Result<Self> {
+        Ok(match json {
+            JsonValue::Null => FbpValue::Null,
+            JsonValue::Bool(b) => FbpValue::Bool(b),
+            JsonValue::Number(number) => {
+                if let Some(i) = number.as_i64() {
+                    FbpValue::Int(i)
+                } else if number.is_u64() {
+                    return Err(Error(format!("integer {} out of range", number)).into());
+                } else {
+                    // neither i64 nor u64, so serde_json holds it as f64
+                    FbpValue::Float(number.as_f64().unwrap_or_default())
+                }
+            }
+            JsonValue::String(text) => FbpValue::Text(text.into()),
+            JsonValue::Array(items) => FbpValue::List(Arc::new(
+                items
+                    .into_iter()
+                    .map(FbpValue::try_from)
+                    .collect::<std::io::Result<_>>()?,
+            )),
+            JsonValue::Object(entries) => FbpValue::Map(Arc::new(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, FbpValue::try_from(value)?)))
+                    .collect::<std::io::Result<_>>()?,
+            )),
+        })
+    }
+}
+
+impl TryFrom<&FbpValue> for JsonValue {
+    type Error = std::io::Error;
+
+    fn try_from(value: &FbpValue) -> std::io::Result<Self> {
+        Ok(match value {
+            FbpValue::Null => JsonValue::Null,
+            FbpValue::Bool(b) => JsonValue::Bool(*b),
+            FbpValue::Int(i) => JsonValue::Number((*i).into()),
+            FbpValue::Float(f) => JsonValue::Number(
+                JsonNumber::from_f64(*f)
+                    .ok_or_else(|| Error(format!("float {} has no JSON representation", f)))?,
+            ),
+            FbpValue::Text(text) => JsonValue::String(text.to_string()),
+            FbpValue::Bytes(_) => {
+                return Err(Error(String::from("bytes have no JSON representation")).into())
+            }
+            FbpValue::List(items) => JsonValue::Array(
+                items
+                    .iter()
+                    .map(JsonValue::try_from)
+                    .collect::<std::io::Result<_>>()?,
+            ),
+            FbpValue::Map(entries) => JsonValue::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), JsonValue::try_from(value)?)))
+                    .collect::<std::io::Result<JsonMap<_, _>>>()?,
+            ),
+        })
+    }
+}
+
+impl TryFrom<FbpValue> for JsonValue {
+    type Error = std::io::Error;
+
+    fn try_from(value: FbpValue) -> std::io::Result<Self> {
+        JsonValue::try_from(&value)
+    }
+}
+
+// FbpValue as serializer output
+
+struct ValueSerializer;
+
+fn int<T: TryInto<i64> + fmt::Display + Copy>(value: T) -> Result<FbpValue> {
+    value
+        .try_into()
+        .map(FbpValue::Int)
+        .map_err(|_| Error(format!("integer {} out of range", value)))
+}
+
+fn variant_map(variant: &str, value: FbpValue) -> FbpValue {
+    FbpValue::Map(Arc::new(HashMap::from([(variant.to_owned(), value)])))
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = FbpValue;
+    type Error = Error;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<FbpValue> {
+        Ok(FbpValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<FbpValue> {
+        int(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<FbpValue> {
+        Ok(FbpValue::Float(v as f64))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<FbpValue> {
+        Ok(FbpValue::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<FbpValue> {
+        Ok(FbpValue::Text(v.to_string().into()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<FbpValue> {
+        Ok(FbpValue::Text(v.into()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<FbpValue> {
+        Ok(FbpValue::Bytes(v.into()))
+    }
+
+    fn serialize_none(self) -> Result<FbpValue> {
+        Ok(FbpValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<FbpValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<FbpValue> {
+        Ok(FbpValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<FbpValue> {
+        Ok(FbpValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<FbpValue> {
+        Ok(FbpValue::Text(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<FbpValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<FbpValue> {
+        Ok(variant_map(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList> {
+        Ok(SerializeList {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeList> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeList> {
+        Ok(SerializeList {
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap> {
+        Ok(SerializeMap {
+            variant: None,
+            entries: HashMap::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap> {
+        Ok(SerializeMap {
+            variant: Some(variant),
+            entries: HashMap::with_capacity(len),
+            key: None,
+        })
+    }
+}
+
+struct SerializeList {
+    variant: Option<&'static str>,
+    items: Vec<FbpValue>,
+}
+
+impl SerializeList {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.items.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<FbpValue> {
+        let list = FbpValue::List(Arc::new(self.items));
+        Ok(match self.variant {
+            Some(variant) => variant_map(variant, list),
+            None => list,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = FbpValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<FbpValue> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = FbpValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<FbpValue> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = FbpValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<FbpValue> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = FbpValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<FbpValue> {
+        self.finish()
+    }
+}
+
+struct SerializeMap {
+    variant: Option<&'static str>,
+    entries: HashMap<String, FbpValue>,
+    key: Option<String>,
+}
+
+impl SerializeMap {
+    fn finish(self) -> Result<FbpValue> {
+        let map = FbpValue::Map(Arc::new(self.entries));
+        Ok(match self.variant {
+            Some(variant) => variant_map(variant, map),
+            None => map,
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = FbpValue;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        // like JSON, map keys are strings and scalar keys are written as their text
+        self.key = Some(match key.serialize(ValueSerializer)? {
+            FbpValue::Text(text) => text.to_string(),
+            FbpValue::Int(i) => i.to_string(),
+            FbpValue::Bool(b) => b.to_string(),
+            _ => return Err(Error(String::from("map key must be a string"))),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error(String::from("map value without key")))?;
+        self.entries.insert(key, value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<FbpValue> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = FbpValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.entries
+            .insert(key.to_owned(), value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<FbpValue> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = FbpValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<FbpValue> {
+        self.finish()
+    }
+}
+
+// FbpValue as deserializer input, borrowing strings and bytes from the value
+
+impl<'de> de::Deserializer<'de> for &'de FbpValue {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            FbpValue::Null => visitor.visit_unit(),
+            FbpValue::Bool(b) => visitor.visit_bool(*b),
+            FbpValue::Int(i) => visitor.visit_i64(*i),
+            FbpValue::Float(f) => visitor.visit_f64(*f),
+            FbpValue::Text(text) => visitor.visit_borrowed_str(text),
+            FbpValue::Bytes(bytes) => visitor.visit_borrowed_bytes(bytes),
+            FbpValue::List(items) => {
+                let mut seq = SeqDeserializer::new(items.iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            FbpValue::Map(entries) => {
+                let mut map =
+                    MapDeserializer::new(entries.iter().map(|(key, value)| (MapKey(key), value)));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            FbpValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            FbpValue::Text(variant) => visitor.visit_enum(variant.as_ref().into_deserializer()),
+            FbpValue::Map(entries) if entries.len() == 1 => {
+                let (variant, value) = entries.iter().next().expect("map has one entry");
+                visitor.visit_enum(Variant { variant, value })
+            }
+            _ => Err(Error(String::from(
+                "expected an enum variant name or a map with a single entry",
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de FbpValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+// map keys are always text, numeric keys are parsed like serde_json does
+struct MapKey<'de>(&'de str);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                match self.0.parse() {
+                    Ok(number) => visitor.$visit(number),
+                    Err(_) => visitor.visit_borrowed_str(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKey<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8, deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32, deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8, deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32, deserialize_u64 => visit_u64
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for MapKey<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct Variant<'de> {
+    variant: &'de str,
+    value: &'de FbpValue,
+}
+
+impl<'de> EnumAccess<'de> for Variant<'de> {
+    type Error = Error;
+    type Variant = &'de FbpValue;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self::Variant)> {
+        let variant = seed.deserialize(MapKey(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &'de FbpValue {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self {
+            FbpValue::Null => Ok(()),
+            _ => Err(Error(String::from("expected a unit variant"))),
+        }
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
@@ -0,0 +1,228 @@
+// Canonical binary packet encoding, used wherever messages leave the process edge
+// (persistent edges, tracing, remote edges, external components).
+//
+// Wire format: MessagePack array [version, message]
+//   FbpMessage    map with a single key naming the variant, for example {"Text": "hello"}
+//   FbpValue      plain MessagePack: nil, bool, int, float 64, str, bin, array, map
+//   ControlEvent  variant name as str for variants without payload, otherwise a single-key map
+// Text is always encoded as MessagePack str and bytes as bin, so Text and Bytes never mix.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::Arc;
+
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::{FbpMessage, FbpValue};
+
+/// Version of the packet encoding written by `FbpMessage::encode()`
+pub const WIRE_FORMAT_VERSION: u8 = 1;
+
+impl FbpMessage {
+    /// Encode the message in the canonical binary packet encoding
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out)?;
+        Ok(out)
+    }
+
+    /// Append the encoded message to `out`
+    pub fn encode_into(&self, out: &mut Vec<u8>) -> Result<()> {
+        rmp::encode::write_array_len(out, 2)?;
+        rmp::encode::write_uint(out, WIRE_FORMAT_VERSION as u64)?;
+        self.serialize(&mut rmp_serde::Serializer::new(out).with_struct_map())
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err.to_string()))
+    }
+
+    /// Decode a message encoded by `encode()`
+    pub fn decode(mut input: &[u8]) -> Result<Self> {
+        let len = rmp::decode::read_array_len(&mut input).map_err(invalid)?;
+        let version: u8 = rmp::decode::read_int(&mut input).map_err(invalid)?;
+        if len != 2 || version != WIRE_FORMAT_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported packet encoding version {}", version),
+            ));
+        }
+        let mut deserializer = rmp_serde::Deserializer::new(&mut input);
+        let message =
+            <FbpMessage as Deserialize>::deserialize(&mut deserializer).map_err(invalid)?;
+        if !input.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "trailing bytes after encoded packet",
+            ));
+        }
+        Ok(message)
+    }
+}
+
+fn invalid(err: impl fmt::Display) -> Error {
+    Error::new(ErrorKind::InvalidData, err.to_string())
+}
+
+// FbpValue maps onto the plain serde data model, so it also works for JSON and other formats
+
+impl Serialize for FbpValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            FbpValue::Null => serializer.serialize_unit(),
+            FbpValue::Bool(b) => serializer.serialize_bool(*b),
+            FbpValue::Int(i) => serializer.serialize_i64(*i),
+            FbpValue::Float(f) => serializer.serialize_f64(*f),
+            FbpValue::Text(text) => serializer.serialize_str(text),
+            FbpValue::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            FbpValue::List(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items.iter() {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            FbpValue::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries.iter() {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FbpValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(FbpValueVisitor)
+    }
+}
+
+struct FbpValueVisitor;
+
+impl<'de> Visitor<'de> for FbpValueVisitor {
+    type Value = FbpValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an FBP value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<FbpValue, E> {
+        Ok(FbpValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> std::result::Result<FbpValue, E> {
+        Ok(FbpValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<FbpValue, D::Error> {
+        FbpValue::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> std::result::Result<FbpValue, E> {
+        Ok(FbpValue::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, i: i64) -> std::result::Result<FbpValue, E> {
+        Ok(FbpValue::Int(i))
+    }
+
+    fn visit_u64<E: de::Error>(self, u: u64) -> std::result::Result<FbpValue, E> {
+        i64::try_from(u)
+            .map(FbpValue::Int)
+            .map_err(|_| E::custom(format!("integer {} out of range", u)))
+    }
+
+    fn visit_f64<E: de::Error>(self, f: f64) -> std::result::Result<FbpValue, E> {
+        Ok(FbpValue::Float(f))
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> std::result::Result<FbpValue, E> {
+        Ok(FbpValue::Text(text.into()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> std::result::Result<FbpValue, E> {
+        Ok(FbpValue::Bytes(bytes.into()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<FbpValue, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(FbpValue::List(Arc::new(items)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<FbpValue, A::Error> {
+        let mut entries = HashMap::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some((key, value)) = map.next_entry::<String, FbpValue>()? {
+            entries.insert(key, value);
+        }
+        Ok(FbpValue::Map(Arc::new(entries)))
+    }
+}
+
+// shared payloads of FbpMessage::Bytes and FbpMessage::Text
+
+pub(crate) mod arc_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Arc<[u8]>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Arc<[u8]>, D::Error> {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Arc<[u8]>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("bytes")
+        }
+
+        fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> std::result::Result<Arc<[u8]>, E> {
+            Ok(bytes.into())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> std::result::Result<Arc<[u8]>, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes.into())
+        }
+    }
+}
+
+pub(crate) mod arc_str {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        text: &Arc<str>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Arc<str>, D::Error> {
+        String::deserialize(deserializer).map(Arc::from)
+    }
+}
@@ -0,0 +1,482 @@
+// #[derive(FlowdComponent)] for components declared by their ports
+//
+// Generates the Component impl of a struct: new() takes the connections out of the port maps,
+// process() answers ping and stop on the signal channel and then calls ProcessData::process_data(),
+// get_metadata() lists the declared ports. See flowd_component_api::ProcessData for an example.
+//
+// Field attributes:
+//   #[inport(...)], #[outport(...)]  port, kind by field type:
+//       ProcessEdgeSource / ProcessEdgeSink          single connection
+//       Option<ProcessEdgeSource / ProcessEdgeSink>  may be unconnected, or reconnected later
+//       Vec<ProcessEdgeSource / ProcessEdgeSink>     addressable port
+//   #[config(...)]  configuration IIP, port name defaults to CONF:
+//       ConfigInport  IIP as it arrived
+//       Config<T>     IIP checked against T::schema() and deserialized, the schema is published
+//                     in ComponentPort.schema, see flowd_component_api::config
+// Port arguments: name = "IN" (default: field name uppercased), type = "any", description = "",
+// required = bool (default: true unless Option or config), default = "" (default value).
+// Fields without attribute are filled by type: ProcessSignalSource, ProcessSignalSink,
+// GraphInportOutportHandle, Option<SchedulerWaker>, everything else with Default::default().
+//
+// Struct attribute:
+//   #[component(name = "Repeat", description = "", icon = "", reconnect)]
+//   name defaults to the struct name without "Component" suffix; reconnect generates
+//   reconnect_inport/outport(), which requires all ports to be Option or config.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitBool, LitStr, Result, Type,
+};
+
+#[proc_macro_derive(FlowdComponent, attributes(component, inport, outport, config))]
+pub fn derive_flowd_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    In,
+    Out,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Arity {
+    Single,
+    Optional,
+    Array,
+}
+
+struct Port {
+    field: Ident,
+    direction: Direction,
+    arity: Arity,
+    config: bool,
+    schema: Option<Type>, // T of a Config<T> field
+    name: String,
+    allowed_type: String,
+    description: String,
+    required: bool,
+    value_default: String,
+}
+
+#[derive(Default)]
+struct ComponentAttr {
+    name: Option<String>,
+    description: String,
+    icon: String,
+    reconnect: bool,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            ident,
+            "FlowdComponent can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            ident,
+            "FlowdComponent needs a struct with named fields",
+        ));
+    };
+    let component = component_attr(&input)?;
+    let component_name = component.name.clone().unwrap_or_else(|| {
+        let name = ident.to_string();
+        name.strip_suffix("Component").unwrap_or(&name).to_owned()
+    });
+
+    let mut ports = Vec::new();
+    let mut initializers = Vec::new();
+    let mut signals_in = None;
+    let mut signals_out = None;
+    for field in fields.named.iter() {
+        let field_ident = field.ident.clone().expect("named field");
+        let mut port = None;
+        for attr in field.attrs.iter() {
+            let (direction, config) = if attr.path().is_ident("inport") {
+                (Direction::In, false)
+            } else if attr.path().is_ident("outport") {
+                (Direction::Out, false)
+            } else if attr.path().is_ident("config") {
+                (Direction::In, true)
+            } else {
+                continue;
+            };
+            if port.is_some() {
+                return Err(Error::new_spanned(
+                    attr,
+                    "field is declared as port more than once",
+                ));
+            }
+            port = Some(port_attr(attr, &field_ident, &field.ty, direction, config)?);
+        }
+
+        let init = match &port {
+            Some(port) => port_initializer(port, &component_name),
+            None => match type_name(&field.ty).as_deref() {
+                Some("ProcessSignalSource") => {
+                    signals_in = Some(field_ident.clone());
+                    quote! { signals_in }
+                }
+                Some("ProcessSignalSink") => {
+                    signals_out = Some(field_ident.clone());
+                    quote! { signals_out }
+                }
+                Some("GraphInportOutportHandle") => quote! { graph_inout },
+                Some("Option")
+                    if generic_inner(&field.ty).and_then(type_name).as_deref()
+                        == Some("SchedulerWaker") =>
+                {
+                    quote! { scheduler_waker }
+                }
+                _ => quote! { ::core::default::Default::default() },
+            },
+        };
+        initializers.push(quote! { #field_ident: #init });
+        ports.extend(port);
+    }
+    let (Some(signals_in), Some(signals_out)) = (signals_in, signals_out) else {
+        return Err(Error::new_spanned(
+            ident,
+            "FlowdComponent needs a ProcessSignalSource and a ProcessSignalSink field",
+        ));
+    };
+    if component.reconnect {
+        if let Some(port) = ports
+            .iter()
+            .find(|port| port.arity != Arity::Optional && !port.config)
+        {
+            return Err(Error::new_spanned(
+                &port.field,
+                "reconnect requires Option<ProcessEdgeSource/ProcessEdgeSink> or config ports",
+            ));
+        }
+    }
+
+    let metadata = |direction: Direction| {
+        let ports = ports
+            .iter()
+            .filter(|port| port.direction == direction)
+            .map(|port| {
+                let Port {
+                    name,
+                    allowed_type,
+                    description,
+                    required,
+                    value_default,
+                    ..
+                } = port;
+                let is_arrayport = port.arity == Arity::Array;
+                let schema = match &port.schema {
+                    Some(config) => quote! {
+                        ::core::option::Option::Some(
+                            <#config as ::flowd_component_api::ComponentConfig>::schema()
+                                .to_json_schema()
+                                .to_string()
+                        )
+                    },
+                    None => quote! { ::core::option::Option::None },
+                };
+                quote! {
+                    ::flowd_component_api::ComponentPort {
+                        name: ::std::string::String::from(#name),
+                        allowed_type: ::std::string::String::from(#allowed_type),
+                        schema: #schema,
+                        required: #required,
+                        is_arrayport: #is_arrayport,
+                        description: ::std::string::String::from(#description),
+                        values_allowed: ::std::vec::Vec::new(),
+                        value_default: ::std::string::String::from(#value_default),
+                    }
+                }
+            });
+        quote! { ::std::vec![#(#ports),*] }
+    };
+    let in_ports = metadata(Direction::In);
+    let out_ports = metadata(Direction::Out);
+    let description = &component.description;
+    let icon = &component.icon;
+    let reconnect = component.reconnect;
+    let reconnect_fns = if reconnect {
+        let reconnect_fn = |direction: Direction| {
+            let arms = ports
+                .iter()
+                .filter(|port| port.direction == direction)
+                .map(|port| {
+                    let (name, field) = (&port.name, &port.field);
+                    if port.config {
+                        return quote! {
+                            #name => ::core::result::Result::Ok(self.#field.reconnect(connection)),
+                        };
+                    }
+                    quote! {
+                        #name => ::core::result::Result::Ok(
+                            ::core::mem::replace(&mut self.#field, connection)
+                        ),
+                    }
+                });
+            let (fn_name, connection_type, kind) = match direction {
+                Direction::In => (
+                    quote! { reconnect_inport },
+                    quote! { ProcessEdgeSource },
+                    "inport",
+                ),
+                Direction::Out => (
+                    quote! { reconnect_outport },
+                    quote! { ProcessEdgeSink },
+                    "outport",
+                ),
+            };
+            let unknown = format!("{} has no {} {{}}", component_name, kind);
+            quote! {
+                fn #fn_name(
+                    &mut self,
+                    port: &str,
+                    connection: ::core::option::Option<::flowd_component_api::#connection_type>,
+                ) -> ::core::result::Result<
+                    ::core::option::Option<::flowd_component_api::#connection_type>,
+                    ::std::io::Error,
+                > {
+                    match port {
+                        #(#arms)*
+                        _ => ::core::result::Result::Err(::std::io::Error::new(
+                            ::std::io::ErrorKind::InvalidInput,
+                            ::std::format!(#unknown, port),
+                        )),
+                    }
+                }
+            }
+        };
+        let inport = reconnect_fn(Direction::In);
+        let outport = reconnect_fn(Direction::Out);
+        quote! { #inport #outport }
+    } else {
+        quote! {}
+    };
+
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::flowd_component_api::Component for #ident #type_generics
+        #where_clause
+        {
+            #[allow(unused_mut, unused_variables)]
+            fn new(
+                mut inports: ::flowd_component_api::ProcessInports,
+                mut outports: ::flowd_component_api::ProcessOutports,
+                signals_in: ::flowd_component_api::ProcessSignalSource,
+                signals_out: ::flowd_component_api::ProcessSignalSink,
+                graph_inout: ::flowd_component_api::GraphInportOutportHandle,
+                scheduler_waker: ::core::option::Option<::flowd_component_api::SchedulerWaker>,
+            ) -> Self {
+                #ident {
+                    #(#initializers),*
+                }
+            }
+
+            fn process(
+                &mut self,
+                context: &mut ::flowd_component_api::NodeContext,
+            ) -> ::flowd_component_api::ProcessResult {
+                if ::flowd_component_api::handle_signals(&self.#signals_in, &self.#signals_out) {
+                    return ::flowd_component_api::ProcessResult::Finished;
+                }
+                ::flowd_component_api::ProcessData::process_data(self, context)
+            }
+
+            fn get_metadata() -> ::flowd_component_api::ComponentComponentPayload {
+                ::flowd_component_api::ComponentComponentPayload {
+                    name: ::std::string::String::from(#component_name),
+                    description: ::std::string::String::from(#description),
+                    icon: ::std::string::String::from(#icon),
+                    subgraph: false,
+                    in_ports: #in_ports,
+                    out_ports: #out_ports,
+                    support_reconnect: #reconnect,
+                    ..::core::default::Default::default()
+                }
+            }
+
+            #reconnect_fns
+
+            fn snapshot(&self) -> ::core::option::Option<::flowd_component_api::FbpValue> {
+                ::flowd_component_api::ProcessData::snapshot(self)
+            }
+
+            fn restore(
+                &mut self,
+                state: ::flowd_component_api::FbpValue,
+            ) -> ::core::result::Result<(), ::std::io::Error> {
+                ::flowd_component_api::ProcessData::restore(self, state)
+            }
+        }
+    })
+}
+
+fn component_attr(input: &DeriveInput) -> Result<ComponentAttr> {
+    let mut component = ComponentAttr::default();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("component"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                component.name = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("description") {
+                component.description = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("icon") {
+                component.icon = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("reconnect") {
+                component.reconnect = true;
+            } else {
+                return Err(meta.error("unknown component argument"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(component)
+}
+
+fn port_attr(
+    attr: &syn::Attribute,
+    field: &Ident,
+    ty: &Type,
+    direction: Direction,
+    config: bool,
+) -> Result<Port> {
+    let mut schema = None;
+    let arity = if config {
+        match type_name(ty).as_deref() {
+            Some("ConfigInport") => {}
+            Some("Config") if generic_inner(ty).is_some() => schema = generic_inner(ty).cloned(),
+            _ => {
+                return Err(Error::new_spanned(
+                    ty,
+                    "#[config] field must be a ConfigInport or Config<T>",
+                ))
+            }
+        }
+        Arity::Single
+    } else {
+        let connection = match direction {
+            Direction::In => "ProcessEdgeSource",
+            Direction::Out => "ProcessEdgeSink",
+        };
+        let (arity, inner) = match type_name(ty).as_deref() {
+            Some("Option") => (Arity::Optional, generic_inner(ty)),
+            Some("Vec") => (Arity::Array, generic_inner(ty)),
+            _ => (Arity::Single, Some(ty)),
+        };
+        if inner.and_then(type_name).as_deref() != Some(connection) {
+            return Err(Error::new_spanned(
+                ty,
+                format!(
+                    "port field must be {0}, Option<{0}> or Vec<{0}>",
+                    connection
+                ),
+            ));
+        }
+        arity
+    };
+    let mut port = Port {
+        field: field.clone(),
+        direction,
+        arity,
+        config,
+        schema,
+        name: if config {
+            String::from("CONF")
+        } else {
+            field.to_string().to_ascii_uppercase()
+        },
+        allowed_type: String::from(if config { "string" } else { "any" }),
+        description: String::new(),
+        required: arity != Arity::Optional && !config,
+        value_default: String::new(),
+    };
+    // #[inport] without arguments takes the defaults
+    if matches!(attr.meta, syn::Meta::Path(_)) {
+        return Ok(port);
+    }
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("name") {
+            port.name = meta.value()?.parse::<LitStr>()?.value();
+        } else if meta.path.is_ident("type") {
+            port.allowed_type = meta.value()?.parse::<LitStr>()?.value();
+        } else if meta.path.is_ident("description") {
+            port.description = meta.value()?.parse::<LitStr>()?.value();
+        } else if meta.path.is_ident("required") {
+            port.required = meta.value()?.parse::<LitBool>()?.value;
+        } else if meta.path.is_ident("default") {
+            port.value_default = meta.value()?.parse::<LitStr>()?.value();
+        } else {
+            return Err(meta.error("unknown port argument"));
+        }
+        Ok(())
+    })?;
+    Ok(port)
+}
+
+// expression taking the connection of `port` out of the inports/outports argument of new()
+fn port_initializer(port: &Port, component_name: &str) -> TokenStream2 {
+    let name = &port.name;
+    let ports = match port.direction {
+        Direction::In => quote! { inports },
+        Direction::Out => quote! { outports },
+    };
+    if port.config {
+        let config = match port.schema {
+            Some(_) => quote! { Config },
+            None => quote! { ConfigInport },
+        };
+        return quote! {
+            ::flowd_component_api::#config::new(
+                #ports.remove(#name).and_then(|mut sources| sources.pop())
+            )
+        };
+    }
+    match (port.arity, port.direction) {
+        (Arity::Optional, _) => {
+            quote! { #ports.remove(#name).and_then(|mut connections| connections.pop()) }
+        }
+        (Arity::Array, _) => quote! { #ports.remove(#name).unwrap_or_default() },
+        (Arity::Single, Direction::In) => {
+            quote! { ::flowd_component_api::take_inport(&mut #ports, #component_name, #name) }
+        }
+        (Arity::Single, Direction::Out) => {
+            quote! { ::flowd_component_api::take_outport(&mut #ports, #component_name, #name) }
+        }
+    }
+}
+
+// last path segment of a type, for example "Option" for std::option::Option<T>
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn generic_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let syn::PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
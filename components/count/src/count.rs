@@ -1,12 +1,14 @@
 use flowd_component_api::{
-    Component, ComponentComponentPayload, ComponentPort, GraphInportOutportHandle, NodeContext,
-    ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult,
-    ProcessSignalSink, ProcessSignalSource,
+    BracketChange, BracketTracker, Component, ComponentComponentPayload, ComponentConfig,
+    ComponentPort, Config, ConfigSchema, ConfigType, FbpMessage, FbpValue,
+    GraphInportOutportHandle, NodeContext, ProcessEdgeSink, ProcessEdgeSource, ProcessInports,
+    ProcessOutports, ProcessResult, ProcessSignalSink, ProcessSignalSource,
 };
 use log::{debug, error, info, trace, warn};
+use serde::Deserialize;
 
 pub struct CountComponent {
-    conf: ProcessEdgeSource,
+    conf: Config<CountConfig>,
     inn: ProcessEdgeSource,
     out: ProcessEdgeSink,
     signals_in: ProcessSignalSource,
@@ -14,29 +16,135 @@ pub struct CountComponent {
     graph_inout: GraphInportOutportHandle,
     // Runtime state
     mode: Option<Mode>,
+    scope: Scope,
     packets: usize,
     packetsize: usize,
     sum: u64,
     start: Option<chrono::DateTime<chrono::Utc>>,
     start_1st: Option<chrono::DateTime<chrono::Utc>>,
     pending_reports: std::collections::VecDeque<flowd_component_api::FbpMessage>,
+    // for per-bracket counting: nesting and the totals when each open bracket began
+    brackets: BracketTracker,
+    bracket_starts: Vec<(usize, usize, u64)>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Mode {
     Packets,
     Size,
     Sum,
 }
 
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Scope {
+    Stream,
+    Bracket,
+}
+
+#[derive(Deserialize)]
+struct CountConfig {
+    mode: Mode,
+    scope: Scope,
+}
+
+impl ComponentConfig for CountConfig {
+    fn schema() -> ConfigSchema {
+        ConfigSchema::new()
+            .required(
+                "mode",
+                ConfigType::Enum(vec![
+                    String::from("packets"),
+                    String::from("size"),
+                    String::from("sum"),
+                ]),
+                "count packets, their total size or sum up the numbers they contain",
+            )
+            .with_default(
+                "scope",
+                ConfigType::Enum(vec![String::from("stream"), String::from("bracket")]),
+                "stream",
+                "report running totals of the stream or also a count per bracket",
+            )
+            .shorthand("mode")
+    }
+}
+
+fn format_count(mode: Mode, (packets, packetsize, sum): (usize, usize, u64)) -> String {
+    //TODO optimize - instead of format try https://docs.rs/itoa/latest/itoa/
+    match mode {
+        Mode::Packets => format!("{}", packets),
+        Mode::Size => format!("{}", packetsize),
+        Mode::Sum => format!("{}", sum),
+    }
+}
+
 impl CountComponent {
+    fn totals(&self) -> (usize, usize, u64) {
+        (self.packets, self.packetsize, self.sum)
+    }
+
+    // keeps the order of reports and forwarded brackets if OUT is full
+    fn send(&mut self, message: FbpMessage) {
+        if !self.pending_reports.is_empty() {
+            self.pending_reports.push_back(message);
+        } else if let Err(flowd_component_api::PushError::Full(returned)) = self.out.push(message) {
+            self.pending_reports.push_back(returned);
+        }
+    }
+
+    // brackets are forwarded, each closing one preceded by the count of the bracket
+    fn count_bracketed(&mut self, mode: Mode, msg: FbpMessage) {
+        match self.brackets.observe(&msg) {
+            Some(BracketChange::Opened(_)) => {
+                self.bracket_starts.push(self.totals());
+                self.send(msg);
+            }
+            Some(BracketChange::Closed(_)) => {
+                let start = self.bracket_starts.pop().unwrap_or_default();
+                let (packets, packetsize, sum) = self.totals();
+                let count = (packets - start.0, packetsize - start.1, sum - start.2);
+                self.send(FbpMessage::from_text(format_count(mode, count)));
+                self.send(msg);
+            }
+            Some(BracketChange::Reset) => {
+                self.bracket_starts.clear();
+                self.send(msg);
+            }
+            None => self.count_message(mode, &msg),
+        }
+    }
+
+    fn count_message(&mut self, mode: Mode, msg: &FbpMessage) {
+        // control events are not data
+        if msg.as_control().is_some() {
+            return;
+        }
+        match mode {
+            Mode::Packets => self.packets += 1,
+            Mode::Size => {
+                self.packetsize += match msg {
+                    FbpMessage::Bytes(data) => data.len(),
+                    FbpMessage::Text(text) => text.len(),
+                    _ => 0, // Values and traces don't have inherent size
+                }
+            }
+            Mode::Sum => {
+                let value_str = msg.as_text()
+                    .or_else(|| msg.as_bytes().and_then(|b| std::str::from_utf8(b).ok()))
+                    .unwrap_or("");
+                if let Some(value) = atoi::atoi::<u64>(value_str.as_bytes()) {
+                    self.sum += value;
+                } else {
+                    error!("value '{}' cannot be summed up - skipping", value_str);
+                }
+            }
+        }
+    }
+
     fn emit_final_count(&mut self, mode: Mode) -> String {
-        let final_count = match mode {
-            //TODO optimize - instead of format try https://docs.rs/itoa/latest/itoa/
-            Mode::Packets => format!("{}", self.packets),
-            Mode::Size => format!("{}", self.packetsize),
-            Mode::Sum => format!("{}", self.sum),
-        };
+        let final_count = format_count(mode, self.totals());
 
         let message = flowd_component_api::FbpMessage::from_text(final_count.clone());
         if let Err(flowd_component_api::PushError::Full(returned)) =
@@ -69,11 +177,7 @@ impl Component for CountComponent {
         Self: Sized,
     {
         CountComponent {
-            conf: inports
-                .remove("CONF")
-                .expect("found no CONF inport")
-                .pop()
-                .unwrap(),
+            conf: Config::new(inports.remove("CONF").and_then(|mut sources| sources.pop())),
             inn: inports
                 .remove("IN")
                 .expect("found no IN inport")
@@ -88,12 +192,15 @@ impl Component for CountComponent {
             signals_out: signals_out,
             graph_inout,
             mode: None,
+            scope: Scope::Stream,
             packets: 0,
             packetsize: 0,
             sum: 0,
             start: None,
             start_1st: None,
             pending_reports: std::collections::VecDeque::new(),
+            brackets: BracketTracker::new(),
+            bracket_starts: Vec::new(),
         }
     }
 
@@ -103,28 +210,15 @@ impl Component for CountComponent {
         // Try to read configuration if not yet configured
         if self.mode.is_none() {
             trace!("reading config IP");
-            if let Ok(config_msg) = self.conf.pop() {
-                let raw_conf = config_msg.as_text()
-                    .or_else(|| config_msg.as_bytes().and_then(|b| std::str::from_utf8(b).ok()))
-                    .unwrap_or("");
-                let query = raw_conf.strip_prefix('?').unwrap_or(raw_conf);
-                let url_str = "https://makeurlhappy/?".to_owned() + query;
-                let url =
-                    url::Url::parse(url_str.as_str()).expect("failed to parse configuration URL");
-
-                if let Some((_key, value)) = url.query_pairs().find(|(key, _)| key == "mode") {
-                    self.mode = match value.to_string().as_str() {
-                        "packets" => Some(Mode::Packets),
-                        "size" => Some(Mode::Size),
-                        "sum" => Some(Mode::Sum),
-                        _ => {
-                            error!("unexpected mode, expected packets|size|sum - finishing");
-                            return ProcessResult::Finished;
-                        }
-                    };
+            match self.conf.get() {
+                Ok(Some(config)) => {
+                    self.mode = Some(config.mode);
+                    self.scope = config.scope;
                     self.start = Some(chrono::Utc::now());
-                } else {
-                    error!("no mode found in configuration URL - finishing");
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    error!("{} - finishing", err);
                     return ProcessResult::Finished;
                 }
             }
@@ -198,6 +292,16 @@ impl Component for CountComponent {
                 }
             }
 
+            if self.scope == Scope::Bracket {
+                let Ok(msg) = self.inn.pop() else {
+                    break;
+                };
+                self.count_bracketed(mode, msg);
+                work_units += 1;
+                context.remaining_budget -= 1;
+                continue;
+            }
+
             let available = self.inn.slots();
             let to_process = available.min(context.remaining_budget as usize);
             if to_process > 0 {
@@ -207,7 +311,14 @@ impl Component for CountComponent {
                     //TODO optimize - instead of if on every IP, prepare Fn and apply it without branching
                     match mode {
                         Mode::Packets => {
-                            self.packets += chunk.len();
+                            // control events like brackets are not counted
+                            let (first, second) = chunk.as_slices();
+                            let controls = first
+                                .iter()
+                                .chain(second)
+                                .filter(|msg| msg.as_control().is_some())
+                                .count();
+                            self.packets += chunk.len() - controls;
                             chunk.commit_all();
                         }
                         Mode::Size => {
@@ -228,6 +339,9 @@ impl Component for CountComponent {
                         }
                         Mode::Sum => {
                             for msg in chunk {
+                                if msg.as_control().is_some() {
+                                    continue;
+                                }
                                 // For sum mode, try to parse numeric values from messages
                                 let value_str = msg.as_text()
                                     .or_else(|| msg.as_bytes().and_then(|b| std::str::from_utf8(b).ok()))
@@ -253,7 +367,12 @@ impl Component for CountComponent {
 
         // Emit current totals when input goes idle so downstream observers can
         // see progress without requiring an EOF/stop race to flush.
-        if work_units > 0 && self.inn.is_empty() && !self.inn.is_abandoned() {
+        // Per bracket, the counts are reported at the end of each bracket instead.
+        if work_units > 0
+            && self.scope == Scope::Stream
+            && self.inn.is_empty()
+            && !self.inn.is_abandoned()
+        {
             let _ = self.emit_final_count(mode);
         }
 
@@ -282,6 +401,35 @@ impl Component for CountComponent {
         }
     }
 
+    // running totals survive restarts, mode and timing come from CONF again
+    fn snapshot(&self) -> Option<FbpValue> {
+        let mut state = std::collections::HashMap::new();
+        state.insert("packets".to_owned(), FbpValue::Int(self.packets as i64));
+        state.insert("packetsize".to_owned(), FbpValue::Int(self.packetsize as i64));
+        state.insert("sum".to_owned(), FbpValue::Int(self.sum as i64));
+        Some(FbpValue::Map(std::sync::Arc::new(state)))
+    }
+
+    fn restore(&mut self, state: FbpValue) -> Result<(), std::io::Error> {
+        let FbpValue::Map(state) = state else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "expected map of running totals",
+            ));
+        };
+        let total = |key: &str| match state.get(key) {
+            Some(FbpValue::Int(total)) => Ok(*total),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("missing running total {}", key),
+            )),
+        };
+        self.packets = total("packets")? as usize;
+        self.packetsize = total("packetsize")? as usize;
+        self.sum = total("sum")? as u64;
+        Ok(())
+    }
+
     fn get_metadata() -> ComponentComponentPayload
     where
         Self: Sized,
@@ -295,10 +443,10 @@ impl Component for CountComponent {
                 ComponentPort {
                     name: String::from("CONF"),
                     allowed_type: String::from("any"),
-                    schema: None,
+                    schema: Some(CountConfig::schema().to_json_schema().to_string()),
                     required: true,
                     is_arrayport: false,
-                    description: String::from("IPs to count"),
+                    description: String::from("configuration, e.g. ?mode=sum&scope=bracket"),
                     values_allowed: vec![],
                     value_default: String::from("")
                 },
@@ -316,7 +464,7 @@ impl Component for CountComponent {
             out_ports: vec![
                 ComponentPort {
                     name: String::from("OUT"),
-                    allowed_type: String::from("any"),
+                    allowed_type: String::from("string"),
                     schema: None,
                     required: true,
                     is_arrayport: false,
@@ -6,7 +6,7 @@ use flowd_component_api::{
 use log::{debug, info, trace, warn};
 
 pub struct DropComponent {
-    inn: ProcessEdgeSource,
+    inn: Option<ProcessEdgeSource>, // None while disconnected in a running network
     signals_in: ProcessSignalSource,
     signals_out: ProcessSignalSink,
     //graph_inout: GraphInportOutportHandle,
@@ -25,11 +25,8 @@ impl Component for DropComponent {
         Self: Sized,
     {
         DropComponent {
-            inn: inports
-                .remove("IN")
-                .expect("found no IN inport")
-                .pop()
-                .unwrap(),
+            // inport may be connected later via reconnect_inport() when added to a running network
+            inn: inports.remove("IN").and_then(|mut sources| sources.pop()),
             signals_in: signals_in,
             signals_out: signals_out,
             //graph_inout: graph_inout,
@@ -57,7 +54,7 @@ impl Component for DropComponent {
         }
 
         // check in port within budget
-        while context.remaining_budget > 0 && !self.inn.is_empty() {
+        while context.remaining_budget > 0 && self.inn.as_ref().is_some_and(|inn| !inn.is_empty()) {
             // stay responsive to stop/ping even while draining a busy input buffer
             if let Ok(sig) = self.signals_in.try_recv() {
                 let signal_text = sig.as_text().unwrap_or("");
@@ -71,10 +68,13 @@ impl Component for DropComponent {
                 }
             }
 
-            let available = self.inn.slots();
+            let Some(inn) = self.inn.as_mut() else {
+                break;
+            };
+            let available = inn.slots();
             let to_process = available.min(context.remaining_budget as usize);
             if to_process > 0 {
-                if let Ok(chunk) = self.inn.read_chunk(to_process) {
+                if let Ok(chunk) = inn.read_chunk(to_process) {
                     let num = chunk.len() as u32;
                     chunk.commit_all();
                     work_units += num;
@@ -91,7 +91,7 @@ impl Component for DropComponent {
             ProcessResult::DidWork(work_units)
         } else {
             // are we done?
-            if self.inn.is_abandoned() {
+            if self.inn.as_ref().is_some_and(|inn| inn.is_abandoned() && inn.is_empty()) {
                 info!("EOF on inport, finishing");
                 return ProcessResult::Finished;
             }
@@ -99,6 +99,20 @@ impl Component for DropComponent {
         }
     }
 
+    fn reconnect_inport(
+        &mut self,
+        port: &str,
+        source: Option<ProcessEdgeSource>,
+    ) -> Result<Option<ProcessEdgeSource>, std::io::Error> {
+        if port != "IN" {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Drop has no inport {}", port),
+            ));
+        }
+        Ok(std::mem::replace(&mut self.inn, source))
+    }
+
     fn get_metadata() -> ComponentComponentPayload
     where
         Self: Sized,
@@ -119,6 +133,7 @@ impl Component for DropComponent {
                 value_default: String::from(""),
             }],
             out_ports: vec![],
+            support_reconnect: true,
             ..Default::default()
         }
     }
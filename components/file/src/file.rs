@@ -31,7 +31,7 @@ pub struct FileReaderComponent {
     //graph_inout: GraphInportOutportHandle,
     // Runtime state
     filenames: std::collections::VecDeque<FbpMessage>,
-    pending_files: std::collections::VecDeque<FbpMessage>, // files to send, buffered for backpressure
+    pending_files: std::collections::VecDeque<FbpMessage>, // bracketed file contents to send, buffered for backpressure
     pending_read: Option<mpsc::Receiver<Result<Vec<u8>, std::io::Error>>>,
     scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
     finished: bool,
@@ -108,24 +108,6 @@ impl Component for FileReaderComponent {
             }
         }
 
-        // First, try to send any pending files that were buffered due to backpressure
-        while context.remaining_budget > 0 && !self.pending_files.is_empty() {
-            if let Some(pending_content) = self.pending_files.front() {
-                match self.out.push(pending_content.clone()) {
-                    Ok(()) => {
-                        self.pending_files.pop_front();
-                        work_units += 1;
-                        context.remaining_budget -= 1;
-                        debug!("sent pending file content");
-                    }
-                    Err(PushError::Full(_)) => {
-                        // Still can't send, stop trying for now
-                        break;
-                    }
-                }
-            }
-        }
-
         // Start one asynchronous file read when idle.
         if self.pending_read.is_none() && !self.filenames.is_empty() {
             if let Some(filename) = self.filenames.front() {
@@ -153,12 +135,11 @@ impl Component for FileReaderComponent {
                             Ok(text) => FbpMessage::from_str(text),
                             Err(_) => FbpMessage::from_bytes(contents),
                         };
-                        match self.out.push(content_msg) {
-                            Ok(()) => {}
-                            Err(PushError::Full(returned_content)) => {
-                                self.pending_files.push_back(returned_content);
-                            }
-                        }
+                        // each file forms a bracket named after it
+                        let filename = self.filenames.front().and_then(message_as_utf8).unwrap_or("");
+                        self.pending_files.push_back(FbpMessage::begin_bracket(filename));
+                        self.pending_files.push_back(content_msg);
+                        self.pending_files.push_back(FbpMessage::end_bracket(filename));
                         self.pending_read = None;
                         self.filenames.pop_front();
                         work_units += 1;
@@ -182,6 +163,24 @@ impl Component for FileReaderComponent {
             }
         }
 
+        // Send the brackets and contents of read files, buffered for backpressure
+        while context.remaining_budget > 0 && !self.pending_files.is_empty() {
+            if let Some(pending_content) = self.pending_files.front() {
+                match self.out.push(pending_content.clone()) {
+                    Ok(()) => {
+                        self.pending_files.pop_front();
+                        work_units += 1;
+                        context.remaining_budget -= 1;
+                        debug!("sent pending file content");
+                    }
+                    Err(PushError::Full(_)) => {
+                        // Still can't send, stop trying for now
+                        break;
+                    }
+                }
+            }
+        }
+
         if work_units > 0 {
             ProcessResult::DidWork(work_units)
         } else {
@@ -209,7 +208,7 @@ impl Component for FileReaderComponent {
         ComponentComponentPayload {
             name: String::from("FileReader"),
             description: String::from(
-                "Reads the contents of the given files and sends the contents.",
+                "Reads the contents of the given files and sends the contents, each file in a bracket named after it.",
             ),
             icon: String::from("file"),
             subgraph: false,
@@ -229,7 +228,7 @@ impl Component for FileReaderComponent {
                 schema: None,
                 required: true,
                 is_arrayport: false,
-                description: String::from("contents of the given file(s)"),
+                description: String::from("contents of the given file(s), each enclosed in a bracket named after the file"),
                 values_allowed: vec![],
                 value_default: String::from(""),
             }],
@@ -248,6 +247,43 @@ pub struct FileTailerComponent {
     filename: Option<&'static str>,
     file: Option<TailedFile<&'static str>>,
     pending_lines: std::collections::VecDeque<Vec<u8>>, // lines to send, buffered for backpressure
+    offset: u64, // file position up to which lines were sent, for checkpointing (not across log rotation)
+    restored: Option<(String, u64)>, // file name and read position from the last checkpoint
+}
+
+impl FileTailerComponent {
+    // tailing starts at the end of the file; after a restart, first send what was appended
+    // since the checkpoint, unless the file was truncated or rotated meanwhile
+    fn catch_up(&mut self, file_name: &str) {
+        let Ok(len) = std::fs::metadata(file_name).map(|meta| meta.len()) else {
+            return;
+        };
+        self.offset = len;
+        let Some((restored_name, restored_offset)) = self.restored.take() else {
+            return;
+        };
+        if restored_name != file_name || restored_offset >= len {
+            return;
+        }
+        let mut missed = Vec::new();
+        let result = File::open(file_name).and_then(|mut file| {
+            file.seek(std::io::SeekFrom::Start(restored_offset))?;
+            file.take(len - restored_offset).read_to_end(&mut missed)
+        });
+        match result {
+            Ok(_) => {
+                debug!("catching up on {} bytes appended since checkpoint", missed.len());
+                // one packet per line as when tailing, the offset advances as they are sent
+                self.offset = restored_offset;
+                self.pending_lines.extend(
+                    missed
+                        .split_inclusive(|byte| *byte == b'\n')
+                        .map(|line| line.to_vec()),
+                );
+            }
+            Err(err) => warn!("failed to read {} from checkpoint offset: {}", file_name, err),
+        }
+    }
 }
 
 impl Component for FileTailerComponent {
@@ -279,6 +315,8 @@ impl Component for FileTailerComponent {
             filename: None,
             file: None,
             pending_lines: std::collections::VecDeque::new(),
+            offset: 0,
+            restored: None,
         }
     }
 
@@ -302,6 +340,7 @@ impl Component for FileTailerComponent {
                     Ok(file) => {
                         self.file = Some(file);
                         trace!("opened file for tailing");
+                        self.catch_up(leaked_name);
                     }
                     Err(e) => {
                         warn!("failed to open file {}: {}", file_name, e);
@@ -345,6 +384,7 @@ impl Component for FileTailerComponent {
             if let Some(pending_line) = self.pending_lines.front() {
                 match self.out.push(pending_line.clone().into()) {
                     Ok(()) => {
+                        self.offset += pending_line.len() as u64;
                         self.pending_lines.pop_front();
                         work_units += 1;
                         context.remaining_budget -= 1;
@@ -384,10 +424,12 @@ impl Component for FileTailerComponent {
                 if let Ok(lines) = file.read() {
                     if lines.len() > 0 {
                         debug!("got line(s), sending...");
+                        let lines_len = lines.len() as u64;
                         // Try to send it
                         let lines_msg = FbpMessage::from_bytes(lines);
                         match self.out.push(lines_msg) {
                             Ok(()) => {
+                                self.offset += lines_len;
                                 work_units += 1;
                                 context.remaining_budget -= 1;
                                 debug!("done");
@@ -427,6 +469,30 @@ impl Component for FileTailerComponent {
         }
     }
 
+    fn snapshot(&self) -> Option<FbpValue> {
+        let filename = self.filename?;
+        let mut state = std::collections::HashMap::new();
+        state.insert("file".to_owned(), FbpValue::Text(filename.into()));
+        state.insert("offset".to_owned(), FbpValue::Int(self.offset as i64));
+        Some(FbpValue::Map(std::sync::Arc::new(state)))
+    }
+
+    fn restore(&mut self, state: FbpValue) -> Result<(), std::io::Error> {
+        let invalid = || {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "expected file and offset")
+        };
+        let FbpValue::Map(state) = state else {
+            return Err(invalid());
+        };
+        match (state.get("file"), state.get("offset")) {
+            (Some(FbpValue::Text(file)), Some(FbpValue::Int(offset))) if *offset >= 0 => {
+                self.restored = Some((file.to_string(), *offset as u64));
+                Ok(())
+            }
+            _ => Err(invalid()),
+        }
+    }
+
     fn get_metadata() -> ComponentComponentPayload
     where
         Self: Sized,
@@ -138,7 +138,7 @@ impl Component for HasherComponent {
             }],
             out_ports: vec![ComponentPort {
                 name: String::from("OUT"),
-                allowed_type: String::from("any"),
+                allowed_type: String::from("string"),
                 schema: None,
                 required: true,
                 is_arrayport: false,
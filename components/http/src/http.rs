@@ -416,7 +416,7 @@ enum HttpClientState {
 }
 
 pub struct HTTPClientComponent {
-    conf: ProcessEdgeSource,
+    conf: Option<ProcessEdgeSource>, // optional, defaults apply when unconnected
     req: ProcessEdgeSource,
     out_resp: ProcessEdgeSink,
     out_err: ProcessEdgeSink,
@@ -586,11 +586,7 @@ impl Component for HTTPClientComponent {
         }));
 
         HTTPClientComponent {
-            conf: inports
-                .remove("CONF")
-                .expect("found no CONF inport")
-                .pop()
-                .unwrap(),
+            conf: inports.remove("CONF").and_then(|mut conf| conf.pop()),
             req: inports
                 .remove("REQ")
                 .expect("found no REQ inport")
@@ -627,7 +623,7 @@ impl Component for HTTPClientComponent {
         debug!("HTTPClient is now process()ing!");
 
         // Check for configuration updates (ADR-017 compliance)
-        if let Ok(conf_msg) = self.conf.pop() {
+        if let Some(Ok(conf_msg)) = self.conf.as_mut().map(|conf| conf.pop()) {
             if let Some(conf_str) = conf_msg.as_text() {
                 // Parse JSON configuration for retry parameters
                 if let Ok(config) = serde_json::from_str::<serde_json::Value>(conf_str) {
@@ -1766,6 +1762,11 @@ impl Component for HTTPServerComponent {
         // Read responses from FBP network
         while context.remaining_budget > 0 {
             if let Ok(response_msg) = self.resp.pop() {
+                // one response per request, brackets and groups passed through by the network are no response
+                if response_msg.as_control().is_some() {
+                    trace!("skipping control packet on RESP: {:?}", response_msg);
+                    continue;
+                }
                 if let Some(request_id) = self.response_wait_queue.pop_front() {
                     let response_body = response_msg.as_bytes().unwrap_or(&[]).to_vec();
                     let response = HttpResponse {
@@ -790,7 +790,7 @@ pub struct IMAPFetchComponent {
     cmd_tx: Sender<NewFetchCommand>,
     result_rx: Receiver<NewFetchResult>,
     worker_handle: Option<JoinHandle<()>>,
-    pending_messages: VecDeque<Vec<u8>>,
+    pending_messages: VecDeque<FbpMessage>,
     poll_inflight: bool,
 }
 
@@ -868,10 +868,15 @@ impl Component for IMAPFetchComponent {
                     work_units += 1;
                 }
                 NewFetchResult::Messages(messages) => {
-                    for message_json in messages {
-                        let json_str = serde_json::to_string(&message_json)
-                            .unwrap_or_else(|_| r#"{"error": "failed to serialize message"}"#.to_string());
-                        self.pending_messages.push_back(json_str.into_bytes());
+                    // the messages of one fetch form a bracket
+                    if !messages.is_empty() {
+                        self.pending_messages.push_back(FbpMessage::begin_bracket(""));
+                        for message_json in messages {
+                            let json_str = serde_json::to_string(&message_json)
+                                .unwrap_or_else(|_| r#"{"error": "failed to serialize message"}"#.to_string());
+                            self.pending_messages.push_back(FbpMessage::from_bytes(json_str.into_bytes()));
+                        }
+                        self.pending_messages.push_back(FbpMessage::end_bracket(""));
                     }
                     self.poll_inflight = false;
                     work_units += 1;
@@ -894,10 +899,9 @@ impl Component for IMAPFetchComponent {
         }
 
         while context.remaining_budget > 0 {
-            let Some(msg) = self.pending_messages.front().cloned() else {
+            let Some(output_msg) = self.pending_messages.front().cloned() else {
                 break;
             };
-            let output_msg = FbpMessage::from_bytes(msg);
             match self.out.push(output_msg) {
                 Ok(()) => {
                     self.pending_messages.pop_front();
@@ -957,7 +961,7 @@ impl Component for IMAPFetchComponent {
     {
         ComponentComponentPayload {
             name: String::from("IMAPFetch"),
-            description: String::from("Fetches new messages from IMAP mailbox without deleting them, outputs UID and body for each message."),
+            description: String::from("Fetches new messages from IMAP mailbox without deleting them, outputs UID and body for each message, the messages of each fetch in a bracket."),
             icon: String::from("cloud-download"),
             subgraph: false,
             in_ports: vec![ComponentPort {
@@ -1,6 +1,6 @@
 use flowd_component_api::{
-    Component, ComponentComponentPayload, ComponentPort, FbpMessage, GraphInportOutportHandle, NodeContext,
-    ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult,
+    Component, ComponentComponentPayload, ComponentPort, FbpMessage, FbpValue, GraphInportOutportHandle,
+    NodeContext, ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult,
     ProcessSignalSink, ProcessSignalSource,
 };
 use log::{debug, error, info, trace, warn};
@@ -130,8 +130,9 @@ impl Component for JSONQueryComponent {
         while context.remaining_budget > 0 && !self.inn.is_empty() {
             if let Ok(ip) = self.inn.pop() {
                 debug!("got a packet, processing...");
-                let json_bytes = ip.as_bytes().unwrap_or(&[]);
-                match serde_json::from_slice::<serde_json::Value>(json_bytes) {
+                // structured values are answered with structured values, JSON text with JSON text
+                let structured = ip.as_value().is_some();
+                match ip.deserialize::<serde_json::Value>() {
                     Ok(input) => {
                         // Iterator over the output values
                         let result = filter.run((Ctx::new([], &inputs), Val::from(input)));
@@ -140,11 +141,21 @@ impl Component for JSONQueryComponent {
                             match value {
                                 Ok(val) => {
                                     // Prepare packet
-                                    let vec_out = format!("{}", val).into_bytes();
+                                    let output_msg = if structured {
+                                        match FbpValue::try_from(serde_json::Value::from(val)) {
+                                            Ok(value) => FbpMessage::Value(value),
+                                            Err(err) => {
+                                                error!("unrepresentable result: {} - discarding",
+                                                    err);
+                                                continue;
+                                            }
+                                        }
+                                    } else {
+                                        FbpMessage::from_bytes(format!("{}", val).into_bytes())
+                                    };
 
                                     // Send it
                                     debug!("sending...");
-                                    let output_msg = FbpMessage::from_bytes(vec_out);
                                     if let Ok(()) = self.out.push(output_msg) {
                                         debug!("done");
                                         work_units += 1;
@@ -1,62 +1,24 @@
 use flowd_component_api::{
-    Component, ComponentComponentPayload, ComponentPort, FbpMessage, GraphInportOutportHandle, NodeContext,
-    ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult,
+    FlowdComponent, NodeContext, ProcessData, ProcessEdgeSink, ProcessEdgeSource, ProcessResult,
     ProcessSignalSink, ProcessSignalSource, PushError,
 };
-use log::{debug, info, trace};
+use log::{debug, info};
 
+#[derive(FlowdComponent)]
+#[component(description = "Copies data as-is from IN port(s) to single OUT port.", icon = "dedent")]
 pub struct MuxerComponent {
+    #[inport(name = "IN", description = "IPs to be multiplexed into outport")]
     inn: Vec<ProcessEdgeSource>,
+    #[outport(name = "OUT", description = "multiplexed IPs from IN port")]
     out: ProcessEdgeSink,
     signals_in: ProcessSignalSource,
     signals_out: ProcessSignalSink,
-    //graph_inout: GraphInportOutportHandle,
 }
 
-impl Component for MuxerComponent {
-    fn new(
-        mut inports: ProcessInports,
-        mut outports: ProcessOutports,
-        signals_in: ProcessSignalSource,
-        signals_out: ProcessSignalSink,
-        _graph_inout: GraphInportOutportHandle,
-        _scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
-    ) -> Self
-    where
-        Self: Sized,
-    {
-        MuxerComponent {
-            inn: inports.remove("IN").expect("found no IN inport"),
-            out: outports
-                .remove("OUT")
-                .expect("found no OUT outport")
-                .pop()
-                .unwrap(),
-            signals_in: signals_in,
-            signals_out: signals_out,
-            //graph_inout: graph_inout,
-        }
-    }
-
-    fn process(&mut self, context: &mut NodeContext) -> ProcessResult {
+impl ProcessData for MuxerComponent {
+    fn process_data(&mut self, context: &mut NodeContext) -> ProcessResult {
         debug!("Muxer process() called");
 
-        // Check signals first
-        if let Ok(signal) = self.signals_in.try_recv() {
-            let signal_text = signal.as_text()
-                .or_else(|| signal.as_bytes().and_then(|b| std::str::from_utf8(b).ok()))
-                .unwrap_or("");
-            trace!("received signal: {}", signal_text);
-            if signal_text == "stop" {
-                info!("got stop signal, finishing");
-                return ProcessResult::Finished;
-            } else if signal_text == "ping" {
-                trace!("got ping signal, responding");
-                let pong_msg = FbpMessage::from_str("pong");
-                let _ = self.signals_out.try_send(pong_msg);
-            }
-        }
-
         let mut work_units = 0;
 
         // Process available packets from all input ports within remaining budget
@@ -103,119 +65,64 @@ impl Component for MuxerComponent {
             ProcessResult::NoWork
         }
     }
-
-    fn get_metadata() -> ComponentComponentPayload
-    where
-        Self: Sized,
-    {
-        ComponentComponentPayload {
-            name: String::from("Muxer"),
-            description: String::from("Copies data as-is from IN port(s) to single OUT port."),
-            icon: String::from("dedent"),
-            subgraph: false,
-            in_ports: vec![ComponentPort {
-                name: String::from("IN"),
-                allowed_type: String::from("any"),
-                schema: None,
-                required: true,
-                is_arrayport: true,
-                description: String::from("IPs to be multiplexed into outport"),
-                values_allowed: vec![],
-                value_default: String::from(""),
-            }],
-            out_ports: vec![ComponentPort {
-                name: String::from("OUT"),
-                allowed_type: String::from("any"),
-                schema: None,
-                required: true,
-                is_arrayport: false,
-                description: String::from("multiplexed IPs from IN port"),
-                values_allowed: vec![],
-                value_default: String::from(""),
-            }],
-            ..Default::default()
-        }
-    }
 }
 
+#[derive(FlowdComponent)]
+#[component(
+    name = "Demux3",
+    description = "Demux router (IN -> A|B|C round-robin)",
+    icon = "random",
+    reconnect
+)]
 pub struct Demux3Component {
-    inn: ProcessEdgeSource,
-    out_a: ProcessEdgeSink,
-    out_b: ProcessEdgeSink,
-    out_c: ProcessEdgeSink,
+    // ports may be connected later via reconnect_inport/outport() when added to a running network
+    #[inport(name = "IN", required = true, description = "IPs to be demultiplexed")]
+    inn: Option<ProcessEdgeSource>, // None while disconnected in a running network
+    #[outport(
+        name = "A",
+        required = true,
+        description = "every third IP, starting with the first"
+    )]
+    out_a: Option<ProcessEdgeSink>,
+    #[outport(
+        name = "B",
+        required = true,
+        description = "every third IP, starting with the second"
+    )]
+    out_b: Option<ProcessEdgeSink>,
+    #[outport(
+        name = "C",
+        required = true,
+        description = "every third IP, starting with the third"
+    )]
+    out_c: Option<ProcessEdgeSink>,
     signals_in: ProcessSignalSource,
     signals_out: ProcessSignalSink,
     branch: usize,
 }
 
-impl Component for Demux3Component {
-    fn new(
-        mut inports: ProcessInports,
-        mut outports: ProcessOutports,
-        signals_in: ProcessSignalSource,
-        signals_out: ProcessSignalSink,
-        _graph_inout: GraphInportOutportHandle,
-        _scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
-    ) -> Self {
-        Demux3Component {
-            inn: inports
-                .remove("IN")
-                .expect("demux missing IN")
-                .pop()
-                .unwrap(),
-            out_a: outports
-                .remove("A")
-                .expect("demux missing A")
-                .pop()
-                .unwrap(),
-            out_b: outports
-                .remove("B")
-                .expect("demux missing B")
-                .pop()
-                .unwrap(),
-            out_c: outports
-                .remove("C")
-                .expect("demux missing C")
-                .pop()
-                .unwrap(),
-            signals_in,
-            signals_out,
-            branch: 0,
-        }
-    }
-
-    fn process(&mut self, context: &mut NodeContext) -> ProcessResult {
+impl ProcessData for Demux3Component {
+    fn process_data(&mut self, context: &mut NodeContext) -> ProcessResult {
         debug!("Demux3 process() called");
 
-        // Check signals first
-        if let Ok(signal) = self.signals_in.try_recv() {
-            let signal_text = signal.as_text()
-                .or_else(|| signal.as_bytes().and_then(|b| std::str::from_utf8(b).ok()))
-                .unwrap_or("");
-            trace!("received signal: {}", signal_text);
-            if signal_text == "stop" {
-                info!("got stop signal, finishing");
-                return ProcessResult::Finished;
-            } else if signal_text == "ping" {
-                trace!("got ping signal, responding");
-                let pong_msg = FbpMessage::from_str("pong");
-                let _ = self.signals_out.try_send(pong_msg);
-            }
-        }
-
         let mut work_units = 0;
 
         // Process available packets within remaining budget
-        while context.remaining_budget > 0 && !self.inn.is_empty() {
-            if let Ok(ip) = self.inn.pop() {
+        while context.remaining_budget > 0 {
+            // Route to appropriate output based on round-robin
+            let out = match self.branch % 3 {
+                0 => self.out_a.as_mut(),
+                1 => self.out_b.as_mut(),
+                _ => self.out_c.as_mut(),
+            };
+            // disconnected ports: keep packets in the edge until reconnected
+            let (Some(inn), Some(out)) = (self.inn.as_mut(), out) else {
+                break;
+            };
+            if let Ok(ip) = inn.pop() {
                 debug!("demultiplexing packet...");
 
-                // Route to appropriate output based on round-robin
-                let result = match self.branch % 3 {
-                    0 => self.out_a.push(ip),
-                    1 => self.out_b.push(ip),
-                    _ => self.out_c.push(ip),
-                };
+                let result = out.push(ip);
 
                 match result {
                     Ok(()) => {
@@ -239,7 +146,7 @@ impl Component for Demux3Component {
         }
 
         // Check if input is abandoned
-        if self.inn.is_abandoned() {
+        if self.inn.as_ref().is_some_and(|inn| inn.is_abandoned()) {
             info!("EOF on inport, shutting down");
             return ProcessResult::Finished;
         }
@@ -250,36 +157,4 @@ impl Component for Demux3Component {
             ProcessResult::NoWork
         }
     }
-
-    fn get_metadata() -> ComponentComponentPayload {
-        ComponentComponentPayload {
-            name: "Demux3".to_string(),
-            description: "Demux router (IN -> A|B|C round-robin)".to_string(),
-            icon: "random".to_string(),
-            subgraph: false,
-            in_ports: vec![ComponentPort {
-                name: "IN".to_string(),
-                allowed_type: "any".to_string(),
-                ..ComponentPort::default_in()
-            }],
-            out_ports: vec![
-                ComponentPort {
-                    name: "A".to_string(),
-                    allowed_type: "any".to_string(),
-                    ..ComponentPort::default_out()
-                },
-                ComponentPort {
-                    name: "B".to_string(),
-                    allowed_type: "any".to_string(),
-                    ..ComponentPort::default_out()
-                },
-                ComponentPort {
-                    name: "C".to_string(),
-                    allowed_type: "any".to_string(),
-                    ..ComponentPort::default_out()
-                },
-            ],
-            ..Default::default()
-        }
-    }
 }
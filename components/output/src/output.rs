@@ -16,8 +16,8 @@ fn message_as_utf8(message: &FbpMessage) -> Option<&str> {
 }
 
 pub struct OutputComponent {
-    inn: ProcessEdgeSource,
-    out: ProcessEdgeSink,
+    inn: Option<ProcessEdgeSource>, // None while disconnected in a running network
+    out: Option<ProcessEdgeSink>,
     signals_in: ProcessSignalSource,
     signals_out: ProcessSignalSink,
     //graph_inout: GraphInportOutportHandle,
@@ -38,16 +38,9 @@ impl Component for OutputComponent {
         Self: Sized,
     {
         OutputComponent {
-            inn: inports
-                .remove("IN")
-                .expect("found no IN inport")
-                .pop()
-                .unwrap(),
-            out: outports
-                .remove("OUT")
-                .expect("found no OUT outport")
-                .pop()
-                .unwrap(),
+            // ports may be connected later via reconnect_inport/outport() when added to a running network
+            inn: inports.remove("IN").and_then(|mut sources| sources.pop()),
+            out: outports.remove("OUT").and_then(|mut sinks| sinks.pop()),
             signals_in: signals_in,
             signals_out: signals_out,
             //graph_inout: graph_inout,
@@ -78,8 +71,11 @@ impl Component for OutputComponent {
 
         // First, try to send any pending packets that were buffered due to backpressure
         while context.remaining_budget > 0 && !self.pending_packets.is_empty() {
+            let Some(out) = self.out.as_mut() else {
+                break;
+            };
             if let Some(pending_ip) = self.pending_packets.front() {
-                match self.out.push(pending_ip.clone()) {
+                match out.push(pending_ip.clone()) {
                     Ok(()) => {
                         self.pending_packets.pop_front();
                         work_units += 1;
@@ -110,19 +106,25 @@ impl Component for OutputComponent {
                 }
             }
 
-            if let Ok(ip) = self.inn.pop() {
+            // disconnected ports: keep packets in the edge until reconnected
+            let (Some(inn), Some(out)) = (self.inn.as_mut(), self.out.as_mut()) else {
+                break;
+            };
+            if let Ok(ip) = inn.pop() {
                 debug!("got a packet");
                 if let Some(text) = message_as_utf8(&ip) {
                     println!("{}", text);
                 } else if matches!(ip, FbpMessage::Bytes(_)) {
                     info!("received binary packet, not printing");
+                } else if ip.as_control().is_some() {
+                    trace!("received control packet, not printing: {:?}", ip);
                 } else {
                     warn!("received unexpected message type: {:?}", ip);
                 }
 
                 // repeat - handle backpressure
                 debug!("repeating packet...");
-                match self.out.push(ip) {
+                match out.push(ip) {
                     Ok(()) => {
                         work_units += 1;
                         context.remaining_budget -= 1;
@@ -146,7 +148,7 @@ impl Component for OutputComponent {
             ProcessResult::DidWork(work_units)
         } else {
             // are we done?
-            if self.inn.is_abandoned() {
+            if self.inn.as_ref().is_some_and(|inn| inn.is_abandoned() && inn.is_empty()) {
                 info!("EOF on inport, finishing");
                 return ProcessResult::Finished;
             }
@@ -154,6 +156,34 @@ impl Component for OutputComponent {
         }
     }
 
+    fn reconnect_inport(
+        &mut self,
+        port: &str,
+        source: Option<ProcessEdgeSource>,
+    ) -> Result<Option<ProcessEdgeSource>, std::io::Error> {
+        if port != "IN" {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Output has no inport {}", port),
+            ));
+        }
+        Ok(std::mem::replace(&mut self.inn, source))
+    }
+
+    fn reconnect_outport(
+        &mut self,
+        port: &str,
+        sink: Option<ProcessEdgeSink>,
+    ) -> Result<Option<ProcessEdgeSink>, std::io::Error> {
+        if port != "OUT" {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Output has no outport {}", port),
+            ));
+        }
+        Ok(std::mem::replace(&mut self.out, sink))
+    }
+
     // modeled after https://github.com/noflo/noflo-core/blob/master/components/Output.js
     // what node.js console.log() does:  https://nodejs.org/api/console.html#consolelogdata-args
     fn get_metadata() -> ComponentComponentPayload
@@ -185,6 +215,7 @@ impl Component for OutputComponent {
                 values_allowed: vec![],
                 value_default: String::from(""),
             }],
+            support_reconnect: true,
             ..Default::default()
         }
     }
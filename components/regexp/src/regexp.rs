@@ -104,6 +104,16 @@ impl Component for RegexpExtractComponent {
             if let Ok(ip) = self.inn.pop() {
                 debug!("got packet, processing...");
 
+                // brackets and other control events pass through unchanged
+                if ip.as_control().is_some() {
+                    if let Ok(()) = self.out.push(ip) {
+                        work_units += 1;
+                        context.remaining_budget -= 1;
+                        continue;
+                    }
+                    break;
+                }
+
                 // Apply regexp
                 let input_str = ip.as_text().unwrap_or("");
                 if let Some(captures) = regexp.captures(input_str) {
@@ -1,53 +1,37 @@
 use flowd_component_api::{
-    Component, ComponentComponentPayload, ComponentPort, FbpMessage, GraphInportOutportHandle, NodeContext,
-    ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult, PushError,
-    ProcessSignalSink, ProcessSignalSource,
+    FbpMessage, FlowdComponent, NodeContext, ProcessData, ProcessEdgeSink, ProcessEdgeSource,
+    ProcessResult, ProcessSignalSink, ProcessSignalSource, PushError,
 };
-use log::{debug, info, trace};
+use log::{debug, info};
 
+#[derive(FlowdComponent)]
+#[component(
+    description = "Copies data as-is from IN port to OUT port.",
+    icon = "arrow-right", // or "copy"
+    reconnect
+)]
 pub struct RepeatComponent {
-    inn: ProcessEdgeSource,
-    out: ProcessEdgeSink,
+    // ports may be connected later via reconnect_inport/outport() when added to a running network
+    #[inport(name = "IN", required = true, description = "data to be repeated on outport")]
+    inn: Option<ProcessEdgeSource>, // None while disconnected in a running network
+    #[outport(name = "OUT", required = true, description = "repeated data from IN port")]
+    out: Option<ProcessEdgeSink>,
     signals_in: ProcessSignalSource,
     signals_out: ProcessSignalSink,
     pending_out: std::collections::VecDeque<FbpMessage>,
-    //graph_inout: GraphInportOutportHandle,
 }
 
-impl Component for RepeatComponent {
-    fn new(
-        mut inports: ProcessInports,
-        mut outports: ProcessOutports,
-        signals_in: ProcessSignalSource,
-        signals_out: ProcessSignalSink,
-        _graph_inout: GraphInportOutportHandle,
-        _scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
-    ) -> Self {
-        RepeatComponent {
-            inn: inports
-                .remove("IN")
-                .expect("found no IN inport")
-                .pop()
-                .unwrap(),
-            out: outports
-                .remove("OUT")
-                .expect("found no OUT outport")
-                .pop()
-                .unwrap(),
-            signals_in,
-            signals_out,
-            pending_out: std::collections::VecDeque::new(),
-            //graph_inout: graph_inout,
-        }
-    }
-
-    fn process(&mut self, context: &mut NodeContext) -> ProcessResult {
+impl ProcessData for RepeatComponent {
+    fn process_data(&mut self, context: &mut NodeContext) -> ProcessResult {
         debug!("Repeat is now process()ing!");
         let mut work_units = 0u32;
 
         while context.remaining_budget > 0 && !self.pending_out.is_empty() {
+            let Some(out) = self.out.as_mut() else {
+                break;
+            };
             if let Some(ip) = self.pending_out.front().cloned() {
-                match self.out.push(ip) {
+                match out.push(ip) {
                     Ok(()) => {
                         self.pending_out.pop_front();
                         work_units += 1;
@@ -58,38 +42,20 @@ impl Component for RepeatComponent {
             }
         }
 
-        // check signals
-        if let Ok(ip) = self.signals_in.try_recv() {
-            let signal_text = ip.as_text().unwrap_or("");
-            trace!("received signal ip: {}", signal_text);
-            // stop signal
-            if signal_text == "stop" {
-                info!("got stop signal, finishing");
-                return ProcessResult::Finished;
-            } else if signal_text == "ping" {
-                trace!("got ping signal, responding");
-                let _ = self.signals_out.try_send(FbpMessage::from_str("pong"));
-            }
-        }
-
         // check in port within budget
         while context.remaining_budget > 0 {
             // stay responsive to stop/ping even while draining a busy input buffer
-            if let Ok(sig) = self.signals_in.try_recv() {
-                let signal_text = sig.as_text().unwrap_or("");
-                trace!("received signal ip: {}", signal_text);
-                if signal_text == "stop" {
-                    info!("got stop signal while processing, finishing");
-                    return ProcessResult::Finished;
-                } else if signal_text == "ping" {
-                    trace!("got ping signal, responding");
-                    let _ = self.signals_out.try_send(FbpMessage::from_str("pong"));
-                }
+            if flowd_component_api::handle_signals(&self.signals_in, &self.signals_out) {
+                return ProcessResult::Finished;
             }
 
-            if let Ok(ip) = self.inn.pop() {
+            // disconnected ports: keep packets in the edge until reconnected
+            let (Some(inn), Some(out)) = (self.inn.as_mut(), self.out.as_mut()) else {
+                break;
+            };
+            if let Ok(ip) = inn.pop() {
                 debug!("repeating packet...");
-                match self.out.push(ip) {
+                match out.push(ip) {
                     Ok(()) => {
                         debug!("done");
                         work_units += 1;
@@ -106,7 +72,9 @@ impl Component for RepeatComponent {
         }
 
         // are we done?
-        if self.inn.is_abandoned() && self.pending_out.is_empty() {
+        if self.inn.as_ref().is_some_and(|inn| inn.is_abandoned() && inn.is_empty())
+            && self.pending_out.is_empty()
+        {
             // input closed, nothing more to do
             info!("EOF on inport, finishing");
             return ProcessResult::Finished;
@@ -118,34 +86,4 @@ impl Component for RepeatComponent {
             ProcessResult::NoWork
         }
     }
-
-    fn get_metadata() -> ComponentComponentPayload {
-        ComponentComponentPayload {
-            name: String::from("Repeat"),
-            description: String::from("Copies data as-is from IN port to OUT port."),
-            icon: String::from("arrow-right"), // or "copy"
-            subgraph: false,
-            in_ports: vec![ComponentPort {
-                name: String::from("IN"),
-                allowed_type: String::from("any"),
-                schema: None,
-                required: true,
-                is_arrayport: false,
-                description: String::from("data to be repeated on outport"),
-                values_allowed: vec![],
-                value_default: String::from(""),
-            }],
-            out_ports: vec![ComponentPort {
-                name: String::from("OUT"),
-                allowed_type: String::from("any"),
-                schema: None,
-                required: true,
-                is_arrayport: false,
-                description: String::from("repeated data from IN port"),
-                values_allowed: vec![],
-                value_default: String::from(""),
-            }],
-            ..Default::default()
-        }
-    }
 }
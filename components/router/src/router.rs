@@ -1,34 +1,51 @@
 use flowd_component_api::{
-    Component, ComponentComponentPayload, ComponentPort, FbpMessage, GraphInportOutportHandle, NodeContext,
-    ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult,
-    ProcessSignalSink, ProcessSignalSource, PushError,
+    Component, ComponentComponentPayload, ComponentConfig, ComponentPort, Config, ConfigSchema,
+    ConfigType, FbpMessage, GraphInportOutportHandle, NodeContext, ProcessEdgeSink,
+    ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult, ProcessSignalSink,
+    ProcessSignalSource, PushError,
 };
 use log::{debug, error, info, trace, warn};
 use regex::Regex;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 struct RoutingRule {
     condition: Condition,
     output_port: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 struct Condition {
     field: String,
     operator: String,
     value: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 struct RouterConfig {
     rules: Vec<RoutingRule>,
     default_output: Option<String>,
 }
 
+impl ComponentConfig for RouterConfig {
+    fn schema() -> ConfigSchema {
+        ConfigSchema::new()
+            .required(
+                "rules",
+                ConfigType::List,
+                "routing rules: {\"condition\": {\"field\", \"operator\", \"value\"}, \"output_port\"}",
+            )
+            .optional(
+                "default_output",
+                ConfigType::String,
+                "outport for messages matching no rule, default: default",
+            )
+    }
+}
+
 pub struct GenericRouterComponent {
-    conf: ProcessEdgeSource,
+    conf: Config<RouterConfig>,
     inn: ProcessEdgeSource,
     out_ports: HashMap<String, ProcessEdgeSink>,
     signals_in: ProcessSignalSource,
@@ -83,11 +100,7 @@ impl Component for GenericRouterComponent {
         debug!("Router initialized with output ports: {:?}", port_names);
 
         GenericRouterComponent {
-            conf: inports
-                .remove("CONF")
-                .expect("found no CONF inport")
-                .pop()
-                .unwrap(),
+            conf: Config::new(inports.remove("CONF").and_then(|mut sources| sources.pop())),
             inn: inports
                 .remove("IN")
                 .expect("found no IN inport")
@@ -126,45 +139,36 @@ impl Component for GenericRouterComponent {
 
         // Check if we have configuration
         if self.config.is_none() {
-            if let Ok(conf_msg) = self.conf.pop() {
-                let conf_text = conf_msg.as_text().expect("config must be text");
-                debug!("received config: {}", conf_text);
-                match serde_json::from_str::<RouterConfig>(conf_text) {
-                    Ok(config) => {
-                        match compile_rules(&config.rules) {
-                            Ok(compiled) => {
-                                self.config = Some(config);
-                                self.compiled_rules = compiled;
-                                work_units += 1;
-                            }
-                            Err(err) => {
-                                error!("failed to compile routing rules: {}", err);
-                                return ProcessResult::Finished;
-                            }
-                        }
-                    }
-                    Err(err) => {
-                        error!("failed to parse config JSON: {}", err);
-                        return ProcessResult::Finished;
-                    }
+            let config = match self.conf.get() {
+                Ok(Some(config)) => config.clone(),
+                Ok(None) => return ProcessResult::NoWork,
+                Err(err) => {
+                    error!("{} - finishing", err);
+                    return ProcessResult::Finished;
+                }
+            };
+            debug!("received config: {:?}", config);
+            match compile_rules(&config.rules) {
+                Ok(compiled) => {
+                    self.config = Some(config);
+                    self.compiled_rules = compiled;
+                    work_units += 1;
+                }
+                Err(err) => {
+                    error!("failed to compile routing rules: {}", err);
+                    return ProcessResult::Finished;
                 }
-            } else {
-                return ProcessResult::NoWork;
             }
         }
 
         // Process input messages
         while context.remaining_budget > 0 && !self.inn.is_empty() {
             if let Ok(input_msg) = self.inn.pop() {
-                let input_text = input_msg.as_text().unwrap_or("");
-                debug!("routing message: {}", input_text.chars().take(100).collect::<String>());
-
-                let output_port = self.route_message(input_text);
+                let output_port = self.route_message(&input_msg);
                 debug!("routed to port: {}", output_port);
 
                 if let Some(out_sink) = self.out_ports.get_mut(&output_port) {
-                    let output_msg = FbpMessage::from_str(input_text);
-                    match out_sink.push(output_msg) {
+                    match out_sink.push(input_msg) {
                         Ok(()) => {
                             work_units += 1;
                             context.remaining_budget -= 1;
@@ -208,7 +212,7 @@ impl Component for GenericRouterComponent {
                 ComponentPort {
                     name: String::from("CONF"),
                     allowed_type: String::from("any"),
-                    schema: None,
+                    schema: Some(RouterConfig::schema().to_json_schema().to_string()),
                     required: true,
                     is_arrayport: false,
                     description: String::from("JSON configuration with routing rules"),
@@ -221,7 +225,7 @@ impl Component for GenericRouterComponent {
                     schema: None,
                     required: true,
                     is_arrayport: false,
-                    description: String::from("input data to route"),
+                    description: String::from("input data to route, JSON text or structured value"),
                     values_allowed: vec![],
                     value_default: String::from(""),
                 },
@@ -235,9 +239,9 @@ impl Component for GenericRouterComponent {
 }
 
 impl GenericRouterComponent {
-    fn route_message(&self, input_text: &str) -> String {
-        // Try to parse as JSON first
-        if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(input_text) {
+    fn route_message(&self, input_msg: &FbpMessage) -> String {
+        // Structured values and JSON text are matched by field
+        if let Ok(json_value) = input_msg.deserialize::<serde_json::Value>() {
             for rule in &self.compiled_rules {
                 if self.evaluate_condition(&json_value, rule) {
                     return rule.output_port.clone();
@@ -245,6 +249,8 @@ impl GenericRouterComponent {
             }
         } else {
             // If not JSON, treat as plain text and check against a "content" field
+            let input_text = input_msg.as_text().unwrap_or("");
+            debug!("routing text: {}", input_text.chars().take(100).collect::<String>());
             for rule in &self.compiled_rules {
                 if rule.field == "content" && self.evaluate_text_condition(input_text, rule) {
                     return rule.output_port.clone();
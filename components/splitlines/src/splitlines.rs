@@ -1,14 +1,17 @@
 use flowd_component_api::{
-    Component, ComponentComponentPayload, ComponentPort, FbpMessage, GraphInportOutportHandle, NodeContext,
-    ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult,
-    ProcessSignalSink, ProcessSignalSource, PushError,
+    Component, ComponentComponentPayload, ComponentConfig, ComponentPort, Config, ConfigSchema,
+    ConfigType, FbpMessage, GraphInportOutportHandle, NodeContext, ProcessEdgeSink,
+    ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult, ProcessSignalSink,
+    ProcessSignalSource, PushError,
 };
-use log::{debug, info, trace, warn};
+use log::{debug, error, info, trace, warn};
+use serde::Deserialize;
 
 // component-specific
 //use std::io::BufRead;
 
 pub struct SplitLinesComponent {
+    conf: Option<Config<SplitLinesConfig>>, // without CONF the lines are bracketed
     inn: ProcessEdgeSource,
     out: ProcessEdgeSink,
     signals_in: ProcessSignalSource,
@@ -16,6 +19,35 @@ pub struct SplitLinesComponent {
     //graph_inout: GraphInportOutportHandle,
     // State for partial processing
     line_iter: Option<std::vec::IntoIter<FbpMessage>>,
+    brackets: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct SplitLinesConfig {
+    brackets: bool,
+}
+
+impl ComponentConfig for SplitLinesConfig {
+    fn schema() -> ConfigSchema {
+        ConfigSchema::new()
+            .with_default(
+                "brackets",
+                ConfigType::Boolean,
+                true,
+                "enclose the lines of each input IP in a bracket",
+            )
+            .shorthand("brackets")
+    }
+}
+
+fn bracketed(brackets: bool, lines: impl Iterator<Item = FbpMessage>) -> Vec<FbpMessage> {
+    if !brackets {
+        return lines.collect();
+    }
+    std::iter::once(FbpMessage::begin_bracket(""))
+        .chain(lines)
+        .chain(std::iter::once(FbpMessage::end_bracket("")))
+        .collect()
 }
 
 impl Component for SplitLinesComponent {
@@ -30,7 +62,10 @@ impl Component for SplitLinesComponent {
     where
         Self: Sized,
     {
+        let conf = inports.remove("CONF").and_then(|mut sources| sources.pop());
         SplitLinesComponent {
+            brackets: if conf.is_none() { Some(true) } else { None },
+            conf: conf.map(|source| Config::new(Some(source))),
             inn: inports
                 .remove("IN")
                 .expect("found no IN inport")
@@ -71,6 +106,23 @@ impl Component for SplitLinesComponent {
             }
         }
 
+        // brackets unless turned off, for consumers that do not handle control packets
+        if self.brackets.is_none() {
+            match self.conf.as_mut().map(|conf| conf.get()) {
+                Some(Ok(Some(config))) => self.brackets = Some(config.brackets),
+                Some(Ok(None)) => {}
+                Some(Err(err)) => {
+                    error!("{} - finishing", err);
+                    return ProcessResult::Finished;
+                }
+                None => self.brackets = Some(true),
+            }
+        }
+        let Some(brackets) = self.brackets else {
+            trace!("not configured yet - no work");
+            return ProcessResult::NoWork;
+        };
+
         // check in port within budget
         if context.remaining_budget > 0 {
             // stay responsive to stop/ping even while draining a busy input buffer
@@ -95,18 +147,21 @@ impl Component for SplitLinesComponent {
                     // read packet - handle both Text and Bytes messages
                     debug!("got input to split");
 
-                     // split into lines based on message type
-                     let lines: Vec<FbpMessage> = if let Some(text) = ip.as_text() {
+                     // split into lines based on message type, each input's lines may form a bracket
+                     let lines: Vec<FbpMessage> = if ip.as_control().is_some() {
+                         // brackets and other control events of the input stream pass through
+                         vec![ip]
+                     } else if let Some(text) = ip.as_text() {
                          // Handle Text messages
-                         text.split('\n').map(|s| FbpMessage::from_str(s)).collect()
+                         bracketed(brackets, text.split('\n').map(|s| FbpMessage::from_str(s)))
                      } else if let Some(bytes) = ip.as_bytes() {
                          // Handle Bytes messages (fallback for binary data)
-                         bytes.split(|&x| x == b'\n').map(|x| {
+                         bracketed(brackets, bytes.split(|&x| x == b'\n').map(|x| {
                              match std::str::from_utf8(x) {
                                  Ok(s) => FbpMessage::from_str(s),
                                  Err(_) => FbpMessage::from_bytes(x.to_vec()),
                              }
-                         }).collect()
+                         }))
                      } else {
                          // Skip unsupported message types
                          debug!("skipping unsupported message type");
@@ -152,7 +207,7 @@ impl Component for SplitLinesComponent {
         }
 
         // are we done?
-        if self.inn.is_abandoned() {
+        if self.inn.is_abandoned() && self.inn.is_empty() && self.line_iter.is_none() {
             info!("EOF on inport, finishing");
             return ProcessResult::Finished;
         }
@@ -171,27 +226,39 @@ impl Component for SplitLinesComponent {
         ComponentComponentPayload {
             name: String::from("SplitLines"),
             description: String::from(
-                "Splits IP contents by newline (\\n) and forwards the parts in separate IPs.",
+                "Splits IP contents by newline (\\n) into separate IPs, bracketed per input IP.",
             ),
             icon: String::from("cut"),
             subgraph: false,
-            in_ports: vec![ComponentPort {
-                name: String::from("IN"),
-                allowed_type: String::from("any"),
-                schema: None,
-                required: true,
-                is_arrayport: false,
-                description: String::from("IPs with text to split"),
-                values_allowed: vec![],
-                value_default: String::from(""),
-            }],
+            in_ports: vec![
+                ComponentPort {
+                    name: String::from("CONF"),
+                    allowed_type: String::from("any"),
+                    schema: Some(SplitLinesConfig::schema().to_json_schema().to_string()),
+                    required: false,
+                    is_arrayport: false,
+                    description: String::from("configuration, e.g. brackets=false"),
+                    values_allowed: vec![],
+                    value_default: String::from(""),
+                },
+                ComponentPort {
+                    name: String::from("IN"),
+                    allowed_type: String::from("any"),
+                    schema: None,
+                    required: true,
+                    is_arrayport: false,
+                    description: String::from("IPs with text to split"),
+                    values_allowed: vec![],
+                    value_default: String::from(""),
+                },
+            ],
             out_ports: vec![ComponentPort {
                 name: String::from("OUT"),
-                allowed_type: String::from("any"),
+                allowed_type: String::from("string"),
                 schema: None,
                 required: true,
                 is_arrayport: false,
-                description: String::from("split lines"),
+                description: String::from("split lines, the lines of each input IP in a bracket unless brackets=false"),
                 values_allowed: vec![],
                 value_default: String::from(""),
             }],
@@ -137,18 +137,23 @@ impl Component for TextReplaceComponent {
             }
 
             if let Ok(ip) = self.inn.pop() {
-                // read packet - expecting UTF-8 string
-                let mut text = ip.as_text().expect("non-text data").to_string();
-                debug!("got a text to process: {}", text);
+                let processed_msg = if ip.as_control().is_some() {
+                    // brackets and other control events pass through unchanged
+                    ip
+                } else {
+                    // read packet - expecting UTF-8 string
+                    let mut text = ip.as_text().expect("non-text data").to_string();
+                    debug!("got a text to process: {}", text);
 
-                // apply text replacements
-                for replacement in &self.replacements {
-                    text = text.replace(replacement.0.as_str(), replacement.1.as_str());
-                }
+                    // apply text replacements
+                    for replacement in &self.replacements {
+                        text = text.replace(replacement.0.as_str(), replacement.1.as_str());
+                    }
 
-                // Try to send the processed packet
-                debug!("forwarding...");
-                let processed_msg = FbpMessage::from_str(&text);
+                    // Try to send the processed packet
+                    debug!("forwarding...");
+                    FbpMessage::from_str(&text)
+                };
                 match self.out.push(processed_msg) {
                     Ok(()) => {
                         work_units += 1;
@@ -1,56 +1,41 @@
 use flowd_component_api::{
-    Component, ComponentComponentPayload, ComponentPort, FbpMessage, GraphInportOutportHandle, NodeContext,
-    ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult, PushError,
-    ProcessSignalSink, ProcessSignalSource,
+    FbpMessage, FlowdComponent, NodeContext, ProcessData, ProcessEdgeSink, ProcessEdgeSource,
+    ProcessResult, ProcessSignalSink, ProcessSignalSource, PushError,
 };
-use log::{debug, info, trace, warn};
+use log::{debug, info};
 
+#[derive(FlowdComponent)]
+#[component(
+    description = "Reads IPs as UTF-8 strings and trims whitespace at beginning and end, forwarding the trimmed string.",
+    icon = "cut",
+    reconnect
+)]
 pub struct TrimComponent {
-    inn: ProcessEdgeSource,
-    out: ProcessEdgeSink,
+    // ports may be connected later via reconnect_inport/outport() when added to a running network
+    #[inport(
+        name = "IN",
+        required = true,
+        description = "IPs with strings to trim, one string per IP"
+    )]
+    inn: Option<ProcessEdgeSource>, // None while disconnected in a running network
+    #[outport(name = "OUT", required = true, description = "trimmed strings")]
+    out: Option<ProcessEdgeSink>,
     signals_in: ProcessSignalSource,
     signals_out: ProcessSignalSink,
     pending_out: std::collections::VecDeque<FbpMessage>,
-    //graph_inout: GraphInportOutportHandle,
 }
 
-impl Component for TrimComponent {
-    fn new(
-        mut inports: ProcessInports,
-        mut outports: ProcessOutports,
-        signals_in: ProcessSignalSource,
-        signals_out: ProcessSignalSink,
-        _graph_inout: GraphInportOutportHandle,
-        _scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
-    ) -> Self
-    where
-        Self: Sized,
-    {
-        TrimComponent {
-            inn: inports
-                .remove("IN")
-                .expect("found no IN inport")
-                .pop()
-                .unwrap(),
-            out: outports
-                .remove("OUT")
-                .expect("found no OUT outport")
-                .pop()
-                .unwrap(),
-            signals_in: signals_in,
-            signals_out: signals_out,
-            pending_out: std::collections::VecDeque::new(),
-            //graph_inout: graph_inout,
-        }
-    }
-
-    fn process(&mut self, context: &mut NodeContext) -> ProcessResult {
+impl ProcessData for TrimComponent {
+    fn process_data(&mut self, context: &mut NodeContext) -> ProcessResult {
         debug!("Trim is now process()ing!");
         let mut work_units = 0u32;
 
         while context.remaining_budget > 0 && !self.pending_out.is_empty() {
+            let Some(out) = self.out.as_mut() else {
+                break;
+            };
             if let Some(ip) = self.pending_out.front().cloned() {
-                match self.out.push(ip) {
+                match out.push(ip) {
                     Ok(()) => {
                         self.pending_out.pop_front();
                         work_units += 1;
@@ -61,51 +46,36 @@ impl Component for TrimComponent {
             }
         }
 
-        // check signals
-        if let Ok(ip) = self.signals_in.try_recv() {
-            let signal_text = ip.as_text().unwrap_or("");
-            trace!("received signal ip: {}", signal_text);
-            // stop signal
-            if signal_text == "stop" {
-                info!("got stop signal, finishing");
-                return ProcessResult::Finished;
-            } else if signal_text == "ping" {
-                trace!("got ping signal, responding");
-                let _ = self.signals_out.try_send(FbpMessage::from_str("pong"));
-            } else {
-                warn!("received unknown signal ip: {}", signal_text)
-            }
-        }
-
         // check in port within budget
         while context.remaining_budget > 0 {
             // stay responsive to stop/ping even while draining a busy input buffer
-            if let Ok(sig) = self.signals_in.try_recv() {
-                let signal_text = sig.as_text().unwrap_or("");
-                trace!("received signal ip: {}", signal_text);
-                if signal_text == "stop" {
-                    info!("got stop signal while processing, finishing");
-                    return ProcessResult::Finished;
-                } else if signal_text == "ping" {
-                    trace!("got ping signal, responding");
-                    let _ = self.signals_out.try_send(FbpMessage::from_str("pong"));
-                }
+            if flowd_component_api::handle_signals(&self.signals_in, &self.signals_out) {
+                return ProcessResult::Finished;
             }
 
-            if let Ok(ip) = self.inn.pop() {
-                // read packet - expecting UTF-8 string
-                let text = ip.as_text().expect("non-text data");
-                debug!("got a text to trim: {}", &text);
+            // disconnected ports: keep packets in the edge until reconnected
+            let (Some(inn), Some(out)) = (self.inn.as_mut(), self.out.as_mut()) else {
+                break;
+            };
+            if let Ok(ip) = inn.pop() {
+                let output_msg = if ip.as_control().is_some() {
+                    // brackets and other control events pass through unchanged
+                    ip
+                } else {
+                    // read packet - expecting UTF-8 string
+                    let text = ip.as_text().expect("non-text data");
+                    debug!("got a text to trim: {}", &text);
 
-                // trim string
-                debug!("len before trim: {}", text.len());
-                let trimmed = text.trim();
-                debug!("len after trim: {}", trimmed.len());
+                    // trim string
+                    debug!("len before trim: {}", text.len());
+                    let trimmed = text.trim();
+                    debug!("len after trim: {}", trimmed.len());
 
-                // send it
-                debug!("forwarding trimmed string...");
-                let output_msg = FbpMessage::from_str(trimmed);
-                match self.out.push(output_msg) {
+                    // send it
+                    debug!("forwarding trimmed string...");
+                    FbpMessage::from_str(trimmed)
+                };
+                match out.push(output_msg) {
                     Ok(()) => {
                         debug!("done");
                         work_units += 1;
@@ -122,7 +92,12 @@ impl Component for TrimComponent {
         }
 
         // are we done?
-        if self.inn.is_abandoned() && self.pending_out.is_empty() {
+        if self
+            .inn
+            .as_ref()
+            .is_some_and(|inn| inn.is_abandoned() && inn.is_empty())
+            && self.pending_out.is_empty()
+        {
             info!("EOF on inport, finishing");
             return ProcessResult::Finished;
         }
@@ -133,41 +108,4 @@ impl Component for TrimComponent {
             ProcessResult::NoWork
         }
     }
-
-    fn get_metadata() -> ComponentComponentPayload
-    where
-        Self: Sized,
-    {
-        ComponentComponentPayload {
-            name: String::from("Trim"),
-            description: String::from("Reads IPs as UTF-8 strings and trims whitespace at beginning and end, forwarding the trimmed string."),
-            icon: String::from("cut"),
-            subgraph: false,
-            in_ports: vec![
-                ComponentPort {
-                    name: String::from("IN"),
-                    allowed_type: String::from("any"),
-                    schema: None,
-                    required: true,
-                    is_arrayport: false,
-                    description: String::from("IPs with strings to trim, one string per IP"),
-                    values_allowed: vec![],
-                    value_default: String::from("")
-                }
-            ],
-            out_ports: vec![
-                ComponentPort {
-                    name: String::from("OUT"),
-                    allowed_type: String::from("any"),
-                    schema: None,
-                    required: true,
-                    is_arrayport: false,
-                    description: String::from("trimmed strings"),
-                    values_allowed: vec![],
-                    value_default: String::from("")
-                }
-            ],
-            ..Default::default()
-        }
-    }
 }
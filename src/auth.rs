@@ -0,0 +1,130 @@
+// ADR-013: token authentication for the management connection
+//
+// Secrets file, JSON:
+//   {"secrets": [{"graph": "main_graph", "sha256": "<hex digest of the token>", "access": "ReadWrite"}]}
+// Only SHA-256 digests of the tokens are stored, in the file as well as in memory.
+// Graph "*" grants its access level on every graph.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::AccessLevel;
+
+/// Graph name of secrets valid for all graphs
+pub const ANY_GRAPH: &str = "*";
+
+type SecretDigest = [u8; 32];
+
+/// Hashed secret tokens with their access level, per graph
+#[derive(Debug, Default, Clone)]
+pub struct SecretStore {
+    graphs: HashMap<String, Vec<(SecretDigest, AccessLevel)>>,
+}
+
+#[derive(Deserialize)]
+struct SecretsFile {
+    secrets: Vec<SecretsFileEntry>,
+}
+
+#[derive(Deserialize)]
+struct SecretsFileEntry {
+    graph: String,
+    sha256: String,
+    access: AccessLevel,
+}
+
+impl SecretStore {
+    /// Load a secrets file, see module comment for the format
+    pub fn load(path: &Path) -> std::result::Result<Self, std::io::Error> {
+        let contents = std::fs::read_to_string(path)?;
+        let file: SecretsFile = serde_json::from_str(&contents).map_err(|err| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("failed to parse secrets file {}: {}", path.display(), err),
+            )
+        })?;
+        let mut store = SecretStore::default();
+        for entry in file.secrets.into_iter() {
+            let digest = parse_digest(&entry.sha256).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "secrets file {}: sha256 of graph {} is not 64 hex digits",
+                        path.display(),
+                        entry.graph
+                    ),
+                )
+            })?;
+            store
+                .graphs
+                .entry(entry.graph)
+                .or_default()
+                .push((digest, entry.access));
+        }
+        Ok(store)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graphs.is_empty()
+    }
+
+    /// Whether any secret applies to `graph`
+    pub(crate) fn has_graph(&self, graph: &str) -> bool {
+        self.graphs.contains_key(graph) || self.graphs.contains_key(ANY_GRAPH)
+    }
+
+    /// Whether `secret` is valid for at least one graph
+    pub(crate) fn is_known(&self, secret: &str) -> bool {
+        let digest = hash_secret(secret);
+        self.graphs
+            .values()
+            .flatten()
+            .any(|(expected, _)| digest_eq(expected, &digest))
+    }
+
+    /// Access level granted by `secret` on `graph`, the higher one if several match
+    pub(crate) fn access(&self, secret: &str, graph: &str) -> Option<AccessLevel> {
+        let digest = hash_secret(secret);
+        let mut granted = None;
+        for name in [graph, ANY_GRAPH] {
+            for (expected, access) in self.graphs.get(name).into_iter().flatten() {
+                if !digest_eq(expected, &digest) {
+                    continue;
+                }
+                if *access == AccessLevel::ReadWrite {
+                    return Some(AccessLevel::ReadWrite);
+                }
+                granted = Some(access.clone());
+            }
+        }
+        granted
+    }
+}
+
+/// SHA-256 digest of a secret token, as written into the secrets file
+pub fn hash_secret(secret: &str) -> SecretDigest {
+    Sha256::digest(secret.as_bytes()).into()
+}
+
+fn parse_digest(hex: &str) -> Option<SecretDigest> {
+    let hex = hex.trim().as_bytes();
+    if hex.len() != 64 || !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(digest)
+}
+
+// no early exit, so timing does not tell how much of the digest matched
+fn digest_eq(a: &SecretDigest, b: &SecretDigest) -> bool {
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+        == 0
+}
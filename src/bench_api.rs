@@ -9,6 +9,7 @@
         graph_inout: Arc<Mutex<GraphInportOutportHolder>>,
         packet_rx: Receiver<RuntimePacketResponsePayload>,
         collected_outputs: Arc<Mutex<HashMap<String, Vec<MessageBuf>>>>,
+        state_dir: std::path::PathBuf,
     }
 
     impl BenchRuntimeHarness {
@@ -20,6 +21,13 @@
             }
         }
 
+        fn message_from_payload(payload: &JsonValue) -> MessageBuf {
+            match payload {
+                JsonValue::String(text) => FbpMessage::from_str(text),
+                _ => FbpMessage::from_text(payload.to_string()),
+            }
+        }
+
         fn drain_runtime_packets(&self) {
             while let Ok(pkt) = self.packet_rx.try_recv() {
                 if matches!(pkt.event, RuntimePacketEvent::Data) {
@@ -28,24 +36,45 @@
                         outputs
                             .entry(pkt.port.clone())
                             .or_insert_with(Vec::new)
-                            .push(FbpMessage::from_text(payload.clone()));
+                            .push(Self::message_from_payload(payload));
                     }
                 }
             }
         }
 
         pub fn new(graph: Graph) -> Self {
+            let graph_name = graph.properties.name.clone();
             let runtime: Arc<RwLock<Runtime>> =
-                Arc::new(RwLock::new(Runtime::new(graph.properties.name.clone())));
+                Arc::new(RwLock::new(Runtime::new(graph_name.clone())));
             let components: Arc<RwLock<ComponentLibrary>> = build_component_library();
             let (packet_tx, packet_rx) = mpsc::sync_channel(65_536);
             let graph_inout = Arc::new(Mutex::new(GraphInportOutportHolder {
-                inports: None,
-                outports: None,
+                inports: HashMap::new(),
+                outports: HashMap::new(),
                 websockets: HashMap::new(),
+                outport_subscribers: HashMap::new(),
                 packet_tap: Some(packet_tx),
             }));
+            // own checkpoints and edge journals, so no state leaks from one harness into the next
+            static HARNESS_COUNT: std::sync::atomic::AtomicUsize =
+                std::sync::atomic::AtomicUsize::new(0);
+            let state_dir = std::env::temp_dir().join(format!(
+                "flowd-harness-{}-{}",
+                std::process::id(),
+                HARNESS_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+            ));
+            {
+                let mut runtime_write = runtime.write().expect("lock poisoned");
+                runtime_write.edge_journal_dir = state_dir.join(EDGE_JOURNAL_DIR);
+                runtime_write.checkpoint_dir = state_dir.join(CHECKPOINT_DIR);
+            }
             let graph = Arc::new(RwLock::new(graph));
+            // runtime:packet looks up the graph inports there
+            runtime
+                .write()
+                .expect("lock poisoned")
+                .graphs
+                .add_graph(graph_name, graph.clone());
 
             BenchRuntimeHarness {
                 runtime,
@@ -54,6 +83,7 @@
                 graph_inout,
                 packet_rx,
                 collected_outputs: Arc::new(Mutex::new(HashMap::new())),
+                state_dir,
             }
         }
 
@@ -66,14 +96,97 @@
             )
         }
 
+        /// Make `graph` available as subgraph component, named like the graph.
+        pub fn add_subgraph(&self, graph: Graph) {
+            let graph_name = graph.properties.name.clone();
+            self.runtime
+                .write()
+                .expect("lock poisoned")
+                .graphs
+                .add_graph(graph_name, Arc::new(RwLock::new(graph)));
+        }
+
+        /// Keep the journals of persistent edges in `dir` instead of the working directory.
+        pub fn set_edge_journal_dir(&self, dir: std::path::PathBuf) {
+            self.runtime.write().expect("lock poisoned").edge_journal_dir = dir;
+        }
+
+        /// Keep node state checkpoints in `dir` instead of the working directory.
+        pub fn set_checkpoint_dir(&self, dir: std::path::PathBuf) {
+            self.runtime.write().expect("lock poisoned").checkpoint_dir = dir;
+        }
+
+        /// Take a checkpoint of the running harness graph, returns the number of saved node states.
+        pub fn checkpoint(&self) -> std::result::Result<usize, std::io::Error> {
+            let graph_name = self.graph_name();
+            self.runtime
+                .read()
+                .expect("lock poisoned")
+                .checkpoint(&graph_name)
+        }
+
+        /// Node and edge metrics of the runtime in OpenMetrics text format.
+        pub fn metrics(&self) -> String {
+            crate::metrics::encode(&self.runtime.read().expect("lock poisoned"))
+        }
+
+        /// network:metrics response for the harness graph, as sent to clients.
+        pub fn network_metrics(&self) -> serde_json::Value {
+            let graph_name = self.graph_name();
+            let status = self
+                .runtime
+                .read()
+                .expect("lock poisoned")
+                .status_snapshot(&graph_name);
+            serde_json::to_value(crate::NetworkMetricsResponse::new(&status))
+                .expect("failed to serialize network:metrics response")
+        }
+
         pub fn stop(&self) -> std::result::Result<(), std::io::Error> {
+            let graph_name = self.graph_name();
+            self.stop_graph(&graph_name)
+        }
+
+        /// Start another linear graph on the same runtime, next to the harness graph.
+        pub fn start_linear_graph(&self, name: &str) -> std::result::Result<(), std::io::Error> {
+            let graph = build_linear_graph(name);
+            let graph_name = graph.properties.name.clone();
+            let graph = Arc::new(RwLock::new(graph));
+            self.runtime
+                .write()
+                .expect("lock poisoned")
+                .graphs
+                .add_graph(graph_name, graph.clone());
+            run_graph(
+                self.runtime.clone(),
+                graph,
+                self.components.clone(),
+                self.graph_inout.clone(),
+            )
+        }
+
+        /// Stop the network of one graph, other networks on the runtime keep running.
+        pub fn stop_graph(&self, graph_name: &str) -> std::result::Result<(), std::io::Error> {
             self.runtime
                 .write()
                 .expect("lock poisoned")
-                .stop(self.graph_inout.clone(), false)?;
+                .stop(graph_name, self.graph_inout.clone(), false)?;
             Ok(())
         }
 
+        /// Return whether the network of the given graph is currently running.
+        pub fn is_graph_running(&self, graph_name: &str) -> bool {
+            self.runtime
+                .read()
+                .expect("lock poisoned")
+                .graph_status(graph_name)
+                .running
+        }
+
+        fn graph_name(&self) -> String {
+            self.graph.read().expect("lock poisoned").properties.name.clone()
+        }
+
         pub fn send_data_to_inport(
             &self,
             inport: &str,
@@ -92,11 +205,34 @@
                 typ: None,
                 schema: None,
                 graph: graph_name,
-                payload: Some(payload_text.to_string()),
+                payload: Some(JsonValue::from(payload_text)),
                 secret: None,
             };
-            Runtime::packet(&packet, self.graph_inout.clone(), self.runtime.clone())?;
-            Ok(())
+            // like a client would, send again while the graph inport is backpressured
+            const INPORT_PUSH_GRACE_DUR: Duration = Duration::from_secs(2);
+            let started = Instant::now();
+            loop {
+                match Runtime::packet(
+                    &packet,
+                    self.graph_inout.clone(),
+                    self.runtime.clone(),
+                    self.components.clone(),
+                ) {
+                    Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                        if started.elapsed() >= INPORT_PUSH_GRACE_DUR {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::TimedOut,
+                                format!(
+                                    "graph inport {} backpressured for {:?}",
+                                    inport, INPORT_PUSH_GRACE_DUR
+                                ),
+                            ));
+                        }
+                        std::thread::sleep(Duration::from_micros(100));
+                    }
+                    result => return result,
+                }
+            }
         }
 
         pub fn wait_for_outport_data(
@@ -129,7 +265,7 @@
                         outputs
                             .entry(outport.to_string())
                             .or_insert_with(Vec::new)
-                            .push(FbpMessage::from_text(payload.clone()));
+                            .push(Self::message_from_payload(payload));
                     }
                     count += 1;
                 }
@@ -207,7 +343,7 @@
 
         /// Return whether the runtime currently reports an active running network.
         pub fn is_running(&self) -> bool {
-            self.runtime.read().expect("lock poisoned").status.running
+            self.is_graph_running(&self.graph_name())
         }
 
         /// Wait until the runtime reports network shutdown (`running == false`).
@@ -230,7 +366,11 @@
 
         /// Return cumulative scheduler work units for a node in the active graph.
         pub fn node_work_units(&self, node_id: &str) -> Option<u64> {
-            let snapshot = self.runtime.read().expect("lock poisoned").status_snapshot();
+            let snapshot = self
+                .runtime
+                .read()
+                .expect("lock poisoned")
+                .status_snapshot(&self.graph_name());
             snapshot
                 .scheduler_metrics
                 .get(&snapshot.graph)
@@ -268,8 +408,145 @@
             ))
         }
 
+        /// Add a node to the graph and, like graph:addnode, to the running network.
+        pub fn add_node_live(
+            &self,
+            component: &str,
+            name: &str,
+        ) -> std::result::Result<(), std::io::Error> {
+            let graph_name = {
+                let mut graph = self.graph.write().expect("lock poisoned");
+                let graph_name = graph.properties.name.clone();
+                graph.add_node(
+                    graph_name.clone(),
+                    component.to_string(),
+                    name.to_string(),
+                    default_node_metadata(0, 0, name),
+                )?;
+                graph_name
+            };
+            let components = self.components.read().expect("lock poisoned");
+            let mut runtime = self.runtime.write().expect("lock poisoned");
+            if !runtime.is_running_graph(&graph_name) {
+                return Ok(());
+            }
+            runtime.live_add_node(
+                &graph_name,
+                name,
+                component,
+                &components,
+                self.graph_inout.clone(),
+            )
+        }
+
+        /// Remove a node and its edges from the graph and, like graph:removenode, from the running network.
+        pub fn remove_node_live(&self, name: &str) -> std::result::Result<(), std::io::Error> {
+            let (graph_name, component_name, removed_edges) = {
+                let mut graph = self.graph.write().expect("lock poisoned");
+                let graph_name = graph.properties.name.clone();
+                let component_name = graph
+                    .nodes
+                    .get(name)
+                    .map(|node| node.component.clone())
+                    .unwrap_or_default();
+                let removed_edges = graph.remove_node(graph_name.clone(), name.to_string())?;
+                (graph_name, component_name, removed_edges)
+            };
+            let drain = {
+                let components = self.components.read().expect("lock poisoned");
+                let mut runtime = self.runtime.write().expect("lock poisoned");
+                if !runtime.is_running_graph(&graph_name) {
+                    return Ok(());
+                }
+                runtime.live_remove_node(
+                    &graph_name,
+                    name,
+                    &component_name,
+                    &components,
+                    &removed_edges,
+                    self.graph_inout.clone(),
+                )?
+            };
+            drain()
+        }
+
+        /// Connect `source` (process, port) to `target` in the graph and, like graph:addedge, in the running network.
+        pub fn add_edge_live(
+            &self,
+            source: (&str, &str),
+            target: (&str, &str),
+        ) -> std::result::Result<(), std::io::Error> {
+            let new_edge = || GraphEdge {
+                source: GraphNodeSpec {
+                    process: source.0.to_string(),
+                    port: source.1.to_string(),
+                    index: None,
+                },
+                data: None,
+                target: GraphNodeSpec {
+                    process: target.0.to_string(),
+                    port: target.1.to_string(),
+                    index: None,
+                },
+                metadata: GraphEdgeMetadata::new(None, None, None),
+            };
+            let (graph_name, contract) = {
+                let mut graph = self.graph.write().expect("lock poisoned");
+                let graph_name = graph.properties.name.clone();
+                graph.add_edge(graph_name.clone(), new_edge())?;
+                let contract = graph.nodes.get(source.0).and_then(|node| {
+                    self.components
+                        .read()
+                        .expect("lock poisoned")
+                        .outport_contract(&node.component, source.1)
+                });
+                (graph_name, contract)
+            };
+            let mut runtime = self.runtime.write().expect("lock poisoned");
+            if !runtime.is_running_graph(&graph_name) {
+                return Ok(());
+            }
+            runtime.live_add_edge(&graph_name, &new_edge(), contract, self.graph_inout.clone())
+        }
+
+        /// Remove an edge from the graph and, like graph:removeedge, from the running network.
+        pub fn remove_edge_live(
+            &self,
+            source: (&str, &str),
+            target: (&str, &str),
+        ) -> std::result::Result<(), std::io::Error> {
+            let source = GraphNodeSpecNetwork {
+                node: source.0.to_string(),
+                port: source.1.to_string(),
+                index: None,
+            };
+            let target = GraphNodeSpecNetwork {
+                node: target.0.to_string(),
+                port: target.1.to_string(),
+                index: None,
+            };
+            let graph_name = {
+                let mut graph = self.graph.write().expect("lock poisoned");
+                let graph_name = graph.properties.name.clone();
+                graph.remove_edge(graph_name.clone(), source.clone(), target.clone())?;
+                graph_name
+            };
+            let drain = {
+                let mut runtime = self.runtime.write().expect("lock poisoned");
+                if !runtime.is_running_graph(&graph_name) {
+                    return Ok(());
+                }
+                runtime.live_remove_edge(&graph_name, &source, &target, self.graph_inout.clone())?
+            };
+            drain()
+        }
+
         pub fn all_node_work_units(&self) -> Vec<(String, u64)> {
-            let snapshot = self.runtime.read().expect("lock poisoned").status_snapshot();
+            let snapshot = self
+                .runtime
+                .read()
+                .expect("lock poisoned")
+                .status_snapshot(&self.graph_name());
             snapshot
                 .scheduler_metrics
                 .values()
@@ -283,6 +560,12 @@
         }
     }
 
+    impl Drop for BenchRuntimeHarness {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.state_dir);
+        }
+    }
+
     fn load_graph_from_persistence_path(
         path: &std::path::Path,
     ) -> std::result::Result<Graph, std::io::Error> {
@@ -308,6 +591,7 @@
             width: Some(NODE_WIDTH_DEFAULT),
             label: Some(label.to_string()),
             icon: None,
+            restart: None,
         }
     }
 
@@ -374,6 +658,70 @@
         graph
     }
 
+    /// Graph `name` running IN -> First -> Stage -> OUT, where Stage is the linear graph `subgraph` used as component.
+    fn build_subgraph_graph(name: &str, subgraph: &str) -> Graph {
+        let mut graph = Graph::new(
+            name.to_string(),
+            "benchmark".to_string(),
+            "random".to_string(),
+        );
+
+        graph.inports.insert(
+            "IN".to_string(),
+            GraphPort {
+                process: "First".to_string(),
+                port: "IN".to_string(),
+                metadata: GraphPortMetadata { x: 0, y: 0 },
+            },
+        );
+        graph.outports.insert(
+            "OUT".to_string(),
+            GraphPort {
+                process: "Stage".to_string(),
+                port: "OUT".to_string(),
+                metadata: GraphPortMetadata { x: 300, y: 0 },
+            },
+        );
+
+        graph
+            .add_node(
+                name.to_string(),
+                "Repeat".to_string(),
+                "First".to_string(),
+                default_node_metadata(80, 80, "First"),
+            )
+            .expect("failed to add First node");
+        graph
+            .add_node(
+                name.to_string(),
+                subgraph.to_string(),
+                "Stage".to_string(),
+                default_node_metadata(180, 80, "Stage"),
+            )
+            .expect("failed to add Stage node");
+        graph
+            .add_edge(
+                name.to_string(),
+                GraphEdge {
+                    source: GraphNodeSpec {
+                        process: "First".to_string(),
+                        port: "OUT".to_string(),
+                        index: None,
+                    },
+                    data: None,
+                    target: GraphNodeSpec {
+                        process: "Stage".to_string(),
+                        port: "IN".to_string(),
+                        index: None,
+                    },
+                    metadata: GraphEdgeMetadata::new(None, None, None),
+                },
+            )
+            .expect("failed to add edge First.OUT -> Stage.IN");
+
+        graph
+    }
+
     fn build_fan_out_graph(name: &str) -> Graph {
         let mut graph = Graph::new(
             name.to_string(),
@@ -727,6 +1075,13 @@
         Ok(BenchRuntimeHarness::new(loaded))
     }
 
+    pub fn subgraph_harness_direct(name: &str) -> BenchRuntimeHarness {
+        let subgraph = format!("{}_pipeline", name);
+        let harness = BenchRuntimeHarness::new(build_subgraph_graph(name, &subgraph));
+        harness.add_subgraph(build_linear_graph(&subgraph));
+        harness
+    }
+
     pub fn fan_out_harness_direct(name: &str) -> BenchRuntimeHarness {
         BenchRuntimeHarness::new(build_fan_out_graph(name))
     }
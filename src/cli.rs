@@ -0,0 +1,386 @@
+// command line interface: management server, headless execution and graph tooling
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use log::{info, warn};
+use signal_hook::consts::signal::*;
+use signal_hook::flag;
+
+use crate::Graph;
+
+const DEFAULT_BIND_ADDR: &str = "localhost:3569"; // local only, unless TLS and secrets are set up
+const HEADLESS_POLL_DUR: Duration = Duration::from_millis(50);
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "flowd",
+    version,
+    about = "inter-language runtime for Flow-based Programming (FBP)"
+)]
+struct Cli {
+    /// Most detailed log level to output
+    #[arg(long, global = true, value_enum, default_value_t = LogLevel::Trace)]
+    log_level: LogLevel,
+
+    /// Format of the log output
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Log level of a graph or of one of its nodes, as GRAPH=LEVEL or GRAPH/NODE=LEVEL (repeatable)
+    #[arg(
+        long = "node-log-level",
+        global = true,
+        value_name = "GRAPH[/NODE]=LEVEL",
+        value_parser = parse_node_log_level
+    )]
+    node_log_levels: Vec<NodeLogLevel>,
+
+    // without subcommand, `flowd-rs [OPTIONS] [ADDRESS]` serves as well
+    #[command(flatten)]
+    serve: ServeArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the management server for FBP protocol clients (default)
+    Serve(ServeArgs),
+    /// Run a graph without management server, exit when all nodes have finished
+    Run {
+        /// Graph file, .json or .fbp
+        graph: PathBuf,
+    },
+    /// Check a graph against the component library and print all problems
+    Validate {
+        /// Graph file, .json or .fbp
+        graph: PathBuf,
+    },
+    /// Print the component library
+    Components,
+    /// Convert a graph between .json and .fbp, by file extension
+    Convert {
+        /// Graph file to read
+        input: PathBuf,
+        /// Graph file to write
+        output: PathBuf,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ServeArgs {
+    /// Address to listen on for FBP protocol clients
+    #[arg(long, default_value = DEFAULT_BIND_ADDR)]
+    bind: String,
+
+    /// Address to listen on, same as --bind
+    #[arg(value_name = "ADDRESS", conflicts_with = "bind")]
+    address: Option<String>,
+
+    /// Directory of the persisted graphs, node state checkpoints and edge journals
+    #[arg(long, default_value = ".")]
+    graph_dir: PathBuf,
+
+    /// Certificate chain for TLS (wss://), PEM
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// Private key for TLS, PEM
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Secret tokens with per-graph access level, JSON with SHA-256 digests of the tokens
+    #[arg(long)]
+    secrets: Option<PathBuf>,
+
+    /// Reject every message not carrying a valid secret
+    #[arg(long, requires = "secrets")]
+    strict_auth: bool,
+
+    /// Address to serve node and edge metrics on, in OpenMetrics format at /metrics
+    #[arg(long)]
+    metrics_bind: Option<String>,
+
+    /// Accept script components from clients (component:setsource), which run the sent code
+    #[arg(long)]
+    allow_scripts: bool,
+}
+
+impl ServeArgs {
+    fn bind_addr(&self) -> &str {
+        self.address.as_deref().unwrap_or(&self.bind)
+    }
+
+    fn security(&self) -> std::result::Result<crate::server::ServerSecurity, std::io::Error> {
+        let tls = match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => Some(crate::tls::load_server_config(cert, key)?),
+            _ => None,
+        };
+        let secrets = match &self.secrets {
+            Some(path) => crate::auth::SecretStore::load(path)?,
+            None => crate::auth::SecretStore::default(),
+        };
+        Ok(crate::server::ServerSecurity {
+            tls,
+            secrets,
+            strict_auth: self.strict_auth,
+            allow_scripts: self.allow_scripts,
+        })
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for simplelog::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => simplelog::LevelFilter::Off,
+            LogLevel::Error => simplelog::LevelFilter::Error,
+            LogLevel::Warn => simplelog::LevelFilter::Warn,
+            LogLevel::Info => simplelog::LevelFilter::Info,
+            LogLevel::Debug => simplelog::LevelFilter::Debug,
+            LogLevel::Trace => simplelog::LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+impl From<LogFormat> for crate::logging::LogFormat {
+    fn from(format: LogFormat) -> Self {
+        match format {
+            LogFormat::Text => crate::logging::LogFormat::Text,
+            LogFormat::Json => crate::logging::LogFormat::Json,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct NodeLogLevel {
+    graph: String,
+    node: Option<String>,
+    level: log::LevelFilter,
+}
+
+fn parse_node_log_level(arg: &str) -> Result<NodeLogLevel, String> {
+    let (position, level) = arg
+        .rsplit_once('=')
+        .ok_or_else(|| String::from("expected GRAPH=LEVEL or GRAPH/NODE=LEVEL"))?;
+    let level =
+        crate::logging::parse_level(level).ok_or_else(|| format!("unknown log level {}", level))?;
+    let (graph, node) = match position.split_once('/') {
+        Some((graph, node)) => (graph, Some(node.to_owned())),
+        None => (position, None),
+    };
+    if graph.is_empty() || node.as_deref() == Some("") {
+        return Err(String::from("graph and node names must not be empty"));
+    }
+    Ok(NodeLogLevel {
+        graph: graph.to_owned(),
+        node,
+        level,
+    })
+}
+
+/// Parse the command line and run the selected subcommand.
+pub fn run() -> std::result::Result<(), std::io::Error> {
+    let cli = Cli::parse();
+    match cli.command.unwrap_or(Command::Serve(cli.serve)) {
+        Command::Serve(args) => {
+            let security = args.security()?;
+            init_logging(cli.log_level, cli.log_format, &cli.node_log_levels);
+            crate::server::run(
+                args.bind_addr(),
+                &args.graph_dir,
+                security,
+                args.metrics_bind.as_deref(),
+            )
+            .map_err(|err| std::io::Error::other(err.to_string()))
+        }
+        Command::Run { graph } => {
+            init_logging(cli.log_level, cli.log_format, &cli.node_log_levels);
+            run_graph_file(&graph)
+        }
+        Command::Validate { graph } => validate(&graph),
+        Command::Components => {
+            print_components();
+            Ok(())
+        }
+        Command::Convert { input, output } => convert(&input, &output),
+    }
+}
+
+fn init_logging(level: LogLevel, format: LogFormat, node_levels: &[NodeLogLevel]) {
+    //NOTE: levels above debug are removed at compile-time, see Cargo.toml
+    crate::logging::init(level.into(), format.into()).expect("logging init failed");
+    for setting in node_levels {
+        crate::logging::set_level(&setting.graph, setting.node.as_deref(), Some(setting.level));
+    }
+    info!("logging initialized");
+}
+
+// graphs of the file and the name of the graph to run
+fn load_graphs(
+    path: &Path,
+) -> std::result::Result<(crate::multi_graph::MultiGraphManager, String), std::io::Error> {
+    let (loaded_graphs, active_graph) = crate::load_graph_file(path)?;
+    let mut graphs = crate::multi_graph::MultiGraphManager::new();
+    for (graph_name, graph) in loaded_graphs.into_iter() {
+        graphs.add_graph(graph_name, graph);
+    }
+    graphs.set_active_graph(&active_graph)?;
+    Ok((graphs, active_graph))
+}
+
+// component library including the plugins and script components next to the graph file
+fn load_components(
+    graph_path: &Path,
+) -> std::result::Result<Arc<RwLock<crate::ComponentLibrary>>, std::io::Error> {
+    let components = crate::create_component_library();
+    let graph_dir = graph_path.parent().unwrap_or(Path::new("."));
+    let mut library = components.write().expect("lock poisoned");
+    library.load_plugins(&graph_dir.join(crate::plugin::PLUGIN_DIR))?;
+    library.load_scripts(&graph_dir.join(crate::script::SCRIPT_DIR))?;
+    drop(library);
+    Ok(components)
+}
+
+/// Run the graph of the file without management server, return once all its nodes have finished.
+pub fn run_graph_file(path: &Path) -> std::result::Result<(), std::io::Error> {
+    let (graphs, graph_name) = load_graphs(path)?;
+    let graph: Arc<RwLock<Graph>> = graphs
+        .get_active_graph()
+        .expect("active graph checked when loading");
+    let runtime = crate::create_runtime(graph_name.clone());
+    let components = load_components(path)?;
+    let graph_inout = crate::create_graph_inout_holder();
+    {
+        let mut runtime_write = runtime.write().expect("lock poisoned");
+        runtime_write.graphs = graphs;
+    }
+
+    // stop the network on SIGINT and SIGTERM, so node state is checkpointed
+    let stop_requested = Arc::new(AtomicBool::new(false));
+    flag::register(SIGINT, stop_requested.clone())?;
+    flag::register(SIGTERM, stop_requested.clone())?;
+
+    crate::run_graph(runtime.clone(), graph, components, graph_inout.clone())?;
+    info!("running graph {} headless", graph_name);
+
+    loop {
+        thread::sleep(HEADLESS_POLL_DUR);
+        if stop_requested.load(Ordering::Relaxed) {
+            runtime.write().expect("lock poisoned").stop(
+                &graph_name,
+                graph_inout.clone(),
+                false,
+            )?;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                format!(
+                    "graph {} stopped by signal before all nodes finished",
+                    graph_name
+                ),
+            ));
+        }
+        let runtime_read = runtime.read().expect("lock poisoned");
+        if !runtime_read.graph_status(&graph_name).running {
+            break;
+        }
+        if runtime_read.watchdog_exited(&graph_name) {
+            // watchdog gave up before it could stop the network itself
+            drop(runtime_read);
+            runtime
+                .write()
+                .expect("lock poisoned")
+                .stop(&graph_name, graph_inout.clone(), true)?;
+            break;
+        }
+    }
+    info!("all nodes of graph {} finished", graph_name);
+    Ok(())
+}
+
+fn validate(path: &Path) -> std::result::Result<(), std::io::Error> {
+    let (graphs, graph_name) = load_graphs(path)?;
+    let components = load_components(path)?;
+    let components = components.read().expect("lock poisoned");
+    let graph = graphs
+        .get_active_graph()
+        .expect("active graph checked when loading");
+    let network = graph
+        .read()
+        .expect("lock poisoned")
+        .inline_subgraphs(&graphs, &components)?;
+    match network.validate(&components) {
+        Ok(()) => {
+            println!("graph {} is valid", graph_name);
+            Ok(())
+        }
+        Err(errors) => {
+            for error in errors.iter() {
+                println!("{}: {}", graph_name, error);
+            }
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("graph {} has {} problem(s)", graph_name, errors.len()),
+            ))
+        }
+    }
+}
+
+fn convert(input: &Path, output: &Path) -> std::result::Result<(), std::io::Error> {
+    let (graphs, _) = load_graphs(input)?;
+    let graph = graphs
+        .get_active_graph()
+        .expect("active graph checked when loading");
+    let graph = graph.read().expect("lock poisoned");
+    let converted = if output.extension().is_some_and(|ext| ext == "fbp") {
+        if graph.edges.iter().any(|edge| edge.metadata.is_persistent()) {
+            warn!("edge metadata is not part of .fbp, persistent edges become plain edges");
+        }
+        graph.to_fbp()?
+    } else {
+        serde_json::to_string_pretty(&*graph)?
+    };
+    std::fs::write(output, converted)
+}
+
+fn print_components() {
+    let components = crate::create_component_library();
+    let components = components.read().expect("lock poisoned");
+    let mut available = components.available.iter().collect::<Vec<_>>();
+    available.sort_by(|a, b| a.name.cmp(&b.name));
+    for component in available {
+        println!("{}  {}", component.name, component.description);
+        for (direction, ports) in [("in", &component.in_ports), ("out", &component.out_ports)] {
+            for port in ports.iter() {
+                println!(
+                    "    {:<3} {}{} ({})",
+                    direction,
+                    port.name,
+                    if port.is_arrayport { "[]" } else { "" },
+                    port.allowed_type
+                );
+            }
+        }
+    }
+}
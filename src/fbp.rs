@@ -0,0 +1,602 @@
+// .fbp network description language: parser and serializer for Graph
+//
+// Supported syntax, one statement per line or separated by , or ;
+//   'data' -> IN Node(Component)                initial IP
+//   A(Component) OUT -> IN B(Component) OUT -> IN C(Component)
+//   A OUT[1] -> IN[0] B                         array port indices
+//   INPORT=Node.PORT:NAME  OUTPORT=Node.PORT:NAME   graph inports and outports
+//   Node(Component)                             node without connections
+//   # comment  # @name graph_name  # @description text  # @icon name
+// Node positions and edge metadata are not part of the language.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::{
+    Graph, GraphEdge, GraphEdgeMetadata, GraphNode, GraphNodeMetadata, GraphNodeSpec, GraphPort,
+    GraphPortMetadata, NODE_HEIGHT_DEFAULT, NODE_WIDTH_DEFAULT,
+};
+
+// layout of parsed nodes, left to right in order of appearance
+const NODE_SPACING_X: i32 = 200;
+const NODE_SPACING_Y: i32 = 150;
+const NODES_PER_ROW: usize = 6;
+
+/// Syntax error in a .fbp network description, line and column start at 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FbpParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FbpParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for FbpParseError {}
+
+impl From<FbpParseError> for std::io::Error {
+    fn from(err: FbpParseError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+    }
+}
+
+impl Graph {
+    /// Parse a .fbp network description. An `# @name` annotation takes precedence over `name`.
+    pub fn from_fbp(name: &str, source: &str) -> Result<Graph, FbpParseError> {
+        let mut parser = Parser::new(source);
+        parser.parse()?;
+
+        let mut graph = Graph::new(
+            parser.name.unwrap_or_else(|| name.to_owned()),
+            parser.description.unwrap_or_default(),
+            parser.icon.unwrap_or_default(),
+        );
+        for (i, (node_name, component)) in parser.nodes.into_iter().enumerate() {
+            let Some(component) = component else {
+                let (line, column) = parser.first_mention[&node_name];
+                return Err(FbpParseError {
+                    line,
+                    column,
+                    message: format!("component of node {} is never declared", node_name),
+                });
+            };
+            let metadata = GraphNodeMetadata {
+                x: (i % NODES_PER_ROW) as i32 * NODE_SPACING_X,
+                y: (i / NODES_PER_ROW) as i32 * NODE_SPACING_Y,
+                width: Some(NODE_WIDTH_DEFAULT),
+                height: Some(NODE_HEIGHT_DEFAULT),
+                label: Some(node_name.clone()),
+                icon: None,
+                restart: None,
+            };
+            graph.nodes.insert(
+                node_name,
+                GraphNode {
+                    component,
+                    protocol_metadata: crate::graph_node_metadata_to_payload(&metadata),
+                    metadata,
+                },
+            );
+        }
+        graph.edges = parser.edges;
+        graph.inports = parser.inports;
+        graph.outports = parser.outports;
+        Ok(graph)
+    }
+
+    /// Serialize the graph as .fbp network description, see `from_fbp()`.
+    /// Node positions and edge metadata are lost, so persistent edges (ADR-010) become plain ones.
+    /// Fails if a node or port name cannot be written in the language.
+    pub fn to_fbp<'a>(&'a self) -> Result<String, std::io::Error> {
+        self.check_fbp_names()?;
+        let mut out = String::new();
+        out.push_str(&format!("# @name {}\n", self.properties.name));
+        if !self.properties.description.is_empty() {
+            let description = self.properties.description.replace('\n', " ");
+            out.push_str(&format!("# @description {}\n", description));
+        }
+        if !self.properties.icon.is_empty() {
+            out.push_str(&format!("# @icon {}\n", self.properties.icon));
+        }
+
+        for (keyword, ports) in [("INPORT", &self.inports), ("OUTPORT", &self.outports)] {
+            let mut ports = ports.iter().collect::<Vec<_>>();
+            ports.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, port) in ports {
+                out.push_str(&format!(
+                    "{}={}.{}:{}\n",
+                    keyword, port.process, port.port, name
+                ));
+            }
+        }
+
+        // the component is written where a node appears first
+        let mut declared: HashSet<&str> = HashSet::new();
+        let mut node_ref = |node: &'a str, out: &mut String| {
+            out.push_str(node);
+            if declared.insert(node) {
+                if let Some(node_def) = self.nodes.get(node) {
+                    out.push_str(&format!("({})", node_def.component));
+                }
+            }
+        };
+        for edge in self.edges.iter() {
+            match &edge.data {
+                Some(data) if edge.source.process.is_empty() => {
+                    out.push_str(&quote_iip(data));
+                }
+                _ => {
+                    node_ref(&edge.source.process, &mut out);
+                    out.push(' ');
+                    out.push_str(&port_ref(&edge.source));
+                }
+            }
+            out.push_str(" -> ");
+            out.push_str(&port_ref(&edge.target));
+            out.push(' ');
+            node_ref(&edge.target.process, &mut out);
+            out.push('\n');
+        }
+
+        // nodes without any connection
+        let mut unconnected = self
+            .nodes
+            .keys()
+            .filter(|node| !declared.contains(node.as_str()))
+            .collect::<Vec<_>>();
+        unconnected.sort();
+        for node in unconnected {
+            out.push_str(&format!("{}({})\n", node, self.nodes[node].component));
+        }
+        Ok(out)
+    }
+
+    // the language has no quoting for names, see is_name_char()
+    fn check_fbp_names(&self) -> Result<(), std::io::Error> {
+        let mut names = vec![];
+        for (name, node) in self.nodes.iter() {
+            names.push(("node", name.as_str()));
+            if node.component.contains([')', ':', '\n']) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "component {} of node {} cannot be written in .fbp",
+                        node.component, name
+                    ),
+                ));
+            }
+        }
+        for ports in [&self.inports, &self.outports] {
+            for (name, port) in ports.iter() {
+                names.extend([
+                    ("graph port", name.as_str()),
+                    ("node", port.process.as_str()),
+                    ("port", port.port.as_str()),
+                ]);
+            }
+        }
+        for edge in self.edges.iter() {
+            if edge.data.is_none() || !edge.source.process.is_empty() {
+                names.extend([
+                    ("node", edge.source.process.as_str()),
+                    ("port", edge.source.port.as_str()),
+                ]);
+            }
+            names.extend([
+                ("node", edge.target.process.as_str()),
+                ("port", edge.target.port.as_str()),
+            ]);
+        }
+        match names
+            .into_iter()
+            .find(|(_, name)| name.is_empty() || !name.chars().all(is_name_char))
+        {
+            Some((what, name)) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} name {:?} cannot be written in .fbp", what, name),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+fn port_ref(spec: &GraphNodeSpec) -> String {
+    match &spec.index {
+        Some(index) => format!("{}[{}]", spec.port, index),
+        None => spec.port.clone(),
+    }
+}
+
+fn quote_iip(data: &str) -> String {
+    let mut out = String::with_capacity(data.len() + 2);
+    out.push('\'');
+    for c in data.chars() {
+        match c {
+            '\'' => out.push_str("\\'"),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '/'
+}
+
+// where an edge comes from while parsing a chain of connections
+enum Source {
+    Iip(String),
+    Port(GraphNodeSpec),
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+    name: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    nodes: Vec<(String, Option<String>)>, // in order of appearance, with component once declared
+    first_mention: HashMap<String, (usize, usize)>,
+    edges: Vec<GraphEdge>,
+    inports: HashMap<String, GraphPort>,
+    outports: HashMap<String, GraphPort>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser {
+            source,
+            pos: 0,
+            line: 1,
+            column: 1,
+            name: None,
+            description: None,
+            icon: None,
+            nodes: vec![],
+            first_mention: HashMap::new(),
+            edges: vec![],
+            inports: HashMap::new(),
+            outports: HashMap::new(),
+        }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, FbpParseError> {
+        Err(FbpParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.source[self.pos..].starts_with(expected) {
+            for _ in expected.chars() {
+                self.bump();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), FbpParseError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            self.error(format!("expected {}", expected))
+        }
+    }
+
+    // spaces and tabs within a statement
+    fn skip_blanks(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    // whitespace, line breaks and comments, used between statements and after ->
+    fn skip_space(&mut self) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => self.comment(),
+                _ => return,
+            }
+        }
+    }
+
+    fn comment(&mut self) {
+        let start = self.pos;
+        while !matches!(self.peek(), None | Some('\n')) {
+            self.bump();
+        }
+        let text = self.source[start..self.pos].trim_start_matches('#').trim();
+        let Some(annotation) = text.strip_prefix('@') else {
+            return;
+        };
+        let (key, value) = annotation
+            .split_once(char::is_whitespace)
+            .unwrap_or((annotation, ""));
+        let value = Some(value.trim().to_owned());
+        match key {
+            "name" => self.name = value,
+            "description" => self.description = value,
+            "icon" => self.icon = value,
+            _ => {} // other annotations, for example @runtime, are not relevant to the graph
+        }
+    }
+
+    fn parse(&mut self) -> Result<(), FbpParseError> {
+        loop {
+            self.skip_space();
+            while matches!(self.peek(), Some(',' | ';')) {
+                self.bump();
+                self.skip_space();
+            }
+            if self.peek().is_none() {
+                return Ok(());
+            }
+            self.statement()?;
+            self.skip_blanks();
+            match self.peek() {
+                None | Some('\n' | '\r' | ',' | ';' | '#') => {}
+                Some(c) => return self.error(format!("unexpected character '{}'", c)),
+            }
+        }
+    }
+
+    fn at_statement_end(&self) -> bool {
+        matches!(self.peek(), None | Some('\n' | '\r' | ',' | ';' | '#'))
+    }
+
+    fn statement(&mut self) -> Result<(), FbpParseError> {
+        if self.eat("INPORT=") {
+            let (name, port) = self.exported_port()?;
+            return self.export(name, port, true);
+        }
+        if self.eat("OUTPORT=") {
+            let (name, port) = self.exported_port()?;
+            return self.export(name, port, false);
+        }
+
+        let mut source = if self.peek() == Some('\'') {
+            let data = self.iip()?;
+            self.skip_blanks();
+            Source::Iip(data)
+        } else {
+            let node = self.node()?;
+            self.skip_blanks();
+            if self.at_statement_end() {
+                // node declaration without connections
+                return Ok(());
+            }
+            let (port, index) = self.port()?;
+            self.skip_blanks();
+            Source::Port(GraphNodeSpec {
+                process: node,
+                port,
+                index,
+            })
+        };
+
+        loop {
+            self.expect("->")?;
+            self.skip_space();
+            let (port, index) = self.port()?;
+            self.skip_blanks();
+            let node = self.node()?;
+            let target = GraphNodeSpec {
+                process: node.clone(),
+                port,
+                index,
+            };
+            self.edges.push(match source {
+                Source::Iip(data) => GraphEdge {
+                    source: GraphNodeSpec {
+                        process: String::new(),
+                        port: String::new(),
+                        index: None,
+                    },
+                    data: Some(data),
+                    target,
+                    metadata: GraphEdgeMetadata::new(None, None, None),
+                },
+                Source::Port(spec) => GraphEdge {
+                    source: spec,
+                    data: None,
+                    target,
+                    metadata: GraphEdgeMetadata::new(None, None, None),
+                },
+            });
+
+            self.skip_blanks();
+            if self.at_statement_end() {
+                return Ok(());
+            }
+            let (port, index) = self.port()?;
+            self.skip_blanks();
+            source = Source::Port(GraphNodeSpec {
+                process: node,
+                port,
+                index,
+            });
+        }
+    }
+
+    fn name(&mut self, what: &str) -> Result<String, FbpParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(is_name_char) {
+            self.bump();
+        }
+        if start == self.pos {
+            return self.error(format!("expected {}", what));
+        }
+        Ok(self.source[start..self.pos].to_owned())
+    }
+
+    // node name, optionally followed by (Component)
+    fn node(&mut self) -> Result<String, FbpParseError> {
+        let position = (self.line, self.column);
+        let name = self.name("node name")?;
+        let component = if self.eat("(") {
+            let start = self.pos;
+            while !matches!(self.peek(), None | Some(')' | ':' | '\n')) {
+                self.bump();
+            }
+            let component = self.source[start..self.pos].trim().to_owned();
+            if self.eat(":") {
+                // component metadata, for example Node(Component:key=value)
+                while !matches!(self.peek(), None | Some(')' | '\n')) {
+                    self.bump();
+                }
+            }
+            self.expect(")")?;
+            if component.is_empty() {
+                None
+            } else {
+                Some(component)
+            }
+        } else {
+            None
+        };
+
+        self.first_mention.entry(name.clone()).or_insert(position);
+        match self.nodes.iter_mut().find(|(node, _)| *node == name) {
+            Some((_, declared)) => {
+                if let Some(component) = component {
+                    match declared {
+                        Some(declared) if *declared != component => {
+                            return Err(FbpParseError {
+                                line: position.0,
+                                column: position.1,
+                                message: format!(
+                                    "node {} is already declared as {}",
+                                    name, declared
+                                ),
+                            });
+                        }
+                        _ => *declared = Some(component),
+                    }
+                }
+            }
+            None => self.nodes.push((name.clone(), component)),
+        }
+        Ok(name)
+    }
+
+    // port name with optional [index]
+    fn port(&mut self) -> Result<(String, Option<String>), FbpParseError> {
+        let port = self.name("port name")?;
+        if !self.eat("[") {
+            return Ok((port, None));
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        if start == self.pos {
+            return self.error("expected array port index");
+        }
+        let index = self.source[start..self.pos].to_owned();
+        self.expect("]")?;
+        Ok((port, Some(index)))
+    }
+
+    fn iip(&mut self) -> Result<String, FbpParseError> {
+        let (line, column) = (self.line, self.column);
+        self.expect("'")?;
+        let mut data = String::new();
+        loop {
+            match self.bump() {
+                None => {
+                    return Err(FbpParseError {
+                        line,
+                        column,
+                        message: "unterminated initial IP".to_owned(),
+                    })
+                }
+                Some('\'') => return Ok(data),
+                Some('\\') => match self.bump() {
+                    Some('n') => data.push('\n'),
+                    Some('r') => data.push('\r'),
+                    Some('t') => data.push('\t'),
+                    Some(c @ ('\'' | '\\')) => data.push(c),
+                    Some(c) => {
+                        // unknown escape, keep as written
+                        data.push('\\');
+                        data.push(c);
+                    }
+                    None => continue,
+                },
+                Some(c) => data.push(c),
+            }
+        }
+    }
+
+    // Node.PORT:NAME of INPORT= and OUTPORT=
+    fn exported_port(&mut self) -> Result<(String, GraphPort), FbpParseError> {
+        let process = self.name("node name")?;
+        self.expect(".")?;
+        let port = self.name("port name")?;
+        self.expect(":")?;
+        let name = self.name("graph port name")?;
+        Ok((
+            name,
+            GraphPort {
+                process,
+                port,
+                metadata: GraphPortMetadata { x: 0, y: 0 },
+            },
+        ))
+    }
+
+    fn export(&mut self, name: String, port: GraphPort, inport: bool) -> Result<(), FbpParseError> {
+        let ports = if inport {
+            &mut self.inports
+        } else {
+            &mut self.outports
+        };
+        if ports.contains_key(&name) {
+            return self.error(format!(
+                "graph {} {} is already declared",
+                if inport { "inport" } else { "outport" },
+                name
+            ));
+        }
+        ports.insert(name, port);
+        Ok(())
+    }
+}
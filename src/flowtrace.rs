@@ -0,0 +1,268 @@
+//! Flowtrace recording for the trace:* protocol.
+//!
+//! Trace events coming out of `ProcessEdgeSink::push` are kept in a ring buffer bounded by
+//! the `buffersize` (in bytes) given on trace:start. When the buffer is full, the oldest
+//! events are evicted. trace:dump renders the buffer as a Flowtrace JSON document,
+//! see https://github.com/flowbased/flowtrace
+
+use std::collections::VecDeque;
+
+use chrono::prelude::*;
+use serde::Serialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+/// Buffer size used when trace:start does not give a usable one (buffersize 0).
+pub const TRACE_BUFFER_SIZE_DEFAULT: usize = 1024 * 1024;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FlowtraceEvent {
+    pub protocol: String, // always "network" for edge events
+    pub command: String,  // connect, data, disconnect
+    pub payload: JsonValue,
+}
+
+#[derive(Serialize, Debug)]
+struct FlowtraceMetadata {
+    start: String,
+    end: String,
+    runtime: String,
+    label: String,
+    #[serde(rename = "type")]
+    typ: String,
+    #[serde(rename = "droppedEvents")]
+    dropped_events: u64, // flowd extension: events evicted because the buffer was full
+}
+
+#[derive(Serialize, Debug)]
+struct FlowtraceHeader {
+    metadata: FlowtraceMetadata,
+    graphs: JsonMap<String, JsonValue>,
+}
+
+#[derive(Serialize, Debug)]
+struct Flowtrace<'a> {
+    header: FlowtraceHeader,
+    events: Vec<&'a FlowtraceEvent>,
+}
+
+/// Runtime identification written into the Flowtrace header.
+#[derive(Debug, Clone)]
+pub struct FlowtraceRuntimeInfo {
+    pub id: String,
+    pub label: String,
+    pub runtime: String,
+}
+
+#[derive(Debug)]
+pub struct TraceBuffer {
+    capacity_bytes: usize,
+    used_bytes: usize,
+    events: VecDeque<(usize, FlowtraceEvent)>, // (encoded size, event)
+    started: DateTime<Utc>,
+    dropped_events: u64,
+}
+
+impl Default for TraceBuffer {
+    fn default() -> Self {
+        TraceBuffer::new(TRACE_BUFFER_SIZE_DEFAULT)
+    }
+}
+
+impl TraceBuffer {
+    pub fn new(capacity_bytes: usize) -> Self {
+        TraceBuffer {
+            capacity_bytes: Self::effective_capacity(capacity_bytes),
+            used_bytes: 0,
+            events: VecDeque::new(),
+            started: Utc::now(),
+            dropped_events: 0,
+        }
+    }
+
+    fn effective_capacity(capacity_bytes: usize) -> usize {
+        if capacity_bytes == 0 {
+            TRACE_BUFFER_SIZE_DEFAULT
+        } else {
+            capacity_bytes
+        }
+    }
+
+    /// Change the capacity, evicting the oldest events if they no longer fit.
+    pub fn set_capacity(&mut self, capacity_bytes: usize) {
+        self.capacity_bytes = Self::effective_capacity(capacity_bytes);
+        self.evict_to_fit(0);
+    }
+
+    pub fn capacity_bytes(&self) -> usize {
+        self.capacity_bytes
+    }
+
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events
+    }
+
+    /// Record one trace event. The payload is stored together with its timestamp.
+    /// Events larger than the whole buffer are dropped.
+    pub fn record<T: Serialize>(&mut self, command: &str, payload: &T) {
+        let mut payload = match serde_json::to_value(payload) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("flowtrace: failed to encode {} event: {}", command, err);
+                return;
+            }
+        };
+        if let JsonValue::Object(fields) = &mut payload {
+            fields.insert(
+                String::from("time"),
+                JsonValue::from(Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)),
+            );
+        }
+        let event = FlowtraceEvent {
+            protocol: String::from("network"),
+            command: command.to_owned(),
+            payload,
+        };
+        let size = serde_json::to_string(&event)
+            .map(|encoded| encoded.len())
+            .unwrap_or(0);
+        if size > self.capacity_bytes {
+            self.dropped_events += 1;
+            return;
+        }
+        self.evict_to_fit(size);
+        self.used_bytes += size;
+        self.events.push_back((size, event));
+    }
+
+    fn evict_to_fit(&mut self, incoming: usize) {
+        while self.used_bytes + incoming > self.capacity_bytes {
+            match self.events.pop_front() {
+                Some((size, _)) => {
+                    self.used_bytes -= size;
+                    self.dropped_events += 1;
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Drop all recorded events and restart the trace period.
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.used_bytes = 0;
+        self.dropped_events = 0;
+        self.started = Utc::now();
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &FlowtraceEvent> {
+        self.events.iter().map(|(_, event)| event)
+    }
+
+    /// Render the buffer as Flowtrace JSON document.
+    /// `graphs` maps graph names to their FBP JSON graph representation.
+    pub fn dump(
+        &self,
+        runtime: &FlowtraceRuntimeInfo,
+        graphs: JsonMap<String, JsonValue>,
+    ) -> Result<String, serde_json::Error> {
+        let flowtrace = Flowtrace {
+            header: FlowtraceHeader {
+                metadata: FlowtraceMetadata {
+                    start: self.started.to_rfc3339_opts(SecondsFormat::Micros, true),
+                    end: Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true),
+                    runtime: runtime.id.clone(),
+                    label: runtime.label.clone(),
+                    typ: runtime.runtime.clone(),
+                    dropped_events: self.dropped_events,
+                },
+                graphs,
+            },
+            events: self.events().collect(),
+        };
+        serde_json::to_string(&flowtrace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn runtime_info() -> FlowtraceRuntimeInfo {
+        FlowtraceRuntimeInfo {
+            id: String::from("runtime-id"),
+            label: String::from("test runtime"),
+            runtime: String::from("flowd"),
+        }
+    }
+
+    #[test]
+    fn record_evicts_oldest_events_when_full() {
+        let mut buffer = TraceBuffer::new(600);
+        for i in 0..20 {
+            buffer.record(
+                "data",
+                &json!({ "id": "a.OUT-b.IN", "graph": "g", "data": i }),
+            );
+        }
+        assert!(buffer.used_bytes() <= buffer.capacity_bytes());
+        assert!(buffer.len() < 20);
+        assert_eq!(buffer.dropped_events() as usize + buffer.len(), 20);
+        // newest event survives
+        let last = buffer.events().last().expect("buffer should not be empty");
+        assert_eq!(last.payload["data"], json!(19));
+        assert!(last.payload["time"].is_string());
+    }
+
+    #[test]
+    fn oversized_event_is_dropped() {
+        let mut buffer = TraceBuffer::new(16);
+        buffer.record(
+            "data",
+            &json!({ "data": "this does not fit into sixteen bytes" }),
+        );
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.dropped_events(), 1);
+    }
+
+    #[test]
+    fn dump_is_flowtrace_document() {
+        let mut buffer = TraceBuffer::new(0);
+        assert_eq!(buffer.capacity_bytes(), TRACE_BUFFER_SIZE_DEFAULT);
+        buffer.record("connect", &json!({ "id": "e1", "graph": "g" }));
+        buffer.record(
+            "data",
+            &json!({ "id": "e1", "graph": "g", "data": "hello" }),
+        );
+
+        let mut graphs = JsonMap::new();
+        graphs.insert(String::from("g"), json!({ "processes": {} }));
+        let dump: JsonValue =
+            serde_json::from_str(&buffer.dump(&runtime_info(), graphs).unwrap()).unwrap();
+
+        assert_eq!(dump["header"]["metadata"]["runtime"], json!("runtime-id"));
+        assert!(dump["header"]["metadata"]["start"].is_string());
+        assert!(dump["header"]["graphs"]["g"].is_object());
+        let events = dump["events"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["protocol"], json!("network"));
+        assert_eq!(events[1]["command"], json!("data"));
+        assert_eq!(events[1]["payload"]["data"], json!("hello"));
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.used_bytes(), 0);
+    }
+}
@@ -30,6 +30,12 @@ struct GraphProperties {
     environment: GraphPropertiesEnvironment,
     description: String,
     icon: String,
+    #[serde(
+        rename = "schedulerWorkers",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    scheduler_workers: Option<usize>, // flowd extension: scheduler worker threads for this graph, default 1
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -39,7 +45,7 @@ struct GraphPropertiesEnvironment {
     content: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct GraphPort {
     process: String,
     port: String,
@@ -47,7 +53,7 @@ struct GraphPort {
     metadata: GraphPortMetadata,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct GraphPortMetadata {
     x: i32,
     y: i32,
@@ -60,7 +66,7 @@ struct GraphGroup {
     metadata: GraphGroupMetadata,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct GraphNode {
     component: String,
     metadata: GraphNodeMetadata,
@@ -70,7 +76,7 @@ struct GraphNode {
 
 #[serde_with::skip_serializing_none]
 // noflo-ui interprets even "data": null as "this is an IIP". not good but we can disable serializing None //TODO make issue in noflo-ui
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct GraphEdge {
     #[serde(rename = "src")]
     source: GraphNodeSpec,
@@ -82,7 +88,7 @@ struct GraphEdge {
 }
 
 #[serde_with::skip_serializing_none] // do not serialize index if it is None
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 struct GraphNodeSpec {
     process: String,
     port: String,
@@ -95,6 +101,62 @@ impl PartialEq<GraphNodeSpecNetwork> for GraphNodeSpec {
     }
 }
 
+/// Problem found by `Graph::validate`, optionally pointing at the node and port concerned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphValidationError {
+    node: Option<String>,
+    port: Option<String>,
+    message: String,
+}
+
+impl GraphValidationError {
+    fn new(node: Option<&String>, port: Option<&String>, message: String) -> Self {
+        GraphValidationError {
+            node: node.cloned(),
+            port: port.cloned(),
+            message,
+        }
+    }
+
+    /// Node concerned, if any
+    pub fn node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+
+    /// Port of the node concerned, if any
+    pub fn port(&self) -> Option<&str> {
+        self.port.as_deref()
+    }
+}
+
+impl std::fmt::Display for GraphValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+// ADR-029: port types known to the registry are checked by it, free-form types by name, where
+// "any" and "all" (noflo) accept every other type
+fn check_port_types(types: &TypeRegistry, source: &str, target: &str) -> Result<(), String> {
+    let compatible = match (types.resolve(source)?, types.resolve(target)?) {
+        (Some(source), Some(target)) => types
+            .compatibility(&source.type_id, &target.type_id)
+            .is_compatible(),
+        _ => is_any_type(source) || is_any_type(target) || source.eq_ignore_ascii_case(target),
+    };
+    if !compatible {
+        return Err(format!("{} is not compatible with {}", source, target));
+    }
+    Ok(())
+}
+
+// port type that takes packets of every type
+fn is_any_type(allowed_type: &str) -> bool {
+    allowed_type.is_empty()
+        || allowed_type.eq_ignore_ascii_case("any")
+        || allowed_type.eq_ignore_ascii_case("all")
+}
+
 fn graph_node_metadata_from_payload(metadata: &JsonMap<String, JsonValue>) -> GraphNodeMetadata {
     let mut parsed = GraphNodeMetadata::default();
     if let Some(JsonValue::Number(x)) = metadata.get("x") {
@@ -119,9 +181,23 @@ fn graph_node_metadata_from_payload(metadata: &JsonMap<String, JsonValue>) -> Gr
     if let Some(JsonValue::String(icon)) = metadata.get("icon") {
         parsed.icon = Some(icon.clone());
     }
+    parsed.restart = graph_node_restart_from_payload(metadata);
     parsed
 }
 
+fn graph_node_restart_from_payload(
+    metadata: &JsonMap<String, JsonValue>,
+) -> Option<GraphNodeRestart> {
+    let restart = metadata.get("restart")?;
+    match serde_json::from_value(restart.clone()) {
+        Ok(restart) => Some(restart),
+        Err(err) => {
+            warn!("ignoring invalid restart policy in node metadata: {}", err);
+            None
+        }
+    }
+}
+
 fn graph_node_metadata_to_payload(metadata: &GraphNodeMetadata) -> JsonMap<String, JsonValue> {
     let mut out = JsonMap::new();
     out.insert(String::from("x"), JsonValue::from(metadata.x));
@@ -138,6 +214,12 @@ fn graph_node_metadata_to_payload(metadata: &GraphNodeMetadata) -> JsonMap<Strin
     if let Some(icon) = &metadata.icon {
         out.insert(String::from("icon"), JsonValue::from(icon.clone()));
     }
+    if let Some(restart) = &metadata.restart {
+        out.insert(
+            String::from("restart"),
+            serde_json::to_value(restart).expect("failed to serialize restart policy"),
+        );
+    }
     out
 }
 
@@ -150,6 +232,7 @@ impl Graph {
                 environment: GraphPropertiesEnvironment::default(),
                 description: description,
                 icon: icon,
+                scheduler_workers: None,
             },
             inports: HashMap::new(),
             outports: HashMap::new(),
@@ -203,7 +286,9 @@ impl Graph {
         payload: &GraphClearRequestPayload,
         runtime: &Runtime,
     ) -> Result<(), std::io::Error> {
-        if runtime.status.running {
+        if runtime.graph_status(&self.properties.name).running
+            || runtime.graph_status(&payload.name).running
+        {
             // not allowed at the moment (TODO), theoretically graph and network could be different and the graph could be modified while the network is still running in the old config, then stop network and immediately start the network again according to the new graph structure, having only short downtime.
             return Err(std::io::Error::new(
                 std::io::ErrorKind::ResourceBusy,
@@ -215,6 +300,7 @@ impl Graph {
         self.properties.name = payload.name.clone();
         self.properties.description = payload.description.clone().unwrap_or_default();
         self.properties.icon = payload.icon.clone().unwrap_or_default();
+        self.properties.scheduler_workers = payload.scheduler_workers;
         // actually clear
         self.groups.clear();
         self.edges.clear();
@@ -592,6 +678,7 @@ impl Graph {
                     node.metadata.y = y as i32;
                 }
             }
+            node.metadata.restart = graph_node_restart_from_payload(&node.protocol_metadata);
             return Ok(node.protocol_metadata.clone());
         } else {
             return Err(std::io::Error::new(
@@ -824,6 +911,440 @@ impl Graph {
         ));
     }
 
+    /// Returns the graph as it is run by the network: nodes using a subgraph component are replaced
+    /// by the nodes and edges of that graph, named `<node>/<subgraph node>`.
+    fn inline_subgraphs(
+        &self,
+        graphs: &multi_graph::MultiGraphManager,
+        components: &ComponentLibrary,
+    ) -> Result<Graph, std::io::Error> {
+        let mut network = Graph::new(
+            self.properties.name.clone(),
+            self.properties.description.clone(),
+            self.properties.icon.clone(),
+        );
+        network.properties.scheduler_workers = self.properties.scheduler_workers;
+        let mut parents = vec![self.properties.name.clone()];
+        self.inline_into(&mut network, "", graphs, components, &mut parents)?;
+        // graph inports and outports may also point into a subgraph
+        for (public_name, exported) in self.inports.iter() {
+            let (process, port) = self.resolve_port(
+                &exported.process,
+                &exported.port,
+                true,
+                "",
+                graphs,
+                components,
+            )?;
+            network.inports.insert(
+                public_name.clone(),
+                GraphPort {
+                    process,
+                    port,
+                    metadata: exported.metadata.clone(),
+                },
+            );
+        }
+        for (public_name, exported) in self.outports.iter() {
+            let (process, port) = self.resolve_port(
+                &exported.process,
+                &exported.port,
+                false,
+                "",
+                graphs,
+                components,
+            )?;
+            network.outports.insert(
+                public_name.clone(),
+                GraphPort {
+                    process,
+                    port,
+                    metadata: exported.metadata.clone(),
+                },
+            );
+        }
+        Ok(network)
+    }
+
+    fn inline_into(
+        &self,
+        network: &mut Graph,
+        prefix: &str,
+        graphs: &multi_graph::MultiGraphManager,
+        components: &ComponentLibrary,
+        parents: &mut Vec<String>,
+    ) -> Result<(), std::io::Error> {
+        for (name, node) in self.nodes.iter() {
+            let Some(subgraph) = graphs.get_subgraph(&node.component, components) else {
+                network
+                    .nodes
+                    .insert(format!("{}{}", prefix, name), node.clone());
+                continue;
+            };
+            if parents.contains(&node.component) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "subgraph {} used by node {}{} contains itself",
+                        node.component, prefix, name
+                    ),
+                ));
+            }
+            parents.push(node.component.clone());
+            subgraph.read().expect("lock poisoned").inline_into(
+                network,
+                &format!("{}{}/", prefix, name),
+                graphs,
+                components,
+                parents,
+            )?;
+            parents.pop();
+        }
+        for edge in self.edges.iter() {
+            let mut inlined = edge.clone();
+            // IIPs have no source process
+            if edge.data.is_none() {
+                (inlined.source.process, inlined.source.port) = self.resolve_port(
+                    &edge.source.process,
+                    &edge.source.port,
+                    false,
+                    prefix,
+                    graphs,
+                    components,
+                )?;
+            }
+            (inlined.target.process, inlined.target.port) = self.resolve_port(
+                &edge.target.process,
+                &edge.target.port,
+                true,
+                prefix,
+                graphs,
+                components,
+            )?;
+            network.edges.push(inlined);
+        }
+        Ok(())
+    }
+
+    /// Follows a port of a node through the exported ports of (nested) subgraphs to the process actually running it.
+    fn resolve_port(
+        &self,
+        process: &str,
+        port: &str,
+        inport: bool,
+        prefix: &str,
+        graphs: &multi_graph::MultiGraphManager,
+        components: &ComponentLibrary,
+    ) -> Result<(String, String), std::io::Error> {
+        let component = self.nodes.get(process).map(|node| node.component.as_str());
+        Self::resolve_component_port(component, process, port, inport, prefix, graphs, components)
+    }
+
+    /// Like resolve_port(), for a node using `component` that need not be in the graph (anymore).
+    fn resolve_component_port(
+        component: Option<&str>,
+        process: &str,
+        port: &str,
+        inport: bool,
+        prefix: &str,
+        graphs: &multi_graph::MultiGraphManager,
+        components: &ComponentLibrary,
+    ) -> Result<(String, String), std::io::Error> {
+        let Some(subgraph) =
+            component.and_then(|component| graphs.get_subgraph(component, components))
+        else {
+            return Ok((format!("{}{}", prefix, process), port.to_owned()));
+        };
+        let subgraph = subgraph.read().expect("lock poisoned");
+        let exported_ports = if inport {
+            &subgraph.inports
+        } else {
+            &subgraph.outports
+        };
+        let Some((_, exported)) = exported_ports
+            .iter()
+            .find(|(public_name, _)| public_name.eq_ignore_ascii_case(port))
+        else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!(
+                    "subgraph {} used by node {}{} has no exported {} {}",
+                    subgraph.properties.name,
+                    prefix,
+                    process,
+                    if inport { "inport" } else { "outport" },
+                    port
+                ),
+            ));
+        };
+        subgraph.resolve_port(
+            &exported.process,
+            &exported.port,
+            inport,
+            &format!("{}{}/", prefix, process),
+            graphs,
+            components,
+        )
+    }
+
+    /// Checks the graph against the component library without starting anything and returns all
+    /// problems found: unknown components, unconnected required ports, edges to nonexistent ports,
+    /// multiple edges on non-addressable ports, type mismatches between connected ports and IIPs
+    /// not matching the configuration schema of their port.
+    pub fn validate(&self, components: &ComponentLibrary) -> Result<(), Vec<GraphValidationError>> {
+        let mut errors = Vec::new();
+
+        // unknown components
+        for (node_name, node) in self.nodes.iter() {
+            if components.find(&node.component).is_none() {
+                errors.push(GraphValidationError::new(
+                    Some(node_name),
+                    None,
+                    format!(
+                        "node {} uses unknown component {}",
+                        node_name, node.component
+                    ),
+                ));
+            }
+        }
+
+        // restart policies hand the connections over to the new instance (ADR-015)
+        for (node_name, node) in self.nodes.iter() {
+            let restarted = node
+                .metadata
+                .restart
+                .as_ref()
+                .is_some_and(|restart| restart.policy != crate::scheduler::RestartMode::Never);
+            if !restarted {
+                continue;
+            }
+            if let Some(component) = components.find(&node.component) {
+                if !component.support_reconnect {
+                    errors.push(GraphValidationError::new(
+                        Some(node_name),
+                        None,
+                        format!(
+                            "node {} has a restart policy, but component {} does not support reconnect",
+                            node_name, node.component
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // edges: existence of nodes and ports, connection count and type compatibility
+        let mut connections: HashMap<(bool, String, String, Option<usize>), usize> = HashMap::new();
+        for edge in self.edges.iter() {
+            let is_iip = edge.data.is_some() && edge.source.process.is_empty();
+            let source_port = if is_iip {
+                None
+            } else {
+                self.validate_edge_end(&edge.source, false, components, &mut errors)
+            };
+            let target_port = self.validate_edge_end(&edge.target, true, components, &mut errors);
+            if is_iip {
+                // ADR-012: configuration IIPs are checked against the schema of their port
+                let schema = target_port.and_then(|port| port.schema.as_deref());
+                if let (Some(schema), Some(data)) = (schema, edge.data.as_deref()) {
+                    if let Err(err) = flowd_component_api::config::validate_config(schema, data) {
+                        errors.push(GraphValidationError::new(
+                            Some(&edge.target.process),
+                            Some(&edge.target.port),
+                            format!(
+                                "invalid IIP for {}.{}: {}",
+                                edge.target.process, edge.target.port, err
+                            ),
+                        ));
+                    }
+                }
+                continue;
+            }
+            if let (Some(source_port), Some(target_port)) = (source_port, target_port) {
+                if let Err(err) = check_port_types(
+                    &components.types,
+                    &source_port.allowed_type,
+                    &target_port.allowed_type,
+                ) {
+                    errors.push(GraphValidationError::new(
+                        Some(&edge.target.process),
+                        Some(&edge.target.port),
+                        format!(
+                            "type mismatch on edge {}.{} -> {}.{}: {}",
+                            edge.source.process,
+                            edge.source.port,
+                            edge.target.process,
+                            edge.target.port,
+                            err
+                        ),
+                    ));
+                }
+            }
+            for (inport, end, port) in [
+                (false, &edge.source, source_port),
+                (true, &edge.target, target_port),
+            ] {
+                let Some(port) = port else {
+                    continue;
+                };
+                let index = match connection_index(end) {
+                    Ok(index) => index,
+                    Err(err) => {
+                        errors.push(GraphValidationError::new(
+                            Some(&end.process),
+                            Some(&end.port),
+                            err.to_string(),
+                        ));
+                        continue;
+                    }
+                };
+                // addressable ports take one edge per index, edges without index get a free one
+                if !port.is_arrayport {
+                    *connections
+                        .entry((
+                            inport,
+                            end.process.clone(),
+                            end.port.to_ascii_uppercase(),
+                            None,
+                        ))
+                        .or_default() += 1;
+                } else if index.is_some() {
+                    *connections
+                        .entry((
+                            inport,
+                            end.process.clone(),
+                            end.port.to_ascii_uppercase(),
+                            index,
+                        ))
+                        .or_default() += 1;
+                }
+            }
+        }
+        let mut connections: Vec<_> = connections
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .collect();
+        connections.sort();
+        for ((inport, process, port, index), count) in connections {
+            if let Some(index) = index {
+                errors.push(GraphValidationError::new(
+                    Some(&process),
+                    Some(&port),
+                    format!(
+                        "{} edges on {} {}.{}[{}]",
+                        count,
+                        if inport { "inport" } else { "outport" },
+                        process,
+                        port,
+                        index
+                    ),
+                ));
+                continue;
+            }
+            errors.push(GraphValidationError::new(
+                Some(&process),
+                Some(&port),
+                format!(
+                    "{} edges on non-addressable {} {}.{}",
+                    count,
+                    if inport { "inport" } else { "outport" },
+                    process,
+                    port
+                ),
+            ));
+        }
+
+        // required ports must be connected by an edge, IIP or exported graph port
+        let mut node_names: Vec<&String> = self.nodes.keys().collect();
+        node_names.sort();
+        for node_name in node_names {
+            let Some(component) = components.find(&self.nodes[node_name].component) else {
+                continue;
+            };
+            for (inport, ports) in [(true, &component.in_ports), (false, &component.out_ports)] {
+                for port in ports.iter().filter(|port| port.required) {
+                    if !self.is_port_connected(node_name, &port.name, inport) {
+                        errors.push(GraphValidationError::new(
+                            Some(node_name),
+                            Some(&port.name),
+                            format!(
+                                "required {} {} of node {} ({}) is not connected",
+                                if inport { "inport" } else { "outport" },
+                                port.name,
+                                node_name,
+                                component.name
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Looks up the component port an edge end refers to, recording an error if the node or port
+    /// does not exist.
+    fn validate_edge_end<'a>(
+        &self,
+        end: &GraphNodeSpec,
+        inport: bool,
+        components: &'a ComponentLibrary,
+        errors: &mut Vec<GraphValidationError>,
+    ) -> Option<&'a ComponentPort> {
+        let Some(node) = self.nodes.get(&end.process) else {
+            errors.push(GraphValidationError::new(
+                Some(&end.process),
+                Some(&end.port),
+                format!("connection to nonexistent node {}", end.process),
+            ));
+            return None;
+        };
+        // unknown components are reported once per node
+        let component = components.find(&node.component)?;
+        let ports = if inport {
+            &component.in_ports
+        } else {
+            &component.out_ports
+        };
+        let port = ports
+            .iter()
+            .find(|port| port.name.eq_ignore_ascii_case(&end.port));
+        if port.is_none() {
+            errors.push(GraphValidationError::new(
+                Some(&end.process),
+                Some(&end.port),
+                format!(
+                    "connection to nonexistent {} {} of node {} ({})",
+                    if inport { "inport" } else { "outport" },
+                    end.port,
+                    end.process,
+                    component.name
+                ),
+            ));
+        }
+        port
+    }
+
+    fn is_port_connected(&self, process: &str, port: &str, inport: bool) -> bool {
+        let edge_connected = self.edges.iter().any(|edge| {
+            let end = if inport { &edge.target } else { &edge.source };
+            end.process == process && end.port.eq_ignore_ascii_case(port)
+        });
+        let exported = if inport {
+            &self.inports
+        } else {
+            &self.outports
+        };
+        edge_connected
+            || exported.values().any(|exported| {
+                exported.process == process && exported.port.eq_ignore_ascii_case(port)
+            })
+    }
+
     fn get_source(&self, name: String) -> Result<ComponentSourcePayload, std::io::Error> {
         //TODO optimize: the message handler has already checked the graph name outside
         //###
@@ -929,6 +1450,16 @@ impl From<GraphNodeSpecNetwork> for GraphNodeSpec {
     }
 }
 
+impl From<&GraphNodeSpec> for GraphNodeSpecNetwork {
+    fn from(nodespec: &GraphNodeSpec) -> Self {
+        GraphNodeSpecNetwork {
+            node: nodespec.process.clone(),
+            port: nodespec.port.clone(),
+            index: nodespec.index.clone(),
+        }
+    }
+}
+
 // ----------
 // processes
 // ----------
@@ -975,15 +1506,202 @@ impl std::fmt::Debug for BoundaryThread {
 #[derive(Default)]
 pub struct ComponentLibrary {
     available: Vec<ComponentComponentPayload>,
+    scripts: HashMap<String, ComponentSourcePayload>, // component:setsource, by component name
+    script_dir: Option<std::path::PathBuf>,           // where script sources are saved
+    script_sources_allowed: bool,                     // clients may send scripts, --allow-scripts
+    plugins: HashMap<String, Arc<plugin::Plugin>>,    // ADR-023 plugin libraries, by component name
+    types: TypeRegistry,                              // ADR-029 types the ports refer to
 }
 
 impl ComponentLibrary {
     fn new(available: Vec<ComponentComponentPayload>) -> Self {
         ComponentLibrary {
             available: available,
+            scripts: HashMap::new(),
+            script_dir: None,
+            script_sources_allowed: false,
+            plugins: HashMap::new(),
+            types: TypeRegistry::new(),
+        }
+    }
+
+    /// Register the port types of the components besides the core types
+    fn with_types(mut self, types: Vec<RegistryEntry>) -> Self {
+        for entry in types {
+            let type_id = entry.type_id.clone();
+            if let Err(err) = self.types.register(entry) {
+                error!("failed to register port type {}: {}", type_id, err);
+            }
+        }
+        self
+    }
+
+    pub fn types(&self) -> &TypeRegistry {
+        &self.types
+    }
+
+    /// Contract for the messages on outport `port` of component `name`, if its type has a schema
+    fn outport_contract(&self, name: &str, port: &str) -> Option<PortContract> {
+        let port = self
+            .find(name)?
+            .out_ports
+            .iter()
+            .find(|out_port| out_port.name.eq_ignore_ascii_case(port))?;
+        self.types.contract(&port.allowed_type)
+    }
+
+    /// Register the plugin components in `dir`, see flowd_plugin.h.
+    /// Libraries which fail to load or whose component already exists are logged and skipped.
+    pub fn load_plugins(&mut self, dir: &Path) -> Result<(), std::io::Error> {
+        for plugin in plugin::load_plugins(dir)? {
+            if self.find(plugin.name()).is_some() {
+                error!(
+                    "skipping plugin component {}, a component of that name already exists",
+                    plugin.name()
+                );
+                continue;
+            }
+            self.available.push(plugin.component());
+            self.plugins
+                .insert(plugin.name().to_owned(), Arc::new(plugin));
+        }
+        Ok(())
+    }
+
+    /// Register the script components saved in `dir` and save new ones there
+    pub fn load_scripts(&mut self, dir: &Path) -> Result<(), std::io::Error> {
+        self.script_dir = Some(dir.to_owned());
+        for source in script::load_sources(dir)? {
+            self.add_script(source)?;
+        }
+        Ok(())
+    }
+
+    /// Accept script sources from clients, see set_script_source()
+    pub fn allow_script_sources(&mut self, allowed: bool) {
+        self.script_sources_allowed = allowed;
+    }
+
+    /// component:setsource - register a component from source code, replacing an earlier version
+    fn set_script_source(
+        &mut self,
+        source: ComponentSourcePayload,
+    ) -> Result<&ComponentComponentPayload, std::io::Error> {
+        if !self.script_sources_allowed {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "script sources are not accepted, start flowd with --allow-scripts",
+            ));
+        }
+        if let Some(dir) = &self.script_dir {
+            self.check_script(&source)?;
+            script::save_source(dir, &source)?;
+        }
+        self.add_script(source)
+    }
+
+    fn add_script(
+        &mut self,
+        source: ComponentSourcePayload,
+    ) -> Result<&ComponentComponentPayload, std::io::Error> {
+        self.check_script(&source)?;
+        self.available
+            .retain(|component| component.name != source.name);
+        self.available.push(script::metadata(&source));
+        self.scripts.insert(source.name.clone(), source);
+        Ok(self.available.last().expect("just pushed"))
+    }
+
+    fn check_script(&self, source: &ComponentSourcePayload) -> Result<(), std::io::Error> {
+        script::check_source(source)?;
+        if self.find(&source.name).is_some() && !self.scripts.contains_key(&source.name) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!(
+                    "component {} is compiled in or a plugin and cannot be replaced",
+                    source.name
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create a component instance, compiled-in, plugin or script
+    #[allow(clippy::too_many_arguments)] // same as instantiate_component() plus self
+    fn instantiate(
+        &self,
+        name: &str,
+        inports: ProcessInports,
+        outports: ProcessOutports,
+        signalsource: ProcessSignalSource,
+        watchdog_signalsink: ProcessSignalSink,
+        graph_inout: GraphInportOutportHandle,
+        scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
+    ) -> Option<Box<dyn Component>> {
+        if let Some(source) = self.scripts.get(name) {
+            return match script::ScriptComponent::from_source(
+                source,
+                inports,
+                outports,
+                signalsource,
+                watchdog_signalsink,
+                scheduler_waker,
+            ) {
+                Ok(component) => Some(Box::new(component)),
+                Err(err) => {
+                    error!("failed to create script component {}: {}", name, err);
+                    None
+                }
+            };
+        }
+        if let Some(plugin) = self.plugins.get(name) {
+            return Some(Box::new(plugin::PluginComponent::from_plugin(
+                plugin.clone(),
+                inports,
+                outports,
+                signalsource,
+                watchdog_signalsink,
+            )));
+        }
+        instantiate_component(
+            name,
+            inports,
+            outports,
+            signalsource,
+            watchdog_signalsink,
+            graph_inout,
+            scheduler_waker,
+        )
+    }
+
+    /// Only what is needed to instantiate component `name`, for restarting processes (ADR-015)
+    fn subset(&self, name: &str) -> ComponentLibrary {
+        ComponentLibrary {
+            available: Vec::new(),
+            scripts: self
+                .scripts
+                .get_key_value(name)
+                .map(|(name, source)| (name.clone(), source.clone()))
+                .into_iter()
+                .collect(),
+            script_dir: None,
+            script_sources_allowed: false,
+            plugins: self
+                .plugins
+                .get_key_value(name)
+                .map(|(name, plugin)| (name.clone(), plugin.clone()))
+                .into_iter()
+                .collect(),
+            types: self.types.clone(),
         }
     }
 
+    fn find(&self, name: &str) -> Option<&ComponentComponentPayload> {
+        self.available
+            .iter()
+            .find(|component| component.name == name)
+    }
+
     fn get_source(&self, name: String) -> Result<ComponentSourcePayload, std::io::Error> {
         // spec: Name of the component to for which to get source code.
         // spec: Should contain the library prefix, example: "my-project/SomeComponent"
@@ -993,6 +1711,9 @@ impl ComponentLibrary {
         //TODO optimize: for component:getsource we need to return an array, but for internal purpose a HashMap would be much more efficient
         //if let Some(node) = self.available.get(&name) {
         //TODO there is vec.binary_search() and vec.sort_by_key() - maybe as fast as hashmap?
+        if let Some(source) = self.scripts.get(&name) {
+            return Ok(source.clone());
+        }
         for component in self.available.iter() {
             if component.name == name {
                 return Ok(ComponentSourcePayload {
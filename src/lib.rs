@@ -8,7 +8,6 @@ use std::sync::{Arc, Mutex, RwLock};
 use std::thread::{self, Thread};
 
 // network and WebSocket
-use std::net::TcpStream;
 use tungstenite::Message;
 
 // persistence
@@ -40,27 +39,35 @@ use std::time::{Duration, Instant};
 
 // flowd component API crate
 pub use flowd_component_api::{
-    BudgetClass, Component, ComponentComponentPayload, ComponentPort, FbpMessage,
+    BudgetClass, Component, ComponentComponentPayload, ComponentPort, EdgeJournal, FbpMessage,
     GraphInportOutportHandle, MessageBuf, NodeContext, ProcessEdge, ProcessEdgeSink,
     ProcessEdgeSinkConnection, ProcessEdgeSource, ProcessInports, ProcessOutports, ProcessResult,
     ProcessSignalSink, ProcessSignalSource, PushError, WakeupNotify, PROCESSEDGE_BUFSIZE,
     PROCESSEDGE_IIP_BUFSIZE, PROCESSEDGE_SIGNAL_BUFSIZE,
 };
 use flowd_component_api::{
-    GraphNodeSpecNetwork as TraceGraphNodeSpecNetwork,
+    ControlEvent, EdgeMetrics, FbpValue, GraphNodeSpecNetwork as TraceGraphNodeSpecNetwork,
     TraceConnectEventPayload as ApiTraceConnectEventPayload,
     TraceDataEventPayload as ApiTraceDataEventPayload,
     TraceDisconnectEventPayload as ApiTraceDisconnectEventPayload,
 };
+use flowd_component_api::types::{PortContract, RegistryEntry, TypeRegistry};
 
 // configuration
 const PROCESS_HEALTHCHECK_DUR: core::time::Duration = Duration::from_secs(7); //NOTE: 7 * core::time::Duration::SECOND is not compile-time calculatable (mul const trait not implemented)
 const WATCHDOG_POLL_DUR: core::time::Duration = Duration::from_millis(50);
 const WATCHDOG_MAX_MISSED_PONGS: u8 = 2;
+const LIVE_EDGE_DRAIN_TIMEOUT: core::time::Duration = Duration::from_secs(2); // hot graph mutation: max. wait for the target process to work off a removed edge
 const CLIENT_BROADCAST_WRITE_TIMEOUT: Option<Duration> = Some(Duration::from_millis(200));
+const ADDRESSABLE_PORT_MAX_INDEX: usize = 1024; // highest accepted index on an addressable port, bounds the slots allocated for it
 const NODE_WIDTH_DEFAULT: u32 = 72;
 const NODE_HEIGHT_DEFAULT: u32 = 72;
 const PERSISTENCE_FILE_NAME: &str = "flowd.graph.json";
+const EDGE_JOURNAL_DIR: &str = "flowd.edges"; // ADR-010: persistent edge journals
+const CHECKPOINT_DIR: &str = "flowd.checkpoints"; // ADR-005: node state checkpoints
+const CHECKPOINT_INTERVAL: core::time::Duration = Duration::from_secs(60);
+const NODE_MAX_RESTARTS_DEFAULT: u32 = 5; // ADR-015: restart policy in node metadata
+const NODE_RESTART_BACKOFF_MS_DEFAULT: u64 = 100;
 
 
 include!("runtime.rs");
@@ -0,0 +1,261 @@
+// ADR-011: log records tagged with the dataflow position they were written from
+//
+// The scheduler marks the node whose process() runs on a worker thread and the runtime marks the
+// edge it is setting up, so every record can carry graph, node, component and edge. Log levels can
+// be raised or lowered per graph and per node while the network is running.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::{Arc, RwLock};
+
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::COMPONENT_LOG_IGNORE;
+
+/// Output format of the log records
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// human-readable lines, errors on stderr and the rest on stdout
+    Text,
+    /// one JSON object per line on stdout
+    Json,
+}
+
+/// Where in the dataflow the current thread is working
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogContext {
+    pub graph: Option<String>,
+    pub node: Option<String>,
+    pub component: Option<String>,
+    pub edge: Option<String>,
+}
+
+impl LogContext {
+    pub fn node(graph: &str, node: &str, component: &str) -> Self {
+        LogContext {
+            graph: Some(graph.to_owned()),
+            node: Some(node.to_owned()),
+            component: Some(component.to_owned()),
+            edge: None,
+        }
+    }
+
+    pub fn edge(graph: &str, edge: &str) -> Self {
+        LogContext {
+            graph: Some(graph.to_owned()),
+            edge: Some(edge.to_owned()),
+            ..Default::default()
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT_CONTEXT: RefCell<Option<Arc<LogContext>>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous log context of the thread when dropped
+pub struct LogContextGuard {
+    previous: Option<Arc<LogContext>>,
+}
+
+impl Drop for LogContextGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_CONTEXT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Tag the records logged by this thread with `context` until the guard is dropped
+pub fn enter(context: Arc<LogContext>) -> LogContextGuard {
+    let previous = CURRENT_CONTEXT.with(|current| current.borrow_mut().replace(context));
+    LogContextGuard { previous }
+}
+
+pub fn current_context() -> Option<Arc<LogContext>> {
+    CURRENT_CONTEXT.with(|current| current.borrow().clone())
+}
+
+struct LevelSettings {
+    default: LevelFilter,
+    // key (graph, None) is the level of the whole graph, (graph, Some(node)) of a single node
+    overrides: BTreeMap<(String, Option<String>), LevelFilter>,
+}
+
+static LEVELS: RwLock<LevelSettings> = RwLock::new(LevelSettings {
+    default: LevelFilter::Trace,
+    overrides: BTreeMap::new(),
+});
+
+/// Set the level used where no graph or node level applies
+pub fn set_default_level(level: LevelFilter) {
+    let mut levels = LEVELS.write().expect("log levels lock poisoned");
+    levels.default = level;
+    update_max_level(&levels);
+}
+
+/// Set the level of a graph (`node` None) or of a single node, None removes the setting again.
+/// Returns the level now in effect there.
+pub fn set_level(graph: &str, node: Option<&str>, level: Option<LevelFilter>) -> LevelFilter {
+    let mut levels = LEVELS.write().expect("log levels lock poisoned");
+    let key = (graph.to_owned(), node.map(str::to_owned));
+    match level {
+        Some(level) => levels.overrides.insert(key, level),
+        None => levels.overrides.remove(&key),
+    };
+    update_max_level(&levels);
+    effective_level(&levels, Some(graph), node)
+}
+
+/// Level in effect for records from `node` in `graph`
+pub fn level_for(graph: Option<&str>, node: Option<&str>) -> LevelFilter {
+    effective_level(
+        &LEVELS.read().expect("log levels lock poisoned"),
+        graph,
+        node,
+    )
+}
+
+fn effective_level(levels: &LevelSettings, graph: Option<&str>, node: Option<&str>) -> LevelFilter {
+    let Some(graph) = graph else {
+        return levels.default;
+    };
+    let mut key = (graph.to_owned(), node.map(str::to_owned));
+    if key.1.is_some() {
+        if let Some(level) = levels.overrides.get(&key) {
+            return *level;
+        }
+        key.1 = None;
+    }
+    levels
+        .overrides
+        .get(&key)
+        .copied()
+        .unwrap_or(levels.default)
+}
+
+// the log macros skip everything above the global maximum before asking the logger
+fn update_max_level(levels: &LevelSettings) {
+    let max = levels
+        .overrides
+        .values()
+        .copied()
+        .fold(levels.default, Ord::max);
+    log::set_max_level(max);
+}
+
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    level.parse().ok()
+}
+
+/// One log record as a JSON object, without trailing newline
+pub fn format_json(record: &Record, context: Option<&LogContext>) -> String {
+    let mut object = JsonMap::new();
+    object.insert(
+        String::from("ts"),
+        JsonValue::String(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true)),
+    );
+    object.insert(
+        String::from("level"),
+        JsonValue::String(record.level().to_string()),
+    );
+    object.insert(
+        String::from("target"),
+        JsonValue::String(record.target().to_owned()),
+    );
+    if let Some(thread) = std::thread::current().name() {
+        object.insert(String::from("thread"), JsonValue::String(thread.to_owned()));
+    }
+    if let Some(context) = context {
+        for (key, value) in [
+            ("graph", &context.graph),
+            ("node", &context.node),
+            ("component", &context.component),
+            ("edge", &context.edge),
+        ] {
+            if let Some(value) = value {
+                object.insert(String::from(key), JsonValue::String(value.clone()));
+            }
+        }
+    }
+    object.insert(
+        String::from("message"),
+        JsonValue::String(record.args().to_string()),
+    );
+    JsonValue::Object(object).to_string()
+}
+
+struct FlowdLogger {
+    format: LogFormat,
+    text: Box<simplelog::TermLogger>,
+}
+
+impl FlowdLogger {
+    fn ignored(target: &str) -> bool {
+        COMPONENT_LOG_IGNORE
+            .iter()
+            .any(|prefix| target.starts_with(prefix))
+    }
+}
+
+impl Log for FlowdLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if Self::ignored(metadata.target()) {
+            return false;
+        }
+        let context = current_context();
+        let context = context.as_deref();
+        metadata.level()
+            <= level_for(
+                context.and_then(|context| context.graph.as_deref()),
+                context.and_then(|context| context.node.as_deref()),
+            )
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match self.format {
+            LogFormat::Text => self.text.log(record),
+            LogFormat::Json => {
+                let line = format_json(record, current_context().as_deref());
+                let mut stdout = std::io::stdout().lock();
+                let _ = writeln!(stdout, "{}", line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        match self.format {
+            LogFormat::Text => self.text.flush(),
+            LogFormat::Json => {
+                let _ = std::io::stdout().flush();
+            }
+        }
+    }
+}
+
+/// Install the process-wide logger, `level` is the default level
+pub fn init(level: LevelFilter, format: LogFormat) -> Result<(), log::SetLoggerError> {
+    //NOTE: important to show the thread name = the FBP process name
+    let mut text_config = simplelog::ConfigBuilder::default();
+    text_config
+        .set_time_level(LevelFilter::Off)
+        .set_thread_level(LevelFilter::Info)
+        .set_target_level(LevelFilter::Off) // no need to see the module path in text
+        .set_thread_mode(simplelog::ThreadLogMode::Names)
+        .set_thread_padding(simplelog::ThreadPadding::Right(21)) // Linux thread names have 15 chars max.
+        .set_level_padding(simplelog::LevelPadding::Right);
+    // filtering by level happens in FlowdLogger
+    let text = simplelog::TermLogger::new(
+        LevelFilter::Trace,
+        text_config.build(),
+        simplelog::TerminalMode::Mixed, // level error and above to stderr, rest to stdout
+        simplelog::ColorChoice::Auto,   // depending on whether interactive or not
+    );
+    log::set_boxed_logger(Box::new(FlowdLogger { format, text }))?;
+    set_default_level(level);
+    Ok(())
+}
@@ -1,6 +1,6 @@
 fn main() {
-    if let Err(err) = flowd_rs::server::run() {
-        eprintln!("Server failed: {}", err);
+    if let Err(err) = flowd_rs::cli::run() {
+        eprintln!("flowd failed: {}", err);
         std::process::exit(1);
     }
 }
@@ -0,0 +1,237 @@
+// ADR-011: metrics of the running networks for monitoring systems, in OpenMetrics text format
+//
+// GET /metrics on the metrics address (serve --metrics-bind) returns the scheduler counters per
+// node and the counters of the edges between processes, labeled with graph, node and edge.
+
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::Runtime;
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(2);
+const WORKERS: usize = 4; // connections served at the same time
+
+/// Listen on `bind_addr` and answer metrics requests on background threads
+pub fn serve(bind_addr: &str, runtime: Arc<RwLock<Runtime>>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(bind_addr)?;
+    log::info!(
+        "metrics available at http://{}/metrics",
+        listener.local_addr()?
+    );
+    // a fixed number of threads accepting in turn, so that a slow client holds up only one
+    for _ in 0..WORKERS {
+        let listener = listener.try_clone()?;
+        let runtime = runtime.clone();
+        std::thread::Builder::new()
+            .name("metrics".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(err) = respond(stream, &runtime) {
+                                log::debug!("metrics request failed: {}", err);
+                            }
+                        }
+                        Err(err) => log::warn!("failed to accept metrics connection: {}", err),
+                    }
+                }
+            })?;
+    }
+    Ok(())
+}
+
+// one request per connection, the request headers are not needed
+fn respond(mut stream: TcpStream, runtime: &Arc<RwLock<Runtime>>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_READ_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = encode(&runtime.read().expect("lock poisoned"));
+            ("200 OK", CONTENT_TYPE, body)
+        }
+        (Some("GET"), Some(_)) => ("404 Not Found", "text/plain", String::from("not found\n")),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            String::from("only GET is supported\n"),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+struct MetricFamily {
+    name: &'static str,
+    typ: &'static str,
+    help: &'static str,
+    samples: Vec<(String, String)>, // label set, value
+}
+
+impl MetricFamily {
+    fn new(name: &'static str, typ: &'static str, help: &'static str) -> Self {
+        MetricFamily {
+            name,
+            typ,
+            help,
+            samples: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, labels: &[(&str, &str)], value: impl ToString) {
+        let labels = labels
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.samples.push((labels, value.to_string()));
+    }
+
+    fn write_to(&self, out: &mut String) {
+        let _ = writeln!(out, "# TYPE {} {}", self.name, self.typ);
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        // counters carry the _total suffix on their samples only
+        let suffix = if self.typ == "counter" { "_total" } else { "" };
+        for (labels, value) in self.samples.iter() {
+            let _ = writeln!(out, "{}{}{{{}}} {}", self.name, suffix, labels, value);
+        }
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Metrics of all networks of the runtime in OpenMetrics text format
+pub fn encode(runtime: &Runtime) -> String {
+    let mut executions = MetricFamily::new(
+        "flowd_node_executions",
+        "counter",
+        "Calls of process() per node.",
+    );
+    let mut work_units = MetricFamily::new(
+        "flowd_node_work_units",
+        "counter",
+        "Work units reported by process() per node.",
+    );
+    let mut since_last_execution = MetricFamily::new(
+        "flowd_node_seconds_since_last_execution",
+        "gauge",
+        "Time since process() was last called on the node.",
+    );
+    let mut panics = MetricFamily::new(
+        "flowd_node_panics",
+        "counter",
+        "Panics inside process() per node.",
+    );
+    let mut finished = MetricFamily::new(
+        "flowd_node_finished",
+        "counter",
+        "Transitions of the node to finished, panics included.",
+    );
+    let mut restarts = MetricFamily::new(
+        "flowd_node_restarts",
+        "counter",
+        "Restarts of the node by its restart policy.",
+    );
+    let mut queue_depth = MetricFamily::new(
+        "flowd_scheduler_queue_depth",
+        "gauge",
+        "Nodes waiting in the ready queue of the scheduler.",
+    );
+    let mut occupancy = MetricFamily::new(
+        "flowd_edge_occupancy",
+        "gauge",
+        "Slots in use in the edge ring buffer.",
+    );
+    let mut capacity = MetricFamily::new(
+        "flowd_edge_capacity",
+        "gauge",
+        "Slots of the edge ring buffer.",
+    );
+    let mut pushes = MetricFamily::new(
+        "flowd_edge_pushes",
+        "counter",
+        "Messages pushed into the edge.",
+    );
+    let mut full = MetricFamily::new(
+        "flowd_edge_full",
+        "counter",
+        "Push attempts rejected because the edge was full.",
+    );
+
+    let mut graphs = runtime.schedulers.keys().collect::<Vec<_>>();
+    graphs.sort();
+    for graph in graphs {
+        let metrics = runtime.schedulers[graph].metrics_snapshot();
+        let mut nodes = metrics.executions_per_node.keys().collect::<Vec<_>>();
+        nodes.sort();
+        for node in nodes {
+            let labels = [("graph", graph.as_str()), ("node", node.as_str())];
+            let count = |counts: &std::collections::HashMap<String, u64>| {
+                counts.get(node).copied().unwrap_or(0)
+            };
+            executions.add(&labels, count(&metrics.executions_per_node));
+            work_units.add(&labels, count(&metrics.work_units_per_node));
+            panics.add(&labels, count(&metrics.panics_per_node));
+            finished.add(&labels, count(&metrics.finished_per_node));
+            restarts.add(&labels, count(&metrics.restarts_per_node));
+            // finished nodes are not executed any more
+            if let Some(duration) = metrics.time_since_last_execution.get(node) {
+                since_last_execution.add(&labels, duration.as_secs_f64());
+            }
+        }
+        queue_depth.add(&[("graph", graph.as_str())], metrics.queue_depth);
+    }
+
+    let mut graphs = runtime.networks.keys().collect::<Vec<_>>();
+    graphs.sort();
+    for graph in graphs {
+        let mut edges = runtime.networks[graph]
+            .edge_metrics
+            .iter()
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| a.0.cmp(b.0));
+        for (edge, metrics) in edges {
+            let labels = [("graph", graph.as_str()), ("edge", edge.as_str())];
+            occupancy.add(&labels, metrics.occupancy());
+            capacity.add(&labels, metrics.capacity());
+            pushes.add(&labels, metrics.pushes());
+            full.add(&labels, metrics.full());
+        }
+    }
+
+    let mut out = String::new();
+    for family in [
+        executions,
+        work_units,
+        since_last_execution,
+        panics,
+        finished,
+        restarts,
+        queue_depth,
+        occupancy,
+        capacity,
+        pushes,
+        full,
+    ] {
+        family.write_to(&mut out);
+    }
+    out.push_str("# EOF\n");
+    out
+}
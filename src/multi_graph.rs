@@ -63,7 +63,50 @@ impl MultiGraphManager {
     pub fn get_active_graph_id(&self) -> &str {
         &self.active_graph
     }
+
+    /// Returns the graph used by nodes of the given component, if it is a subgraph component.
+    /// Compiled-in components take precedence over graphs of the same name.
+    pub fn get_subgraph(
+        &self,
+        component: &str,
+        components: &ComponentLibrary,
+    ) -> Option<Arc<RwLock<Graph>>> {
+        if components
+            .available
+            .iter()
+            .any(|available| available.name == component)
+        {
+            return None;
+        }
+        self.get_graph(component)
+    }
+
+    /// Component descriptions for all graphs usable as subgraph components, for component:list.
+    pub fn subgraph_components(
+        &self,
+        components: &ComponentLibrary,
+    ) -> Vec<ComponentComponentPayload> {
+        let mut graph_ids = self.list_graphs();
+        graph_ids.sort();
+        graph_ids
+            .into_iter()
+            .filter_map(|graph_id| {
+                let graph_arc = self.get_subgraph(&graph_id, components)?;
+                let graph = graph_arc.read().expect("lock poisoned");
+                Some(ComponentComponentPayload {
+                    name: graph_id,
+                    description: graph.properties.description.clone(),
+                    icon: graph.properties.icon.clone(),
+                    subgraph: true,
+                    in_ports: graph.ports_as_componentportsarray(&graph.inports),
+                    out_ports: graph.ports_as_componentportsarray(&graph.outports),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
 }
 
 // Re-export Graph from lib.rs for convenience
 pub use crate::Graph;
+use crate::{ComponentComponentPayload, ComponentLibrary};
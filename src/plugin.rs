@@ -0,0 +1,440 @@
+// ADR-023: plugin components, shared libraries implementing the C ABI of
+// component_api/include/flowd_plugin.h
+//
+// Plugins are loaded from the plugin directory at startup and listed in the component library
+// next to the compiled-in components. Packets cross the boundary in the canonical packet encoding.
+
+use std::collections::VecDeque;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::Path;
+use std::sync::Arc;
+
+use libloading::Library;
+use log::{error, info, trace, warn};
+use serde::Deserialize;
+
+use crate::{
+    Component, ComponentComponentPayload, ComponentPort, FbpMessage, GraphInportOutportHandle,
+    NodeContext, ProcessEdgeSink, ProcessEdgeSource, ProcessInports, ProcessOutports,
+    ProcessResult, ProcessSignalSink, ProcessSignalSource, PushError,
+};
+
+/// Directory of the plugin libraries, relative to the graph directory
+pub const PLUGIN_DIR: &str = "flowd.plugins";
+
+/// Version of the plugin C ABI, FLOWD_PLUGIN_ABI_VERSION in flowd_plugin.h
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type MetadataFn = unsafe extern "C" fn() -> *const c_char;
+type InitFn = unsafe extern "C" fn() -> *mut c_void;
+type EmitFn = extern "C" fn(*mut c_void, *const c_char, *const u8, usize);
+type ProcessFn =
+    unsafe extern "C" fn(*mut c_void, *const c_char, *const u8, usize, EmitFn, *mut c_void) -> i32;
+type DropFn = unsafe extern "C" fn(*mut c_void);
+
+#[derive(Deserialize)]
+struct PluginMetadata {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    icon: String,
+    #[serde(default)]
+    inports: Vec<PluginPort>,
+    #[serde(default)]
+    outports: Vec<PluginPort>,
+}
+
+#[derive(Deserialize)]
+struct PluginPort {
+    name: String,
+    #[serde(default = "any_type", rename = "type")]
+    allowed_type: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "required_default")]
+    required: bool,
+}
+
+fn any_type() -> String {
+    String::from("any")
+}
+
+fn required_default() -> bool {
+    true
+}
+
+/// A loaded plugin library
+pub(crate) struct Plugin {
+    metadata: PluginMetadata,
+    init: InitFn,
+    process: ProcessFn,
+    drop: DropFn,
+    // the entry points above are valid as long as the library stays loaded
+    _library: Library,
+}
+
+impl Plugin {
+    /// Load the plugin library at `path` and check its ABI version and metadata
+    pub(crate) fn load(path: &Path) -> Result<Self, std::io::Error> {
+        let invalid = |message: String| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("plugin {}: {}", path.display(), message),
+            )
+        };
+        // SAFETY: loading runs the initializers of the library, plugins are trusted like the
+        // runtime binary itself
+        let library = unsafe { Library::new(path) }.map_err(|err| invalid(err.to_string()))?;
+        // SAFETY: signatures as declared in flowd_plugin.h
+        let (abi_version, metadata, init, process, drop) = unsafe {
+            let symbol_err = |err: libloading::Error| invalid(err.to_string());
+            (
+                *library
+                    .get::<AbiVersionFn>(b"flowd_plugin_abi_version")
+                    .map_err(symbol_err)?,
+                *library
+                    .get::<MetadataFn>(b"flowd_plugin_metadata")
+                    .map_err(symbol_err)?,
+                *library
+                    .get::<InitFn>(b"flowd_plugin_init")
+                    .map_err(symbol_err)?,
+                *library
+                    .get::<ProcessFn>(b"flowd_plugin_process")
+                    .map_err(symbol_err)?,
+                *library
+                    .get::<DropFn>(b"flowd_plugin_drop")
+                    .map_err(symbol_err)?,
+            )
+        };
+
+        let version = unsafe { abi_version() };
+        if version != PLUGIN_ABI_VERSION {
+            return Err(invalid(format!(
+                "ABI version {} is not supported, expected {}",
+                version, PLUGIN_ABI_VERSION
+            )));
+        }
+        let metadata_json = unsafe { metadata() };
+        if metadata_json.is_null() {
+            return Err(invalid(String::from("no metadata")));
+        }
+        let metadata: PluginMetadata =
+            serde_json::from_slice(unsafe { CStr::from_ptr(metadata_json) }.to_bytes())
+                .map_err(|err| invalid(format!("invalid metadata: {}", err)))?;
+        if metadata.name.is_empty() || metadata.name.contains('/') {
+            return Err(invalid(format!(
+                "invalid component name '{}'",
+                metadata.name
+            )));
+        }
+        if metadata.inports.is_empty() {
+            return Err(invalid(String::from("component has no inports")));
+        }
+        // edges address ports by their uppercased name
+        if let Some(port) = metadata
+            .inports
+            .iter()
+            .chain(metadata.outports.iter())
+            .find(|port| port.name.is_empty() || port.name != port.name.to_ascii_uppercase())
+        {
+            return Err(invalid(format!(
+                "invalid port name '{}', port names are uppercase",
+                port.name
+            )));
+        }
+
+        Ok(Plugin {
+            metadata,
+            init,
+            process,
+            drop,
+            _library: library,
+        })
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.metadata.name
+    }
+
+    /// Component metadata of the plugin
+    pub(crate) fn component(&self) -> ComponentComponentPayload {
+        let ports = |ports: &[PluginPort]| {
+            ports
+                .iter()
+                .map(|port| ComponentPort {
+                    name: port.name.clone(),
+                    allowed_type: port.allowed_type.clone(),
+                    schema: None,
+                    required: port.required,
+                    is_arrayport: false,
+                    description: port.description.clone(),
+                    values_allowed: vec![],
+                    value_default: String::from(""),
+                })
+                .collect()
+        };
+        ComponentComponentPayload {
+            name: self.metadata.name.clone(),
+            description: self.metadata.description.clone(),
+            icon: if self.metadata.icon.is_empty() {
+                String::from("plug")
+            } else {
+                self.metadata.icon.clone()
+            },
+            subgraph: false,
+            in_ports: ports(&self.metadata.inports),
+            out_ports: ports(&self.metadata.outports),
+            ..Default::default()
+        }
+    }
+}
+
+/// Load all plugin libraries in `dir`, libraries which fail to load are logged and skipped
+pub(crate) fn load_plugins(dir: &Path) -> Result<Vec<Plugin>, std::io::Error> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut plugins = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(std::env::consts::DLL_EXTENSION) {
+            continue;
+        }
+        let plugin = match Plugin::load(&path) {
+            Ok(plugin) => plugin,
+            Err(err) => {
+                error!("skipping plugin library: {}", err);
+                continue;
+            }
+        };
+        info!(
+            "loaded plugin component {} from {}",
+            plugin.name(),
+            path.display()
+        );
+        plugins.push(plugin);
+    }
+    Ok(plugins)
+}
+
+// packets emitted by the plugin during one process call: outport name, encoded packet
+type Emitted = Vec<(String, Vec<u8>)>;
+
+// must not panic, unwinding out of an extern "C" fn aborts
+extern "C" fn emit(ctx: *mut c_void, port: *const c_char, data: *const u8, len: usize) {
+    if ctx.is_null() || port.is_null() || (data.is_null() && len > 0) {
+        return;
+    }
+    // SAFETY: ctx is the Emitted of the running process call, the rest as documented in the header
+    let emitted = unsafe { &mut *(ctx as *mut Emitted) };
+    let port = unsafe { CStr::from_ptr(port) }
+        .to_string_lossy()
+        .into_owned();
+    let data = if len == 0 {
+        vec![]
+    } else {
+        unsafe { std::slice::from_raw_parts(data, len) }.to_vec()
+    };
+    emitted.push((port, data));
+}
+
+pub struct PluginComponent {
+    instance: Option<(Arc<Plugin>, *mut c_void)>, // plugin and its handle, None if init failed
+    inports: Vec<(CString, ProcessEdgeSource)>,
+    next_inport: usize, // round-robin over the inports
+    outports: Vec<(String, ProcessEdgeSink)>,
+    signals_in: ProcessSignalSource,
+    signals_out: ProcessSignalSink,
+    pending: VecDeque<(usize, FbpMessage)>, // outport index, packet waiting for room
+}
+
+// SAFETY: the ABI allows calls from any thread, and the component is only ever used by one
+unsafe impl Send for PluginComponent {}
+
+impl PluginComponent {
+    pub(crate) fn from_plugin(
+        plugin: Arc<Plugin>,
+        mut inports: ProcessInports,
+        mut outports: ProcessOutports,
+        signals_in: ProcessSignalSource,
+        signals_out: ProcessSignalSink,
+    ) -> Self {
+        let handle = unsafe { (plugin.init)() };
+        if handle.is_null() {
+            error!("init of plugin {} failed", plugin.name());
+        }
+        let inports = plugin
+            .metadata
+            .inports
+            .iter()
+            .filter_map(|port| {
+                let edge = inports.remove(&port.name)?.pop()?;
+                Some((CString::new(port.name.clone()).ok()?, edge))
+            })
+            .collect();
+        let outports = plugin
+            .metadata
+            .outports
+            .iter()
+            .filter_map(|port| Some((port.name.clone(), outports.remove(&port.name)?.pop()?)))
+            .collect();
+        PluginComponent {
+            instance: (!handle.is_null()).then_some((plugin, handle)),
+            inports,
+            next_inport: 0,
+            outports,
+            signals_in,
+            signals_out,
+            pending: VecDeque::new(),
+        }
+    }
+
+    // next packet from the inports, with the index of its port
+    fn pop(&mut self) -> Option<(usize, FbpMessage)> {
+        for _ in 0..self.inports.len() {
+            let index = self.next_inport;
+            self.next_inport = (self.next_inport + 1) % self.inports.len();
+            if let Ok(packet) = self.inports[index].1.pop() {
+                return Some((index, packet));
+            }
+        }
+        None
+    }
+
+    fn call_process(&mut self, inport: usize, packet: &FbpMessage) -> Result<(), String> {
+        let Some((plugin, handle)) = &self.instance else {
+            return Err(String::from("plugin is not initialized"));
+        };
+        let data = packet
+            .encode()
+            .map_err(|err| format!("failed to encode packet: {}", err))?;
+        let mut emitted: Emitted = vec![];
+        let result = unsafe {
+            (plugin.process)(
+                *handle,
+                self.inports[inport].0.as_ptr(),
+                data.as_ptr(),
+                data.len(),
+                emit,
+                &mut emitted as *mut Emitted as *mut c_void,
+            )
+        };
+        for (port, data) in emitted {
+            let Some(index) = self.outports.iter().position(|(name, _)| *name == port) else {
+                trace!(
+                    "dropping packet for unconnected or unknown outport {}",
+                    port
+                );
+                continue;
+            };
+            match FbpMessage::decode(&data) {
+                Ok(message) => self.pending.push_back((index, message)),
+                Err(err) => warn!("dropping invalid packet for outport {}: {}", port, err),
+            }
+        }
+        if result < 0 {
+            return Err(format!("process returned {}", result));
+        }
+        Ok(())
+    }
+}
+
+impl Component for PluginComponent {
+    // plugin components are created from their library, see from_plugin(); without one, the
+    // component finishes right away
+    fn new(
+        _inports: ProcessInports,
+        _outports: ProcessOutports,
+        signals_in: ProcessSignalSource,
+        signals_out: ProcessSignalSink,
+        _graph_inout: GraphInportOutportHandle,
+        _scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
+    ) -> Self {
+        PluginComponent {
+            instance: None,
+            inports: vec![],
+            next_inport: 0,
+            outports: vec![],
+            signals_in,
+            signals_out,
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn process(&mut self, context: &mut NodeContext) -> ProcessResult {
+        if let Ok(signal) = self.signals_in.try_recv() {
+            let signal_text = signal.as_text().unwrap_or("");
+            trace!("received signal: {}", signal_text);
+            if signal_text == "stop" {
+                info!("got stop signal, finishing");
+                return ProcessResult::Finished;
+            } else if signal_text == "ping" {
+                let _ = self.signals_out.try_send(FbpMessage::from_str("pong"));
+            } else {
+                warn!("received unknown signal: {}", signal_text);
+            }
+        }
+        let Some((plugin, _)) = &self.instance else {
+            return ProcessResult::Finished;
+        };
+        let plugin = plugin.clone();
+
+        let mut work_units = 0;
+        while context.remaining_budget > 0 {
+            if let Some((index, message)) = self.pending.pop_front() {
+                if let Err(PushError::Full(message)) = self.outports[index].1.push(message) {
+                    self.pending.push_front((index, message));
+                    break;
+                }
+                work_units += 1;
+                context.remaining_budget -= 1;
+                continue;
+            }
+            let Some((index, packet)) = self.pop() else {
+                break;
+            };
+            if let Err(err) = self.call_process(index, &packet) {
+                error!("plugin {}: {}, finishing", plugin.name(), err);
+                return ProcessResult::Finished;
+            }
+            work_units += 1;
+            context.remaining_budget -= 1;
+        }
+
+        if self.pending.is_empty()
+            && self
+                .inports
+                .iter()
+                .all(|(_, edge)| edge.is_abandoned() && edge.is_empty())
+        {
+            info!("EOF on all inports, finishing");
+            return ProcessResult::Finished;
+        }
+        if work_units > 0 {
+            ProcessResult::DidWork(work_units)
+        } else {
+            ProcessResult::NoWork
+        }
+    }
+
+    fn get_metadata() -> ComponentComponentPayload {
+        ComponentComponentPayload {
+            name: String::from("Plugin"),
+            description: String::from("component from a plugin library, see flowd_plugin.h"),
+            icon: String::from("plug"),
+            ..Default::default()
+        }
+    }
+}
+
+impl Drop for PluginComponent {
+    fn drop(&mut self) {
+        if let Some((plugin, handle)) = self.instance.take() {
+            unsafe { (plugin.drop)(handle) };
+        }
+    }
+}
@@ -15,6 +15,9 @@ enum Capability {
     //TODO implement, implied messages
     #[serde(rename = "network:control")]
     NetworkControl,
+    // flowd extension ADR-011: network:metrics to read the scheduler metrics of a network
+    #[serde(rename = "network:metrics")]
+    NetworkMetrics,
 
     // spec: can list components of the runtime using the component:list message.
     #[serde(rename = "protocol:component")]
@@ -23,6 +26,9 @@ enum Capability {
     ComponentGetsource,
     #[serde(rename = "component:setsource")]
     ComponentSetsource,
+    // flowd extension ADR-029: component:types to list the type registry the ports refer to
+    #[serde(rename = "component:types")]
+    ComponentTypes,
 
     // spec: can expose ports of main graph and transmit packet information to/from them
     // input messages: runtime:packet
@@ -37,6 +43,13 @@ enum Capability {
     // output messages graph:clear graph:addnode graph:removenode graph:renamenode graph:changenode graph:addedge graph:removeedge graph:changeedge graph:addinitial graph:removeinitial graph:addinport graph:removeinport graph:renameinport graph:addoutport graph:removeoutport graph:renameoutport graph:addgroup graph:removegroup graph:renamegroup graph:changegroup graph:error
     #[serde(rename = "protocol:graph")]
     ProtocolGraph,
+    // flowd extension ADR-025: graph:list, graph:create, graph:delete, graph:rename, graph:switch
+    #[serde(rename = "graph:management")]
+    GraphManagement,
+
+    // flowd extension ADR-011: runtime:loglevel to change log levels of a graph or node
+    #[serde(rename = "runtime:loglevel")]
+    RuntimeLoglevel,
 
     // spec: runtime is able to record and send over flowtraces, used for retroactive debugging
     #[serde(rename = "protocol:trace")]
@@ -93,10 +106,15 @@ struct GraphErrorResponse {
     payload: GraphErrorResponsePayload,
 }
 
+#[serde_with::skip_serializing_none]
 #[derive(Serialize, Debug)]
 struct GraphErrorResponsePayload {
     //TODO spec: graph:error response message payload is not defined in spec!
     message: String,
+    // set for graph validation problems, so that editors can highlight the node and port concerned
+    graph: Option<String>,
+    node: Option<String>,
+    port: Option<String>,
 }
 
 impl Default for GraphErrorResponse {
@@ -113,6 +131,9 @@ impl Default for GraphErrorResponsePayload {
     fn default() -> Self {
         GraphErrorResponsePayload {
             message: String::from("default graph error message"),
+            graph: None,
+            node: None,
+            port: None,
         }
     }
 }
@@ -122,7 +143,25 @@ impl GraphErrorResponse {
         GraphErrorResponse {
             protocol: String::from("graph"),
             command: String::from("error"),
-            payload: GraphErrorResponsePayload { message: err },
+            payload: GraphErrorResponsePayload {
+                message: err,
+                graph: None,
+                node: None,
+                port: None,
+            },
+        }
+    }
+
+    fn from_validation(graph: String, error: &GraphValidationError) -> Self {
+        GraphErrorResponse {
+            protocol: String::from("graph"),
+            command: String::from("error"),
+            payload: GraphErrorResponsePayload {
+                message: error.message.clone(),
+                graph: Some(graph),
+                node: error.node.clone(),
+                port: error.port.clone(),
+            },
         }
     }
 }
@@ -178,7 +217,6 @@ impl NetworkErrorResponse {
 
 // component:error response
 #[derive(Serialize, Debug)]
-#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
 struct ComponentErrorResponse {
     protocol: String,
     command: String,
@@ -200,6 +238,16 @@ impl Default for ComponentErrorResponse {
     }
 }
 
+impl ComponentErrorResponse {
+    fn new(message: String) -> Self {
+        ComponentErrorResponse {
+            protocol: String::from("component"),
+            command: String::from("error"),
+            payload: ComponentErrorResponsePayload { message },
+        }
+    }
+}
+
 impl Default for ComponentErrorResponsePayload {
     fn default() -> Self {
         ComponentErrorResponsePayload {
@@ -233,8 +281,8 @@ struct RuntimePacketRequestPayload {
     typ: Option<String>, // spec: the basic data type send, example "array" -- TODO which values are allowed here? TODO serde rename correct?
     schema: Option<String>, // spec: URL to JSON schema describing the format of the data
     graph: String,
-    payload: Option<String>, // spec: payload for the packet. Used only with begingroup (for group names) and data packets. //TODO type "any" allowed
-    secret: Option<String>,  // only present on the request payload
+    payload: Option<JsonValue>, // spec: payload for the packet. Used only with begingroup (for group names) and data packets. Any JSON type
+    secret: Option<String>,     // only present on the request payload
 }
 
 #[derive(Serialize, Debug)]
@@ -254,7 +302,7 @@ struct RuntimePacketResponsePayload {
     typ: Option<String>, // spec: the basic data type send, example "array" -- TODO which values are allowed here? TODO serde rename correct?
     schema: Option<String>, // spec: URL to JSON schema describing the format of the data
     graph: String,
-    payload: Option<String>, // spec: payload for the packet. Used only with begingroup (for group names) and data packets. //TODO type "any" allowed
+    payload: Option<JsonValue>, // spec: payload for the packet. Used only with begingroup (for group names) and data packets. Any JSON type
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -285,7 +333,7 @@ impl Default for RuntimePacketResponsePayload {
             typ: Some(String::from("string")), //TODO is this correct?
             schema: None,
             graph: String::from("default_graph"),
-            payload: Some(String::from("default packet payload")),
+            payload: Some(JsonValue::from("default packet payload")),
         }
     }
 }
@@ -335,7 +383,7 @@ impl RuntimePacketResponse {
                 event: RuntimePacketEvent::BeginGroup,
                 typ: typ,
                 schema: schema,
-                payload: Some(group_name),
+                payload: Some(JsonValue::String(group_name)),
             },
         }
     }
@@ -345,7 +393,7 @@ impl RuntimePacketResponse {
         port: String,
         typ: Option<String>,
         schema: Option<String>,
-        payload: String,
+        payload: JsonValue,
     ) -> Self {
         RuntimePacketResponse {
             protocol: String::from("runtime"),
@@ -353,7 +401,7 @@ impl RuntimePacketResponse {
             payload: RuntimePacketResponsePayload {
                 graph: graph,
                 port: port,
-                event: RuntimePacketEvent::Connect,
+                event: RuntimePacketEvent::Data,
                 typ: typ,
                 schema: schema,
                 payload: Some(payload),
@@ -377,7 +425,7 @@ impl RuntimePacketResponse {
                 event: RuntimePacketEvent::EndGroup,
                 typ: typ,
                 schema: schema,
-                payload: Some(group_name),
+                payload: Some(JsonValue::String(group_name)),
             },
         }
     }
@@ -420,7 +468,7 @@ struct RuntimePacketsentPayload {
     typ: Option<String>, // spec: the basic data type send, example "array" -- TODO which values are allowed here? TODO serde rename correct?
     schema: Option<String>, // spec: URL to JSON schema describing the format of the data
     graph: String,
-    payload: Option<String>, // spec: payload for the packet. Used only with begingroup (for group names) and data packets. //TODO type "any" allowed
+    payload: Option<JsonValue>, // spec: payload for the packet. Used only with begingroup (for group names) and data packets. Any JSON type
 }
 
 impl RuntimePacketsentMessage {
@@ -448,6 +496,54 @@ impl From<RuntimePacketRequestPayload> for RuntimePacketsentPayload {
     }
 }
 
+// runtime:loglevel -> runtime:loglevel | runtime:error
+// flowd extension: level of a whole graph or, with node, of a single process; level null removes
+// the setting again so that the graph level or the default level of the runtime applies
+#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
+#[derive(Deserialize, Debug)]
+struct RuntimeLoglevelRequest {
+    protocol: String,
+    command: String,
+    payload: RuntimeLoglevelRequestPayload,
+}
+
+#[derive(Deserialize, Debug)]
+struct RuntimeLoglevelRequestPayload {
+    graph: String,
+    node: Option<String>,
+    level: Option<String>, // off, error, warn, info, debug, trace
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct RuntimeLoglevelMessage {
+    protocol: String,
+    command: String,
+    payload: RuntimeLoglevelResponsePayload,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug)]
+struct RuntimeLoglevelResponsePayload {
+    graph: String,
+    node: Option<String>,
+    level: String, // the level now in effect
+}
+
+impl RuntimeLoglevelMessage {
+    fn new(graph: String, node: Option<String>, level: log::LevelFilter) -> Self {
+        RuntimeLoglevelMessage {
+            protocol: String::from("runtime"),
+            command: String::from("loglevel"),
+            payload: RuntimeLoglevelResponsePayload {
+                graph,
+                node,
+                level: level.as_str().to_ascii_lowercase(),
+            },
+        }
+    }
+}
+
 // runtime:ports response
 #[derive(Serialize, Debug)]
 struct RuntimePortsMessage {
@@ -869,8 +965,8 @@ impl Default for NetworkIconResponsePayload {
 
 // network:processerror
 // spec: When in debug mode, a network can signal an error happening inside a process.
+// flowd sends it whenever a process is restarted by its restart policy (ADR-015).
 #[derive(Serialize, Debug)]
-#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
 struct NetworkProcesserrorResponse {
     protocol: String,
     command: String,
@@ -887,8 +983,17 @@ impl Default for NetworkProcesserrorResponse {
     }
 }
 
+impl NetworkProcesserrorResponse {
+    fn new(graph: String, id: String, error: String) -> Self {
+        NetworkProcesserrorResponse {
+            protocol: String::from("network"),
+            command: String::from("processerror"),
+            payload: NetworkProcesserrorResponsePayload { id, error, graph },
+        }
+    }
+}
+
 #[derive(Serialize, Debug)]
-#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
 struct NetworkProcesserrorResponsePayload {
     id: String, // spec: identifier of the node
     error: String,
@@ -944,6 +1049,17 @@ struct NetworkSchedulerMetricsPayload {
     queue_depth: usize,
     #[serde(rename = "loopIterations")]
     loop_iterations: u64,
+    workers: Vec<NetworkSchedulerWorkerMetricsPayload>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct NetworkSchedulerWorkerMetricsPayload {
+    executions: u64,
+    #[serde(rename = "workUnits")]
+    work_units: u64,
+    #[serde(rename = "busyTimeMs")]
+    busy_time_ms: u64,
+    utilisation: f64, // 0.0..=1.0 share of wall time spent in process() calls
 }
 
 fn network_scheduler_metrics(
@@ -966,6 +1082,16 @@ fn network_scheduler_metrics(
                         time_since_last_execution_ms: metrics.time_since_last_execution_ms.clone(),
                         queue_depth: metrics.queue_depth,
                         loop_iterations: metrics.loop_iterations,
+                        workers: metrics
+                            .workers
+                            .iter()
+                            .map(|worker| NetworkSchedulerWorkerMetricsPayload {
+                                executions: worker.executions,
+                                work_units: worker.work_units,
+                                busy_time_ms: worker.busy_time_ms,
+                                utilisation: worker.utilisation,
+                            })
+                            .collect(),
                     },
                 )
             })
@@ -973,6 +1099,41 @@ fn network_scheduler_metrics(
     )
 }
 
+// flowd extension ADR-011: network:metrics -> network:metrics | network:error
+// separate from network:status, which has to stay schema-compatible with strict fbp-protocol tests
+#[derive(Deserialize, Debug)]
+struct NetworkMetricsRequestPayload {
+    graph: String,
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct NetworkMetricsResponse {
+    protocol: String,
+    command: String,
+    payload: NetworkMetricsResponsePayload,
+}
+
+#[derive(Serialize, Debug)]
+struct NetworkMetricsResponsePayload {
+    graph: String,
+    #[serde(rename = "schedulerMetrics")]
+    scheduler_metrics: HashMap<String, NetworkSchedulerMetricsPayload>,
+}
+
+impl NetworkMetricsResponse {
+    fn new(status: &RuntimeStatusSnapshot) -> Self {
+        NetworkMetricsResponse {
+            protocol: String::from("network"),
+            command: String::from("metrics"),
+            payload: NetworkMetricsResponsePayload {
+                graph: status.graph.clone(),
+                scheduler_metrics: network_scheduler_metrics(status).unwrap_or_default(),
+            },
+        }
+    }
+}
+
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Debug, Clone)]
 struct NetworkStartedResponsePayload {
@@ -1433,6 +1594,40 @@ impl ComponentComponentsreadyMessage {
     }
 }
 
+// ----------
+// component:types
+// ----------
+
+// flowd extension ADR-029: component:types -> component:types | component:error
+#[derive(Deserialize, Debug)]
+struct ComponentTypesRequestPayload {
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct ComponentTypesMessage<'a> {
+    protocol: String,
+    command: String,
+    payload: ComponentTypesResponsePayload<'a>,
+}
+
+#[derive(Serialize, Debug)]
+struct ComponentTypesResponsePayload<'a> {
+    types: Vec<&'a RegistryEntry>,
+}
+
+impl<'a> ComponentTypesMessage<'a> {
+    fn new(types: &'a TypeRegistry) -> Self {
+        ComponentTypesMessage {
+            protocol: String::from("component"),
+            command: String::from("types"),
+            payload: ComponentTypesResponsePayload {
+                types: types.entries().collect(),
+            },
+        }
+    }
+}
+
 // ----------
 // component:getsource
 // ----------
@@ -1481,7 +1676,7 @@ impl ComponentSourceMessage {
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct ComponentSourcePayload {
     name: String, // spec: Name of the component. Must not contain library prefix
     language: String,
@@ -1510,6 +1705,30 @@ impl Default for ComponentSourcePayload {
 //NOTE: find implementation of component:source above in section component:getsource
 //NOTE: find implementation of component:component below in section protocol:component
 
+#[derive(Deserialize, Debug)]
+struct ComponentSourceRequestPayload {
+    name: String, // spec: Name of the component. Must not contain library prefix
+    language: String,
+    #[serde(default)]
+    library: String,
+    code: String,
+    #[serde(default)]
+    tests: String,
+    secret: Option<String>,
+}
+
+impl From<ComponentSourceRequestPayload> for ComponentSourcePayload {
+    fn from(payload: ComponentSourceRequestPayload) -> Self {
+        ComponentSourcePayload {
+            name: payload.name,
+            language: payload.language,
+            library: payload.library,
+            code: payload.code,
+            tests: payload.tests,
+        }
+    }
+}
+
 // ----------
 // graph:readonly
 // ----------
@@ -1541,6 +1760,8 @@ struct GraphClearRequestPayload {
     main: bool,              // TODO clarify spec
     icon: Option<String>,
     description: Option<String>,
+    #[serde(rename = "schedulerWorkers")]
+    scheduler_workers: Option<usize>, // flowd extension: scheduler worker threads for this graph
     secret: Option<String>,
 }
 
@@ -1563,6 +1784,8 @@ struct GraphClearResponsePayload {
     main: bool,              // TODO clarify spec
     icon: Option<String>,
     description: Option<String>,
+    #[serde(rename = "schedulerWorkers")]
+    scheduler_workers: Option<usize>,
 }
 
 impl Default for GraphClearResponse {
@@ -1584,6 +1807,7 @@ impl Default for GraphClearResponsePayload {
             main: true,
             icon: None,
             description: None,
+            scheduler_workers: None,
         }
     }
 }
@@ -1604,6 +1828,7 @@ impl GraphClearResponse {
                 main: payload.main,
                 icon: payload.icon.clone(),
                 description: payload.description.clone(),
+                scheduler_workers: payload.scheduler_workers,
             },
         }
     }
@@ -1641,6 +1866,8 @@ struct GraphNodeMetadata {
     height: Option<u32>, // not mentioned in specs, but used by noflo-ui, usually 72
     label: Option<String>, // not mentioned in specs, but used by noflo-ui, used for the process name in bigger letters than component name
     icon: Option<String>,  // icon for the node, used by FBP protocol clients like noflo-ui
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    restart: Option<GraphNodeRestart>, // flowd extension, ADR-015: restart policy of the process
 }
 
 impl Clone for GraphNodeMetadata {
@@ -1652,6 +1879,29 @@ impl Clone for GraphNodeMetadata {
             height: self.height,
             label: self.label.clone(),
             icon: self.icon.clone(),
+            restart: self.restart.clone(),
+        }
+    }
+}
+
+// node metadata "restart": {"policy": "on-panic", "maxRestarts": 3, "backoffMs": 100}
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct GraphNodeRestart {
+    policy: crate::scheduler::RestartMode, // never, on-panic, always (also after finishing)
+    max_restarts: Option<u32>,
+    backoff_ms: Option<u64>, // doubled for each further restart
+}
+
+impl GraphNodeRestart {
+    fn policy(&self) -> crate::scheduler::RestartPolicy {
+        crate::scheduler::RestartPolicy {
+            mode: self.policy,
+            max_restarts: self.max_restarts.unwrap_or(NODE_MAX_RESTARTS_DEFAULT),
+            backoff: Duration::from_millis(
+                self.backoff_ms.unwrap_or(NODE_RESTART_BACKOFF_MS_DEFAULT),
+            ),
         }
     }
 }
@@ -1665,6 +1915,7 @@ impl Default for GraphNodeMetadata {
             height: Some(NODE_HEIGHT_DEFAULT),
             label: None,
             icon: None,
+            restart: None,
         }
     }
 }
@@ -1940,9 +2191,10 @@ impl Default for GraphNodeSpecNetwork {
 #[serde_with::skip_serializing_none]
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct GraphEdgeMetadata {
-    route: Option<i32>,     //TODO clarify spec: Route identifier of a graph edge
+    route: Option<i32>,       //TODO clarify spec: Route identifier of a graph edge
     schema: Option<String>, //TODO clarify spec: JSON schema associated with a graph edge (TODO check schema)
     secure: Option<bool>,   //TODO clarify spec: Whether edge data should be treated as secure
+    persistent: Option<bool>, // flowd extension, ADR-010: journal edge, replay on next start
 }
 
 impl Default for GraphEdgeMetadata {
@@ -1952,6 +2204,7 @@ impl Default for GraphEdgeMetadata {
             route: None,
             schema: None,
             secure: None,
+            persistent: None,
         }
     }
 }
@@ -1962,8 +2215,13 @@ impl GraphEdgeMetadata {
             route: route,
             schema: schema,
             secure: secure,
+            persistent: None,
         }
     }
+
+    fn is_persistent(&self) -> bool {
+        self.persistent.unwrap_or(false)
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -2042,7 +2300,7 @@ struct GraphRemoveedgeResponse {
     payload: GraphRemoveedgeResponsePayload,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 struct GraphRemoveedgeResponsePayload {
     //TODO clarify spec: should request values be echoed back as confirmation or is message type graph:addedge instead of graph:error enough? if not sending a response, then comes a timeout. sending empty payload gives an error.
     graph: String, //TODO spec: for graph:addedge the graph attricbute is after src,tgt but for removeedge it is first
@@ -2810,6 +3068,233 @@ impl Default for GraphChangegroupResponsePayload {
     }
 }
 
+// ADR-025 graph management, flowd extensions gated by capability graph:management
+
+// graph:list -> graph:graph (one per graph), then a final graph:graphsdone | graph:error
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
+struct GraphListRequest {
+    protocol: String,
+    command: String,
+    payload: GraphListRequestPayload,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphListRequestPayload {
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphGraphMessage {
+    protocol: String,
+    command: String,
+    payload: GraphGraphPayload,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug)]
+struct GraphGraphPayload {
+    id: String,
+    name: String, // graphs have no separate label
+    description: Option<String>,
+    icon: Option<String>,
+    active: bool, // the active graph of the runtime, see runtime:runtime
+    running: bool,
+}
+
+impl GraphGraphMessage {
+    fn new(graph: &Graph, active: bool, running: bool) -> Self {
+        let non_empty = |value: &String| Some(value.clone()).filter(|value| !value.is_empty());
+        GraphGraphMessage {
+            protocol: String::from("graph"),
+            command: String::from("graph"),
+            payload: GraphGraphPayload {
+                id: graph.properties.name.clone(),
+                name: graph.properties.name.clone(),
+                description: non_empty(&graph.properties.description),
+                icon: non_empty(&graph.properties.icon),
+                active,
+                running,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct GraphGraphsdoneMessage {
+    protocol: String,
+    command: String,
+    payload: GraphGraphsdonePayload,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct GraphGraphsdonePayload {}
+
+impl Default for GraphGraphsdoneMessage {
+    fn default() -> Self {
+        GraphGraphsdoneMessage {
+            protocol: String::from("graph"),
+            command: String::from("graphsdone"),
+            payload: GraphGraphsdonePayload::default(),
+        }
+    }
+}
+
+// graph:create -> graph:created | graph:error
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
+struct GraphCreateRequest {
+    protocol: String,
+    command: String,
+    payload: GraphCreateRequestPayload,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphCreateRequestPayload {
+    id: String,
+    description: Option<String>,
+    icon: Option<String>,
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphCreatedResponse {
+    protocol: String,
+    command: String,
+    payload: GraphCreatedResponsePayload,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphCreatedResponsePayload {
+    id: String,
+}
+
+impl GraphCreatedResponse {
+    fn new(id: String) -> Self {
+        GraphCreatedResponse {
+            protocol: String::from("graph"),
+            command: String::from("created"),
+            payload: GraphCreatedResponsePayload { id },
+        }
+    }
+}
+
+// graph:delete -> graph:deleted | graph:error
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
+struct GraphDeleteRequest {
+    protocol: String,
+    command: String,
+    payload: GraphDeleteRequestPayload,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphDeleteRequestPayload {
+    id: String,
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphDeletedResponse {
+    protocol: String,
+    command: String,
+    payload: GraphDeletedResponsePayload,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphDeletedResponsePayload {
+    id: String,
+}
+
+impl GraphDeletedResponse {
+    fn new(id: String) -> Self {
+        GraphDeletedResponse {
+            protocol: String::from("graph"),
+            command: String::from("deleted"),
+            payload: GraphDeletedResponsePayload { id },
+        }
+    }
+}
+
+// graph:rename -> graph:renamed | graph:error
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
+struct GraphRenameRequest {
+    protocol: String,
+    command: String,
+    payload: GraphRenameRequestPayload,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphRenameRequestPayload {
+    from: String,
+    to: String,
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphRenamedResponse {
+    protocol: String,
+    command: String,
+    payload: GraphRenamedResponsePayload,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphRenamedResponsePayload {
+    from: String,
+    to: String,
+}
+
+impl GraphRenamedResponse {
+    fn new(payload: &GraphRenameRequestPayload) -> Self {
+        GraphRenamedResponse {
+            protocol: String::from("graph"),
+            command: String::from("renamed"),
+            payload: GraphRenamedResponsePayload {
+                from: payload.from.clone(),
+                to: payload.to.clone(),
+            },
+        }
+    }
+}
+
+// graph:switch -> graph:switched | graph:error
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
+struct GraphSwitchRequest {
+    protocol: String,
+    command: String,
+    payload: GraphSwitchRequestPayload,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphSwitchRequestPayload {
+    id: String,
+    secret: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphSwitchedResponse {
+    protocol: String,
+    command: String,
+    payload: GraphSwitchedResponsePayload,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphSwitchedResponsePayload {
+    id: String,
+}
+
+impl GraphSwitchedResponse {
+    fn new(id: String) -> Self {
+        GraphSwitchedResponse {
+            protocol: String::from("graph"),
+            command: String::from("switched"),
+            payload: GraphSwitchedResponsePayload { id },
+        }
+    }
+}
+
 // ----------
 // protocol:runtime
 // ----------
@@ -2827,6 +3312,8 @@ enum RuntimeMessage {
     Packet(RuntimePacketRequestPayload),
     #[serde(rename = "packetsent")]
     Packetsent(RuntimePacketsentPayload),
+    #[serde(rename = "loglevel")]
+    Loglevel(RuntimeLoglevelRequestPayload),
 }
 
 // ----------
@@ -2850,6 +3337,8 @@ enum NetworkMessage {
     Stop(NetworkStopRequestPayload),
     #[serde(rename = "debug")]
     Debug(NetworkDebugRequestPayload),
+    #[serde(rename = "metrics")]
+    Metrics(NetworkMetricsRequestPayload),
 }
 
 // ----------
@@ -2862,9 +3351,11 @@ enum ComponentMessage {
     #[serde(rename = "getsource")]
     Getsource(ComponentGetsourcePayload),
     #[serde(rename = "source")]
-    Source,
+    Source(ComponentSourceRequestPayload),
     #[serde(rename = "list")]
     List(ComponentListRequestPayload),
+    #[serde(rename = "types")]
+    Types(ComponentTypesRequestPayload),
     #[serde(rename = "component")]
     Component,
     #[serde(rename = "componentsready")]
@@ -2918,6 +3409,16 @@ enum GraphMessage {
     Renamegroup(GraphRenamegroupRequestPayload),
     #[serde(rename = "changegroup")]
     Changegroup(GraphChangegroupRequestPayload),
+    #[serde(rename = "list")]
+    List(GraphListRequestPayload),
+    #[serde(rename = "create")]
+    Create(GraphCreateRequestPayload),
+    #[serde(rename = "delete")]
+    Delete(GraphDeleteRequestPayload),
+    #[serde(rename = "rename")]
+    Rename(GraphRenameRequestPayload),
+    #[serde(rename = "switch")]
+    Switch(GraphSwitchRequestPayload),
 }
 
 // ----------
@@ -3116,6 +3617,8 @@ impl TraceClearResponse {
 }
 
 // trace:dump -> trace:dump | trace:error
+const FLOWTRACE_TYPE: &str = "flowtrace.json";
+
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)] // Never constructed, only used for serde deserialization into enum variants
 struct TraceDumpRequest {
@@ -3127,9 +3630,11 @@ struct TraceDumpRequest {
 #[derive(Deserialize, Debug)]
 struct TraceDumpRequestPayload {
     graph: String,
-    #[serde(rename = "type")] //TODO is this correct?
-    typ: String, //TODO spec which types are possible? // spec calls this field "type"
-    flowtrace: String, // spec: a Flowtrace file of the type given in attribute "type" -- TODO format defined there:  https://github.com/flowbased/flowtrace
+    #[serde(rename = "type", default)]
+    #[allow(dead_code)] // only flowtrace.json is supported
+    typ: Option<String>, // spec calls this field "type"
+    #[allow(dead_code)]
+    flowtrace: Option<String>, // spec: only meaningful in the response, tolerated in the request
     secret: Option<String>, // only present in the request payload
 }
 
@@ -3143,7 +3648,9 @@ struct TraceDumpResponse {
 #[derive(Serialize, Debug)]
 struct TraceDumpResponsePayload {
     graph: String,
-    flowtrace: String, //TODO any better format than String - a data structure?
+    #[serde(rename = "type")]
+    typ: String, // spec: type of the flowtrace, we only produce "flowtrace.json"
+    flowtrace: String, // spec: Flowtrace file contents, see https://github.com/flowbased/flowtrace
 } //TODO clarify spec: should request values be echoed back as confirmation or is message type trace:dump instead of trace:error enough?
 
 impl Default for TraceDumpResponse {
@@ -3160,7 +3667,8 @@ impl Default for TraceDumpResponsePayload {
     fn default() -> Self {
         TraceDumpResponsePayload {
             graph: String::from("default_graph"),
-            flowtrace: String::from(""), //TODO clarify spec: how to indicate an empty tracefile? Does it need "[]" or "{}" at least?
+            typ: String::from(FLOWTRACE_TYPE),
+            flowtrace: String::from(""),
         }
     }
 }
@@ -3172,6 +3680,7 @@ impl TraceDumpResponse {
             command: String::from("dump"),
             payload: TraceDumpResponsePayload {
                 graph: graph,
+                typ: String::from(FLOWTRACE_TYPE),
                 flowtrace: dump,
             },
         }
@@ -40,9 +40,10 @@ pub fn create_component_library() -> Arc<RwLock<ComponentLibrary>> {
 /// Create a graph inport/outport holder
 pub fn create_graph_inout_holder() -> Arc<Mutex<GraphInportOutportHolder>> {
     Arc::new(Mutex::new(GraphInportOutportHolder {
-        inports: None,
-        outports: None,
+        inports: HashMap::new(),
+        outports: HashMap::new(),
         websockets: HashMap::new(),
+        outport_subscribers: HashMap::new(),
         packet_tap: None,
     }))
 }
@@ -82,6 +83,7 @@ fn create_default_graph() -> std::result::Result<Graph, std::io::Error> {
             width: Some(NODE_WIDTH_DEFAULT),
             label: Some("Repeat".to_owned()),
             icon: None,
+            restart: None,
         },
     )?;
     //NOTE: bug in noflo-ui, which does not allow reconnecting exported ports to other components, they just vanish then (TODO)
@@ -113,6 +115,7 @@ fn create_default_graph() -> std::result::Result<Graph, std::io::Error> {
             width: Some(NODE_WIDTH_DEFAULT),
             label: Some("Repeat".to_owned()),
             icon: None,
+            restart: None,
         },
     )?;
     // add components required for test suite
@@ -127,6 +130,7 @@ fn create_default_graph() -> std::result::Result<Graph, std::io::Error> {
             width: Some(NODE_WIDTH_DEFAULT),
             label: Some("Repeat".to_owned()),
             icon: None,
+            restart: None,
         },
     )?;
     graph.add_node(
@@ -140,6 +144,7 @@ fn create_default_graph() -> std::result::Result<Graph, std::io::Error> {
             width: Some(NODE_WIDTH_DEFAULT),
             label: Some("Drop".to_owned()),
             icon: None,
+            restart: None,
         },
     )?;
     graph.add_node(
@@ -153,6 +158,7 @@ fn create_default_graph() -> std::result::Result<Graph, std::io::Error> {
             width: Some(NODE_WIDTH_DEFAULT),
             label: Some("Output".to_owned()),
             icon: None,
+            restart: None,
         },
     )?;
     graph.add_edge(
@@ -209,45 +215,25 @@ fn create_default_graph() -> std::result::Result<Graph, std::io::Error> {
     Ok(graph)
 }
 
+// hot graph mutation: waits for processes to work off removed edges, only needs the scheduler
+// and therefore runs after the runtime lock was released
+type LiveDrain = Box<dyn FnOnce() -> std::result::Result<(), std::io::Error> + Send>;
+
+// graphs by name and the name of the active graph
+type GraphSet = (HashMap<String, Arc<RwLock<Graph>>>, String);
+
 /// Load graph set from persistence file or create a default set.
-pub fn load_or_create_graph_set(
-) -> std::result::Result<(HashMap<String, Arc<RwLock<Graph>>>, String), std::io::Error> {
-    if Path::new(PERSISTENCE_FILE_NAME).exists() {
-        let json_data = std::fs::read_to_string(PERSISTENCE_FILE_NAME)?;
-        if let Ok(graph_set) = serde_json::from_str::<PersistedGraphSet>(&json_data) {
-            let mut graph_map: HashMap<String, Arc<RwLock<Graph>>> = HashMap::new();
-            for (graph_id, graph) in graph_set.graphs {
-                graph_map.insert(graph_id, Arc::new(RwLock::new(graph)));
-            }
-            if graph_map.is_empty() {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "persisted graph set is empty",
-                ));
-            }
-            let active = if graph_map.contains_key(&graph_set.active_graph) {
-                graph_set.active_graph
-            } else {
-                graph_map
-                    .keys()
-                    .next()
-                    .expect("already checked non-empty")
-                    .clone()
-            };
-            info!(
-                "loaded multi-graph persistence with {} graph(s), active '{}'",
-                graph_map.len(),
-                active
-            );
-            return Ok((graph_map, active));
-        }
+pub fn load_or_create_graph_set() -> std::result::Result<GraphSet, std::io::Error> {
+    load_or_create_graph_set_in(Path::new("."))
+}
 
-        let graph: Graph = serde_json::from_str(&json_data)?;
-        let graph_name = graph.properties.name.clone();
-        let mut graph_map: HashMap<String, Arc<RwLock<Graph>>> = HashMap::new();
-        graph_map.insert(graph_name.clone(), Arc::new(RwLock::new(graph)));
-        info!("loaded single-graph persistence, graph '{}'", graph_name);
-        Ok((graph_map, graph_name))
+/// Load graph set from the persistence file in `graph_dir` or create a default set.
+pub fn load_or_create_graph_set_in(
+    graph_dir: &Path,
+) -> std::result::Result<GraphSet, std::io::Error> {
+    let persistence_path = graph_dir.join(PERSISTENCE_FILE_NAME);
+    if persistence_path.exists() {
+        load_graph_file(&persistence_path)
     } else {
         let graph = create_default_graph()?;
         let graph_name = graph.properties.name.clone();
@@ -258,6 +244,66 @@ pub fn load_or_create_graph_set(
     }
 }
 
+/// Load a graph set or a single graph from a JSON file, or a graph from a .fbp file,
+/// returning the graphs and the active one.
+pub fn load_graph_file(path: &Path) -> std::result::Result<GraphSet, std::io::Error> {
+    let json_data = std::fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "fbp") {
+        // named like the file unless there is a @name annotation
+        let file_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let graph = Graph::from_fbp(&file_stem, &json_data).map_err(|err| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("failed to parse graph {}: {}", path.display(), err),
+            )
+        })?;
+        let graph_name = graph.properties.name.clone();
+        let mut graph_map: HashMap<String, Arc<RwLock<Graph>>> = HashMap::new();
+        graph_map.insert(graph_name.clone(), Arc::new(RwLock::new(graph)));
+        info!("loaded graph '{}' from {}", graph_name, path.display());
+        return Ok((graph_map, graph_name));
+    }
+    if let Ok(graph_set) = serde_json::from_str::<PersistedGraphSet>(&json_data) {
+        let mut graph_map: HashMap<String, Arc<RwLock<Graph>>> = HashMap::new();
+        for (graph_id, graph) in graph_set.graphs {
+            graph_map.insert(graph_id, Arc::new(RwLock::new(graph)));
+        }
+        if graph_map.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "persisted graph set is empty",
+            ));
+        }
+        let active = if graph_map.contains_key(&graph_set.active_graph) {
+            graph_set.active_graph
+        } else {
+            graph_map
+                .keys()
+                .next()
+                .expect("already checked non-empty")
+                .clone()
+        };
+        info!(
+            "loaded multi-graph persistence with {} graph(s), active '{}'",
+            graph_map.len(),
+            active
+        );
+        return Ok((graph_map, active));
+    }
+
+    let graph: Graph = serde_json::from_str(&json_data).map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("failed to parse graph {}: {}", path.display(), err),
+        )
+    })?;
+    let graph_name = graph.properties.name.clone();
+    let mut graph_map: HashMap<String, Arc<RwLock<Graph>>> = HashMap::new();
+    graph_map.insert(graph_name.clone(), Arc::new(RwLock::new(graph)));
+    info!("loaded single-graph persistence, graph '{}'", graph_name);
+    Ok((graph_map, graph_name))
+}
+
 /// Load active graph from persistence, keeping backward compatibility for single-graph callers.
 pub fn load_or_create_graph() -> std::result::Result<Arc<RwLock<Graph>>, std::io::Error> {
     let (graph_map, active_graph) = load_or_create_graph_set()?;
@@ -280,12 +326,48 @@ pub mod multi_graph;
 // scheduler for new execution model
 pub mod scheduler;
 
+// flowtrace recording for trace:* protocol
+pub mod flowtrace;
+
 // server module
 pub mod server;
 
+// TLS for the management connection
+pub mod tls;
+
+// secret tokens for the management connection
+pub mod auth;
+
+// component:setsource script components
+pub mod script;
+
+// ADR-023 plugin components from shared libraries
+pub mod plugin;
+
+// ADR-011 metrics exporter
+pub mod metrics;
+
+// ADR-011 structured logging with per-node log levels
+pub mod logging;
+
+// command line interface
+pub mod cli;
+
+// .fbp network description language
+pub mod fbp;
+
 // test harness for testing strategy implementation
 pub mod test_harness;
 
+fn duration_as_millis_clamped(duration: Duration) -> u64 {
+    let millis = duration.as_millis();
+    if millis > u64::MAX as u128 {
+        u64::MAX
+    } else {
+        millis as u64
+    }
+}
+
 fn format_duration(duration: Duration) -> String {
     let total_seconds = duration.as_secs();
     let hours = total_seconds / 3600;
@@ -389,18 +471,29 @@ pub struct Runtime {
     repository_version: String,        // spec: build/repository version
 
     // runtime state
+    networks: HashMap<String, GraphNetwork>, // per-graph network state, graphs can run side by side
+    scheduler_threads: HashMap<String, std::thread::JoinHandle<()>>, // per-graph scheduler threads
+    schedulers: HashMap<String, Arc<crate::scheduler::Scheduler>>, // per-graph schedulers
+    secrets: auth::SecretStore,              // hashed secret tokens with per-graph access level
+    strict_auth: bool,                       // every message has to carry a valid secret
+    graphs: multi_graph::MultiGraphManager,  // multi-graph support
+    debug_edges: HashMap<String, Vec<GraphEdgeSpec>>, // per-graph selected edges for network:data debugging
+    edge_journal_dir: std::path::PathBuf, // ADR-010: persistent edge journals, one dir per graph
+    checkpoint_dir: std::path::PathBuf,   // ADR-005: node state checkpoints, one file per graph
+    persistence_path: std::path::PathBuf, // graph set written by network:persist
+}
+
+// state of the network of one graph
+#[derive(Debug, Default)]
+struct GraphNetwork {
     status: RuntimeStatus, // for network:status, network:started, network:stopped
-    //TODO ^ also contains graph = active graph, maybe replace status.graph with a pointer so that not 2 updates are neccessary?
-    tracing: bool,                           //TODO implement
+    tracing: bool,
     trace_sender: Option<std::sync::mpsc::Sender<FbpMessage>>,
+    trace_buffer: Arc<Mutex<flowtrace::TraceBuffer>>, // recorded events for trace:dump
     boundary_threads: BoundaryThreadManager, // non-component boundary handlers (for example graph outport bridge); scheduler executes components
     watchdog_thread: Option<std::thread::JoinHandle<()>>,
     watchdog_channel: Option<std::sync::mpsc::SyncSender<MessageBuf>>,
-    scheduler_threads: HashMap<String, std::thread::JoinHandle<()>>, // per-graph scheduler threads
-    schedulers: HashMap<String, Arc<crate::scheduler::Scheduler>>,   // per-graph schedulers
-    secrets: HashMap<String, (String, AccessLevel)>, // graph name -> (secret token, access level) for token-based security
-    graphs: multi_graph::MultiGraphManager,          // multi-graph support
-    debug_edges: HashMap<String, Vec<GraphEdgeSpec>>, // per-graph selected edges for network:data debugging
+    edge_metrics: HashMap<String, Arc<EdgeMetrics>>, // by trace edge id, for the metrics exporter
 }
 
 #[derive(Debug)]
@@ -443,6 +536,15 @@ struct SchedulerMetricsSnapshot {
     time_since_last_execution_ms: HashMap<String, u64>,
     queue_depth: usize,
     loop_iterations: u64,
+    workers: Vec<SchedulerWorkerMetricsSnapshot>,
+}
+
+#[derive(Debug, Clone)]
+struct SchedulerWorkerMetricsSnapshot {
+    executions: u64,
+    work_units: u64,
+    busy_time_ms: u64,
+    utilisation: f64,
 }
 
 impl Default for RuntimeRuntimePayload {
@@ -457,11 +559,15 @@ impl Default for RuntimeRuntimePayload {
                 Capability::NetworkStatus,
                 Capability::NetworkData,
                 Capability::NetworkControl,
+                Capability::NetworkMetrics,
                 Capability::ProtocolComponent,
                 Capability::ComponentGetsource,
                 Capability::ComponentSetsource,
+                Capability::ComponentTypes,
                 Capability::ProtocolRuntime,
                 Capability::ProtocolGraph,
+                Capability::GraphManagement,
+                Capability::RuntimeLoglevel,
                 Capability::GraphReadonly,
                 Capability::ProtocolTrace,
             ],
@@ -471,11 +577,15 @@ impl Default for RuntimeRuntimePayload {
                 Capability::NetworkStatus,
                 Capability::NetworkData,
                 Capability::NetworkControl,
+                Capability::NetworkMetrics,
                 Capability::ProtocolComponent,
                 Capability::ComponentGetsource,
                 Capability::ComponentSetsource,
+                Capability::ComponentTypes,
                 Capability::ProtocolRuntime,
                 Capability::ProtocolGraph,
+                Capability::GraphManagement,
+                Capability::RuntimeLoglevel,
                 Capability::ProtocolTrace,
             ],
             graph: String::from("default_graph"), // currently active graph
@@ -501,17 +611,16 @@ impl Default for Runtime {
             namespace: payload.namespace,
             repository: payload.repository,
             repository_version: payload.repository_version,
-            status: RuntimeStatus::default(),
-            tracing: false,
-            trace_sender: None,
-            boundary_threads: BoundaryThreadManager::default(),
-            watchdog_thread: None,
-            watchdog_channel: None,
+            networks: HashMap::new(),
             scheduler_threads: HashMap::new(),
             schedulers: HashMap::new(),
-            secrets: HashMap::new(),
+            secrets: auth::SecretStore::default(),
+            strict_auth: false,
             graphs: multi_graph::MultiGraphManager::new(),
             debug_edges: HashMap::new(),
+            edge_journal_dir: std::path::PathBuf::from(EDGE_JOURNAL_DIR),
+            checkpoint_dir: std::path::PathBuf::from(CHECKPOINT_DIR),
+            persistence_path: std::path::PathBuf::from(PERSISTENCE_FILE_NAME),
         }
     }
 }
@@ -549,35 +658,60 @@ impl Runtime {
         }
     }
 
-    fn status_snapshot(&self) -> RuntimeStatusSnapshot {
-        let status = self.status.snapshot();
+    fn status_snapshot(&self, graph: &str) -> RuntimeStatusSnapshot {
+        let status = self.graph_status(graph);
         RuntimeStatusSnapshot {
             time_started: status.time_started,
             graph: status.graph,
             started: status.started,
             running: status.running,
             debug: status.debug,
-            scheduler_metrics: self.collect_scheduler_metrics(),
+            scheduler_metrics: self.collect_scheduler_metrics(graph),
+        }
+    }
+
+    /// Status of the network of the given graph, also for graphs that were never started.
+    fn graph_status(&self, graph: &str) -> RuntimeStatus {
+        match self.networks.get(graph) {
+            Some(network) => network.status.clone(),
+            None => GraphNetwork::new(graph).status,
         }
     }
 
-    fn collect_scheduler_metrics(&self) -> HashMap<String, SchedulerMetricsSnapshot> {
+    fn network_mut(&mut self, graph: &str) -> &mut GraphNetwork {
+        self.networks
+            .entry(graph.to_owned())
+            .or_insert_with(|| GraphNetwork::new(graph))
+    }
+
+    /// Names of all graphs with a running network.
+    fn running_graphs(&self) -> Vec<String> {
+        self.networks
+            .iter()
+            .filter(|(_, network)| network.status.running)
+            .map(|(graph, _)| graph.clone())
+            .collect()
+    }
+
+    fn collect_scheduler_metrics(&self, graph: &str) -> HashMap<String, SchedulerMetricsSnapshot> {
         let mut snapshots = HashMap::new();
-        for (graph_name, scheduler) in &self.schedulers {
+        for (graph_name, scheduler) in self.schedulers.iter().filter(|(name, _)| *name == graph) {
             let metrics = scheduler.metrics_snapshot();
             let time_since_last_execution_ms = metrics
                 .time_since_last_execution
                 .iter()
-                .map(|(node_id, duration)| {
-                    let millis = duration.as_millis();
-                    let clamped = if millis > u64::MAX as u128 {
-                        u64::MAX
-                    } else {
-                        millis as u64
-                    };
-                    (node_id.clone(), clamped)
-                })
+                .map(|(node_id, duration)| (node_id.clone(), duration_as_millis_clamped(*duration)))
                 .collect::<HashMap<_, _>>();
+            let workers = metrics
+                .workers
+                .iter()
+                .map(|worker| SchedulerWorkerMetricsSnapshot {
+                    executions: worker.executions,
+                    work_units: worker.work_units,
+                    busy_time_ms: duration_as_millis_clamped(worker.busy_time),
+                    utilisation: worker.utilisation,
+                })
+                .collect();
 
             snapshots.insert(
                 graph_name.clone(),
@@ -587,6 +721,7 @@ impl Runtime {
                     time_since_last_execution_ms,
                     queue_depth: metrics.queue_depth,
                     loop_iterations: metrics.loop_iterations,
+                    workers,
                 },
             );
         }
@@ -604,24 +739,16 @@ impl Runtime {
         graphs.add_graph(active_graph.clone(), initial_graph);
 
         Runtime {
-            graph: active_graph.clone(), //TODO any way to avoid the clone and point to the other one?
-            status: RuntimeStatus {
-                time_started: chrono::DateTime::<Utc>::MIN_UTC, // zero value - //TODO rather use None
-                graph: active_graph,
-                started: false,
-                running: false,
-                debug: None,
-            },
+            graph: active_graph, //TODO any way to avoid the clone and point to the other one?
             graphs,
             schedulers: HashMap::new(),
             scheduler_threads: HashMap::new(),
-            trace_sender: None,
             ..Default::default() //TODO mock other fields as well
         }
     }
 
     // persistence
-    fn persist(&self, graph: &Graph) -> std::result::Result<(), std::io::Error> {
+    fn persist(&self) -> std::result::Result<(), std::io::Error> {
         //###
         // get source
         //TODO is the source according to the FBP JSON Network Protocol the same as the specified FBP JSON Graph format or does it differ? In which do we want to persist?
@@ -660,20 +787,10 @@ impl Runtime {
             }
         }
         if persisted_graphs.is_empty() {
+            // nothing loaded or created yet, persist the active graph as an empty graph
             persisted_graphs.insert(
                 self.graph.clone(),
-                serde_json::from_value(serde_json::to_value(graph).map_err(|err| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("failed to serialize fallback graph: {}", err),
-                    )
-                })?)
-                .map_err(|err| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("failed to clone fallback graph: {}", err),
-                    )
-                })?,
+                Graph::new(self.graph.clone(), String::from(""), String::from("")),
             );
         }
         let active_graph = if persisted_graphs.contains_key(&self.graph) {
@@ -690,7 +807,7 @@ impl Runtime {
             graphs: persisted_graphs,
         };
 
-        let mut output = File::create(PERSISTENCE_FILE_NAME)?;
+        let mut output = File::create(&self.persistence_path)?;
         output.write(
             serde_json::to_string_pretty(&graph_set)
                 .map_err(|err| {
@@ -705,6 +822,162 @@ impl Runtime {
         Ok(())
     }
 
+    // graph management, ADR-025
+
+    fn check_graph_not_running(&self, graph: &str) -> std::result::Result<(), std::io::Error> {
+        if self.graph_status(graph).running {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::ResourceBusy,
+                format!("network of graph '{}' still running", graph),
+            ));
+        }
+        Ok(())
+    }
+
+    /// graph:clear - empty the graph `payload.name` if it exists, otherwise the active graph
+    /// is emptied and replaced by `payload.name`; either way it becomes the active graph
+    fn clear_graph(
+        &mut self,
+        payload: &GraphClearRequestPayload,
+    ) -> std::result::Result<(), std::io::Error> {
+        let previous_active_graph = self.graph.clone();
+        let graph_arc = self
+            .graphs
+            .get_graph(&payload.name)
+            .or_else(|| self.graphs.get_active_graph())
+            .unwrap_or_else(|| {
+                Arc::new(RwLock::new(Graph::new(
+                    payload.name.clone(),
+                    String::from(""),
+                    String::from(""),
+                )))
+            });
+        graph_arc
+            .write()
+            .expect("lock poisoned")
+            .clear(payload, self)?;
+        let replaced = self.graphs.get_graph(&payload.name).is_none()
+            && self.graphs.get_graph(&previous_active_graph).is_some();
+        self.graphs.add_graph(payload.name.clone(), graph_arc);
+        if replaced {
+            self.graphs.remove_graph(&previous_active_graph);
+            self.networks.remove(&previous_active_graph);
+            self.debug_edges.remove(&previous_active_graph);
+        }
+        self.switch_graph(&payload.name)
+    }
+
+    /// graph:create - add an empty graph next to the existing ones
+    fn create_graph(
+        &mut self,
+        payload: &GraphCreateRequestPayload,
+    ) -> std::result::Result<(), std::io::Error> {
+        if self.graphs.get_graph(&payload.id).is_some() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("graph '{}' already exists", payload.id),
+            ));
+        }
+        self.graphs.add_graph(
+            payload.id.clone(),
+            Arc::new(RwLock::new(Graph::new(
+                payload.id.clone(),
+                payload.description.clone().unwrap_or_default(),
+                payload.icon.clone().unwrap_or_default(),
+            ))),
+        );
+        self.persist_if_persisted()
+    }
+
+    /// graph:switch - make `graph` the active graph
+    fn switch_graph(&mut self, graph: &str) -> std::result::Result<(), std::io::Error> {
+        self.graphs.set_active_graph(graph)?;
+        self.graph = graph.to_owned();
+        Ok(())
+    }
+
+    /// graph:rename - rename a graph together with its checkpoint and edge journals
+    fn rename_graph(&mut self, from: &str, to: &str) -> std::result::Result<(), std::io::Error> {
+        self.check_graph_not_running(from)?;
+        if self.graphs.get_graph(to).is_some() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("graph '{}' already exists", to),
+            ));
+        }
+        let graph_arc = self.graphs.remove_graph(from).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("graph '{}' not found", from),
+            )
+        })?;
+        graph_arc.write().expect("lock poisoned").properties.name = to.to_owned();
+        self.graphs.add_graph(to.to_owned(), graph_arc);
+        if self.graph == from {
+            self.switch_graph(to)?;
+        }
+        self.networks.remove(from);
+        if let Some(edges) = self.debug_edges.remove(from) {
+            self.debug_edges.insert(to.to_owned(), edges);
+        }
+        for (old_path, new_path) in [
+            (self.checkpoint_path(from), self.checkpoint_path(to)),
+            (
+                self.edge_journal_dir.join(sanitize_file_name(from)),
+                self.edge_journal_dir.join(sanitize_file_name(to)),
+            ),
+        ] {
+            if old_path.exists() {
+                std::fs::rename(&old_path, &new_path)?;
+            }
+        }
+        self.persist_if_persisted()
+    }
+
+    /// graph:delete - remove a graph with its checkpoint and edge journals. The last graph
+    /// cannot be deleted; if the active graph is deleted, another one becomes active.
+    fn delete_graph(&mut self, graph: &str) -> std::result::Result<(), std::io::Error> {
+        self.check_graph_not_running(graph)?;
+        if self.graphs.get_graph(graph).is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("graph '{}' not found", graph),
+            ));
+        }
+        let mut remaining = self.graphs.list_graphs();
+        remaining.retain(|graph_id| graph_id != graph);
+        remaining.sort();
+        let Some(fallback) = remaining.first() else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("graph '{}' is the only graph and cannot be deleted", graph),
+            ));
+        };
+        if self.graph == graph {
+            self.switch_graph(fallback)?;
+        }
+        self.graphs.remove_graph(graph);
+        self.networks.remove(graph);
+        self.debug_edges.remove(graph);
+        let checkpoint_path = self.checkpoint_path(graph);
+        if checkpoint_path.exists() {
+            std::fs::remove_file(checkpoint_path)?;
+        }
+        let journal_dir = self.edge_journal_dir.join(sanitize_file_name(graph));
+        if journal_dir.exists() {
+            std::fs::remove_dir_all(journal_dir)?;
+        }
+        self.persist_if_persisted()
+    }
+
+    // a deleted or renamed graph must not come back from an earlier network:persist
+    fn persist_if_persisted(&self) -> std::result::Result<(), std::io::Error> {
+        if !self.persistence_path.exists() {
+            return Ok(());
+        }
+        self.persist()
+    }
+
     //fn start(&mut self, graph: &Graph, boundary_threads: &mut BoundaryThreadManager) -> std::result::Result<&RuntimeStatus, std::io::Error> {
     fn start(
         &mut self,
@@ -714,30 +987,61 @@ impl Runtime {
         runtime: Arc<RwLock<Runtime>>,
     ) -> std::result::Result<&RuntimeStatus, std::io::Error> {
         info!("starting network for graph {}", graph.properties.name);
-        if self.status.running
-            || !self.boundary_threads.is_empty()
-            || self.watchdog_thread.is_some()
+        let graph_name = graph.properties.name.clone();
+        let network = self.network_mut(&graph_name);
+        if network.status.running
+            || !network.boundary_threads.is_empty()
+            || network.watchdog_thread.is_some()
         {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::AlreadyExists,
                 String::from("network already running or previous shutdown incomplete"),
             ));
         }
+        network.edge_metrics.clear(); // counters of the previous run
+
+        // nodes using subgraph components are run as processes of this network
+        let network_graph = graph.inline_subgraphs(&self.graphs, components)?;
+        let graph = &network_graph;
+
+        // report all problems of the graph at once before anything is spawned
+        if let Err(errors) = graph.validate(components) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "graph validation failed: {}",
+                    errors
+                        .iter()
+                        .map(|error| error.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                ),
+            ));
+        }
 
         // Initialize a fresh scheduler instance for this start cycle.
         // Reusing a previously stopped scheduler can carry `running=false`
         // into the next run, causing immediate scheduler thread exit.
-        let graph_name = graph.properties.name.clone();
+        let scheduler_workers = graph
+            .properties
+            .scheduler_workers
+            .unwrap_or(crate::scheduler::SCHEDULER_WORKERS_DEFAULT);
+        info!(
+            "graph {} uses {} scheduler worker(s)",
+            graph_name, scheduler_workers
+        );
         self.schedulers.insert(
             graph_name.clone(),
-            Arc::new(crate::scheduler::Scheduler::new()),
+            Arc::new(crate::scheduler::Scheduler::with_workers(scheduler_workers)),
         );
 
         // Initialize trace channel and dispatch thread.
         // We always keep this pipeline active because network:data debug streaming
         // is sourced from per-edge trace data events.
         let (trace_tx, trace_rx) = std::sync::mpsc::channel::<FbpMessage>();
-        self.trace_sender = Some(trace_tx);
+        let network = self.network_mut(&graph_name);
+        network.trace_sender = Some(trace_tx);
+        let trace_buffer = network.trace_buffer.clone();
         let graph_inout_clone = graph_inout_arc.clone();
         let runtime_for_trace_dispatch = runtime.clone();
         std::thread::spawn(move || {
@@ -749,8 +1053,10 @@ impl Runtime {
                         let should_emit_network_data = {
                             let runtime_read =
                                 runtime_for_trace_dispatch.read().expect("lock poisoned");
-                            let debug_enabled = runtime_read.status.debug.unwrap_or(false)
-                                && runtime_read.status.graph == payload.graph;
+                            let debug_enabled = runtime_read
+                                .graph_status(&payload.graph)
+                                .debug
+                                .unwrap_or(false);
                             if !debug_enabled {
                                 false
                             } else {
@@ -767,6 +1073,12 @@ impl Runtime {
                                             .tgt
                                             .port
                                             .eq_ignore_ascii_case(&payload.tgt.port)
+                                        && selected.src.index.as_ref().is_none_or(|index| {
+                                            payload.src.index.as_ref() == Some(index)
+                                        })
+                                        && selected.tgt.index.as_ref().is_none_or(|index| {
+                                            payload.tgt.index.as_ref() == Some(index)
+                                        })
                                 })
                             }
                         };
@@ -795,8 +1107,12 @@ impl Runtime {
                         let tracing_enabled = runtime_for_trace_dispatch
                             .read()
                             .expect("lock poisoned")
-                            .tracing;
+                            .is_tracing(&payload.graph);
                         if tracing_enabled {
+                            trace_buffer
+                                .lock()
+                                .expect("lock poisoned")
+                                .record("data", &payload);
                             send_trace_data(&graph_inout_clone, payload);
                         }
                     }
@@ -804,8 +1120,12 @@ impl Runtime {
                         let tracing_enabled = runtime_for_trace_dispatch
                             .read()
                             .expect("lock poisoned")
-                            .tracing;
+                            .is_tracing(&payload.graph);
                         if tracing_enabled {
+                            trace_buffer
+                                .lock()
+                                .expect("lock poisoned")
+                                .record("connect", &payload);
                             send_trace_connect(&graph_inout_clone, payload);
                         }
                     }
@@ -813,8 +1133,12 @@ impl Runtime {
                         let tracing_enabled = runtime_for_trace_dispatch
                             .read()
                             .expect("lock poisoned")
-                            .tracing;
+                            .is_tracing(&payload.graph);
                         if tracing_enabled {
+                            trace_buffer
+                                .lock()
+                                .expect("lock poisoned")
+                                .record("disconnect", &payload);
                             send_trace_disconnect(&graph_inout_clone, payload);
                         }
                     }
@@ -879,6 +1203,9 @@ impl Runtime {
         let watchdog_graph_inout = graph_inout_arc.clone();
         // prepare runtime reference for watchdong stopping the network
         let watchdog_runtime = runtime;
+        let watchdog_graph_name = graph_name.clone();
+        let watchdog_scheduler = scheduler_arc.clone();
+        let watchdog_checkpoint_path = self.checkpoint_path(&graph_name);
         // prepare per-process watchdog control + response channels
         let mut watchdog_threadandsignal: HashMap<
             String,
@@ -981,28 +1308,27 @@ impl Runtime {
         }
         drop(known_target_processes);
 
-        let mut iips_per_target: HashMap<(String, String), Vec<String>> = HashMap::new();
+        let mut iips_per_target: HashMap<(String, String, Option<usize>), Vec<String>> =
+            HashMap::new();
         for edge in graph.edges.iter() {
             if let Some(iip) = &edge.data {
                 iips_per_target
-                    .entry((edge.target.process.clone(), edge.target.port.clone()))
+                    .entry((
+                        edge.target.process.clone(),
+                        edge.target.port.clone(),
+                        connection_index(&edge.target)?,
+                    ))
                     .or_insert_with(Vec::new)
                     .push(iip.clone());
             }
         }
 
         // generate all connections
+        // connections are collected with their index on addressable ports, see index_ports()
+        #[derive(Default)]
         struct ProcPorts {
-            inports: ProcessInports, // including ports with IIPs
-            outports: ProcessOutports,
-        }
-        impl Default for ProcPorts {
-            fn default() -> Self {
-                ProcPorts {
-                    inports: ProcessInports::new(),
-                    outports: ProcessOutports::new(),
-                }
-            }
+            inports: Vec<(String, Option<usize>, ProcessEdgeSource)>, // including ports with IIPs
+            outports: Vec<(String, Option<usize>, ProcessEdgeSink)>,
         }
         impl std::fmt::Debug for ProcPorts {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -1047,38 +1373,43 @@ impl Runtime {
             }
 
             // prepare edge
+            let _log_context = crate::logging::enter(Arc::new(crate::logging::LogContext::edge(
+                &graph_name,
+                &trace_edge_id(
+                    &GraphNodeSpecNetwork::from(&edge.source),
+                    &GraphNodeSpecNetwork::from(&edge.target),
+                ),
+            )));
             debug!(
                 "preparing edge from {}.{} to {}.{}",
                 edge.source.process, edge.source.port, edge.target.process, edge.target.port
             );
-            let (sink, source) = ProcessEdge::new(PROCESSEDGE_BUFSIZE);
-
-            // insert into inports of target process
-            let targetproc = ports_all
-                .get_mut(&edge.target.process)
-                .expect("process IIP target assignment process not found");
-            // arrayports: insert, but remember that this is a multimap
-            // Compatibility: normalize protocol port names to component runtime port names.
-            targetproc
-                .inports
-                .insert(edge.target.port.to_ascii_uppercase(), source);
-            // assign into outports of source process
-            let sourceproc = ports_all
-                .get_mut(&edge.source.process)
-                .expect("process source assignment process not found");
-            // arrayports: insert, but remember that this is a multimap
-            let target_process = edge.target.process.clone();
-            let scheduler_clone = scheduler_for_signaling.clone();
-            let mut edge_sink = ProcessEdgeSink::new(
+            // persistent edge: messages left over from the previous run need room as well
+            let journal = if edge.metadata.is_persistent() {
+                Some(EdgeJournal::open(
+                    &self.edge_journal_path(&graph.properties.name, edge),
+                )?)
+            } else {
+                None
+            };
+            let replay_len = journal.as_ref().map_or(0, |journal| journal.pending_len());
+            let (sink, source) = ProcessEdge::new(PROCESSEDGE_BUFSIZE + replay_len);
+            let mut source = ProcessEdgeSource::new(source);
+            let mut edge_sink = self.new_edge_sink(
+                &scheduler_for_signaling,
                 sink,
-                None,
-                Some(target_process.clone()),
-                Some(Arc::new(move || {
-                    let _ = scheduler_clone.signal_ready(&target_process);
-                })),
+                &mut source,
+                edge,
+                &graph.properties.name,
             );
 
-            let iip_target_key = (edge.target.process.clone(), edge.target.port.clone());
+            // Compatibility: normalize protocol port names to component runtime port names.
+            let target_index = connection_index(&edge.target)?;
+            let iip_target_key = (
+                edge.target.process.clone(),
+                edge.target.port.clone(),
+                target_index,
+            );
             if let Some(iips) = iips_per_target.remove(&iip_target_key) {
                 for iip in iips {
                     edge_sink
@@ -1086,66 +1417,82 @@ impl Runtime {
                         .expect("failed to inject IIP into process edge channel");
                 }
             }
-
-            // Enable per-edge trace events that feed trace protocol and network:data debug stream.
-            let edge_id = format!(
-                "{}.{}-{}.{}",
-                edge.source.process, edge.source.port, edge.target.process, edge.target.port
-            );
-            if let Some(trace_sender) = &self.trace_sender {
-                edge_sink.enable_tracing(
-                    edge_id,
-                    edge.source.process.clone(),
-                    edge.source.port.clone(),
-                    edge.target.process.clone(),
-                    edge.target.port.clone(),
-                    graph.properties.name.clone(),
-                    trace_sender.clone(),
+            // ADR-029: messages of the source process are checked against its declared port type
+            let source_component = graph
+                .nodes
+                .get(&edge.source.process)
+                .map(|node| &node.component);
+            if let Some(contract) = source_component
+                .and_then(|name| components.outport_contract(name, &edge.source.port))
+            {
+                edge_sink.check_contract(Arc::new(contract));
+            }
+            // IIPs first, then replay - the journal only covers messages from the source process
+            if let Some(journal) = journal {
+                if replay_len > 0 {
+                    info!(
+                        "replaying {} journaled messages into edge {}",
+                        replay_len,
+                        journal.path().display()
+                    );
+                }
+                let journal_ack = Arc::new(edge_sink.enable_journal(journal, &mut source)?);
+                // messages count as delivered once the target returned from processing them
+                let processed_ack = journal_ack.clone();
+                scheduler_for_signaling.on_processed(
+                    &edge.target.process,
+                    Arc::new(move || processed_ack.ack_taken()),
+                );
+                // what is still in the edge when the source finished is acknowledged once the
+                // target process finished, otherwise it would be replayed on the next start
+                scheduler_for_signaling.on_finish(
+                    &edge.target.process,
+                    Box::new(move || journal_ack.ack_all()),
                 );
             }
 
-            sourceproc.outports.insert(
+            // insert into inports of target process
+            let targetproc = ports_all
+                .get_mut(&edge.target.process)
+                .expect("process IIP target assignment process not found");
+            targetproc
+                .inports
+                .push((edge.target.port.to_ascii_uppercase(), target_index, source));
+            // assign into outports of source process
+            let sourceproc = ports_all
+                .get_mut(&edge.source.process)
+                .expect("process source assignment process not found");
+            sourceproc.outports.push((
                 edge.source.port.to_ascii_uppercase(),
+                connection_index(&edge.source)?,
                 edge_sink,
-            );
+            ));
 
             // Emit trace:connect event if tracing is enabled
-            if self.tracing {
-                let edge_id = format!(
-                    "{}.{}-{}.{}",
-                    edge.source.process, edge.source.port, edge.target.process, edge.target.port
-                );
-                let connect_payload = ApiTraceConnectEventPayload {
-                    id: edge_id,
-                    src: TraceGraphNodeSpecNetwork {
-                        node: edge.source.process.clone(),
-                        port: edge.source.port.clone(),
-                        index: edge.source.index.clone(),
-                    },
-                    tgt: TraceGraphNodeSpecNetwork {
-                        node: edge.target.process.clone(),
-                        port: edge.target.port.clone(),
-                        index: edge.target.index.clone(),
-                    },
-                    graph: graph.properties.name.clone(),
-                };
-                send_trace_connect(&graph_inout_arc, connect_payload);
-            }
+            self.trace_edge_connect(edge, &graph.properties.name, &graph_inout_arc);
         }
 
         // Remaining IIPs have no matching incoming edge and therefore keep their dedicated channel.
-        for ((target_process, target_port), iips) in iips_per_target.into_iter() {
+        // Restarted processes get these again (ADR-015).
+        let mut dedicated_iips: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for ((target_process, target_port, target_index), iips) in iips_per_target.into_iter() {
             for iip in iips {
                 debug!("preparing edge from IIP to {}.{}", target_process, target_port);
+                dedicated_iips
+                    .entry(target_process.clone())
+                    .or_default()
+                    .push((target_port.to_ascii_uppercase(), iip.clone()));
                 let (mut sink, source) = ProcessEdge::new(PROCESSEDGE_IIP_BUFSIZE);
                 sink.push(FbpMessage::from_text(iip))
                     .expect("failed to send IIP into process channel");
                 let targetproc = ports_all
                     .get_mut(&target_process)
                     .expect("process IIP target assignment process not found");
-                targetproc
-                    .inports
-                    .insert(target_port.to_ascii_uppercase(), source);
+                targetproc.inports.push((
+                    target_port.to_ascii_uppercase(),
+                    target_index,
+                    source.into(),
+                ));
             }
         }
 
@@ -1161,21 +1508,20 @@ impl Runtime {
             let targetproc = ports_all
                 .get_mut(&edge.process)
                 .expect("graph target assignment process not found");
-            // arrayports: insert, but remember that this is a multimap
             // Compatibility: normalize graph export mapping to uppercase runtime port names.
             targetproc
                 .inports
-                .insert(edge.port.to_ascii_uppercase(), source);
+                .push((edge.port.to_ascii_uppercase(), None, source.into()));
             // assign into outports of source process
             // source process name = graphname-IN
             let sourceproc: &mut ProcPorts = ports_all
                 .get_mut(format!("{}-IN", graph.properties.name).as_str())
                 .expect("graph source assignment process not found");
-            // arrayports: insert, but remember that this is a multimap
             let target_process = edge.process.clone();
             let scheduler_clone = scheduler_for_signaling.clone();
-            sourceproc.outports.insert(
+            sourceproc.outports.push((
                 public_name.clone(),
+                None,
                 ProcessEdgeSink::new(
                     sink,
                     None, // Scheduler handles signaling, no thread wakeup needed
@@ -1184,7 +1530,7 @@ impl Runtime {
                         let _ = scheduler_clone.signal_ready(&target_process);
                     })),
                 ),
-            );
+            ));
         }
         for (public_name, edge) in graph.outports.iter() {
             // prepare edge
@@ -1199,24 +1545,37 @@ impl Runtime {
             let targetproc = ports_all
                 .get_mut(format!("{}-OUT", graph.properties.name).as_str())
                 .expect("graph target assignment process not found");
-            // arrayports: insert, but remember that this is a multimap
-            targetproc.inports.insert(public_name.clone(), source);
+            targetproc
+                .inports
+                .push((public_name.clone(), None, source.into()));
             // assign into outports of source process
             let sourceproc = ports_all
                 .get_mut(&edge.process)
                 .expect("graph source assignment process not found");
-            // arrayports: insert, but remember that this is a multimap
-            sourceproc.outports.insert(
+            sourceproc.outports.push((
                 edge.port.to_ascii_uppercase(),
+                None,
                 ProcessEdgeSink::new(
                     sink,
                     None,
                     Some(format!("{}-OUT", graph.properties.name)),
                     None,
                 ),
-            );
+            ));
         }
 
+        // ADR-005: node states saved by the last checkpoint of this graph
+        let mut node_states = match read_checkpoint(&self.checkpoint_path(&graph_name)) {
+            Ok(node_states) => node_states,
+            Err(err) => {
+                warn!(
+                    "ignoring unreadable checkpoint of graph {}: {}",
+                    graph_name, err
+                );
+                HashMap::new()
+            }
+        };
+
         // generate processes and assign prepared connections
         let mut found: bool;
         let mut found2: bool;
@@ -1232,9 +1591,9 @@ impl Runtime {
                 "prepared connections for a node not found, source+target nodes in edges != nodes",
             );
             //TODO would be great to have the port name here for diagnostics
-            let inports: ProcessInports = ports_this.inports;
+            let inports: ProcessInports = index_ports(ports_this.inports, disconnected_inport)?;
             //TODO would be great to have the port name here for diagnostics
-            let outports: ProcessOutports = ports_this.outports;
+            let outports: ProcessOutports = index_ports(ports_this.outports, disconnected_outport)?;
 
             // check if all ports exist
             found = false;
@@ -1252,7 +1611,10 @@ impl Runtime {
                         let has_inport = inports.contains_key(&inport.name)
                             || inports.contains_key(&inport_lower);
                         if !has_inport {
-                            //TODO check if port is required, maybe add strict checking true/false as parameter
+                            // optional inports may stay unconnected, the component handles their absence
+                            if !inport.required {
+                                continue;
+                            }
 
                             // check if connected to a graph inport
                             found2 = false;
@@ -1336,11 +1698,15 @@ impl Runtime {
             // Register node before creating scheduler waker so async components always receive
             // a usable wake handle during construction.
             scheduler_arc.add_node(proc_name.clone(), budget_class);
+            let log_context =
+                crate::logging::LogContext::node(&graph_name, proc_name, &component_name);
+            scheduler_arc.set_log_context(proc_name, log_context.clone());
+            let _log_context = crate::logging::enter(Arc::new(log_context));
 
             // instantiate component for scheduler
             let scheduler_waker =
                 crate::scheduler::Scheduler::create_waker(&scheduler_arc, proc_name.clone());
-            let component_instance: Box<dyn Component> = match instantiate_component(
+            let mut component_instance: Box<dyn Component> = match components.instantiate(
                 component_name.as_str(),
                 inports,
                 outports,
@@ -1359,8 +1725,30 @@ impl Runtime {
                 }
             };
 
+            // continue where the last checkpoint left off
+            if let Some(node_state) = node_states.remove(proc_name) {
+                if let Err(err) = component_instance.restore(node_state) {
+                    warn!("failed to restore state of process {}: {}", proc_name, err);
+                }
+            }
+
             // add component to scheduler
             scheduler_arc.add_component(component_instance, proc_name.clone());
+            if let Some(restart) = &node.metadata.restart {
+                scheduler_arc.supervise(
+                    proc_name,
+                    restart.policy(),
+                    Self::node_restarter(
+                        &graph_name,
+                        proc_name,
+                        components,
+                        &component_name,
+                        dedicated_iips.remove(proc_name).unwrap_or_default(),
+                        &scheduler_arc,
+                        graph_inout_arc.clone(),
+                    ),
+                );
+            }
         }
 
         // Signal all nodes as ready initially to start execution
@@ -1369,8 +1757,8 @@ impl Runtime {
         }
         // work off graphname-IN and graphname-OUT special processes for graph inports and graph outports
         //TODO the signal channel and joinhandle of the graph outport process/thread could also simply be stored in boundary_threads with all other boundary handlers
-        graph_inout.inports = None;
-        graph_inout.outports = None;
+        graph_inout.inports.remove(&graph_name);
+        graph_inout.outports.remove(&graph_name);
         if ports_all.len() > 0 {
             // insert graph inport handler
             if ports_all.contains_key(format!("{}-IN", graph.properties.name).as_str()) {
@@ -1381,7 +1769,8 @@ impl Runtime {
                     .remove(format!("{}-IN", graph.properties.name).as_str())
                     .expect("prepared connections for graph inports not found");
                 // add sinks of all target processes (no thread wakeup needed for scheduled components)
-                for (port_name, mut edge) in ports_this.outports {
+                for (port_name, mut edge) in index_ports(ports_this.outports, disconnected_outport)?
+                {
                     // arrayports: check if there are multiple edges going out of the graph inport, which is not allowed
                     if edge.len() != 1 {
                         return Err(std::io::Error::new(
@@ -1394,14 +1783,14 @@ impl Runtime {
                     outports.insert(port_name, edge0);
                 }
                 // save the inports (where we put packets into) as the graph inport channel handles; they are "outport handles" because they are being written into (packet sink)
-                graph_inout.inports = Some(outports);
+                graph_inout.inports.insert(graph_name.clone(), outports);
             }
             if ports_all.contains_key(format!("{}-OUT", graph.properties.name).as_str()) {
                 // get ports for this special component, of interest here are the inports (source channels)
                 let ports_this: ProcPorts = ports_all
                     .remove(format!("{}-OUT", graph.properties.name).as_str())
                     .expect("prepared connections for graph outports not found");
-                let mut inports = ports_this.inports;
+                let mut inports = index_ports(ports_this.inports, disconnected_inport)?;
                 // prepare process signal channel
                 let (signalsink, signalsource): (ProcessSignalSink, ProcessSignalSource) =
                     std::sync::mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
@@ -1460,29 +1849,20 @@ impl Runtime {
                                 //TODO while !inport.is_empty() {
                                 loop {
                                     if let Ok(ip) = inport.pop() {
-                                        // output the packet data with newline
                                         debug!("got a packet for graph outport {}", port_name);
-                                        let payload_text = message_as_utf8(&ip)
-                                            .expect("graph outport payload must be UTF-8 text");
-                                        trace!("{}", payload_text);
-
-                                        // send out to FBP network protocol client
+                                        let Some(packet) = runtime_packet_from_message(
+                                            &graph_name,
+                                            port_name,
+                                            &ip,
+                                        ) else {
+                                            debug!("not forwarding {:?} to clients", ip);
+                                            continue;
+                                        };
+
+                                        // send out to the subscribed FBP network protocol clients
                                         debug!("sending out to client...");
                                         //TODO optimize lock only once for all packets available in inport buffer
-                                        send_runtime_packet(
-                                            &graph_inoutref,
-                                            &RuntimePacketResponse::new(
-                                                RuntimePacketResponsePayload {
-                                                    port: port_name.clone(), //TODO optimize
-                                                    event: RuntimePacketEvent::Data,
-                                                    typ: None, //TODO implement properly, OTOH it is an optional field
-                                                    schema: None,
-                                                    graph: graph_name.clone(),
-                                                    payload: Some(payload_text.to_owned()), //TODO optimize useless conversions here - we could make RuntimePacketResponse separate from RuntimePacketRequest and make payload on the Response &str
-                                                                                            // TODO optimize conversion; just handing over Some(String::from_utf8(ip) = move causes "this reinitialization might get skipped" -> https://github.com/rust-lang/rust/issues/92858
-                                                },
-                                            ),
-                                        );
+                                        send_runtime_packet(&graph_inoutref, &packet);
                                         debug!("done");
                                     } else {
                                         //TODO optimize unlock graph_inout here
@@ -1525,13 +1905,15 @@ impl Runtime {
                     ),
                 );
                 // store process signal channel and join handle so that the other processes writing into this graph outport component can find it
-                self.boundary_threads.insert(
-                    format!("{}-OUT", graph.properties.name),
-                    BoundaryThread {
-                        signal: signalsink,
-                        joinhandle: joinhandle,
-                    },
-                );
+                self.network_mut(&graph.properties.name)
+                    .boundary_threads
+                    .insert(
+                        format!("{}-OUT", graph.properties.name),
+                        BoundaryThread {
+                            signal: signalsink,
+                            joinhandle: joinhandle,
+                        },
+                    );
 
                 // save single joinhandle and signal for that component
                 //TODO optimize, cannot clone joinhandle
@@ -1549,13 +1931,13 @@ impl Runtime {
         // sanity check
         if ports_all.len() != 0 {
             // reset to known state
-            self.boundary_threads.clear();
+            self.network_mut(&graph_name).boundary_threads.clear();
             // report error
             return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, String::from("there are ports for processes left over, source+target nodes in edges != nodes")));
         }
 
         // unpark all processes since all joinhandles are now known and so that they can replace the process names with the join handles and instantiate their components
-        for proc in self.boundary_threads.iter() {
+        for proc in self.network_mut(&graph_name).boundary_threads.iter() {
             proc.1.joinhandle.thread().unpark();
         }
 
@@ -1571,6 +1953,7 @@ impl Runtime {
         let watchdog_thread = thread::Builder::new().name("watchdog".to_owned()).spawn( move || {
             debug!("watchdog is running");
             let mut missed_pongs: HashMap<String, u8> = HashMap::new();
+            let mut last_checkpoint = Instant::now();
             'watchdog_loop: loop {
                 while let Ok(signal) = watchdog_signalsource2.try_recv() {
                     let signal_text = message_as_utf8(&signal).unwrap_or("");
@@ -1692,13 +2075,10 @@ impl Runtime {
                 }
                 let all_schedulers_exited = if watchdog_threadandsignal.is_empty() {
                     match watchdog_runtime.try_read() {
-                        Ok(runtime_read) => {
-                            !runtime_read.scheduler_threads.is_empty()
-                                && runtime_read
-                                    .scheduler_threads
-                                    .values()
-                                    .all(|scheduler_thread| scheduler_thread.is_finished())
-                        }
+                        Ok(runtime_read) => runtime_read
+                            .scheduler_threads
+                            .get(&watchdog_graph_name)
+                            .is_some_and(|scheduler_thread| scheduler_thread.is_finished()),
                         Err(std::sync::TryLockError::WouldBlock) => false,
                         Err(std::sync::TryLockError::Poisoned(_)) => {
                             warn!("watchdog: runtime lock poisoned while checking scheduler completion");
@@ -1712,7 +2092,7 @@ impl Runtime {
                 if all_schedulers_exited {
                     info!("process health check: all scheduler threads exited, shutting down network");
                     let stop_err = match watchdog_runtime.try_write() {
-                        Ok(mut runtime_write) => runtime_write.stop(watchdog_graph_inout.clone(), true).err(),
+                        Ok(mut runtime_write) => runtime_write.stop(&watchdog_graph_name, watchdog_graph_inout.clone(), true).err(),
                         Err(std::sync::TryLockError::WouldBlock) => {
                             info!("watchdog: runtime lock busy during scheduler-exited shutdown; external stop is likely in progress, exiting watchdog loop");
                             break;
@@ -1727,7 +2107,7 @@ impl Runtime {
                     }
                     let stopped_packet = {
                         let runtime_read = watchdog_runtime.read().expect("watchdog failed to acquire lock for runtime");
-                        NetworkStoppedResponse::new(&runtime_read.status_snapshot())
+                        NetworkStoppedResponse::new(&runtime_read.status_snapshot(&watchdog_graph_name))
                     };
                     send_network_stopped(&watchdog_graph_inout, &stopped_packet);
                     break;
@@ -1740,7 +2120,7 @@ impl Runtime {
                     // Use non-blocking lock acquisition to avoid deadlocking with concurrent network:stop,
                     // which already holds runtime.write() and may wait for watchdog join.
                     let stop_err = match watchdog_runtime.try_write() {
-                        Ok(mut runtime_write) => runtime_write.stop(watchdog_graph_inout.clone(), true).err(),
+                        Ok(mut runtime_write) => runtime_write.stop(&watchdog_graph_name, watchdog_graph_inout.clone(), true).err(),
                         Err(std::sync::TryLockError::WouldBlock) => {
                             info!("watchdog: runtime lock busy during all-exited shutdown; external stop is likely in progress, exiting watchdog loop");
                             break;
@@ -1758,12 +2138,19 @@ impl Runtime {
                     // send network stop notification to all FBP protocol clients//###
                     let stopped_packet = {
                         let runtime_read = watchdog_runtime.read().expect("watchdog failed to acquire lock for runtime");
-                        NetworkStoppedResponse::new(&runtime_read.status_snapshot())
+                        NetworkStoppedResponse::new(&runtime_read.status_snapshot(&watchdog_graph_name))
                     };
                     send_network_stopped(&watchdog_graph_inout, &stopped_packet);
                     // exit thread
                     break;
                 }
+                // ADR-005: periodic checkpoint, a crash loses at most CHECKPOINT_INTERVAL of node state
+                if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                    if let Err(err) = write_checkpoint(&watchdog_scheduler, &watchdog_checkpoint_path, false) {
+                        warn!("watchdog: checkpoint failed: {}", err);
+                    }
+                    last_checkpoint = Instant::now();
+                }
                 let sleep_started = Instant::now();
                 while sleep_started.elapsed() < PROCESS_HEALTHCHECK_DUR {
                     if let Ok(ip) = watchdog_signalsource2.try_recv() {
@@ -1772,6 +2159,10 @@ impl Runtime {
                             break 'watchdog_loop;
                         }
                     }
+                    // all nodes finished: check right away instead of after the full interval
+                    if !watchdog_scheduler.is_running() && watchdog_threadandsignal.values().all(|proc| proc.2.load(Ordering::Acquire)) {
+                        break;
+                    }
                     thread::sleep(WATCHDOG_POLL_DUR);
                 }
             }
@@ -1779,7 +2170,7 @@ impl Runtime {
 
         // all set, now "open the doors" = inform FBP Network Protocol clients / remote runtimes that the graph inports are now connected as well (runtime:packet event type = connect)
         // check if graph has inports
-        if let Some(inports) = &graph_inout.inports {
+        if let Some(inports) = graph_inout.inports.get(&graph_name) {
             let inport_names = inports.keys().cloned().collect::<Vec<_>>(); //TODO optimize wow, but works:  https://stackoverflow.com/a/45312076/5120003
             drop(graph_inout);
             for port_name in inport_names {
@@ -1810,21 +2201,24 @@ impl Runtime {
         };
 
         // Store the scheduler thread
-        self.scheduler_threads.insert(graph_name, scheduler_thread);
+        self.scheduler_threads
+            .insert(graph_name.clone(), scheduler_thread);
 
         // return status
-        self.watchdog_thread = Some(watchdog_thread);
-        self.watchdog_channel = Some(watchdog_signalsink2);
-        self.status.time_started = chrono::Utc::now();
-        self.status.graph = self.graph.clone();
-        self.status.started = true;
-        self.status.running = true;
-        info!("network started");
-        Ok(&self.status)
+        let network = self.network_mut(&graph_name);
+        network.watchdog_thread = Some(watchdog_thread);
+        network.watchdog_channel = Some(watchdog_signalsink2);
+        network.status.time_started = chrono::Utc::now();
+        network.status.graph = graph_name.clone();
+        network.status.started = true;
+        network.status.running = true;
+        info!("network for graph {} started", graph_name);
+        Ok(&network.status)
     }
 
     fn stop(
         &mut self,
+        graph: &str,
         graph_inout: Arc<std::sync::Mutex<GraphInportOutportHolder>>,
         watchdog_all_exited: bool,
     ) -> std::result::Result<&RuntimeStatus, std::io::Error> {
@@ -1835,6 +2229,12 @@ impl Runtime {
 
         // if true, the watchdog informed us that all processes have already exited
         if !watchdog_all_exited {
+            // ADR-005: save node state while all components are still there
+            if let Some(scheduler) = self.schedulers.get(graph) {
+                if let Err(err) = write_checkpoint(scheduler, &self.checkpoint_path(graph), true) {
+                    warn!("stop: checkpoint of graph {} failed: {}", graph, err);
+                }
+            }
             // close front door early - inform FBP Network Protocol clients that graph inports are now disconnected (runtime:packet event type = connect)
             //NOTE: this happens before the GraphOutports handler thread is notified below among all the processes, so processing can be finished but no new packets are sent in anymore by the FBP client(s)
             // Keep this lock scope short: collect names first, then send disconnects with per-packet locking.
@@ -1842,7 +2242,7 @@ impl Runtime {
                 let graph_inout_inner = graph_inout.lock().expect("lock poisoned");
                 graph_inout_inner
                     .inports
-                    .as_ref()
+                    .get(graph)
                     .map(|inports| inports.keys().cloned().collect::<Vec<_>>())
                     .unwrap_or_default()
             };
@@ -1852,7 +2252,7 @@ impl Runtime {
                     send_runtime_packet(
                         &graph_inout,
                         &RuntimePacketResponse::new_disconnect(
-                            self.graph.clone(),
+                            graph.to_owned(),
                             port_name.clone(),
                             None,
                             None,
@@ -1863,7 +2263,7 @@ impl Runtime {
 
             // stop watchdog first so it cannot continue filling process signal channels with pings
             info!("stop: signaling watchdog");
-            if let Some(watchdog_channel) = self.watchdog_channel.take() {
+            if let Some(watchdog_channel) = self.network_mut(graph).watchdog_channel.take() {
                 if let Err(err) = watchdog_channel.send(FbpMessage::from_str("stop")) {
                     warn!(
                         "stop: watchdog already disconnected while sending stop signal: {}",
@@ -1873,17 +2273,26 @@ impl Runtime {
             } else {
                 warn!("stop: watchdog channel missing");
             }
-            if let Some(watchdog_thread) = &self.watchdog_thread {
+            if let Some(watchdog_thread) = &self.network_mut(graph).watchdog_thread {
                 watchdog_thread.thread().unpark();
             }
         } else {
             // runtime stop triggered from watchdog because all processes already exited
-            self.watchdog_channel.take();
+            self.network_mut(graph).watchdog_channel.take();
+            // the network ran to completion, so the next start begins with fresh node state
+            match std::fs::remove_file(self.checkpoint_path(graph)) {
+                Ok(()) => info!("stop: removed checkpoint of completed graph {}", graph),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => warn!(
+                    "stop: failed to remove checkpoint of graph {}: {}",
+                    graph, err
+                ),
+            }
         }
 
         // signal all threads
         info!("stop: signaling all processes...");
-        for (name, proc) in self.boundary_threads.iter() {
+        for (name, proc) in self.network_mut(graph).boundary_threads.iter() {
             if proc.joinhandle.is_finished() {
                 info!("stop: process {} already exited", name);
                 continue;
@@ -1921,15 +2330,16 @@ impl Runtime {
         }
         info!("done");
 
-        // Stop all scheduler threads first
-        for (graph_name, scheduler) in &self.schedulers {
-            info!("stop: stopping scheduler for graph {}", graph_name);
+        // Stop the scheduler of this graph first
+        if let Some(scheduler) = self.schedulers.get(graph) {
+            info!("stop: stopping scheduler for graph {}", graph);
             scheduler.stop();
         }
 
-        // Join all scheduler threads
+        // Join the scheduler thread of this graph, other graphs keep running
         let mut timed_out_schedulers = Vec::new();
-        for (graph_name, scheduler_thread) in std::mem::take(&mut self.scheduler_threads) {
+        if let Some(scheduler_thread) = self.scheduler_threads.remove(graph) {
+            let graph_name = graph.to_owned();
             info!("stop: joining scheduler thread for graph {}", graph_name);
             let join_started = Instant::now();
             while !scheduler_thread.is_finished() && join_started.elapsed() < PROCESS_JOIN_GRACE_DUR
@@ -1943,6 +2353,10 @@ impl Runtime {
                         graph_name, err
                     );
                 }
+                // close all edges now, so persistent edges record what was delivered
+                if let Some(scheduler) = self.schedulers.get(graph) {
+                    scheduler.release_components();
+                }
             } else {
                 warn!(
                     "stop: joining scheduler thread for graph {} timed out after {:?}",
@@ -1961,7 +2375,7 @@ impl Runtime {
         info!("stop: joining all threads...");
         let mut timed_out_threads: Vec<String> = Vec::new();
         let mut still_running: BoundaryThreadManager = BoundaryThreadManager::new();
-        for (name, proc) in std::mem::take(&mut self.boundary_threads) {
+        for (name, proc) in std::mem::take(&mut self.network_mut(graph).boundary_threads) {
             info!("stop: joining {}", name);
             let join_started = Instant::now();
             while !proc.joinhandle.is_finished() && join_started.elapsed() < PROCESS_JOIN_GRACE_DUR
@@ -1981,11 +2395,11 @@ impl Runtime {
                 still_running.insert(name, proc);
             } //TODO there is .thread() -> for killing
         }
-        self.boundary_threads = still_running;
+        self.network_mut(graph).boundary_threads = still_running;
         info!("done");
 
         // join watchdog thread (unless stop() was called from watchdog itself)
-        if let Some(watchdog_thread) = self.watchdog_thread.take() {
+        if let Some(watchdog_thread) = self.network_mut(graph).watchdog_thread.take() {
             if watchdog_all_exited {
                 // called from watchdog thread itself; dropping handle detaches and avoids self-join deadlock
                 info!("stop: watchdog self-stop acknowledged");
@@ -2004,15 +2418,16 @@ impl Runtime {
                 } else {
                     warn!("stop: joining watchdog timed out after {:?}, keeping thread tracked for retry", WATCHDOG_JOIN_GRACE_DUR);
                     timed_out_threads.push("watchdog".to_owned());
-                    self.watchdog_thread = Some(watchdog_thread);
+                    self.network_mut(graph).watchdog_thread = Some(watchdog_thread);
                 }
             }
         }
 
         if !timed_out_threads.is_empty() {
-            self.status.graph = self.graph.clone();
-            self.status.started = true;
-            self.status.running = true;
+            let status = &mut self.network_mut(graph).status;
+            status.graph = graph.to_owned();
+            status.started = true;
+            status.running = true;
             return Err(std::io::Error::new(
                 std::io::ErrorKind::TimedOut,
                 format!(
@@ -2023,74 +2438,821 @@ impl Runtime {
         }
 
         // Emit trace:disconnect for all graph edges when tracing is active.
-        if self.tracing {
-            if let Some(active_graph) = self.graphs.get_graph(&self.graph) {
+        if self.is_tracing(graph) {
+            if let Some(active_graph) = self.graphs.get_graph(graph) {
                 let active_graph = active_graph.read().expect("graph lock poisoned");
                 for edge in active_graph.edges.iter() {
                     // IIP pseudo-edges are not runtime transport edges.
                     if edge.data.is_some() && edge.source.process.is_empty() {
                         continue;
                     }
-                    let edge_id = format!(
-                        "{}.{}-{}.{}",
-                        edge.source.process, edge.source.port, edge.target.process, edge.target.port
+                    self.trace_edge_disconnect(
+                        &GraphNodeSpecNetwork::from(&edge.source),
+                        &GraphNodeSpecNetwork::from(&edge.target),
+                        &active_graph.properties.name,
+                        &graph_inout,
                     );
-                    let disconnect_payload = ApiTraceDisconnectEventPayload {
-                        id: edge_id,
-                        src: TraceGraphNodeSpecNetwork {
-                            node: edge.source.process.clone(),
-                            port: edge.source.port.clone(),
-                            index: edge.source.index.clone(),
-                        },
-                        tgt: TraceGraphNodeSpecNetwork {
-                            node: edge.target.process.clone(),
-                            port: edge.target.port.clone(),
-                            index: edge.target.index.clone(),
-                        },
-                        graph: active_graph.properties.name.clone(),
-                    };
-                    send_trace_disconnect(&graph_inout, disconnect_payload);
                 }
             }
         }
 
         // Drop trace channel sender so the trace relay thread can exit cleanly.
-        self.trace_sender = None;
+        let network = self.network_mut(graph);
+        network.trace_sender = None;
 
         // set status
-        info!("network is shut down.");
-        self.status.graph = self.graph.clone();
+        info!("network for graph {} is shut down.", graph);
+        network.status.graph = graph.to_owned();
         // Preserve "started=true, running=false" for watchdog-driven auto-finish:
         // fbp-protocol expects this when a short-lived network already completed
         // by the time network:getstatus is queried right after network:start.
-        self.status.started = watchdog_all_exited;
-        self.status.running = false; // was started, but not running any more
+        network.status.started = watchdog_all_exited;
+        network.status.running = false; // was started, but not running any more
 
         // Broadcast network:status update to all clients when graph stops (self-shutdown)
-        let status_message = NetworkStatusMessage::new(NetworkStatusPayload::new(&self.status.snapshot()));
+        let status_message =
+            NetworkStatusMessage::new(NetworkStatusPayload::new(&network.status.snapshot()));
         broadcast_to_clients(&graph_inout, &status_message, "network:status");
-        Ok(&self.status)
+        Ok(&network.status)
     }
 
-    fn debug_mode(&mut self, graph: &str, mode: bool) -> std::result::Result<(), std::io::Error> {
-        if self.graphs.get_graph(graph).is_none() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("Graph '{}' not found", graph),
-            ));
-        }
-        self.graph = graph.to_string();
-        self.status.graph = graph.to_string();
-        self.status.debug = Some(mode);
-        Ok(())
+    // keep the persisted graphs, checkpoints and edge journals in `dir`
+    fn set_graph_dir(&mut self, dir: &Path) {
+        self.persistence_path = dir.join(PERSISTENCE_FILE_NAME);
+        self.checkpoint_dir = dir.join(CHECKPOINT_DIR);
+        self.edge_journal_dir = dir.join(EDGE_JOURNAL_DIR);
     }
 
-    //TODO optimize: better to hand over String or &str? Difference between Vec and vec?
-    fn set_debug_edges(
-        &mut self,
-        graph: &str,
-        edges: &Vec<GraphEdgeSpec>,
+    // true if the watchdog of a started network exited without stopping the network
+    fn watchdog_exited(&self, graph: &str) -> bool {
+        self.networks
+            .get(graph)
+            .and_then(|network| network.watchdog_thread.as_ref())
+            .is_some_and(|watchdog_thread| watchdog_thread.is_finished())
+    }
+
+    // checkpoint file of a graph, see write_checkpoint()
+    fn checkpoint_path(&self, graph_name: &str) -> std::path::PathBuf {
+        self.checkpoint_dir
+            .join(format!("{}.checkpoint", sanitize_file_name(graph_name)))
+    }
+
+    /// Take a consistent checkpoint of the running network of `graph` and save it (ADR-005).
+    /// Returns the number of nodes whose state was saved.
+    fn checkpoint(&self, graph: &str) -> std::result::Result<usize, std::io::Error> {
+        let scheduler = self.live_scheduler(graph)?;
+        write_checkpoint(&scheduler, &self.checkpoint_path(graph), false)
+    }
+
+    // journal file of a persistent edge, stable across runs as long as the edge stays the same
+    fn edge_journal_path(&self, graph_name: &str, edge: &GraphEdge) -> std::path::PathBuf {
+        let edge_id = trace_edge_id(
+            &GraphNodeSpecNetwork::from(&edge.source),
+            &GraphNodeSpecNetwork::from(&edge.target),
+        );
+        self.edge_journal_dir
+            .join(sanitize_file_name(graph_name))
+            .join(format!("{}.journal", sanitize_file_name(&edge_id)))
+    }
+
+    /// Outport side of a process edge: signals the target process in the scheduler and emits
+    /// trace events for the trace protocol and network:data debug stream. Both sides of the
+    /// edge report into its metrics.
+    fn new_edge_sink(
+        &mut self,
+        scheduler: &Arc<crate::scheduler::Scheduler>,
+        sink: ProcessEdgeSinkConnection,
+        source: &mut ProcessEdgeSource,
+        edge: &GraphEdge,
+        graph_name: &str,
+    ) -> ProcessEdgeSink {
+        let target_process = edge.target.process.clone();
+        let scheduler_clone = scheduler.clone();
+        let mut edge_sink = ProcessEdgeSink::new(
+            sink,
+            None,
+            Some(target_process.clone()),
+            Some(Arc::new(move || {
+                let _ = scheduler_clone.signal_ready(&target_process);
+            })),
+        );
+        let edge_id = trace_edge_id(
+            &GraphNodeSpecNetwork::from(&edge.source),
+            &GraphNodeSpecNetwork::from(&edge.target),
+        );
+        let metrics = Arc::new(EdgeMetrics::default());
+        edge_sink.enable_metrics(metrics.clone());
+        source.enable_metrics(metrics.clone());
+        let network = self.network_mut(graph_name);
+        network.edge_metrics.insert(edge_id.clone(), metrics);
+        if let Some(trace_sender) = &network.trace_sender {
+            edge_sink.enable_tracing(
+                edge_id,
+                edge.source.process.clone(),
+                edge.source.port.clone(),
+                edge.target.process.clone(),
+                edge.target.port.clone(),
+                graph_name.to_owned(),
+                trace_sender.clone(),
+            );
+            edge_sink.set_trace_index(edge.source.index.clone(), edge.target.index.clone());
+        }
+        edge_sink
+    }
+
+    fn trace_edge_connect(
+        &self,
+        edge: &GraphEdge,
+        graph_name: &str,
+        graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
+    ) {
+        let Some(network) = self
+            .networks
+            .get(graph_name)
+            .filter(|network| network.tracing)
+        else {
+            return;
+        };
+        let connect_payload = ApiTraceConnectEventPayload {
+            id: trace_edge_id(
+                &GraphNodeSpecNetwork::from(&edge.source),
+                &GraphNodeSpecNetwork::from(&edge.target),
+            ),
+            src: TraceGraphNodeSpecNetwork {
+                node: edge.source.process.clone(),
+                port: edge.source.port.clone(),
+                index: edge.source.index.clone(),
+            },
+            tgt: TraceGraphNodeSpecNetwork {
+                node: edge.target.process.clone(),
+                port: edge.target.port.clone(),
+                index: edge.target.index.clone(),
+            },
+            graph: graph_name.to_owned(),
+        };
+        network
+            .trace_buffer
+            .lock()
+            .expect("lock poisoned")
+            .record("connect", &connect_payload);
+        send_trace_connect(graph_inout, connect_payload);
+    }
+
+    fn trace_edge_disconnect(
+        &self,
+        source: &GraphNodeSpecNetwork,
+        target: &GraphNodeSpecNetwork,
+        graph_name: &str,
+        graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
+    ) {
+        let Some(network) = self
+            .networks
+            .get(graph_name)
+            .filter(|network| network.tracing)
+        else {
+            return;
+        };
+        let disconnect_payload = ApiTraceDisconnectEventPayload {
+            id: trace_edge_id(source, target),
+            src: TraceGraphNodeSpecNetwork {
+                node: source.node.clone(),
+                port: source.port.clone(),
+                index: source.index.clone(),
+            },
+            tgt: TraceGraphNodeSpecNetwork {
+                node: target.node.clone(),
+                port: target.port.clone(),
+                index: target.index.clone(),
+            },
+            graph: graph_name.to_owned(),
+        };
+        network
+            .trace_buffer
+            .lock()
+            .expect("lock poisoned")
+            .record("disconnect", &disconnect_payload);
+        send_trace_disconnect(graph_inout, disconnect_payload);
+    }
+
+    // hot graph mutation: apply graph:* changes to the running network without restarting it
+
+    /// Validates the graph as it would be run by the network, with subgraphs inlined.
+    fn validate_graph(
+        &self,
+        graph: &Graph,
+        components: &ComponentLibrary,
+    ) -> std::result::Result<(), Vec<GraphValidationError>> {
+        let network_graph = graph
+            .inline_subgraphs(&self.graphs, components)
+            .map_err(|err| vec![GraphValidationError::new(None, None, err.to_string())])?;
+        network_graph.validate(components).map_err(|mut errors| {
+            // editors only know the subgraph node, not the processes inlined from it
+            for error in errors.iter_mut() {
+                let Some((outer, _)) = error.node.as_deref().and_then(|node| node.split_once('/'))
+                else {
+                    continue;
+                };
+                if graph.nodes.contains_key(outer) {
+                    error.node = Some(outer.to_owned());
+                    error.port = None;
+                }
+            }
+            errors
+        })
+    }
+
+    fn is_tracing(&self, graph: &str) -> bool {
+        self.networks
+            .get(graph)
+            .is_some_and(|network| network.tracing)
+    }
+
+    /// True if changes to this graph have to be applied to the running network as well.
+    fn is_running_graph(&self, graph: &str) -> bool {
+        self.networks
+            .get(graph)
+            .is_some_and(|network| network.status.running)
+            && self.schedulers.contains_key(graph)
+    }
+
+    fn live_scheduler(
+        &self,
+        graph: &str,
+    ) -> std::result::Result<Arc<crate::scheduler::Scheduler>, std::io::Error> {
+        let scheduler_finished = self
+            .scheduler_threads
+            .get(graph)
+            .is_none_or(|scheduler_thread| scheduler_thread.is_finished());
+        match self.schedulers.get(graph) {
+            Some(scheduler) if !scheduler_finished => Ok(scheduler.clone()),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                format!(
+                    "network for graph {} is not running or shutting down",
+                    graph
+                ),
+            )),
+        }
+    }
+
+    /// Run a reconnect call on a process, either still in the scheduler or already taken out of it.
+    fn live_with_process<R>(
+        scheduler: &crate::scheduler::Scheduler,
+        retiring: &mut HashMap<String, Box<dyn Component>>,
+        proc_name: &str,
+        reconnect: impl FnOnce(&mut dyn Component) -> std::result::Result<R, std::io::Error>,
+    ) -> std::result::Result<R, std::io::Error> {
+        if let Some(component) = retiring.get_mut(proc_name) {
+            return reconnect(component.as_mut());
+        }
+        scheduler
+            .with_component(proc_name, reconnect)
+            .unwrap_or_else(|| {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!(
+                        "process {} is not running (not started or already finished)",
+                        proc_name
+                    ),
+                ))
+            })
+    }
+
+    /// ADR-015: create the next instance of a process from the one that panicked or finished.
+    /// Its connections are taken over through the reconnect API, dedicated IIPs are sent again.
+    #[allow(clippy::too_many_arguments)]
+    fn node_restarter(
+        graph_name: &str,
+        proc_name: &str,
+        components: &ComponentLibrary,
+        component_name: &str,
+        iips: Vec<(String, String)>, // (PORT, IIP) with their own channel
+        scheduler: &Arc<crate::scheduler::Scheduler>,
+        graph_inout_arc: Arc<Mutex<GraphInportOutportHolder>>,
+    ) -> crate::scheduler::Restarter {
+        let graph_name = graph_name.to_owned();
+        let proc_name = proc_name.to_owned();
+        let component_name = component_name.to_owned();
+        let library = components.subset(&component_name);
+        let port_names = |ports: &Vec<ComponentPort>| {
+            ports
+                .iter()
+                .map(|port| port.name.to_ascii_uppercase())
+                .collect::<Vec<_>>()
+        };
+        let (in_ports, out_ports) = components
+            .find(&component_name)
+            .map(|component| {
+                (
+                    port_names(&component.in_ports),
+                    port_names(&component.out_ports),
+                )
+            })
+            .unwrap_or_default();
+        let scheduler = Arc::downgrade(scheduler);
+        Box::new(move |mut retired, restart| {
+            let mut inports = ProcessInports::new();
+            for port in in_ports.iter() {
+                let previous = retired.reconnect_inport(port, None);
+                let mut port_iips = iips
+                    .iter()
+                    .filter(|(iip_port, _)| iip_port == port)
+                    .peekable();
+                if port_iips.peek().is_some() {
+                    // the previous instance took the IIPs out of their channel already
+                    for (_, iip) in port_iips {
+                        let (mut sink, source) = ProcessEdge::new(PROCESSEDGE_IIP_BUFSIZE);
+                        sink.push(FbpMessage::from_text(iip.clone()))
+                            .expect("failed to send IIP into process channel");
+                        inports.insert(port.clone(), source.into());
+                    }
+                    continue;
+                }
+                match previous {
+                    Ok(Some(source)) => inports.insert(port.clone(), source),
+                    Ok(None) => {}
+                    Err(err) => warn!(
+                        "restart of process {}: cannot take over inport {}: {}",
+                        proc_name, port, err
+                    ),
+                }
+            }
+            let mut outports = ProcessOutports::new();
+            for port in out_ports.iter() {
+                match retired.reconnect_outport(port, None) {
+                    Ok(Some(sink)) => outports.insert(port.clone(), sink),
+                    Ok(None) => {}
+                    Err(err) => warn!(
+                        "restart of process {}: cannot take over outport {}: {}",
+                        proc_name, port, err
+                    ),
+                }
+            }
+            drop(retired);
+
+            let scheduler = scheduler.upgrade()?;
+            let (_signalsink, signalsource) =
+                std::sync::mpsc::sync_channel::<MessageBuf>(PROCESSEDGE_SIGNAL_BUFSIZE);
+            let (watchdog_signalsink, _watchdog_signalsource) =
+                std::sync::mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+            let instance = library.instantiate(
+                &component_name,
+                inports,
+                outports,
+                signalsource,
+                watchdog_signalsink,
+                create_graph_inout_handle(graph_inout_arc.clone()),
+                crate::scheduler::Scheduler::create_waker(&scheduler, proc_name.clone()),
+            );
+            let error = format!(
+                "process {} {}, {} (restart {} of {})",
+                proc_name,
+                if restart.panicked {
+                    "panicked"
+                } else {
+                    "finished"
+                },
+                if instance.is_some() {
+                    format!("restarting in {:?}", restart.backoff)
+                } else {
+                    String::from("restart failed")
+                },
+                restart.restart,
+                restart.max_restarts
+            );
+            warn!("{}", error);
+            broadcast_to_clients(
+                &graph_inout_arc,
+                &NetworkProcesserrorResponse::new(graph_name.clone(), proc_name.clone(), error),
+                "network:processerror",
+            );
+            instance
+        })
+    }
+
+    /// Instantiate a new process and register it with the running scheduler.
+    /// The process starts out without connections, they are handed over by live_add_edge().
+    fn live_add_node(
+        &mut self,
+        graph: &str,
+        proc_name: &str,
+        component_name: &str,
+        components: &ComponentLibrary,
+        graph_inout_arc: Arc<Mutex<GraphInportOutportHolder>>,
     ) -> std::result::Result<(), std::io::Error> {
+        let scheduler = self.live_scheduler(graph)?;
+        if self
+            .graphs
+            .get_subgraph(component_name, components)
+            .is_some()
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "subgraph component {} cannot be added to a running network, restart the network to run process {}",
+                    component_name, proc_name
+                ),
+            ));
+        }
+        let Some(component) = components
+            .available
+            .iter()
+            .find(|component| component.name == component_name)
+        else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("component '{}' not found", component_name),
+            ));
+        };
+        if !component.support_reconnect {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "component {} does not support reconnect, restart the network to run process {}",
+                    component_name, proc_name
+                ),
+            ));
+        }
+        if scheduler.has_node(proc_name) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("process {} already running", proc_name),
+            ));
+        }
+
+        let (_signalsink, signalsource) =
+            std::sync::mpsc::sync_channel::<MessageBuf>(PROCESSEDGE_SIGNAL_BUFSIZE);
+        let (watchdog_signalsink, _watchdog_signalsource) =
+            std::sync::mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+        scheduler.add_node(
+            proc_name.to_owned(),
+            get_component_budget_class(component_name),
+        );
+        let log_context = crate::logging::LogContext::node(graph, proc_name, component_name);
+        scheduler.set_log_context(proc_name, log_context.clone());
+        let _log_context = crate::logging::enter(Arc::new(log_context));
+        let scheduler_waker =
+            crate::scheduler::Scheduler::create_waker(&scheduler, proc_name.to_owned());
+        let Some(component_instance) = components.instantiate(
+            component_name,
+            ProcessInports::new(),
+            ProcessOutports::new(),
+            signalsource,
+            watchdog_signalsink,
+            create_graph_inout_handle(graph_inout_arc.clone()),
+            scheduler_waker,
+        ) else {
+            scheduler.remove_node(proc_name);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("component '{}' not found", component_name),
+            ));
+        };
+        scheduler.add_component(component_instance, proc_name.to_owned());
+        let restart = self.graphs.get_graph(graph).and_then(|graph| {
+            let graph = graph.read().expect("lock poisoned");
+            graph.nodes.get(proc_name)?.metadata.restart.clone()
+        });
+        if let Some(restart) = restart {
+            scheduler.supervise(
+                proc_name,
+                restart.policy(),
+                Self::node_restarter(
+                    graph,
+                    proc_name,
+                    components,
+                    component_name,
+                    Vec::new(), // IIPs of running networks are delivered by live_add_edge()
+                    &scheduler,
+                    graph_inout_arc,
+                ),
+            );
+        }
+        scheduler.signal_ready(proc_name);
+        info!(
+            "added process {} ({}) to running network",
+            proc_name, component_name
+        );
+        Ok(())
+    }
+
+    /// Connect two running processes or deliver an IIP (edge with data) to a running process.
+    fn live_add_edge(
+        &mut self,
+        graph: &str,
+        edge: &GraphEdge,
+        contract: Option<PortContract>, // declared type of the source port, see outport_contract()
+        graph_inout_arc: Arc<Mutex<GraphInportOutportHolder>>,
+    ) -> std::result::Result<(), std::io::Error> {
+        let scheduler = self.live_scheduler(graph)?;
+        let _log_context = crate::logging::enter(Arc::new(crate::logging::LogContext::edge(
+            graph,
+            &trace_edge_id(
+                &GraphNodeSpecNetwork::from(&edge.source),
+                &GraphNodeSpecNetwork::from(&edge.target),
+            ),
+        )));
+        let mut retiring = HashMap::new();
+        let target_port = edge.target.port.to_ascii_uppercase();
+
+        if let Some(iip) = &edge.data {
+            let (mut sink, source) = ProcessEdge::new(PROCESSEDGE_IIP_BUFSIZE);
+            sink.push(FbpMessage::from_text(iip.clone()))
+                .expect("failed to send IIP into process channel");
+            let replaced = Self::live_with_process(
+                &scheduler,
+                &mut retiring,
+                &edge.target.process,
+                |component| component.reconnect_inport(&target_port, Some(source.into())),
+            )?;
+            // a used-up IIP channel may be replaced, but not a connection that still delivers packets
+            if let Some(previous) = replaced {
+                if !(previous.is_abandoned() && previous.is_empty()) {
+                    Self::live_with_process(
+                        &scheduler,
+                        &mut retiring,
+                        &edge.target.process,
+                        |component| component.reconnect_inport(&target_port, Some(previous)),
+                    )?;
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        format!(
+                            "inport {}.{} is already connected, IIP will be sent on next network start",
+                            edge.target.process, edge.target.port
+                        ),
+                    ));
+                }
+            }
+            scheduler.signal_ready(&edge.target.process);
+            info!(
+                "sent IIP to {}.{} in running network",
+                edge.target.process, edge.target.port
+            );
+            return Ok(());
+        }
+
+        let (sink, source) = ProcessEdge::new(PROCESSEDGE_BUFSIZE);
+        let mut source = ProcessEdgeSource::new(source);
+        let mut edge_sink = self.new_edge_sink(&scheduler, sink, &mut source, edge, graph);
+        // ADR-029: like on network start
+        if let Some(contract) = contract {
+            edge_sink.check_contract(Arc::new(contract));
+        }
+        let replaced_inport = Self::live_with_process(
+            &scheduler,
+            &mut retiring,
+            &edge.target.process,
+            |component| component.reconnect_inport(&target_port, Some(source)),
+        )?;
+        if replaced_inport.is_some() {
+            //TODO arrayports: add the connection instead of replacing it
+            warn!(
+                "replaced existing connection on {}.{}, packets left in it are lost",
+                edge.target.process, edge.target.port
+            );
+        }
+        let source_port = edge.source.port.to_ascii_uppercase();
+        if let Err(err) = Self::live_with_process(
+            &scheduler,
+            &mut retiring,
+            &edge.source.process,
+            |component| component.reconnect_outport(&source_port, Some(edge_sink)),
+        ) {
+            // roll back the target side
+            let _ = Self::live_with_process(
+                &scheduler,
+                &mut retiring,
+                &edge.target.process,
+                |component| component.reconnect_inport(&target_port, replaced_inport),
+            );
+            return Err(err);
+        }
+        scheduler.signal_ready(&edge.source.process);
+        scheduler.signal_ready(&edge.target.process);
+        self.trace_edge_connect(edge, graph, &graph_inout_arc);
+        info!(
+            "connected {}.{} -> {}.{} in running network",
+            edge.source.process, edge.source.port, edge.target.process, edge.target.port
+        );
+        Ok(())
+    }
+
+    /// Disconnect two running processes. The packets still in the edge are handed to the
+    /// target process before it is detached (bounded by LIVE_EDGE_DRAIN_TIMEOUT). Waiting for
+    /// that is left to the returned LiveDrain, to be run without holding the runtime lock.
+    fn live_remove_edge(
+        &mut self,
+        graph: &str,
+        source: &GraphNodeSpecNetwork,
+        target: &GraphNodeSpecNetwork,
+        graph_inout_arc: Arc<Mutex<GraphInportOutportHolder>>,
+    ) -> std::result::Result<LiveDrain, std::io::Error> {
+        let scheduler = self.live_scheduler(graph)?;
+        self.network_mut(graph)
+            .edge_metrics
+            .remove(&trace_edge_id(source, target));
+        self.trace_edge_disconnect(source, target, graph, &graph_inout_arc);
+        let graph = graph.to_owned();
+        let source = source.clone();
+        let target = target.clone();
+        Ok(Box::new(move || {
+            let _log_context = crate::logging::enter(Arc::new(crate::logging::LogContext::edge(
+                &graph,
+                &trace_edge_id(&source, &target),
+            )));
+            Self::live_disconnect(&scheduler, &mut HashMap::new(), &source, &target, true)?;
+            info!(
+                "disconnected {}.{} -> {}.{} in running network",
+                source.node, source.port, target.node, target.port
+            );
+            Ok(())
+        }))
+    }
+
+    /// Retire a process from the running network. `edges` are the edges connected to it,
+    /// which were already removed from the graph. Incoming edges are drained into the process
+    /// before it is taken out of the scheduler, then its outgoing edges are drained and detached.
+    /// A node using a subgraph component retires all processes inlined from the subgraph.
+    /// The draining is left to the returned LiveDrain, see live_remove_edge().
+    fn live_remove_node(
+        &mut self,
+        graph: &str,
+        proc_name: &str,
+        component_name: &str,
+        components: &ComponentLibrary,
+        edges: &[GraphRemoveedgeResponsePayload],
+        graph_inout_arc: Arc<Mutex<GraphInportOutportHolder>>,
+    ) -> std::result::Result<LiveDrain, std::io::Error> {
+        let scheduler = self.live_scheduler(graph)?;
+        // edges as connected in the network, which runs subgraphs as processes named <node>/<process>
+        let mut network_edges = Vec::with_capacity(edges.len());
+        for edge in edges.iter() {
+            // IIPs have no source process
+            if edge.src.node.is_empty() {
+                continue;
+            }
+            network_edges.push((
+                self.resolve_live_port(
+                    graph,
+                    &edge.src,
+                    false,
+                    proc_name,
+                    component_name,
+                    components,
+                )?,
+                self.resolve_live_port(
+                    graph,
+                    &edge.tgt,
+                    true,
+                    proc_name,
+                    component_name,
+                    components,
+                )?,
+            ));
+        }
+        for (src, tgt) in network_edges.iter() {
+            self.trace_edge_disconnect(src, tgt, graph, &graph_inout_arc);
+        }
+        let network = self.network_mut(graph);
+        for (src, tgt) in network_edges.iter() {
+            network.edge_metrics.remove(&trace_edge_id(src, tgt));
+        }
+        let proc_name = proc_name.to_owned();
+        Ok(Box::new(move || {
+            let retired = |node: &str| {
+                node == proc_name
+                    || node
+                        .strip_prefix(proc_name.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            };
+            let mut retiring = HashMap::new();
+            // incoming edges first, while the processes are still running and can work off the packets
+            for (src, tgt) in network_edges.iter().filter(|(src, _)| !retired(&src.node)) {
+                Self::live_disconnect(&scheduler, &mut retiring, src, tgt, false)?;
+            }
+            let processes = scheduler.node_ids_matching(|node| retired(node));
+            if processes.is_empty() {
+                debug!("process {} already finished", proc_name);
+            }
+            for process in processes {
+                if let Some(component) = scheduler.remove_node(&process) {
+                    retiring.insert(process, component);
+                }
+            }
+            for (src, tgt) in network_edges.iter().filter(|(src, _)| retired(&src.node)) {
+                if !retired(&tgt.node) {
+                    Self::live_disconnect(&scheduler, &mut retiring, src, tgt, true)?;
+                }
+            }
+            drop(retiring);
+            info!("removed process {} from running network", proc_name);
+            Ok(())
+        }))
+    }
+
+    /// Process and port running the end of an edge, following ports of subgraph nodes into the
+    /// inlined subgraph. `removed` is the node already taken out of the graph, using `component`.
+    fn resolve_live_port(
+        &self,
+        graph: &str,
+        spec: &GraphNodeSpecNetwork,
+        inport: bool,
+        removed: &str,
+        component: &str,
+        components: &ComponentLibrary,
+    ) -> std::result::Result<GraphNodeSpecNetwork, std::io::Error> {
+        let (node, port) = if spec.node == removed {
+            Graph::resolve_component_port(
+                Some(component),
+                &spec.node,
+                &spec.port,
+                inport,
+                "",
+                &self.graphs,
+                components,
+            )?
+        } else {
+            let Some(graph) = self.graphs.get_graph(graph) else {
+                return Ok(spec.clone());
+            };
+            let graph = graph.read().expect("lock poisoned");
+            graph.resolve_port(&spec.node, &spec.port, inport, "", &self.graphs, components)?
+        };
+        Ok(GraphNodeSpecNetwork {
+            node,
+            port,
+            index: spec.index.clone(),
+        })
+    }
+
+    fn live_disconnect(
+        scheduler: &crate::scheduler::Scheduler,
+        retiring: &mut HashMap<String, Box<dyn Component>>,
+        source: &GraphNodeSpecNetwork,
+        target: &GraphNodeSpecNetwork,
+        detach_target: bool,
+    ) -> std::result::Result<(), std::io::Error> {
+        let source_port = source.port.to_ascii_uppercase();
+        let target_port = target.port.to_ascii_uppercase();
+        let sink = match Self::live_with_process(scheduler, retiring, &source.node, |component| {
+            component.reconnect_outport(&source_port, None)
+        }) {
+            Ok(sink) => sink,
+            // a retiring process that cannot hand back its outport is simply dropped, as is a finished one
+            Err(err)
+                if retiring.contains_key(&source.node)
+                    || err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                debug!(
+                    "cannot drain edge from {}.{}: {}",
+                    source.node, source.port, err
+                );
+                None
+            }
+            Err(err) => return Err(err),
+        };
+
+        if let Some(sink) = &sink {
+            scheduler.signal_ready(&target.node);
+            let drain_started = Instant::now();
+            while !sink.is_drained() && !sink.is_abandoned() && scheduler.has_node(&target.node) {
+                if drain_started.elapsed() >= LIVE_EDGE_DRAIN_TIMEOUT {
+                    warn!(
+                        "edge {}.{} -> {}.{} not drained within {:?}, dropping remaining packets",
+                        source.node, source.port, target.node, target.port, LIVE_EDGE_DRAIN_TIMEOUT
+                    );
+                    break;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        if detach_target {
+            match Self::live_with_process(scheduler, retiring, &target.node, |component| {
+                component.reconnect_inport(&target_port, None)
+            }) {
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    debug!("target process {} already finished", target.node);
+                }
+                Err(err) => {
+                    // put the source side back so that the network stays as it was
+                    if let Some(sink) = sink {
+                        let _ = Self::live_with_process(
+                            scheduler,
+                            retiring,
+                            &source.node,
+                            |component| component.reconnect_outport(&source_port, Some(sink)),
+                        );
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn debug_mode(&mut self, graph: &str, mode: bool) -> std::result::Result<(), std::io::Error> {
         if self.graphs.get_graph(graph).is_none() {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -2098,7 +3260,26 @@ impl Runtime {
             ));
         }
         self.graph = graph.to_string();
-        self.status.graph = graph.to_string();
+        let status = &mut self.network_mut(graph).status;
+        status.graph = graph.to_string();
+        status.debug = Some(mode);
+        Ok(())
+    }
+
+    //TODO optimize: better to hand over String or &str? Difference between Vec and vec?
+    fn set_debug_edges(
+        &mut self,
+        graph: &str,
+        edges: &Vec<GraphEdgeSpec>,
+    ) -> std::result::Result<(), std::io::Error> {
+        if self.graphs.get_graph(graph).is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Graph '{}' not found", graph),
+            ));
+        }
+        self.graph = graph.to_string();
+        self.network_mut(graph).status.graph = graph.to_string();
 
         self.debug_edges.insert(graph.to_string(), edges.clone());
         info!(
@@ -2116,47 +3297,60 @@ impl Runtime {
     fn start_trace(
         &mut self,
         graph: &str,
-        _buffer_size: u32,
+        buffer_size: u32,
     ) -> std::result::Result<(), std::io::Error> {
-        // Validate graph parameter matches current graph (single-graph mode)
-        if graph != self.graph {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound,
-                format!("Graph '{}' not found. Current graph is '{}'. Multi-graph support not yet implemented.",
-                    graph, self.graph)));
+        if self.graphs.get_graph(graph).is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Graph '{}' not found", graph),
+            ));
         }
 
-        // TODO: implement actual tracing with buffer_size parameter (FBP spec: size of tracing buffer to keep in bytes)
-        // TODO: check if graph exists and is current graph (multi-graph)
-        if self.tracing {
+        let network = self.network_mut(graph);
+        if network.tracing {
             // wrong state
             return Err(std::io::Error::new(
                 std::io::ErrorKind::AlreadyExists,
                 String::from("tracing already started"),
             ));
         }
-        self.tracing = true;
-        info!("tracing enabled for graph '{}'", graph);
+        // spec: buffer_size is the size of the tracing buffer to keep, in bytes
+        network
+            .trace_buffer
+            .lock()
+            .expect("lock poisoned")
+            .set_capacity(buffer_size as usize);
+        network.tracing = true;
+        info!(
+            "tracing enabled for graph '{}' with buffer size {} bytes",
+            graph,
+            network
+                .trace_buffer
+                .lock()
+                .expect("lock poisoned")
+                .capacity_bytes()
+        );
         Ok(())
     }
 
     fn stop_trace(&mut self, graph: &str) -> std::result::Result<(), std::io::Error> {
-        // Validate graph parameter matches current graph (single-graph mode)
-        if graph != self.graph {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound,
-                format!("Graph '{}' not found. Current graph is '{}'. Multi-graph support not yet implemented.",
-                    graph, self.graph)));
+        if self.graphs.get_graph(graph).is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Graph '{}' not found", graph),
+            ));
         }
 
-        // TODO: implement actual tracing stop
-        // TODO: check if graph exists and is current graph (multi-graph)
-        if !self.tracing {
+        // NOTE: recorded events are kept for trace:dump until trace:clear
+        let network = self.network_mut(graph);
+        if !network.tracing {
             // wrong state
             return Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 String::from("tracing not started"),
             ));
         }
-        self.tracing = false;
+        network.tracing = false;
         info!("tracing disabled for graph '{}'", graph);
         Ok(())
     }
@@ -2164,32 +3358,62 @@ impl Runtime {
     //TODO can this function fail, at all? can the error response be removed?
     //TODO clarify spec: when is clear() allowed? in running state or in stopped state?
     fn clear_trace(&mut self, graph: &str) -> std::result::Result<(), std::io::Error> {
-        // Validate graph parameter matches current graph (single-graph mode)
-        if graph != self.graph {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound,
-                format!("Graph '{}' not found. Current graph is '{}'. Multi-graph support not yet implemented.",
-                    graph, self.graph)));
+        if self.graphs.get_graph(graph).is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Graph '{}' not found", graph),
+            ));
         }
 
-        // TODO: implement actual trace clearing
-        // TODO: check if graph exists and is current graph (multi-graph)
+        self.network_mut(graph)
+            .trace_buffer
+            .lock()
+            .expect("lock poisoned")
+            .clear();
+        info!("trace buffer cleared for graph '{}'", graph);
         Ok(())
     }
 
     //TODO can this function fail, at all? can the error response be removed?
     //TODO clarify spec: when is dump() allowed? in running state or in stopped state?
     fn dump_trace(&mut self, graph: &str) -> std::result::Result<String, std::io::Error> {
-        // Validate graph parameter matches current graph (single-graph mode)
-        if graph != self.graph {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound,
-                format!("Graph '{}' not found. Current graph is '{}'. Multi-graph support not yet implemented.",
-                    graph, self.graph)));
+        if self.graphs.get_graph(graph).is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Graph '{}' not found", graph),
+            ));
         }
 
-        // TODO: implement actual trace dumping
-        // TODO: check if graph exists and is current graph (multi-graph)
-        // TODO: implement Flowtrace format?
-        Ok(String::from("")) //TODO how to indicate "empty"? Does it maybe require at least "[]" or "{}"?
+        let mut graphs = JsonMap::new();
+        if let Some(graph_arc) = self.graphs.get_graph(graph) {
+            let graph_value = serde_json::to_value(&*graph_arc.read().expect("lock poisoned"))
+                .map_err(|err| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "failed to serialize graph '{}' for flowtrace: {}",
+                            graph, err
+                        ),
+                    )
+                })?;
+            graphs.insert(graph.to_owned(), graph_value);
+        }
+        let runtime_info = flowtrace::FlowtraceRuntimeInfo {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            runtime: self.runtime.clone(),
+        };
+        self.network_mut(graph)
+            .trace_buffer
+            .lock()
+            .expect("lock poisoned")
+            .dump(&runtime_info, graphs)
+            .map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("failed to encode flowtrace: {}", err),
+                )
+            })
     }
 
     fn validate_secret(&self, secret: Option<&String>, graph: &str) -> Result<(), std::io::Error> {
@@ -2206,98 +3430,167 @@ impl Runtime {
         // Treat that the same as not providing a secret token.
         let secret = match secret {
             Some(secret) if !secret.is_empty() => secret,
-            _ => return Ok(()),
-        };
-
-        if let Some((expected_secret, access_level)) = self.secrets.get(graph) {
-            if secret != expected_secret {
+            _ if self.strict_auth => {
                 return Err(std::io::Error::new(
                     std::io::ErrorKind::PermissionDenied,
-                    "invalid secret token",
-                ));
+                    "secret token required",
+                ))
             }
-            if *access_level == AccessLevel::ReadOnly && required_access == AccessLevel::ReadWrite {
-                return Err(std::io::Error::new(
+            _ => return Ok(()),
+        };
+
+        if !self.secrets.has_graph(graph) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "no secret configured for graph",
+            ));
+        }
+        match self.secrets.access(secret, graph) {
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "invalid secret token",
+            )),
+            Some(AccessLevel::ReadOnly) if required_access == AccessLevel::ReadWrite => {
+                Err(std::io::Error::new(
                     std::io::ErrorKind::PermissionDenied,
                     "readonly access not sufficient for write operation",
-                ));
+                ))
             }
-        } else {
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Use the given secret tokens; if `strict`, messages without valid secret are rejected
+    pub fn set_secrets(&mut self, secrets: auth::SecretStore, strict: bool) {
+        self.secrets = secrets;
+        self.strict_auth = strict;
+    }
+
+    /// Change the log level of a graph or of one of its processes, returns the level now in effect.
+    /// Nodes need not be in the graph, processes of inlined subgraphs are named differently.
+    fn set_log_level(
+        &self,
+        payload: &RuntimeLoglevelRequestPayload,
+    ) -> std::result::Result<log::LevelFilter, std::io::Error> {
+        if self.graphs.get_graph(&payload.graph).is_none() {
             return Err(std::io::Error::new(
-                std::io::ErrorKind::PermissionDenied,
-                "no secret configured for graph",
+                std::io::ErrorKind::NotFound,
+                format!("graph {} not found", payload.graph),
             ));
         }
-        Ok(())
+        let level = match payload.level.as_deref() {
+            Some(level) => Some(crate::logging::parse_level(level).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown log level {}", level),
+                )
+            })?),
+            None => None,
+        };
+        let effective = crate::logging::set_level(&payload.graph, payload.node.as_deref(), level);
+        info!(
+            "log level of graph {} node {} is now {}",
+            payload.graph,
+            payload.node.as_deref().unwrap_or("*"),
+            effective
+        );
+        Ok(effective)
     }
 
     fn packet(
         payload: &RuntimePacketRequestPayload,
         graph_inout: Arc<std::sync::Mutex<GraphInportOutportHolder>>,
         runtime: Arc<RwLock<Runtime>>,
+        components: Arc<RwLock<ComponentLibrary>>,
     ) -> std::result::Result<(), std::io::Error> {
-        const INPORT_PUSH_GRACE_DUR: Duration = Duration::from_secs(2);
-
         // Implement token-based security: validate secret if provided
-        let runtime_read = runtime.read().expect("lock poisoned");
-        runtime_read.validate_secret(payload.secret.as_ref(), &payload.graph)?;
-
-        //TODO check if graph exists and if that port actually exists
-        //TODO check payload datatype, schema, event (?) etc.
-        //TODO implement and deliver to destination process
+        let graph = {
+            let runtime_read = runtime.read().expect("lock poisoned");
+            runtime_read.validate_secret(payload.secret.as_ref(), &payload.graph)?;
+            runtime_read
+                .graphs
+                .get_graph(&payload.graph)
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("graph {} not found", payload.graph),
+                    )
+                })?
+        };
         info!(
-            "runtime: got a packet for port {}: {:?}",
-            payload.port, payload.payload
-        );
-        let mut packet = FbpMessage::from_str(
-            payload
-                .payload
-                .as_ref()
-                .expect("graph inport runtime:packet is missing payload"),
+            "runtime: got a {:?} packet for port {}: {:?}",
+            payload.event, payload.port, payload.payload
         );
-        let wait_started = Instant::now();
-        loop {
-            let mut graph_inout_locked = graph_inout.lock().expect("lock poisoned");
-            // deliver to destination process
-            if let Some(inports) = graph_inout_locked.inports.as_mut() {
-                if let Some(inport) = inports.get_mut(payload.port.as_str()) {
-                    match inport.push(packet) {
-                        Ok(()) => {
-                            return Ok(());
-                        }
-                        Err(err) => {
-                            packet = match err {
-                                PushError::Full(data) => data,
-                            };
-                        }
-                    }
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        String::from("graph inport with that name not found"),
-                    ));
-                }
-            } else {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    String::from("no graph inports exist"),
-                ));
-            }
-            drop(graph_inout_locked);
 
-            if wait_started.elapsed() >= INPORT_PUSH_GRACE_DUR {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::TimedOut,
-                    format!(
-                        "graph inport {} backpressured for {:?}",
-                        payload.port, INPORT_PUSH_GRACE_DUR
-                    ),
-                ));
+        let packet = match payload.event {
+            RuntimePacketEvent::Data => {
+                let data = payload.payload.as_ref().ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "runtime:packet data event is missing payload",
+                    )
+                })?;
+                let allowed_type = graph_inport_type(&graph, &components, &payload.port)?;
+                // the type sent along only matters if the port takes anything
+                let packet_type = match payload.typ.as_deref() {
+                    Some(typ) if is_any_type(&allowed_type) => typ,
+                    _ => allowed_type.as_str(),
+                };
+                packet_from_json(data, packet_type).map_err(|err| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("payload for graph inport {}: {}", payload.port, err),
+                    )
+                })?
             }
+            RuntimePacketEvent::BeginGroup => match payload.payload.as_ref() {
+                Some(JsonValue::String(group)) => {
+                    FbpMessage::Control(ControlEvent::BeginGroup(group.clone()))
+                }
+                _ => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "runtime:packet begingroup event needs the group name as payload",
+                    ))
+                }
+            },
+            RuntimePacketEvent::EndGroup => match payload.payload.as_ref() {
+                Some(JsonValue::String(group)) => {
+                    FbpMessage::Control(ControlEvent::EndGroup(group.clone()))
+                }
+                None => FbpMessage::Control(ControlEvent::EndGroup(String::new())),
+                Some(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "runtime:packet endgroup event payload must be the group name",
+                    ))
+                }
+            },
+            // graph inport edges stay connected while the network runs, nothing to deliver
+            RuntimePacketEvent::Connect | RuntimePacketEvent::Disconnect => return Ok(()),
+        };
 
-            // sleep some time resp. give up CPU timeslot
-            thread::yield_now();
-        }
+        // deliver to destination process
+        let mut graph_inout_locked = graph_inout.lock().expect("lock poisoned");
+        let Some(inports) = graph_inout_locked.inports.get_mut(&payload.graph) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("network of graph {} is not running", payload.graph),
+            ));
+        };
+        let Some(inport) = inports.get_mut(payload.port.as_str()) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                String::from("graph inport with that name not found"),
+            ));
+        };
+        // under backpressure the client gets an error and may send the packet again later
+        inport.push(packet).map_err(|PushError::Full(_)| {
+            std::io::Error::new(
+                std::io::ErrorKind::WouldBlock,
+                format!("graph inport {} is full, try again later", payload.port),
+            )
+        })
     }
 
     //TODO return path: process that sends to an outport -> send to client. TODO clarify spec: which client should receive it?
@@ -2305,6 +3598,21 @@ impl Runtime {
     //TODO runtime: command to connect an outport to a remote runtime as remote subgraph.
 }
 
+impl GraphNetwork {
+    fn new(graph: &str) -> Self {
+        GraphNetwork {
+            status: RuntimeStatus {
+                time_started: chrono::DateTime::<Utc>::MIN_UTC, // zero value - //TODO rather use None
+                graph: graph.to_owned(),
+                started: false,
+                running: false,
+                debug: None,
+            },
+            ..Default::default()
+        }
+    }
+}
+
 impl Default for RuntimeStatus {
     fn default() -> Self {
         RuntimeStatus {
@@ -2335,14 +3643,19 @@ impl RuntimeStatus {
 pub struct GraphInportOutportHolder {
     // inports
     // the edge sinks are stored here because the connection handler in handle_client() needs to send into these
-    inports: Option<HashMap<String, ProcessEdgeSink>>,
+    // keyed by graph name, then by graph inport name
+    inports: HashMap<String, HashMap<String, ProcessEdgeSink>>,
 
     // outports are handled by 1 special component that needs to be signaled and joined on network stop()
     // sink and wakeup are given to the processes that write into the graph outport process, so they are not stored here
-    outports: Option<BoundaryThread>,
+    // keyed by graph name
+    outports: HashMap<String, BoundaryThread>,
 
     // connected client websockets ready to send responses to connected clients, for graphout process
-    websockets: HashMap<std::net::SocketAddr, Arc<Mutex<tungstenite::WebSocket<TcpStream>>>>,
+    websockets:
+        HashMap<std::net::SocketAddr, Arc<Mutex<tungstenite::WebSocket<tls::ClientStream>>>>,
+    // clients getting the runtime:packet messages from the graph outports, keyed by graph name
+    outport_subscribers: HashMap<String, std::collections::HashSet<std::net::SocketAddr>>,
     // benchmark hook to observe runtime packets without websocket transport
     packet_tap: Option<std::sync::mpsc::SyncSender<RuntimePacketResponsePayload>>,
 }
@@ -2354,12 +3667,62 @@ impl GraphInportOutportHolder {
     ) {
         self.packet_tap = tap;
     }
+
+    /// Send the packets leaving the outports of the given graph to the client
+    pub fn subscribe_outports(&mut self, graph: &str, client: std::net::SocketAddr) {
+        self.outport_subscribers
+            .entry(graph.to_owned())
+            .or_default()
+            .insert(client);
+    }
+
+    /// Forget a disconnected client together with its outport subscriptions
+    pub fn remove_client(&mut self, client: &std::net::SocketAddr) {
+        self.websockets.remove(client);
+        for clients in self.outport_subscribers.values_mut() {
+            clients.remove(client);
+        }
+    }
+
+    /// Keep the outport subscriptions of a renamed graph, drop them for a deleted one (`to` = None)
+    pub fn move_outport_subscriptions(&mut self, from: &str, to: Option<&str>) {
+        if let Some(clients) = self.outport_subscribers.remove(from) {
+            if let Some(to) = to {
+                self.outport_subscribers.insert(to.to_owned(), clients);
+            }
+        }
+    }
 }
 
 pub fn broadcast_to_clients<T: Serialize>(
     graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
     packet: &T,
     packet_name: &str,
+) {
+    broadcast_to_other_clients(graph_inout, None, packet, packet_name);
+}
+
+/// Send to all clients except the one at `except`, which got its own response already
+pub fn broadcast_to_other_clients<T: Serialize>(
+    graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
+    except: Option<std::net::SocketAddr>,
+    packet: &T,
+    packet_name: &str,
+) {
+    send_to_clients(
+        graph_inout,
+        |addr| Some(*addr) != except,
+        packet,
+        packet_name,
+    );
+}
+
+// send to the connected clients selected by `to`, dropping those that cannot be written to
+fn send_to_clients<T: Serialize>(
+    graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
+    to: impl Fn(&std::net::SocketAddr) -> bool,
+    packet: &T,
+    packet_name: &str,
 ) {
     let msg =
         serde_json::to_string(packet).expect("failed to serialize packet for websocket clients");
@@ -2368,6 +3731,7 @@ pub fn broadcast_to_clients<T: Serialize>(
         graph_inout_locked
             .websockets
             .iter()
+            .filter(|(addr, _)| to(addr))
             .map(|(addr, client)| (*addr, Arc::clone(client)))
             .collect::<Vec<_>>()
     };
@@ -2408,23 +3772,169 @@ pub fn broadcast_to_clients<T: Serialize>(
     if !failed_clients.is_empty() {
         let mut graph_inout_locked = graph_inout.lock().expect("lock poisoned");
         for addr in failed_clients {
-            graph_inout_locked.websockets.remove(&addr);
+            graph_inout_locked.remove_client(&addr);
         }
     }
 }
 
+// allowed type of the component inport behind a graph inport, "any" if unknown like for subgraphs
+fn graph_inport_type(
+    graph: &Arc<RwLock<Graph>>,
+    components: &Arc<RwLock<ComponentLibrary>>,
+    port: &str,
+) -> std::result::Result<String, std::io::Error> {
+    let (component, target_port) = {
+        let graph_read = graph.read().expect("lock poisoned");
+        let Some(graph_port) = graph_read.inports.get(port) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("graph has no inport {}", port),
+            ));
+        };
+        let Some(node) = graph_read.nodes.get(&graph_port.process) else {
+            return Ok(String::from("any"));
+        };
+        (node.component.clone(), graph_port.port.clone())
+    };
+    let components_read = components.read().expect("lock poisoned");
+    Ok(components_read
+        .find(&component)
+        .and_then(|component| {
+            component
+                .in_ports
+                .iter()
+                .find(|port| port.name == target_port)
+        })
+        .map(|port| port.allowed_type.clone())
+        .unwrap_or_else(|| String::from("any")))
+}
+
+// map a runtime:packet JSON payload onto a message for a port of the given type;
+// ports taking any type get strings as text and everything else as structured value
+fn packet_from_json(
+    data: &JsonValue,
+    allowed_type: &str,
+) -> std::result::Result<FbpMessage, String> {
+    let as_value = || {
+        serde_json::from_value::<FbpValue>(data.clone())
+            .map(FbpMessage::Value)
+            .map_err(|err| err.to_string())
+    };
+    let matches = match allowed_type.to_ascii_lowercase().as_str() {
+        "string" | "color" | "date" => {
+            return match data {
+                JsonValue::String(text) => Ok(FbpMessage::from_str(text)),
+                _ => Err(format!("expected {}, got {}", allowed_type, data)),
+            }
+        }
+        "buffer" | "bytes" | "stream" => {
+            return match data {
+                JsonValue::String(text) => Ok(FbpMessage::from_bytes(text.as_bytes().to_vec())),
+                JsonValue::Array(_) => serde_json::from_value::<Vec<u8>>(data.clone())
+                    .map(FbpMessage::from_bytes)
+                    .map_err(|err| format!("expected {}: {}", allowed_type, err)),
+                _ => Err(format!("expected {}, got {}", allowed_type, data)),
+            }
+        }
+        "bang" => return Ok(FbpMessage::Value(FbpValue::Null)),
+        "int" | "integer" => data.is_i64(),
+        "number" => data.is_number(),
+        "boolean" => data.is_boolean(),
+        "object" => data.is_object(),
+        "array" => data.is_array(),
+        _ => {
+            return match data {
+                JsonValue::String(text) => Ok(FbpMessage::from_str(text)),
+                _ => as_value(),
+            }
+        }
+    };
+    if !matches {
+        return Err(format!("expected {}, got {}", allowed_type, data));
+    }
+    as_value()
+}
+
+// runtime:packet for a message leaving a graph outport, None if not shown to clients
+fn runtime_packet_from_message(
+    graph: &str,
+    port: &str,
+    message: &FbpMessage,
+) -> Option<RuntimePacketResponse> {
+    let (typ, data) = match message {
+        FbpMessage::Text(text) => ("string", JsonValue::from(&**text)),
+        FbpMessage::Bytes(bytes) => match std::str::from_utf8(bytes) {
+            Ok(text) => ("buffer", JsonValue::from(text)),
+            Err(_) => ("buffer", JsonValue::from(bytes.to_vec())),
+        },
+        FbpMessage::Value(value) => {
+            let typ = match value {
+                FbpValue::Null => "bang",
+                FbpValue::Bool(_) => "boolean",
+                FbpValue::Int(_) => "int",
+                FbpValue::Float(_) => "number",
+                FbpValue::Text(_) => "string",
+                FbpValue::Bytes(_) => "buffer",
+                FbpValue::List(_) => "array",
+                FbpValue::Map(_) => "object",
+            };
+            (typ, serde_json::to_value(value).ok()?)
+        }
+        // the protocol only knows groups, brackets are shown as groups
+        FbpMessage::Control(
+            ControlEvent::BeginGroup(group) | ControlEvent::BeginBracket(group),
+        ) => {
+            return Some(RuntimePacketResponse::new_begingroup(
+                graph.to_owned(),
+                port.to_owned(),
+                None,
+                None,
+                group.clone(),
+            ))
+        }
+        FbpMessage::Control(ControlEvent::EndGroup(group) | ControlEvent::EndBracket(group)) => {
+            return Some(RuntimePacketResponse::new_endgroup(
+                graph.to_owned(),
+                port.to_owned(),
+                None,
+                None,
+                group.clone(),
+            ))
+        }
+        _ => return None,
+    };
+    Some(RuntimePacketResponse::new_data(
+        graph.to_owned(),
+        port.to_owned(),
+        Some(String::from(typ)),
+        None,
+        data,
+    ))
+}
+
 fn send_runtime_packet(
     graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
     packet: &RuntimePacketResponse,
 ) {
-    {
+    let subscribers = {
         let graph_inout_locked = graph_inout.lock().expect("lock poisoned");
         if let Some(tap) = &graph_inout_locked.packet_tap {
             let _ = tap.try_send(packet.payload.clone());
         }
-    }
-    //TODO add capabilities check for each client!
-    broadcast_to_clients(graph_inout, packet, "runtime:packet");
+        match graph_inout_locked
+            .outport_subscribers
+            .get(&packet.payload.graph)
+        {
+            Some(clients) if !clients.is_empty() => clients.clone(),
+            _ => return,
+        }
+    };
+    send_to_clients(
+        graph_inout,
+        |addr| subscribers.contains(addr),
+        packet,
+        "runtime:packet",
+    );
 }
 
 fn send_network_stopped(
@@ -2486,6 +3996,159 @@ fn send_trace_data(
     broadcast_to_clients(graph_inout, &message, "trace:data");
 }
 
+fn trace_edge_id(src: &GraphNodeSpecNetwork, tgt: &GraphNodeSpecNetwork) -> String {
+    format!("{}-{}", trace_port_id(src), trace_port_id(tgt))
+}
+
+// addressable ports are written like in .fbp, as NODE.PORT[INDEX]
+fn trace_port_id(spec: &GraphNodeSpecNetwork) -> String {
+    match &spec.index {
+        Some(index) => format!("{}.{}[{}]", spec.node, spec.port, index),
+        None => format!("{}.{}", spec.node, spec.port),
+    }
+}
+
+// ADR-005: quiesce the scheduler, collect all node states and replace the checkpoint file.
+// The file holds one FbpValue::Map of process name to state in the packet encoding.
+fn write_checkpoint(
+    scheduler: &crate::scheduler::Scheduler,
+    path: &Path,
+    last: bool,
+) -> std::result::Result<usize, std::io::Error> {
+    let Some(node_states) = scheduler.checkpoint(last) else {
+        return Ok(0);
+    };
+    if node_states.is_empty() {
+        // no stateful components, nothing to restore - also not a checkpoint of an earlier run
+        match std::fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => return Ok(0),
+        }
+    }
+    let count = node_states.len();
+    let encoded = FbpMessage::Value(FbpValue::Map(Arc::new(node_states))).encode()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // write and rename, so a crash never leaves a half-written checkpoint behind
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{:?}.tmp", thread::current().id()));
+    std::fs::write(&tmp_path, encoded)?;
+    std::fs::rename(&tmp_path, path)?;
+    debug!(
+        "checkpoint of {} node states saved to {}",
+        count,
+        path.display()
+    );
+    Ok(count)
+}
+
+// node states of the last checkpoint, empty if there is none
+fn read_checkpoint(path: &Path) -> std::result::Result<HashMap<String, FbpValue>, std::io::Error> {
+    let encoded = match std::fs::read(path) {
+        Ok(encoded) => encoded,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    match FbpMessage::decode(&encoded)? {
+        FbpMessage::Value(FbpValue::Map(node_states)) => Ok(Arc::unwrap_or_clone(node_states)),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("checkpoint {} holds no node states", path.display()),
+        )),
+    }
+}
+
+// keep graph and edge names from escaping the journal directory
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .enumerate()
+        .map(|(i, c)| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '[' | ']' => c,
+            '.' if i > 0 => c, // no "." or ".." path components
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Parses the connection index of an edge end on an addressable port.
+fn connection_index(spec: &GraphNodeSpec) -> std::result::Result<Option<usize>, std::io::Error> {
+    spec.index
+        .as_ref()
+        .map(|index| {
+            index.parse::<usize>().map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "invalid connection index {} on port {}.{}",
+                        index, spec.process, spec.port
+                    ),
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Builds the port map handed to a process. On addressable ports the position of a connection in
+/// the Vec of its port is its index: connections without index take the lowest free positions and
+/// positions left free in between get a disconnected edge, so that sparse indices keep their place.
+fn index_ports<T>(
+    connections: Vec<(String, Option<usize>, T)>,
+    disconnected: fn() -> T,
+) -> std::result::Result<MultiMap<String, T>, std::io::Error> {
+    let mut slots: HashMap<String, Vec<Option<T>>> = HashMap::new();
+    let mut unindexed: Vec<(String, T)> = Vec::new();
+    for (port, index, connection) in connections {
+        let Some(index) = index else {
+            unindexed.push((port, connection));
+            continue;
+        };
+        if index > ADDRESSABLE_PORT_MAX_INDEX {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "port {}[{}] exceeds the maximum index {}",
+                    port, index, ADDRESSABLE_PORT_MAX_INDEX
+                ),
+            ));
+        }
+        let port_slots = slots.entry(port.clone()).or_default();
+        if port_slots.len() <= index {
+            port_slots.resize_with(index + 1, || None);
+        }
+        if port_slots[index].replace(connection).is_some() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("port {}[{}] is connected more than once", port, index),
+            ));
+        }
+    }
+    for (port, connection) in unindexed {
+        let port_slots = slots.entry(port).or_default();
+        match port_slots.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => *slot = Some(connection),
+            None => port_slots.push(Some(connection)),
+        }
+    }
+    let mut ports = MultiMap::new();
+    for (port, port_slots) in slots {
+        for slot in port_slots {
+            ports.insert(port.clone(), slot.unwrap_or_else(disconnected));
+        }
+    }
+    Ok(ports)
+}
+
+// fills an unused index of an addressable inport, reports is_abandoned() from the start
+fn disconnected_inport() -> ProcessEdgeSource {
+    ProcessEdge::new(1).1.into()
+}
+
+// fills an unused index of an addressable outport, reports is_abandoned() from the start
+fn disconnected_outport() -> ProcessEdgeSink {
+    ProcessEdgeSink::new(ProcessEdge::new(1).0, None, None, None)
+}
+
 fn send_trace_connect(
     graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
     payload: ApiTraceConnectEventPayload,
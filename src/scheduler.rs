@@ -4,15 +4,31 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
 
-use flowd_component_api::{BudgetClass, Component, NodeContext, ProcessResult};
+use flowd_component_api::{BudgetClass, Component, FbpValue, NodeContext, ProcessResult};
+use serde::{Deserialize, Serialize};
+
+use crate::logging::LogContext;
 
 #[derive(Debug, Clone)]
 pub struct SchedulerMetrics {
     pub executions_per_node: HashMap<String, u64>,
     pub work_units_per_node: HashMap<String, u64>,
     pub time_since_last_execution: HashMap<String, std::time::Duration>,
+    pub panics_per_node: HashMap<String, u64>,
+    pub finished_per_node: HashMap<String, u64>, // transitions to Finished, panics included
+    pub restarts_per_node: HashMap<String, u64>,
     pub queue_depth: usize,
     pub loop_iterations: u64,
+    pub workers: Vec<WorkerMetrics>,
+}
+
+/// Per-worker counters, one entry per scheduler worker thread.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerMetrics {
+    pub executions: u64,
+    pub work_units: u64,
+    pub busy_time: Duration,
+    pub utilisation: f64, // share of wall time since run() started spent inside process() calls
 }
 
 #[derive(Debug)]
@@ -22,11 +38,91 @@ struct SchedulerState {
     ready_flags: HashMap<String, std::sync::Arc<AtomicBool>>,
     ready_queue: VecDeque<String>,
     ready_set: HashSet<String>,
+    in_flight: HashSet<String>,  // nodes currently executed by some worker
+    deferred: HashSet<String>,   // nodes that became ready while in flight, requeued on completion
+    paused: bool,                // checkpoint in progress: workers start no new process() calls
+    last_checkpoint_taken: bool, // network is shutting down, later checkpoints would be incomplete
+    started_at: Option<Instant>,
     timers: BinaryHeap<TimerWake>,
     timer_latest_by_node: HashMap<String, Instant>,
+    log_contexts: HashMap<String, Arc<LogContext>>, // tags of the records logged in process()
+    supervisions: HashMap<String, Supervision>,
+    finish_hooks: HashMap<String, Vec<PendingFinishHook>>, // run once the node is retired
+    processed_hooks: HashMap<String, Vec<NodeProcessedHook>>, // run after each process() call
     metrics: SchedulerMetrics,
 }
 
+/// ADR-015: which ends of a node are followed by a restart
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    OnPanic,
+    Always, // also when process() returns Finished
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    pub max_restarts: u32,
+    pub backoff: Duration, // before the first restart, doubled for each further one
+}
+
+/// Why a node is restarted, handed to its Restarter
+#[derive(Clone, Debug)]
+pub struct NodeRestart {
+    pub node_id: String,
+    pub panicked: bool,
+    pub restart: u32, // 1 for the first restart
+    pub max_restarts: u32,
+    pub backoff: Duration,
+}
+
+/// Creates the next instance of a node from the instance that ended, which hands over its
+/// connections. Returns None if that fails, the node is then retired. A panic counts as
+/// failure as well and ends the supervision of the node.
+pub type Restarter =
+    Box<dyn FnMut(Box<dyn Component>, &NodeRestart) -> Option<Box<dyn Component>> + Send>;
+
+struct Supervision {
+    policy: RestartPolicy,
+    restarts: u32,
+    restarter: Option<Restarter>, // None while a worker is restarting the node
+}
+
+impl std::fmt::Debug for Supervision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Supervision")
+            .field("policy", &self.policy)
+            .field("restarts", &self.restarts)
+            .finish()
+    }
+}
+
+/// Runs once a node finished for good, after all process() calls of it returned
+pub type FinishHook = Box<dyn FnOnce() + Send>;
+
+struct PendingFinishHook(FinishHook);
+
+/// Runs after every process() call of a node which returned, on the worker which made the call
+pub type ProcessedHook = Arc<dyn Fn() + Send + Sync>;
+
+#[derive(Clone)]
+struct NodeProcessedHook(ProcessedHook);
+
+impl std::fmt::Debug for NodeProcessedHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NodeProcessedHook")
+    }
+}
+
+impl std::fmt::Debug for PendingFinishHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PendingFinishHook")
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct TimerWake {
     when: Instant,
@@ -85,8 +181,15 @@ pub struct Scheduler {
     state: Mutex<SchedulerState>,
     condvar: Condvar,
     running: AtomicBool,
+    worker_count: usize,
 }
 
+pub const SCHEDULER_WORKERS_DEFAULT: usize = 1;
+const IN_FLIGHT_POLL_DUR: Duration = Duration::from_micros(200);
+// a worker that died in process() never clears its node from in_flight
+const CHECKPOINT_QUIESCE_TIMEOUT: Duration = Duration::from_secs(1);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
 #[cfg(feature = "enforce-process-non-blocking-contract")]
 const PROCESS_CALL_MAX_BLOCKING: Duration = Duration::from_millis(10);
 
@@ -111,6 +214,13 @@ fn enforce_non_blocking_process_contract(node_id: &str, elapsed: Duration) {
 
 impl Scheduler {
     pub fn new() -> Self {
+        Self::with_workers(SCHEDULER_WORKERS_DEFAULT)
+    }
+
+    /// Create a scheduler whose run() drives the ready queue from `worker_count` threads.
+    /// A node is never executed by more than one worker at a time.
+    pub fn with_workers(worker_count: usize) -> Self {
+        let worker_count = worker_count.max(1);
         Scheduler {
             state: Mutex::new(SchedulerState {
                 nodes: HashMap::new(),
@@ -118,21 +228,39 @@ impl Scheduler {
                 ready_flags: HashMap::new(),
                 ready_queue: VecDeque::new(),
                 ready_set: HashSet::new(),
+                in_flight: HashSet::new(),
+                deferred: HashSet::new(),
+                paused: false,
+                last_checkpoint_taken: false,
+                started_at: None,
                 timers: BinaryHeap::new(),
                 timer_latest_by_node: HashMap::new(),
+                log_contexts: HashMap::new(),
+                supervisions: HashMap::new(),
+                finish_hooks: HashMap::new(),
+                processed_hooks: HashMap::new(),
                 metrics: SchedulerMetrics {
                     executions_per_node: HashMap::new(),
                     work_units_per_node: HashMap::new(),
                     time_since_last_execution: HashMap::new(),
+                    panics_per_node: HashMap::new(),
+                    finished_per_node: HashMap::new(),
+                    restarts_per_node: HashMap::new(),
                     queue_depth: 0,
                     loop_iterations: 0,
+                    workers: vec![WorkerMetrics::default(); worker_count],
                 },
             }),
             condvar: Condvar::new(),
             running: AtomicBool::new(true),
+            worker_count,
         }
     }
 
+    pub fn worker_count(&self) -> usize {
+        self.worker_count
+    }
+
     pub fn add_node(&self, node_id: String, budget_class: BudgetClass) {
         let mut state = self.state.lock().expect("scheduler state lock poisoned");
         let ready_signal = std::sync::Arc::new(AtomicBool::new(false));
@@ -142,12 +270,58 @@ impl Scheduler {
         state.nodes.insert(node_id.clone(), context);
         state.metrics.executions_per_node.insert(node_id.clone(), 0);
         state.metrics.work_units_per_node.insert(node_id.clone(), 0);
+        state.metrics.panics_per_node.insert(node_id.clone(), 0);
+        state.metrics.finished_per_node.insert(node_id.clone(), 0);
+        state.metrics.restarts_per_node.insert(node_id.clone(), 0);
         state
             .metrics
             .time_since_last_execution
             .insert(node_id, std::time::Duration::ZERO);
     }
 
+    /// Tag the log records written while the node is executed
+    pub fn set_log_context(&self, node_id: &str, context: LogContext) {
+        let mut state = self.state.lock().expect("scheduler state lock poisoned");
+        state
+            .log_contexts
+            .insert(node_id.to_owned(), Arc::new(context));
+    }
+
+    /// Restart the node according to `policy` when it panics or finishes (ADR-015)
+    pub fn supervise(&self, node_id: &str, policy: RestartPolicy, restarter: Restarter) {
+        let mut state = self.state.lock().expect("scheduler state lock poisoned");
+        state.supervisions.insert(
+            node_id.to_owned(),
+            Supervision {
+                policy,
+                restarts: 0,
+                restarter: Some(restarter),
+            },
+        );
+    }
+
+    /// Run `hook` once the node finished and is not restarted anymore.
+    /// Not run if the node is removed or the scheduler stops before.
+    pub fn on_finish(&self, node_id: &str, hook: FinishHook) {
+        let mut state = self.state.lock().expect("scheduler state lock poisoned");
+        state
+            .finish_hooks
+            .entry(node_id.to_owned())
+            .or_default()
+            .push(PendingFinishHook(hook));
+    }
+
+    /// Run `hook` after each process() call of the node, before it can be executed again.
+    /// Dropped with the node once it finished or is removed.
+    pub fn on_processed(&self, node_id: &str, hook: ProcessedHook) {
+        let mut state = self.state.lock().expect("scheduler state lock poisoned");
+        state
+            .processed_hooks
+            .entry(node_id.to_owned())
+            .or_default()
+            .push(NodeProcessedHook(hook));
+    }
+
     pub fn add_component(&self, component: Box<dyn Component>, node_id: String) {
         let mut state = self.state.lock().expect("scheduler state lock poisoned");
         state
@@ -155,6 +329,113 @@ impl Scheduler {
             .insert(node_id, ScheduledComponent::new(component));
     }
 
+    /// Run `f` on the component of a node, waiting until no worker is executing it.
+    /// Returns None if the node is unknown or was already retired.
+    pub fn with_component<R>(
+        &self,
+        node_id: &str,
+        f: impl FnOnce(&mut dyn Component) -> R,
+    ) -> Option<R> {
+        let mut state = self.wait_not_in_flight(node_id);
+        let component = state.components.get_mut(node_id)?;
+        Some(f(component.instance.as_mut()))
+    }
+
+    /// Unregister a node and hand back its component, waiting until no worker is executing it.
+    /// The scheduler keeps running even if this was the last node (the network may get new ones).
+    pub fn remove_node(&self, node_id: &str) -> Option<Box<dyn Component>> {
+        let mut state = self.wait_not_in_flight(node_id);
+        state.nodes.remove(node_id);
+        state.ready_flags.remove(node_id);
+        state.deferred.remove(node_id);
+        state.timer_latest_by_node.remove(node_id);
+        state.log_contexts.remove(node_id);
+        state.supervisions.remove(node_id);
+        state.finish_hooks.remove(node_id);
+        state.processed_hooks.remove(node_id);
+        if state.ready_set.remove(node_id) {
+            state.ready_queue.retain(|queued| queued != node_id);
+            state.metrics.queue_depth = state.ready_queue.len();
+        }
+        state.metrics.executions_per_node.remove(node_id);
+        state.metrics.work_units_per_node.remove(node_id);
+        state.metrics.time_since_last_execution.remove(node_id);
+        state.metrics.panics_per_node.remove(node_id);
+        state.metrics.finished_per_node.remove(node_id);
+        state.metrics.restarts_per_node.remove(node_id);
+        state
+            .components
+            .remove(node_id)
+            .map(|component| component.instance)
+    }
+
+    /// Drop all components, and with them their connections, once the scheduler has stopped.
+    /// Metrics stay available for the final network status.
+    pub fn release_components(&self) {
+        let mut state = self.state.lock().expect("scheduler state lock poisoned");
+        state.components.clear();
+        state.supervisions.clear();
+        state.finish_hooks.clear();
+        state.processed_hooks.clear();
+    }
+
+    /// Quiesce the scheduler and collect the state of all components which have any (ADR-005).
+    /// Workers finish their current process() call, then wait until all snapshots are taken,
+    /// so the snapshots form a consistent checkpoint across the whole network.
+    /// `last` marks the checkpoint taken before shutdown; any later call returns None, because
+    /// components are already finishing then. Also returns None if the workers do not quiesce.
+    pub fn checkpoint(&self, last: bool) -> Option<HashMap<String, FbpValue>> {
+        let mut state = self.state.lock().expect("scheduler state lock poisoned");
+        if state.last_checkpoint_taken {
+            return None;
+        }
+        state.last_checkpoint_taken = last;
+        state.paused = true;
+        let quiesce_started = Instant::now();
+        while !state.in_flight.is_empty() {
+            if quiesce_started.elapsed() > CHECKPOINT_QUIESCE_TIMEOUT {
+                state.paused = false;
+                self.condvar.notify_all();
+                return None;
+            }
+            drop(state);
+            // process() calls are short by contract, so polling is fine here
+            std::thread::sleep(IN_FLIGHT_POLL_DUR);
+            state = self.state.lock().expect("scheduler state lock poisoned");
+        }
+        let snapshots = state
+            .components
+            .iter()
+            .filter_map(|(node_id, component)| {
+                component
+                    .instance
+                    .snapshot()
+                    .map(|snapshot| (node_id.clone(), snapshot))
+            })
+            .collect();
+        state.paused = false;
+        self.condvar.notify_all();
+        Some(snapshots)
+    }
+
+    /// True while the node is registered and not yet retired (finished nodes are retired).
+    pub fn has_node(&self, node_id: &str) -> bool {
+        let state = self.state.lock().expect("scheduler state lock poisoned");
+        state.nodes.contains_key(node_id) || state.in_flight.contains(node_id)
+    }
+
+    fn wait_not_in_flight(&self, node_id: &str) -> std::sync::MutexGuard<'_, SchedulerState> {
+        loop {
+            let state = self.state.lock().expect("scheduler state lock poisoned");
+            if !state.in_flight.contains(node_id) {
+                return state;
+            }
+            drop(state);
+            // process() calls are short by contract, so polling is fine here
+            std::thread::sleep(IN_FLIGHT_POLL_DUR);
+        }
+    }
+
     pub fn create_waker(
         scheduler_arc: &Arc<Self>,
         node_id: String,
@@ -209,6 +490,22 @@ impl Scheduler {
         state.nodes.keys().cloned().collect()
     }
 
+    /// Ids of the nodes for which `predicate` is true, including the ones executing right now.
+    pub fn node_ids_matching(&self, predicate: impl Fn(&str) -> bool) -> Vec<String> {
+        let state = self.state.lock().expect("scheduler state lock poisoned");
+        // a node being restarted is in flight and in the nodes at the same time
+        let mut node_ids: Vec<String> = state
+            .nodes
+            .keys()
+            .chain(state.in_flight.iter())
+            .filter(|node_id| predicate(node_id))
+            .cloned()
+            .collect();
+        node_ids.sort();
+        node_ids.dedup();
+        node_ids
+    }
+
     pub fn metrics_snapshot(&self) -> SchedulerMetrics {
         let mut state = self.state.lock().expect("scheduler state lock poisoned");
         let now = Instant::now();
@@ -217,16 +514,56 @@ impl Scheduler {
             time_since.insert(node_id.clone(), now.duration_since(context.last_execution));
         }
         state.metrics.time_since_last_execution = time_since;
+        let wall_time = state
+            .started_at
+            .map(|started_at| now.duration_since(started_at))
+            .unwrap_or_default();
+        for worker in state.metrics.workers.iter_mut() {
+            worker.utilisation = if wall_time.is_zero() {
+                0.0
+            } else {
+                (worker.busy_time.as_secs_f64() / wall_time.as_secs_f64()).min(1.0)
+            };
+        }
         state.metrics.clone()
     }
 
+    /// Run the scheduler until stopped or until all nodes finished.
+    /// Worker 0 runs on the calling thread, additional workers on scoped threads,
+    /// so this returns only after every worker has exited.
     pub fn run(&self) {
+        {
+            let mut state = self.state.lock().expect("scheduler state lock poisoned");
+            state.started_at.get_or_insert_with(Instant::now);
+        }
+        if self.worker_count == 1 {
+            self.run_worker(0);
+            return;
+        }
+        std::thread::scope(|scope| {
+            for worker_id in 1..self.worker_count {
+                let current_name = std::thread::current()
+                    .name()
+                    .unwrap_or("scheduler")
+                    .to_owned();
+                std::thread::Builder::new()
+                    .name(format!("{}-worker-{}", current_name, worker_id))
+                    .spawn_scoped(scope, move || self.run_worker(worker_id))
+                    .expect("failed to spawn scheduler worker thread");
+            }
+            self.run_worker(0);
+        });
+    }
+
+    fn run_worker(&self, worker_id: usize) {
         loop {
-            let (node_id, mut component, mut context) = {
+            let (node_id, mut component, mut context, log_context) = {
                 let mut state = self.state.lock().expect("scheduler state lock poisoned");
                 loop {
                     Self::drain_expired_timers(&mut state);
-                    if !self.running.load(Ordering::Acquire) || !state.ready_queue.is_empty() {
+                    if !self.running.load(Ordering::Acquire)
+                        || (!state.paused && !state.ready_queue.is_empty())
+                    {
                         break;
                     }
 
@@ -260,6 +597,13 @@ impl Scheduler {
                 state.metrics.queue_depth = state.ready_queue.len();
                 state.metrics.loop_iterations += 1;
 
+                // Another worker is executing this node: never run process() concurrently
+                // with itself, but remember the wakeup so it is requeued on completion.
+                if state.in_flight.contains(&node_id) {
+                    state.deferred.insert(node_id);
+                    continue;
+                }
+
                 let Some(component) = state.components.remove(&node_id) else {
                     continue;
                 };
@@ -267,12 +611,39 @@ impl Scheduler {
                     state.components.insert(node_id, component);
                     continue;
                 };
+                state.in_flight.insert(node_id.clone());
+                let log_context = state.log_contexts.get(&node_id).cloned();
 
-                (node_id, component, context)
+                (node_id, component, context, log_context)
             };
 
+            let execution_started = Instant::now();
+            let log_context_guard = log_context.map(crate::logging::enter);
             let outcome = Self::execute_component(&mut component, &mut context);
+            drop(log_context_guard);
+            let busy_time = execution_started.elapsed();
             let mut state = self.state.lock().expect("scheduler state lock poisoned");
+            let processed_hooks = if outcome.panicked {
+                None
+            } else {
+                state.processed_hooks.get(&node_id).cloned()
+            };
+            if let Some(processed_hooks) = processed_hooks {
+                // still in flight, hooks may do I/O
+                drop(state);
+                for hook in processed_hooks {
+                    (hook.0)();
+                }
+                state = self.state.lock().expect("scheduler state lock poisoned");
+            }
+            state.in_flight.remove(&node_id);
+            let deferred = state.deferred.remove(&node_id);
+
+            if let Some(worker) = state.metrics.workers.get_mut(worker_id) {
+                worker.executions += outcome.executions;
+                worker.work_units += outcome.work_units;
+                worker.busy_time += busy_time;
+            }
 
             if let Some(executions) = state.metrics.executions_per_node.get_mut(&node_id) {
                 *executions += outcome.executions;
@@ -281,7 +652,70 @@ impl Scheduler {
                 *work_units += outcome.work_units;
             }
 
+            if outcome.panicked {
+                *state
+                    .metrics
+                    .panics_per_node
+                    .entry(node_id.clone())
+                    .or_default() += 1;
+            }
             if outcome.finished {
+                *state
+                    .metrics
+                    .finished_per_node
+                    .entry(node_id.clone())
+                    .or_default() += 1;
+
+                if let Some((restart, mut restarter)) =
+                    self.restart_due(&mut state, &node_id, outcome.panicked)
+                {
+                    // stays in flight, so that remove_node() waits for the new instance;
+                    // the context is needed by create_waker() while instantiating
+                    state.in_flight.insert(node_id.clone());
+                    state.nodes.insert(node_id.clone(), context);
+                    drop(state);
+                    let instance = component.instance;
+                    let replacement =
+                        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            restarter(instance, &restart)
+                        }));
+                    state = self.state.lock().expect("scheduler state lock poisoned");
+                    state.in_flight.remove(&node_id);
+                    let replacement = match replacement {
+                        Ok(replacement) => {
+                            if let Some(supervision) = state.supervisions.get_mut(&node_id) {
+                                supervision.restarter = Some(restarter);
+                            }
+                            replacement
+                        }
+                        Err(_) => {
+                            // a failed restart, and the restarter is not trusted with another one
+                            error!("restarter of node {} panicked, retiring the node", node_id);
+                            *state
+                                .metrics
+                                .panics_per_node
+                                .entry(node_id.clone())
+                                .or_default() += 1;
+                            None
+                        }
+                    };
+                    if let Some(instance) = replacement {
+                        *state
+                            .metrics
+                            .restarts_per_node
+                            .entry(node_id.clone())
+                            .or_default() += 1;
+                        state
+                            .components
+                            .insert(node_id.clone(), ScheduledComponent::new(instance));
+                        let when = Instant::now() + restart.backoff;
+                        state.timer_latest_by_node.insert(node_id.clone(), when);
+                        state.timers.push(TimerWake { when, node_id });
+                        continue;
+                    }
+                    state.nodes.remove(&node_id);
+                }
+
                 // Retire finished nodes permanently: they should not be polled again.
                 state.ready_flags.remove(&node_id);
                 state.timer_latest_by_node.remove(&node_id);
@@ -289,9 +723,23 @@ impl Scheduler {
                 state.ready_queue.retain(|queued| queued != &node_id);
                 state.metrics.queue_depth = state.ready_queue.len();
                 state.metrics.time_since_last_execution.remove(&node_id);
+                state.processed_hooks.remove(&node_id);
+                if let Some(finish_hooks) = state.finish_hooks.remove(&node_id) {
+                    // hooks may do I/O, and they have to be done before the scheduler stops
+                    state.in_flight.insert(node_id.clone());
+                    drop(state);
+                    for hook in finish_hooks {
+                        (hook.0)();
+                    }
+                    state = self.state.lock().expect("scheduler state lock poisoned");
+                    state.in_flight.remove(&node_id);
+                }
 
-                // All nodes in this scheduler finished: allow the scheduler thread to exit.
-                if state.nodes.is_empty() && state.components.is_empty() {
+                // All nodes in this scheduler finished: allow the scheduler workers to exit.
+                if state.nodes.is_empty()
+                    && state.components.is_empty()
+                    && state.in_flight.is_empty()
+                {
                     self.running.store(false, Ordering::Release);
                     self.condvar.notify_all();
                 }
@@ -309,7 +757,7 @@ impl Scheduler {
             state.components.insert(node_id.clone(), component);
             state.nodes.insert(node_id.clone(), context);
 
-            let should_requeue = !outcome.finished && (outcome.did_work || ready_flag);
+            let should_requeue = !outcome.finished && (outcome.did_work || ready_flag || deferred);
             if should_requeue && state.ready_set.insert(node_id.clone()) {
                 state.ready_queue.push_back(node_id);
                 state.metrics.queue_depth = state.ready_queue.len();
@@ -318,6 +766,51 @@ impl Scheduler {
         }
     }
 
+    // restart policy of the node says to restart it and the restarter is not in use
+    fn restart_due(
+        &self,
+        state: &mut SchedulerState,
+        node_id: &str,
+        panicked: bool,
+    ) -> Option<(NodeRestart, Restarter)> {
+        if !self.running.load(Ordering::Acquire) {
+            return None; // network is stopping
+        }
+        let supervision = state.supervisions.get_mut(node_id)?;
+        let wanted = match supervision.policy.mode {
+            RestartMode::Never => false,
+            RestartMode::OnPanic => panicked,
+            RestartMode::Always => true,
+        };
+        if !wanted {
+            return None;
+        }
+        if supervision.restarts >= supervision.policy.max_restarts {
+            warn!(
+                "node {} was restarted {} times already, retiring it",
+                node_id, supervision.restarts
+            );
+            return None;
+        }
+        let restarter = supervision.restarter.take()?;
+        let backoff = supervision
+            .policy
+            .backoff
+            .saturating_mul(1 << supervision.restarts.min(16))
+            .min(RESTART_BACKOFF_MAX);
+        supervision.restarts += 1;
+        Some((
+            NodeRestart {
+                node_id: node_id.to_owned(),
+                panicked,
+                restart: supervision.restarts,
+                max_restarts: supervision.policy.max_restarts,
+                backoff,
+            },
+            restarter,
+        ))
+    }
+
     fn execute_component(
         component: &mut ScheduledComponent,
         context: &mut NodeContext,
@@ -382,6 +875,11 @@ impl Scheduler {
         outcome
     }
 
+    /// False once stopped or once all nodes have finished.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
     pub fn stop(&self) {
         self.running.store(false, Ordering::Release);
         self.condvar.notify_all();
@@ -543,6 +1041,143 @@ mod tests {
         assert_eq!(context.work_units_processed, BudgetClass::Heavy as u64);
     }
 
+    struct FinishingComponent;
+
+    impl Component for FinishingComponent {
+        fn new(
+            _inports: flowd_component_api::ProcessInports,
+            _outports: flowd_component_api::ProcessOutports,
+            _signals_in: flowd_component_api::ProcessSignalSource,
+            _signals_out: flowd_component_api::ProcessSignalSink,
+            _graph_inout: flowd_component_api::GraphInportOutportHandle,
+            _scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
+        ) -> Self {
+            Self
+        }
+
+        fn process(&mut self, _context: &mut NodeContext) -> ProcessResult {
+            ProcessResult::Finished
+        }
+
+        fn get_metadata() -> flowd_component_api::ComponentComponentPayload {
+            flowd_component_api::ComponentComponentPayload::default()
+        }
+    }
+
+    #[test]
+    fn finished_node_is_restarted_until_max_restarts() {
+        let scheduler = Arc::new(Scheduler::new());
+        let restarts = Arc::new(Mutex::new(Vec::new()));
+        scheduler.add_node("node".to_string(), BudgetClass::Normal);
+        scheduler.add_component(Box::new(FinishingComponent), "node".to_string());
+        let seen = restarts.clone();
+        scheduler.supervise(
+            "node",
+            RestartPolicy {
+                mode: RestartMode::Always,
+                max_restarts: 2,
+                backoff: Duration::from_millis(5),
+            },
+            Box::new(move |_retired, restart| {
+                seen.lock().expect("lock poisoned").push((
+                    restart.restart,
+                    restart.backoff,
+                    restart.panicked,
+                ));
+                Some(Box::new(FinishingComponent))
+            }),
+        );
+        // runs once, after the last instance finished
+        let finish_runs = Arc::new(Mutex::new(Vec::new()));
+        let (finished, restarted) = (finish_runs.clone(), restarts.clone());
+        scheduler.on_finish(
+            "node",
+            Box::new(move || {
+                let restarted = restarted.lock().expect("lock poisoned").len();
+                finished.lock().expect("lock poisoned").push(restarted);
+            }),
+        );
+        scheduler.signal_ready("node");
+
+        // the scheduler exits once the node is retired for good
+        let runner = scheduler.clone();
+        let started = Instant::now();
+        std::thread::spawn(move || runner.run())
+            .join()
+            .expect("scheduler thread panicked");
+        assert!(started.elapsed() >= Duration::from_millis(15));
+        assert_eq!(
+            *restarts.lock().expect("lock poisoned"),
+            [
+                (1, Duration::from_millis(5), false),
+                (2, Duration::from_millis(10), false)
+            ]
+        );
+        let metrics = scheduler.metrics_snapshot();
+        assert_eq!(metrics.restarts_per_node["node"], 2);
+        assert_eq!(metrics.finished_per_node["node"], 3);
+        assert_eq!(*finish_runs.lock().expect("lock poisoned"), [2]);
+    }
+
+    #[test]
+    fn restart_policy_on_panic_does_not_restart_finished_node() {
+        let scheduler = Arc::new(Scheduler::new());
+        scheduler.add_node("node".to_string(), BudgetClass::Normal);
+        scheduler.add_component(Box::new(FinishingComponent), "node".to_string());
+        scheduler.supervise(
+            "node",
+            RestartPolicy {
+                mode: RestartMode::OnPanic,
+                max_restarts: 2,
+                backoff: Duration::ZERO,
+            },
+            Box::new(|_retired, _restart| panic!("finished node restarted")),
+        );
+        scheduler.signal_ready("node");
+        let runner = scheduler.clone();
+        std::thread::spawn(move || runner.run())
+            .join()
+            .expect("scheduler thread panicked");
+        let metrics = scheduler.metrics_snapshot();
+        assert_eq!(metrics.restarts_per_node["node"], 0);
+        // a panicking restarter would be counted here
+        assert_eq!(metrics.panics_per_node["node"], 0);
+    }
+
+    #[test]
+    fn panicking_restarter_retires_the_node() {
+        let scheduler = Arc::new(Scheduler::new());
+        scheduler.add_node("node".to_string(), BudgetClass::Normal);
+        scheduler.add_component(Box::new(FinishingComponent), "node".to_string());
+        scheduler.supervise(
+            "node",
+            RestartPolicy {
+                mode: RestartMode::Always,
+                max_restarts: 2,
+                backoff: Duration::ZERO,
+            },
+            Box::new(|_retired, _restart| panic!("restart failed")),
+        );
+        let finish_runs = Arc::new(Mutex::new(0));
+        let finished = finish_runs.clone();
+        scheduler.on_finish(
+            "node",
+            Box::new(move || *finished.lock().expect("lock poisoned") += 1),
+        );
+        scheduler.signal_ready("node");
+
+        // the worker survives and exits once the node is retired
+        let runner = scheduler.clone();
+        std::thread::spawn(move || runner.run())
+            .join()
+            .expect("scheduler thread panicked");
+        let metrics = scheduler.metrics_snapshot();
+        assert_eq!(metrics.restarts_per_node["node"], 0);
+        assert_eq!(metrics.panics_per_node["node"], 1);
+        assert_eq!(metrics.finished_per_node["node"], 1);
+        assert_eq!(*finish_runs.lock().expect("lock poisoned"), 1);
+    }
+
     #[cfg(feature = "enforce-process-non-blocking-contract")]
     #[test]
     #[should_panic(expected = "exceeded non-blocking limit")]
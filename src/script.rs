@@ -0,0 +1,392 @@
+// component:setsource - components given as source code of a scripting language
+//
+// The code runs in an external interpreter process, one per packet: the packet from IN is written
+// to its STDIN, every line of its STDOUT becomes a packet on OUT. Every packet therefore costs a
+// process start plus two threads feeding STDIN and reading STDOUT, which suits low packet rates.
+// Sources are kept in the component library and saved as <name>.json into the script directory.
+// Clients can only send sources if the runtime was started with --allow-scripts.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use log::{debug, error, info, trace, warn};
+
+use flowd_component_api::{take_inport, take_outport};
+
+use crate::{
+    Component, ComponentComponentPayload, ComponentPort, ComponentSourcePayload, FbpMessage,
+    GraphInportOutportHandle, NodeContext, ProcessEdge, ProcessEdgeSink, ProcessEdgeSource,
+    ProcessInports, ProcessOutports, ProcessResult, ProcessSignalSink, ProcessSignalSource,
+    PushError,
+};
+
+/// Directory of the script component sources, relative to the graph directory
+pub const SCRIPT_DIR: &str = "flowd.scripts";
+
+const SCRIPT_POLL_DUR: Duration = Duration::from_millis(10);
+
+// language as sent by the client -> interpreter and its option for inline code
+const INTERPRETERS: &[(&str, &str, &str)] = &[
+    ("sh", "sh", "-c"),
+    ("shell", "sh", "-c"),
+    ("bash", "bash", "-c"),
+    ("python", "python3", "-c"),
+    ("javascript", "node", "-e"),
+    ("ruby", "ruby", "-e"),
+    ("perl", "perl", "-e"),
+];
+
+fn interpreter(language: &str) -> Option<(&'static str, &'static str)> {
+    INTERPRETERS
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(language))
+        .map(|(_, program, option)| (*program, *option))
+}
+
+/// Check that `source` can be registered as a script component
+pub(crate) fn check_source(source: &ComponentSourcePayload) -> Result<(), std::io::Error> {
+    // name becomes a file name, so no path separators or dot files
+    if source.name.is_empty()
+        || !source
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "invalid component name '{}', allowed are letters, digits, _ and -",
+                source.name
+            ),
+        ));
+    }
+    if interpreter(&source.language).is_none() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "unsupported language '{}', supported are: {}",
+                source.language,
+                INTERPRETERS
+                    .iter()
+                    .map(|(name, _, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Component metadata of a script component
+pub(crate) fn metadata(source: &ComponentSourcePayload) -> ComponentComponentPayload {
+    ComponentComponentPayload {
+        name: source.name.clone(),
+        description: format!("{} script, runs once per packet", source.language),
+        icon: String::from("code"),
+        subgraph: false,
+        in_ports: vec![ComponentPort {
+            name: String::from("IN"),
+            allowed_type: String::from("any"),
+            schema: None,
+            required: true,
+            is_arrayport: false,
+            description: String::from("data to be sent to the script STDIN"),
+            values_allowed: vec![],
+            value_default: String::from(""),
+        }],
+        out_ports: vec![ComponentPort {
+            name: String::from("OUT"),
+            allowed_type: String::from("any"),
+            schema: None,
+            required: true,
+            is_arrayport: false,
+            description: String::from("lines of the script STDOUT"),
+            values_allowed: vec![],
+            value_default: String::from(""),
+        }],
+        ..Default::default()
+    }
+}
+
+/// Load all script component sources from `dir`
+pub(crate) fn load_sources(dir: &Path) -> Result<Vec<ComponentSourcePayload>, std::io::Error> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut sources = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let source: ComponentSourcePayload = serde_json::from_slice(&std::fs::read(&path)?)
+            .map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("failed to parse script {}: {}", path.display(), err),
+                )
+            })?;
+        check_source(&source)?;
+        sources.push(source);
+    }
+    Ok(sources)
+}
+
+/// Save a script component source into `dir`
+pub(crate) fn save_source(
+    dir: &Path,
+    source: &ComponentSourcePayload,
+) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(dir)?;
+    let contents = serde_json::to_vec_pretty(source).map_err(std::io::Error::other)?;
+    std::fs::write(dir.join(format!("{}.json", source.name)), contents)
+}
+
+struct ScriptRun {
+    child: Child,
+    stdout_rx: mpsc::Receiver<Vec<u8>>,
+}
+
+pub struct ScriptComponent {
+    inn: ProcessEdgeSource,
+    out: ProcessEdgeSink,
+    signals_in: ProcessSignalSource,
+    signals_out: ProcessSignalSink,
+    command: Option<(&'static str, [String; 2])>, // interpreter and its arguments, None fails
+    running: Option<ScriptRun>,
+    pending: Option<FbpMessage>, // output line waiting for room on OUT
+    scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
+}
+
+impl ScriptComponent {
+    pub(crate) fn from_source(
+        source: &ComponentSourcePayload,
+        mut inports: ProcessInports,
+        mut outports: ProcessOutports,
+        signals_in: ProcessSignalSource,
+        signals_out: ProcessSignalSink,
+        scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
+    ) -> Result<Self, std::io::Error> {
+        check_source(source)?;
+        let (program, option) = interpreter(&source.language).expect("language checked above");
+        let missing = |direction: &str, port: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "script component {} has no {} {}",
+                    source.name, port, direction
+                ),
+            )
+        };
+        Ok(ScriptComponent {
+            inn: inports
+                .remove("IN")
+                .and_then(|mut sources| sources.pop())
+                .ok_or_else(|| missing("inport", "IN"))?,
+            out: outports
+                .remove("OUT")
+                .and_then(|mut sinks| sinks.pop())
+                .ok_or_else(|| missing("outport", "OUT"))?,
+            signals_in,
+            signals_out,
+            command: Some((program, [option.to_owned(), source.code.clone()])),
+            running: None,
+            pending: None,
+            scheduler_waker,
+        })
+    }
+
+    fn spawn(&mut self, packet: FbpMessage) {
+        let Some((program, args)) = &self.command else {
+            return;
+        };
+        let mut child = match Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => {
+                warn!("could not start {}, dropping packet: {}", program, err);
+                return;
+            }
+        };
+
+        // a large packet may not fit into the pipe buffer
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        std::thread::spawn(move || {
+            let data = packet
+                .as_bytes()
+                .map(|bytes| bytes.to_vec())
+                .or_else(|| packet.as_text().map(|text| text.as_bytes().to_vec()))
+                .or_else(|| {
+                    packet
+                        .as_value()
+                        .and_then(|value| serde_json::to_vec(value).ok())
+                })
+                .unwrap_or_default();
+            if let Err(err) = stdin.write_all(&data) {
+                debug!("script closed STDIN early: {}", err);
+            }
+        });
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (stdout_tx, stdout_rx) = mpsc::channel();
+        let scheduler_waker = self.scheduler_waker.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).split(b'\n').map_while(Result::ok) {
+                if stdout_tx.send(line).is_err() {
+                    break;
+                }
+                flowd_component_api::wake_scheduler(&scheduler_waker);
+            }
+            drop(stdout_tx);
+            flowd_component_api::wake_scheduler(&scheduler_waker);
+        });
+
+        self.running = Some(ScriptRun { child, stdout_rx });
+    }
+
+    // the script runs asynchronously, so the receiver of OUT may already be waiting for
+    // more and has to be woken up to notice the closed connection
+    fn close_out(&mut self) {
+        let (sink, _source) = ProcessEdge::new(1);
+        std::mem::replace(&mut self.out, ProcessEdgeSink::new(sink, None, None, None)).close();
+    }
+}
+
+impl Component for ScriptComponent {
+    // script components are created from their source, see from_source(); without one, the
+    // component finishes with an error on its first process() call
+    fn new(
+        mut inports: ProcessInports,
+        mut outports: ProcessOutports,
+        signals_in: ProcessSignalSource,
+        signals_out: ProcessSignalSink,
+        _graph_inout: GraphInportOutportHandle,
+        scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
+    ) -> Self {
+        ScriptComponent {
+            inn: take_inport(&mut inports, "Script", "IN"),
+            out: take_outport(&mut outports, "Script", "OUT"),
+            signals_in,
+            signals_out,
+            command: None,
+            running: None,
+            pending: None,
+            scheduler_waker,
+        }
+    }
+
+    fn process(&mut self, context: &mut NodeContext) -> ProcessResult {
+        if self.command.is_none() {
+            error!("script component without source, finishing");
+            self.close_out();
+            return ProcessResult::Finished;
+        }
+        if let Ok(signal) = self.signals_in.try_recv() {
+            let signal_text = signal.as_text().unwrap_or("");
+            trace!("received signal: {}", signal_text);
+            if signal_text == "stop" {
+                info!("got stop signal, finishing");
+                return ProcessResult::Finished;
+            } else if signal_text == "ping" {
+                let _ = self.signals_out.try_send(FbpMessage::from_str("pong"));
+            } else {
+                warn!("received unknown signal: {}", signal_text);
+            }
+        }
+
+        let mut work_units = 0;
+        while context.remaining_budget > 0 {
+            if let Some(message) = self.pending.take() {
+                if let Err(PushError::Full(message)) = self.out.push(message) {
+                    self.pending = Some(message);
+                    break;
+                }
+                work_units += 1;
+                context.remaining_budget -= 1;
+                continue;
+            }
+
+            if let Some(run) = self.running.as_mut() {
+                match run.stdout_rx.try_recv() {
+                    Ok(line) => {
+                        self.pending = Some(match String::from_utf8(line) {
+                            Ok(text) => FbpMessage::from_text(text),
+                            Err(err) => FbpMessage::from_bytes(err.into_bytes()),
+                        });
+                        continue;
+                    }
+                    Err(mpsc::TryRecvError::Empty) => {
+                        context.wake_at(Instant::now() + SCRIPT_POLL_DUR);
+                        break;
+                    }
+                    Err(mpsc::TryRecvError::Disconnected) => {}
+                }
+                // STDOUT closed, so the script is done or about to be
+                match run.child.try_wait() {
+                    Ok(None) => {
+                        context.wake_at(Instant::now() + SCRIPT_POLL_DUR);
+                        break;
+                    }
+                    Ok(Some(status)) if !status.success() => {
+                        warn!("script exited with {}", status)
+                    }
+                    Ok(Some(_)) => {}
+                    Err(err) => warn!("failed to query script status: {}", err),
+                }
+                self.running = None;
+            }
+
+            match self.inn.pop() {
+                Ok(packet) => {
+                    self.spawn(packet);
+                    work_units += 1;
+                    context.remaining_budget -= 1;
+                }
+                Err(_) => break,
+            }
+        }
+
+        if self.running.is_none()
+            && self.pending.is_none()
+            && self.inn.is_abandoned()
+            && self.inn.is_empty()
+        {
+            info!("EOF on inport, finishing");
+            self.close_out();
+            return ProcessResult::Finished;
+        }
+        if work_units > 0 {
+            ProcessResult::DidWork(work_units)
+        } else {
+            ProcessResult::NoWork
+        }
+    }
+
+    fn get_metadata() -> ComponentComponentPayload {
+        metadata(&ComponentSourcePayload {
+            name: String::from("Script"),
+            language: String::from("sh"),
+            library: String::from(""),
+            code: String::from(""),
+            tests: String::from(""),
+        })
+    }
+}
+
+impl Drop for ScriptComponent {
+    fn drop(&mut self) {
+        if let Some(mut run) = self.running.take() {
+            let _ = run.child.kill();
+            let _ = run.child.wait();
+        }
+    }
+}
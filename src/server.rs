@@ -1,4 +1,5 @@
-use std::net::{TcpListener, TcpStream};
+use std::net::TcpListener;
+use std::path::Path;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex, RwLock};
@@ -10,24 +11,28 @@ use signal_hook::flag;
 use tungstenite::handshake::server::{Request, Response};
 use tungstenite::{accept_hdr, Error, Message, Result};
 
+use crate::auth::SecretStore;
+use crate::tls::ClientStream;
 use crate::{
-    broadcast_to_clients, register_component_log_filters, AccessLevel, Capability, ComponentComponentMessage,
-    ComponentComponentsreadyMessage, ComponentLibrary, ComponentMessage, ComponentSourceMessage,
-    FBPMessage, Graph, GraphAddedgeResponse, GraphAddgroupResponse, GraphAddinitialResponse,
+    broadcast_to_clients, broadcast_to_other_clients, AccessLevel, Capability,
+    ComponentComponentMessage, ComponentComponentsreadyMessage, ComponentErrorResponse,
+    ComponentLibrary, ComponentMessage, ComponentSourceMessage, ComponentTypesMessage, FBPMessage,
+    Graph, GraphAddedgeResponse, GraphAddgroupResponse, GraphAddinitialResponse,
     GraphAddinportResponse, GraphAddnodeResponse, GraphAddoutportResponse, GraphChangeedgeResponse,
     GraphChangegroupResponse, GraphChangenodeResponse, GraphChangenodeResponsePayload,
-    GraphClearResponse, GraphEdge, GraphErrorResponse, GraphInportOutportHolder, GraphMessage,
-    GraphPort, GraphRemoveedgeResponse, GraphRemovegroupResponse,
-    GraphRemoveinitialResponse, GraphRemoveinportResponse, GraphRemovenodeResponse,
-    GraphRemoveoutportResponse, GraphRenamegroupResponse, GraphRenameinportResponse,
-    GraphRenamenodeResponse, GraphRenameoutportResponse, NetworkDebugResponse,
-    NetworkEdgesResponse, NetworkErrorResponse, NetworkMessage, NetworkPersistResponse,
-    NetworkStartedResponse, NetworkStartedResponsePayload, NetworkStatusMessage,
-    NetworkStatusPayload, NetworkStoppedResponse, Runtime,
-    RuntimeErrorResponse, RuntimeMessage, RuntimePacketsentMessage, RuntimePacketsentPayload,
-    RuntimePortsMessage, RuntimeRuntimeMessage, RuntimeRuntimePayload, TraceClearResponse,
-    TraceDumpResponse, TraceErrorResponse, TraceMessage, TraceStartResponse, TraceStopResponse,
-    CLIENT_BROADCAST_WRITE_TIMEOUT,
+    GraphClearResponse, GraphCreatedResponse, GraphDeletedResponse, GraphEdge, GraphErrorResponse,
+    GraphGraphMessage, GraphGraphsdoneMessage, GraphInportOutportHolder, GraphMessage, GraphPort,
+    GraphRemoveedgeResponse, GraphRemovegroupResponse, GraphRemoveinitialResponse,
+    GraphRemoveinportResponse, GraphRemovenodeResponse, GraphRemoveoutportResponse,
+    GraphRenamedResponse, GraphRenamegroupResponse, GraphRenameinportResponse,
+    GraphRenamenodeResponse, GraphRenameoutportResponse, GraphSwitchedResponse, LiveDrain,
+    NetworkDebugResponse, NetworkEdgesResponse, NetworkErrorResponse, NetworkMessage,
+    NetworkMetricsResponse, NetworkPersistResponse, NetworkStartedResponse,
+    NetworkStartedResponsePayload, NetworkStatusMessage, NetworkStatusPayload,
+    NetworkStoppedResponse, Runtime, RuntimeErrorResponse, RuntimeLoglevelMessage, RuntimeMessage,
+    RuntimePacketsentMessage, RuntimePacketsentPayload, RuntimePortsMessage, RuntimeRuntimeMessage,
+    RuntimeRuntimePayload, TraceClearResponse, TraceDumpResponse, TraceErrorResponse, TraceMessage,
+    TraceStartResponse, TraceStopResponse, CLIENT_BROADCAST_WRITE_TIMEOUT,
 };
 /* unused imports
 , RuntimePacketRequestPayload,
@@ -40,29 +45,25 @@ use crate::{
     send_network_stopped, send_network_output, send_network_error, send_network_data
  */
 
-pub fn run() -> Result<()> {
+/// TLS and authentication settings of the management listener
+#[derive(Default)]
+pub struct ServerSecurity {
+    pub tls: Option<Arc<rustls::ServerConfig>>,
+    pub secrets: SecretStore,
+    pub strict_auth: bool,   // reject every message without valid secret
+    pub allow_scripts: bool, // accept component:setsource, clients can run code on this host
+}
+
+/// Run the management server on `bind_addr`, keeping graphs and runtime state in `graph_dir`.
+pub fn run(
+    bind_addr: &str,
+    graph_dir: &Path,
+    security: ServerSecurity,
+    metrics_bind: Option<&str>,
+) -> Result<()> {
     let flowd_start_time = Instant::now();
     println!("flowd {}", env!("CARGO_PKG_VERSION"));
 
-    //NOTE: important to show the thread name = the FBP process name
-    let mut logger_config = simplelog::ConfigBuilder::default();
-    logger_config
-        .set_time_level(simplelog::LevelFilter::Off)
-        .set_thread_level(simplelog::LevelFilter::Info)
-        .set_target_level(simplelog::LevelFilter::Off) // no need to see the module path, for example flowd::components::repeat
-        .set_thread_mode(simplelog::ThreadLogMode::Names)
-        .set_thread_padding(simplelog::ThreadPadding::Right(21)) // maximum thread name length on Linux is 15 by the way
-        .set_level_padding(simplelog::LevelPadding::Right);
-    register_component_log_filters(&mut logger_config);
-    simplelog::TermLogger::init(
-        simplelog::LevelFilter::Trace, // can locally increase this for dev, TODO make configurable via args - but better configure this in Cargo.toml
-        logger_config.build(),
-        simplelog::TerminalMode::Mixed, // level error and above to stderr, rest to stdout
-        simplelog::ColorChoice::Auto,   // depending on whether interactive or not
-    )
-    .expect("logging init failed");
-    info!("logging initialized");
-
     log::info!("Starting flowd server...");
 
     // Create runtime, component library, and graph using public APIs
@@ -70,20 +71,40 @@ pub fn run() -> Result<()> {
     log::info!("runtime initialized");
 
     let components = crate::create_component_library();
+    // plugins first, script components must not shadow them
+    if let Err(err) = components
+        .write()
+        .expect("lock poisoned")
+        .load_plugins(&graph_dir.join(crate::plugin::PLUGIN_DIR))
+    {
+        log::warn!("failed to load plugin components: {}", err);
+    }
+    if let Err(err) = components
+        .write()
+        .expect("lock poisoned")
+        .load_scripts(&graph_dir.join(crate::script::SCRIPT_DIR))
+    {
+        log::warn!("failed to load script components: {}", err);
+    }
+    components
+        .write()
+        .expect("lock poisoned")
+        .allow_script_sources(security.allow_scripts);
     log::info!("component library initialized");
 
     let graph_inout = crate::create_graph_inout_holder();
     log::info!("graph inout holder created");
 
     let (loaded_graphs, active_graph) =
-        crate::load_or_create_graph_set().expect("failed to load or create graphs");
-    let graph = loaded_graphs
-        .get(&active_graph)
-        .cloned()
-        .expect("active graph missing from loaded graph set");
+        crate::load_or_create_graph_set_in(graph_dir).expect("failed to load or create graphs");
     log::info!("graph loaded or created");
     {
         let mut runtime_write = runtime.write().expect("lock poisoned");
+        runtime_write.set_graph_dir(graph_dir);
+        if security.secrets.is_empty() {
+            log::info!("no secrets configured, clients have full access");
+        }
+        runtime_write.set_secrets(security.secrets, security.strict_auth);
         for (graph_name, graph_arc) in loaded_graphs.into_iter() {
             runtime_write.graphs.add_graph(graph_name, graph_arc);
         }
@@ -94,25 +115,26 @@ pub fn run() -> Result<()> {
         runtime_write.graph = active_graph;
     }
 
-    // start network
-    let bind_addr;
-    //TODO features - add better argument parsing. currently defaulting to localhost since no security checks are in place
-    // NOTE: dependencies - used by Kraftfile
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() == 2 {
-        bind_addr = args[1].as_str();
-    } else {
-        bind_addr = "localhost:3569";
+    if let Some(metrics_bind) = metrics_bind {
+        if let Err(err) = crate::metrics::serve(metrics_bind, runtime.clone()) {
+            log::error!(
+                "metrics exporter failed to start on {}: {}",
+                metrics_bind,
+                err
+            );
+            std::process::exit(1);
+        }
     }
 
     // Create and start the server
+    // NOTE: dependencies - Kraftfile passes the bind address
     let mut server = FlowdServer::new(
         bind_addr.to_string(),
         runtime,
-        graph,
         components,
         graph_inout,
         flowd_start_time,
+        security.tls,
     );
 
     if let Err(err) = server.start() {
@@ -135,32 +157,32 @@ fn must_not_block<Role: tungstenite::handshake::HandshakeRole>(
 pub struct FlowdServer {
     bind_addr: String,
     runtime: Arc<RwLock<Runtime>>,
-    graph: Arc<RwLock<Graph>>,
     components: Arc<RwLock<ComponentLibrary>>,
     graph_inout: Arc<Mutex<GraphInportOutportHolder>>,
     sigterm_received: Arc<AtomicBool>,
     sigint_received: Arc<AtomicBool>,
     flowd_start_time: Instant,
+    tls: Option<Arc<rustls::ServerConfig>>,
 }
 
 impl FlowdServer {
     pub fn new(
         bind_addr: String,
         runtime: Arc<RwLock<Runtime>>,
-        graph: Arc<RwLock<Graph>>,
         components: Arc<RwLock<ComponentLibrary>>,
         graph_inout: Arc<Mutex<GraphInportOutportHolder>>,
         flowd_start_time: Instant,
+        tls: Option<Arc<rustls::ServerConfig>>,
     ) -> Self {
         FlowdServer {
             bind_addr,
             runtime,
-            graph,
             components,
             graph_inout,
             sigterm_received: Arc::new(AtomicBool::new(false)),
             sigint_received: Arc::new(AtomicBool::new(false)),
             flowd_start_time,
+            tls,
         }
     }
 
@@ -176,8 +198,9 @@ impl FlowdServer {
         server
             .set_nonblocking(true)
             .expect("Failed to set non-blocking mode");
-        log::info!("management listening on {} - manage via GUI at https://app.noflojs.org/#runtime/endpoint?protocol%3Dwebsocket%26address%3Dws%3A%2F%2F{}",
+        log::info!("management listening on {} - manage via GUI at https://app.noflojs.org/#runtime/endpoint?protocol%3Dwebsocket%26address%3D{}%3A%2F%2F{}",
                    self.bind_addr,
+                   if self.tls.is_some() { "wss" } else { "ws" },
                    self.bind_addr.replace("localhost:", "localhost:"));
 
         // start listening for incoming connections on socket
@@ -190,26 +213,30 @@ impl FlowdServer {
             match stream_res {
                 Ok(stream) => {
                     // create Arc pointers for the new thread
-                    let graphref = self.graph.clone();
                     let runtimeref = self.runtime.clone();
                     let componentlibref = self.components.clone();
                     let graph_inoutref = self.graph_inout.clone();
+                    let tls = self.tls.clone();
 
                     // start thread
                     // since the thread name can only be 15 characters on Linux and an IP address already has up to 15, the IP address is not in the name
                     thread::Builder::new()
                         .name("client-handler".into())
                         .spawn(move || {
-                            log::info!(
-                                "got a client from {}",
-                                stream.peer_addr().expect("get peer address failed")
-                            );
+                            let peer_addr = stream.peer_addr().expect("get peer address failed");
+                            log::info!("got a client from {}", peer_addr);
+                            let stream = match ClientStream::accept(stream, tls.as_ref()) {
+                                Ok(stream) => stream,
+                                Err(err) => {
+                                    log::warn!("TLS handshake with client failed: {}", err);
+                                    return;
+                                }
+                            };
                             if let Err(err) = Self::handle_client(
                                 stream,
-                                graphref,
                                 runtimeref,
                                 componentlibref,
-                                graph_inoutref,
+                                graph_inoutref.clone(),
                             ) {
                                 match err {
                                     Error::ConnectionClosed | Error::Protocol(_) | Error::Utf8 => {
@@ -218,6 +245,11 @@ impl FlowdServer {
                                     e => log::error!("test: {}", e),
                                 }
                             }
+                            // disconnected, no more broadcasts and runtime:packet messages
+                            graph_inoutref
+                                .lock()
+                                .expect("lock poisoned")
+                                .remove_client(&peer_addr);
                         })
                         .expect("thread start for connection handler failed");
                 }
@@ -235,29 +267,28 @@ impl FlowdServer {
         // Graceful shutdown: handle signal-specific actions
         if self.sigterm_received.load(Ordering::Relaxed) {
             // Persist on SIGTERM (systemd graceful shutdown)
-            if let Err(e) = self
-                .runtime
-                .read()
-                .unwrap()
-                .persist(&self.graph.read().unwrap())
-            {
+            if let Err(e) = self.runtime.read().unwrap().persist() {
                 log::warn!("Failed to persist graph on SIGTERM: {}", e);
             } else {
                 log::info!("Graph persisted on SIGTERM");
             }
         }
 
-        // Stop network if running
-        if self.runtime.read().unwrap().status.running {
-            log::info!("Signal received, waiting for network shutdown...");
+        // Stop all running networks
+        let running_graphs = self.runtime.read().unwrap().running_graphs();
+        for graph_name in running_graphs {
+            log::info!(
+                "Signal received, waiting for network shutdown of graph {}...",
+                graph_name
+            );
             match self
                 .runtime
                 .write()
                 .unwrap()
-                .stop(self.graph_inout.clone(), false)
+                .stop(&graph_name, self.graph_inout.clone(), false)
             {
-                Ok(_) => log::info!("Network stopped gracefully"),
-                Err(e) => log::warn!("Network stop failed: {}", e),
+                Ok(_) => log::info!("Network of graph {} stopped gracefully", graph_name),
+                Err(e) => log::warn!("Network stop of graph {} failed: {}", graph_name, e),
             }
         }
 
@@ -274,8 +305,7 @@ impl FlowdServer {
     }
 
     fn handle_client(
-        stream: TcpStream,
-        graph: Arc<RwLock<Graph>>,
+        stream: ClientStream,
         runtime: Arc<RwLock<Runtime>>,
         components: Arc<RwLock<ComponentLibrary>>,
         graph_inout: Arc<std::sync::Mutex<GraphInportOutportHolder>>,
@@ -294,6 +324,25 @@ impl FlowdServer {
                 self.capabilities = payload.capabilities.clone();
             }
 
+            // graph the client works on, the active graph unless it chose another one;
+            // that one may have been deleted or renamed by another client meanwhile
+            fn addressed_graph(
+                &self,
+                runtime: &Arc<RwLock<Runtime>>,
+            ) -> Result<String, std::io::Error> {
+                let runtime_read = runtime.read().expect("lock poisoned");
+                match &self.graph {
+                    Some(graph) if runtime_read.graphs.get_graph(graph).is_none() => {
+                        Err(std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("graph '{}' not found", graph),
+                        ))
+                    }
+                    Some(graph) => Ok(graph.clone()),
+                    None => Ok(runtime_read.graph.clone()),
+                }
+            }
+
             fn can_receive_network_debug_for_graph(&self, graph: &str) -> bool {
                 if self.graph.as_deref() != Some(graph) {
                     return false;
@@ -337,16 +386,110 @@ impl FlowdServer {
             let Some(secret) = secret.filter(|secret| !secret.is_empty()) else {
                 return false;
             };
-            let runtime_read = runtime.read().expect("lock poisoned");
-            let Some((expected_secret, access_level)) = runtime_read.secrets.get(graph) else {
-                return false;
+            runtime
+                .read()
+                .expect("lock poisoned")
+                .secrets
+                .access(secret, graph)
+                == Some(AccessLevel::ReadOnly)
+        }
+
+        // keep a clone of the connection for messages to all clients, see broadcast_to_clients()
+        fn register_for_broadcasts(
+            graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
+            stream: ClientStream,
+        ) {
+            let peer_addr = stream.peer_addr().expect("could not get peer socketaddr");
+            if let Err(err) = stream.set_write_timeout(CLIENT_BROADCAST_WRITE_TIMEOUT) {
+                log::warn!("set_write_timeout call failed on cloned stream: {:?}", err);
+            }
+            graph_inout
+                .lock()
+                .expect(r#"could not acquire lock for saving TcpStream for graph outport process"#)
+                .websockets
+                .insert(
+                    peer_addr,
+                    Arc::new(Mutex::new(tungstenite::WebSocket::from_raw_socket(
+                        stream,
+                        tungstenite::protocol::Role::Server,
+                        None,
+                    ))),
+                );
+        }
+
+        // runtime:packet messages from the graph outports go to the clients that sent
+        // network:start or runtime:packet for that graph
+        fn subscribe_outports(
+            graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
+            websocket: &tungstenite::WebSocket<ClientStream>,
+            graph: &str,
+        ) {
+            if let Ok(peer_addr) = websocket.get_ref().peer_addr() {
+                graph_inout
+                    .lock()
+                    .expect("lock poisoned")
+                    .subscribe_outports(graph, peer_addr);
+            }
+        }
+
+        // graph management: respond to the client and let the other clients follow the change
+        fn send_and_broadcast<T: serde::Serialize>(
+            graph_inout: &Arc<Mutex<GraphInportOutportHolder>>,
+            websocket: &mut tungstenite::WebSocket<ClientStream>,
+            message: &T,
+            message_name: &str,
+        ) {
+            websocket
+                .send(Message::text(
+                    serde_json::to_string(message).expect("failed to serialize response"),
+                ))
+                .expect("failed to write message into websocket");
+            let peer_addr = websocket.get_ref().peer_addr().ok();
+            broadcast_to_other_clients(graph_inout, peer_addr, message, message_name);
+        }
+
+        // hot graph mutation: if the changed graph is running, apply the change to the network as well.
+        // The graph change itself is kept, failures are reported as network:error so that the client can restart the network.
+        // A returned LiveDrain waits for the processes after the runtime lock was released, so that other clients are not blocked.
+        fn apply_to_running_network(
+            runtime: &Arc<RwLock<Runtime>>,
+            graph_name: &str,
+            websocket: &mut tungstenite::WebSocket<ClientStream>,
+            change: impl FnOnce(&mut Runtime) -> std::result::Result<Option<LiveDrain>, std::io::Error>,
+        ) {
+            let result = {
+                let mut runtime_write = runtime.write().expect("lock poisoned");
+                if !runtime_write.is_running_graph(graph_name) {
+                    return;
+                }
+                change(&mut runtime_write)
+            };
+            let result = match result {
+                Ok(Some(drain)) => drain(),
+                Ok(None) => Ok(()),
+                Err(err) => Err(err),
             };
-            secret == expected_secret && *access_level == AccessLevel::ReadOnly
+            if let Err(err) = result {
+                log::warn!("could not apply graph change to running network: {}", err);
+                log::info!("response: sending network:error response");
+                websocket
+                    .send(Message::text(
+                        serde_json::to_string(&NetworkErrorResponse::new(
+                            format!(
+                                "graph changed, but running network could not be updated: {}",
+                                err
+                            ),
+                            String::from(""),
+                            graph_name.to_owned(),
+                        ))
+                        .expect("failed to serialize network:error response"),
+                    ))
+                    .expect("failed to write message into websocket");
+            }
         }
 
         fn get_graph_by_name(
             runtime: &Arc<RwLock<Runtime>>,
-            fallback_graph: &Arc<RwLock<Graph>>,
             graph_name: &str,
         ) -> Result<Arc<RwLock<Graph>>, std::io::Error> {
             if let Some(graph_arc) = runtime
@@ -357,15 +500,6 @@ impl FlowdServer {
             {
                 return Ok(graph_arc);
             }
-            if fallback_graph
-                .read()
-                .expect("lock poisoned")
-                .properties
-                .name
-                == graph_name
-            {
-                return Ok(fallback_graph.clone());
-            }
             Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("Graph '{}' not found", graph_name),
@@ -383,25 +517,13 @@ impl FlowdServer {
         tungstenite::protocol::Role::Server,
         None
         */
-        let peer_addr = stream.peer_addr().expect("could not get peer socketaddr");
-        {
-            let cloned_stream = stream.try_clone().expect("could not try_clone() TcpStream");
-            if let Err(err) = cloned_stream.set_write_timeout(CLIENT_BROADCAST_WRITE_TIMEOUT) {
-                log::warn!("set_write_timeout call failed on cloned stream: {:?}", err);
-            }
-            graph_inout
-                .lock()
-                .expect(r#"could not acquire lock for saving TcpStream for graph outport process"#)
-                .websockets
-                .insert(
-                    peer_addr,
-                    Arc::new(Mutex::new(tungstenite::WebSocket::from_raw_socket(
-                        cloned_stream,
-                        tungstenite::protocol::Role::Server,
-                        None,
-                    ))),
-                );
-        }
+        // in strict mode, clients only get broadcasts once they sent a valid secret
+        let strict_auth = runtime.read().expect("lock poisoned").strict_auth;
+        let mut broadcast_stream = Some(
+            stream
+                .try_clone()
+                .expect("could not try_clone() client stream"),
+        );
 
         let callback = |req: &Request, mut response: Response| {
             log::debug!("Received a new ws handshake");
@@ -424,29 +546,38 @@ impl FlowdServer {
         };
         //let mut socket = accept(stream).map_err(must_not_block)?;
         let mut websocket = accept_hdr(stream, callback).map_err(must_not_block)?;
-
-        //TODO wss
-        //TODO check secret
-
-        // Send initial runtime:status message to inform client about current runtime state
-        // This is required for noflo-ui to show start/stop buttons
-        let runtime_status = runtime.read().expect("lock poisoned");
-        let status_payload = NetworkStatusPayload {
-            graph: runtime_status.graph.clone(),
-            uptime: None,
-            started: runtime_status.status.started,
-            running: runtime_status.status.running,
-            debug: runtime_status.status.debug,
-            scheduler_metrics: None,
-        };
-        let status_message = NetworkStatusMessage::new(status_payload);
-        drop(runtime_status);
-        if let Err(err) = websocket.send(Message::text(
-            serde_json::to_string(&status_message).expect("failed to serialize initial runtime:status message")
-        )) {
-            log::warn!("failed to send initial runtime:status message to client: {}", err);
-        } else {
-            log::debug!("sent initial runtime:status message to client");
+        // without strict mode, clients are not asked for a secret before getting runtime state
+        if !strict_auth {
+            register_for_broadcasts(
+                &graph_inout,
+                broadcast_stream.take().expect("not taken yet"),
+            );
+
+            // Send initial runtime:status message to inform client about current runtime state
+            // This is required for noflo-ui to show start/stop buttons
+            let runtime_status = runtime.read().expect("lock poisoned");
+            let graph_status = runtime_status.graph_status(&runtime_status.graph);
+            let status_payload = NetworkStatusPayload {
+                graph: runtime_status.graph.clone(),
+                uptime: None,
+                started: graph_status.started,
+                running: graph_status.running,
+                debug: graph_status.debug,
+                scheduler_metrics: None,
+            };
+            let status_message = NetworkStatusMessage::new(status_payload);
+            drop(runtime_status);
+            if let Err(err) = websocket.send(Message::text(
+                serde_json::to_string(&status_message)
+                    .expect("failed to serialize initial runtime:status message"),
+            )) {
+                log::warn!(
+                    "failed to send initial runtime:status message to client: {}",
+                    err
+                );
+            } else {
+                log::debug!("sent initial runtime:status message to client");
+            }
         }
 
         let mut client_capabilities = ClientCapabilityHolder::default();
@@ -477,10 +608,45 @@ impl FlowdServer {
                         };
                     if let Some(map) = json_envelope.as_object_mut() {
                         map.remove("id");
-                        map.remove("secret");
+                        let envelope_secret = map.remove("secret");
                         if !map.contains_key("payload") {
                             map.insert("payload".to_string(), serde_json::json!({}));
                         }
+                        // the payload secret is the one checked, so carry over the envelope one
+                        if let (Some(secret), Some(payload)) = (
+                            envelope_secret,
+                            map.get_mut("payload").and_then(|p| p.as_object_mut()),
+                        ) {
+                            payload.entry("secret").or_insert(secret);
+                        }
+                    }
+
+                    if strict_auth {
+                        let secret = json_envelope
+                            .pointer("/payload/secret")
+                            .and_then(|secret| secret.as_str())
+                            .unwrap_or_default();
+                        if secret.is_empty()
+                            || !runtime
+                                .read()
+                                .expect("lock poisoned")
+                                .secrets
+                                .is_known(secret)
+                        {
+                            log::warn!("rejecting message without valid secret");
+                            websocket
+                                .send(Message::text(
+                                    serde_json::to_string(&RuntimeErrorResponse::new(
+                                        "invalid secret token".to_string(),
+                                    ))
+                                    .expect("failed to serialize runtime:error response"),
+                                ))
+                                .expect("failed to write message into websocket");
+                            continue;
+                        }
+                        if let Some(stream) = broadcast_stream.take() {
+                            register_for_broadcasts(&graph_inout, stream);
+                        }
                     }
 
                     let fbpmsg: FBPMessage = match serde_json::from_value(json_envelope) {
@@ -535,41 +701,50 @@ impl FlowdServer {
                                 runtime_payload.capabilities.push(Capability::GraphReadonly);
                             }
                             client_capabilities.update_from_runtime_payload(&runtime_payload);
-                             websocket
-                                 .send(Message::text(
-                                     //TODO handing over value inside lock would work like this:  serde_json::to_string(&*runtime.read().expect("lock poisoned"))
-                                     serde_json::to_string(&RuntimeRuntimeMessage::new(
-                                         runtime_payload,
-                                     ))
-                                     .expect("failed to serialize runtime:runtime message"),
-                                 ))
-                                 .expect("failed to write message into websocket");
-                             // Send initial runtime:status message so noflo-ui shows start/stop buttons
-                             log::info!("response: sending initial runtime:status message");
-                             let status_payload = {
-                                 let runtime_read = runtime.read().expect("lock poisoned");
-                                 NetworkStatusPayload {
-                                     graph: runtime_read.graph.clone(),
-                                     uptime: None,
-                                     started: runtime_read.status.started,
-                                     running: runtime_read.status.running,
-                                     debug: runtime_read.status.debug,
-                                     scheduler_metrics: None,
-                                 }
-                             };
-                             websocket
-                                 .send(Message::text(
-                                     serde_json::to_string(&NetworkStatusMessage::new(status_payload))
-                                         .expect("failed to serialize runtime:status message"),
-                                 ))
-                                 .expect("failed to write message into websocket");
-                             // spec: "If the runtime is currently running a graph and it is able to speak the full Runtime protocol, it should follow up with a ports message."
-                             log::info!("response: sending runtime:ports message");
+                            websocket
+                                .send(Message::text(
+                                    //TODO handing over value inside lock would work like this:  serde_json::to_string(&*runtime.read().expect("lock poisoned"))
+                                    serde_json::to_string(&RuntimeRuntimeMessage::new(
+                                        runtime_payload,
+                                    ))
+                                    .expect("failed to serialize runtime:runtime message"),
+                                ))
+                                .expect("failed to write message into websocket");
+                            // Send initial runtime:status message so noflo-ui shows start/stop buttons
+                            log::info!("response: sending initial runtime:status message");
+                            let status_payload = {
+                                let runtime_read = runtime.read().expect("lock poisoned");
+                                let graph_status = runtime_read.graph_status(&runtime_read.graph);
+                                NetworkStatusPayload {
+                                    graph: runtime_read.graph.clone(),
+                                    uptime: None,
+                                    started: graph_status.started,
+                                    running: graph_status.running,
+                                    debug: graph_status.debug,
+                                    scheduler_metrics: None,
+                                }
+                            };
+                            websocket
+                                .send(Message::text(
+                                    serde_json::to_string(&NetworkStatusMessage::new(
+                                        status_payload,
+                                    ))
+                                    .expect("failed to serialize runtime:status message"),
+                                ))
+                                .expect("failed to write message into websocket");
+                            // spec: "If the runtime is currently running a graph and it is able to speak the full Runtime protocol, it should follow up with a ports message."
+                            log::info!("response: sending runtime:ports message");
+                            let active_graph = runtime
+                                .read()
+                                .expect("lock poisoned")
+                                .graphs
+                                .get_active_graph()
+                                .expect("active graph is always present");
                             websocket
                                 .send(Message::text(
                                     serde_json::to_string(&RuntimePortsMessage::new(
                                         &*runtime.read().expect("lock poisoned"),
-                                        &graph.read().expect("lock poisoned"),
+                                        &active_graph.read().expect("lock poisoned"),
                                     ))
                                     .expect("failed to serialize runtime:ports message"),
                                 ))
@@ -577,12 +752,75 @@ impl FlowdServer {
                         }
 
                         // protocol:component
+                        // flowd extension ADR-029: the type registry the ports refer to
+                        FBPMessage::Component(ComponentMessage::Types(payload)) => {
+                            log::info!("got component:types message");
+                            let addressed_graph = match client_capabilities
+                                .addressed_graph(&runtime)
+                            {
+                                Ok(graph) => graph,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&ComponentErrorResponse::new(
+                                                err.to_string(),
+                                            ))
+                                            .expect("failed to serialize component:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
+                            if validate_secret_readonly(
+                                &runtime,
+                                payload.secret.as_ref(),
+                                &addressed_graph,
+                            )
+                            .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&ComponentErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize component:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let components_read = components.read().expect("lock poisoned");
+                            log::info!("response: sending component:types message");
+                            websocket
+                                .send(Message::text(
+                                    serde_json::to_string(&ComponentTypesMessage::new(
+                                        components_read.types(),
+                                    ))
+                                    .expect("failed to serialize component:types response"),
+                                ))
+                                .expect("failed to write message into websocket");
+                        }
+
                         FBPMessage::Component(ComponentMessage::List(_payload)) => {
                             log::info!("got component:list message");
+                            let addressed_graph =
+                                match client_capabilities.addressed_graph(&runtime) {
+                                    Ok(graph) => graph,
+                                    Err(err) => {
+                                        websocket
+                                            .send(Message::text(
+                                                serde_json::to_string(&GraphErrorResponse::new(
+                                                    err.to_string(),
+                                                ))
+                                                .expect("failed to serialize graph:error response"),
+                                            ))
+                                            .expect("failed to write message into websocket");
+                                        continue;
+                                    }
+                                };
                             if validate_secret_readonly(
                                 &runtime,
                                 _payload.secret.as_ref(),
-                                &runtime.read().expect("lock poisoned").graph,
+                                &addressed_graph,
                             )
                             .is_err()
                             {
@@ -597,8 +835,17 @@ impl FlowdServer {
                                 continue;
                             }
                             let mut count: u32 = 0;
-                            for component in
-                                components.read().expect("lock poisoned").available.iter()
+                            let components_read = components.read().expect("lock poisoned");
+                            // graphs can be used as subgraph components
+                            let subgraph_components = runtime
+                                .read()
+                                .expect("lock poisoned")
+                                .graphs
+                                .subgraph_components(&components_read);
+                            for component in components_read
+                                .available
+                                .iter()
+                                .chain(subgraph_components.iter())
                             {
                                 log::info!("response: sending component:component message");
                                 websocket
@@ -611,6 +858,7 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 count += 1;
                             }
+                            drop(components_read);
                             log::info!("response: sending component:componentsready response");
                             websocket
                                 .send(Message::text(
@@ -630,12 +878,15 @@ impl FlowdServer {
                             // NOTE: this message is not explicitly specified in the protocol, but emerges
                             // implicitly from the event-driven nature of the protocol and client
                             // implementation in noflo-ui.
+                            let active_graph = addressed_graph;
                             let runtime_read = runtime.read().expect("lock poisoned");
-                            let active_graph = runtime_read.graph.clone();
                             if client_capabilities
                                 .can_receive_network_debug_for_graph(&active_graph)
                             {
-                                let debug_enabled = runtime_read.status.debug.unwrap_or(false);
+                                let debug_enabled = runtime_read
+                                    .graph_status(&active_graph)
+                                    .debug
+                                    .unwrap_or(false);
                                 let debug_response =
                                     NetworkDebugResponse::new(active_graph.clone(), debug_enabled);
                                 let debug_response_json = serde_json::to_string(&debug_response)
@@ -677,7 +928,7 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            if get_graph_by_name(&runtime, &graph, &_payload.graph).is_err() {
+                            if get_graph_by_name(&runtime, &_payload.graph).is_err() {
                                 websocket
                                     .send(Message::text(
                                         serde_json::to_string(&NetworkErrorResponse::new(
@@ -691,8 +942,10 @@ impl FlowdServer {
                                 continue;
                             }
                             let status_snapshot = {
-                                let mut snapshot =
-                                    runtime.read().expect("lock poisoned").status_snapshot();
+                                let mut snapshot = runtime
+                                    .read()
+                                    .expect("lock poisoned")
+                                    .status_snapshot(&_payload.graph);
                                 // Compatibility: tiny test graphs may complete immediately after network:start.
                                 // Allow a short grace window so getstatus reflects completed state.
                                 if snapshot.graph == _payload.graph
@@ -704,7 +957,7 @@ impl FlowdServer {
                                         snapshot = runtime
                                             .read()
                                             .expect("lock poisoned")
-                                            .status_snapshot();
+                                            .status_snapshot(&_payload.graph);
                                         if !snapshot.running {
                                             break;
                                         }
@@ -741,12 +994,13 @@ impl FlowdServer {
                                 .expect("failed to write message into websocket");
                         }
 
-                        FBPMessage::Network(NetworkMessage::Persist(_payload)) => {
-                            log::info!("got network:persist message");
-                            if validate_secret(
+                        // flowd extension: scheduler metrics incl. utilisation of each worker
+                        FBPMessage::Network(NetworkMessage::Metrics(payload)) => {
+                            log::info!("got network:metrics message");
+                            if validate_secret_readonly(
                                 &runtime,
-                                _payload.secret.as_ref(),
-                                &runtime.read().expect("lock poisoned").graph,
+                                payload.secret.as_ref(),
+                                &payload.graph,
                             )
                             .is_err()
                             {
@@ -755,20 +1009,83 @@ impl FlowdServer {
                                         serde_json::to_string(&NetworkErrorResponse::new(
                                             "invalid secret token".to_string(),
                                             String::from(""),
-                                            runtime.read().expect("lock poisoned").graph.clone(),
+                                            payload.graph.clone(),
                                         ))
                                         .expect("failed to serialize network:error response"),
                                     ))
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            // persist and send either network:persist or network:error
-                            //###
-                            match runtime
+                            if get_graph_by_name(&runtime, &payload.graph).is_err() {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&NetworkErrorResponse::new(
+                                            String::from("Requested graph not found"),
+                                            String::from(""),
+                                            payload.graph,
+                                        ))
+                                        .expect("failed to serialize network:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let status_snapshot = runtime
                                 .read()
                                 .expect("lock poisoned")
-                                .persist(&graph.read().expect("lock poisoned"))
+                                .status_snapshot(&payload.graph);
+                            log::info!("response: sending network:metrics message");
+                            websocket
+                                .send(Message::text(
+                                    serde_json::to_string(&NetworkMetricsResponse::new(
+                                        &status_snapshot,
+                                    ))
+                                    .expect("failed to serialize network:metrics response"),
+                                ))
+                                .expect("failed to write message into websocket");
+                        }
+
+                        FBPMessage::Network(NetworkMessage::Persist(_payload)) => {
+                            log::info!("got network:persist message");
+                            let addressed_graph = match client_capabilities
+                                .addressed_graph(&runtime)
+                            {
+                                Ok(graph) => graph,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&NetworkErrorResponse::new(
+                                                err.to_string(),
+                                                String::from(""),
+                                                runtime
+                                                    .read()
+                                                    .expect("lock poisoned")
+                                                    .graph
+                                                    .clone(),
+                                            ))
+                                            .expect("failed to serialize network:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
+                            if validate_secret(&runtime, _payload.secret.as_ref(), &addressed_graph)
+                                .is_err()
                             {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&NetworkErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                            String::from(""),
+                                            runtime.read().expect("lock poisoned").graph.clone(),
+                                        ))
+                                        .expect("failed to serialize network:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            // persist and send either network:persist or network:error
+                            //###
+                            match runtime.read().expect("lock poisoned").persist() {
                                 // NOTE: persist() now stores all known graphs from the runtime graph manager
                                 Ok(_) => {
                                     log::info!("response: sending network:persist message");
@@ -804,10 +1121,25 @@ impl FlowdServer {
 
                         FBPMessage::Component(ComponentMessage::Getsource(payload)) => {
                             log::info!("got component:getsource message");
+                            let addressed_graph =
+                                match client_capabilities.addressed_graph(&runtime) {
+                                    Ok(graph) => graph,
+                                    Err(err) => {
+                                        websocket
+                                            .send(Message::text(
+                                                serde_json::to_string(&GraphErrorResponse::new(
+                                                    err.to_string(),
+                                                ))
+                                                .expect("failed to serialize graph:error response"),
+                                            ))
+                                            .expect("failed to write message into websocket");
+                                        continue;
+                                    }
+                                };
                             if validate_secret_readonly(
                                 &runtime,
                                 payload.secret.as_ref(),
-                                &runtime.read().expect("lock poisoned").graph,
+                                &addressed_graph,
                             )
                             .is_err()
                             {
@@ -821,10 +1153,8 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            //TODO multi-graph support (runtime has the info which graph is running currently)
                             //TODO optimize: need 2 locks to get graph source - and it is not the common case
-                            if graph.read().expect("lock poisoned").properties.name == payload.name
-                            {
+                            if let Ok(graph) = get_graph_by_name(&runtime, &payload.name) {
                                 // retrieve graph source
                                 log::info!("got a request for graph source of {}", &payload.name);
                                 //TODO why does Rust require a write lock here? "cannot borrow data in dereference as mutable"
@@ -910,50 +1240,360 @@ impl FlowdServer {
                             }
                         }
 
-                        FBPMessage::Graph(GraphMessage::Clear(payload)) => {
-                            log::info!("got graph:clear message");
-                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.name)
-                                .is_err()
+                        // component:setsource
+                        FBPMessage::Component(ComponentMessage::Source(payload)) => {
+                            log::info!("got component:source message for {}", payload.name);
+                            let addressed_graph = match client_capabilities
+                                .addressed_graph(&runtime)
                             {
-                                websocket
-                                    .send(Message::text(
-                                        serde_json::to_string(&GraphErrorResponse::new(
-                                            "invalid secret token".to_string(),
+                                Ok(graph) => graph,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&ComponentErrorResponse::new(
+                                                err.to_string(),
+                                            ))
+                                            .expect("failed to serialize component:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
+                            if validate_secret(&runtime, payload.secret.as_ref(), &addressed_graph)
+                                .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&ComponentErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize component:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let mut components_write = components.write().expect("lock poisoned");
+                            let response = match components_write.set_script_source(payload.into())
+                            {
+                                Ok(component) => {
+                                    log::info!("response: sending component:component message");
+                                    serde_json::to_string(&ComponentComponentMessage::new(
+                                        component,
+                                    ))
+                                    .expect("failed to serialize component:component message")
+                                }
+                                Err(err) => {
+                                    log::warn!("component:source failed: {}", err);
+                                    log::info!("response: sending component:error response");
+                                    serde_json::to_string(&ComponentErrorResponse::new(
+                                        err.to_string(),
+                                    ))
+                                    .expect("failed to serialize component:error response")
+                                }
+                            };
+                            drop(components_write);
+                            websocket
+                                .send(Message::text(response))
+                                .expect("failed to write message into websocket");
+                        }
+
+                        FBPMessage::Graph(GraphMessage::Clear(payload)) => {
+                            log::info!("got graph:clear message");
+                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.name)
+                                .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&GraphErrorResponse::new(
+                                            "invalid secret token".to_string(),
                                         ))
                                         .expect("failed to serialize graph:error response"),
                                     ))
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let mut runtime_write = runtime.write().expect("lock poisoned");
-                            let previous_active_graph = runtime_write.graph.clone();
-                            match graph
+                            let clear_result = runtime
                                 .write()
                                 .expect("lock poisoned")
-                                .clear(&payload, &*runtime_write)
-                            {
+                                .clear_graph(&payload);
+                            match clear_result {
                                 Ok(_) => {
-                                    runtime_write.graph = payload.name.clone();
-                                    runtime_write
-                                        .graphs
-                                        .add_graph(payload.name.clone(), graph.clone());
-                                    if previous_active_graph != payload.name {
-                                        runtime_write.graphs.remove_graph(&previous_active_graph);
-                                    }
-                                    let _ = runtime_write.graphs.set_active_graph(&payload.name);
+                                    client_capabilities.graph = Some(payload.name.clone());
                                     log::info!("response: sending graph:clear response");
+                                    send_and_broadcast(
+                                        &graph_inout,
+                                        &mut websocket,
+                                        &GraphClearResponse::new(&payload),
+                                        "graph:clear",
+                                    );
+                                }
+                                Err(err) => {
+                                    log::error!("graph.clear() failed: {}", err);
+                                    log::info!("response: sending graph:error response");
                                     websocket
                                         .send(Message::text(
-                                            serde_json::to_string(&GraphClearResponse::new(
-                                                &payload,
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to serialize graph:clear response"),
+                                            .expect("failed to serialize graph:error response"),
                                         ))
                                         .expect("failed to write message into websocket");
                                 }
+                            }
+                        }
+
+                        // graph management, ADR-025
+                        FBPMessage::Graph(GraphMessage::List(payload)) => {
+                            log::info!("got graph:list message");
+                            let addressed_graph =
+                                match client_capabilities.addressed_graph(&runtime) {
+                                    Ok(graph) => graph,
+                                    Err(err) => {
+                                        websocket
+                                            .send(Message::text(
+                                                serde_json::to_string(&GraphErrorResponse::new(
+                                                    err.to_string(),
+                                                ))
+                                                .expect("failed to serialize graph:error response"),
+                                            ))
+                                            .expect("failed to write message into websocket");
+                                        continue;
+                                    }
+                                };
+                            if validate_secret_readonly(
+                                &runtime,
+                                payload.secret.as_ref(),
+                                &addressed_graph,
+                            )
+                            .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&GraphErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize graph:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            // collect first, graph locks are taken before the runtime lock
+                            let graphs = {
+                                let runtime_read = runtime.read().expect("lock poisoned");
+                                let mut graph_ids = runtime_read.graphs.list_graphs();
+                                graph_ids.sort();
+                                graph_ids
+                                    .into_iter()
+                                    .filter_map(|graph_id| {
+                                        let graph_arc = runtime_read.graphs.get_graph(&graph_id)?;
+                                        let active = graph_id == runtime_read.graph;
+                                        let running = runtime_read.graph_status(&graph_id).running;
+                                        Some((graph_arc, active, running))
+                                    })
+                                    .collect::<Vec<_>>()
+                            };
+                            log::info!("response: sending {} graph:graph messages", graphs.len());
+                            for (graph_arc, active, running) in graphs {
+                                let message = GraphGraphMessage::new(
+                                    &graph_arc.read().expect("lock poisoned"),
+                                    active,
+                                    running,
+                                );
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&message)
+                                            .expect("failed to serialize graph:graph message"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                            }
+                            websocket
+                                .send(Message::text(
+                                    serde_json::to_string(&GraphGraphsdoneMessage::default())
+                                        .expect("failed to serialize graph:graphsdone message"),
+                                ))
+                                .expect("failed to write message into websocket");
+                        }
+
+                        FBPMessage::Graph(GraphMessage::Create(payload)) => {
+                            log::info!("got graph:create message for {}", payload.id);
+                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.id)
+                                .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&GraphErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize graph:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let create_result = runtime
+                                .write()
+                                .expect("lock poisoned")
+                                .create_graph(&payload);
+                            match create_result {
+                                Ok(()) => {
+                                    // the creating client addresses the new graph, the active graph stays
+                                    client_capabilities.graph = Some(payload.id.clone());
+                                    log::info!("response: sending graph:created response");
+                                    send_and_broadcast(
+                                        &graph_inout,
+                                        &mut websocket,
+                                        &GraphCreatedResponse::new(payload.id),
+                                        "graph:created",
+                                    );
+                                }
                                 Err(err) => {
-                                    log::error!("graph.clear() failed: {}", err);
-                                    log::info!("response: sending graph:error response");
+                                    log::error!("creating graph failed: {}", err);
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
+                                            ))
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                }
+                            }
+                        }
+
+                        FBPMessage::Graph(GraphMessage::Delete(payload)) => {
+                            log::info!("got graph:delete message for {}", payload.id);
+                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.id)
+                                .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&GraphErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize graph:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let delete_result = runtime
+                                .write()
+                                .expect("lock poisoned")
+                                .delete_graph(&payload.id);
+                            match delete_result {
+                                Ok(()) => {
+                                    if client_capabilities.graph.as_deref() == Some(&payload.id) {
+                                        client_capabilities.graph = None;
+                                    }
+                                    graph_inout
+                                        .lock()
+                                        .expect("lock poisoned")
+                                        .move_outport_subscriptions(&payload.id, None);
+                                    log::info!("response: sending graph:deleted response");
+                                    send_and_broadcast(
+                                        &graph_inout,
+                                        &mut websocket,
+                                        &GraphDeletedResponse::new(payload.id),
+                                        "graph:deleted",
+                                    );
+                                }
+                                Err(err) => {
+                                    log::error!("deleting graph failed: {}", err);
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
+                                            ))
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                }
+                            }
+                        }
+
+                        FBPMessage::Graph(GraphMessage::Rename(payload)) => {
+                            log::info!("got graph:rename message for {}", payload.from);
+                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.from)
+                                .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&GraphErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize graph:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let rename_result = runtime
+                                .write()
+                                .expect("lock poisoned")
+                                .rename_graph(&payload.from, &payload.to);
+                            match rename_result {
+                                Ok(()) => {
+                                    if client_capabilities.graph.as_deref() == Some(&payload.from) {
+                                        client_capabilities.graph = Some(payload.to.clone());
+                                    }
+                                    graph_inout
+                                        .lock()
+                                        .expect("lock poisoned")
+                                        .move_outport_subscriptions(
+                                            &payload.from,
+                                            Some(&payload.to),
+                                        );
+                                    log::info!("response: sending graph:renamed response");
+                                    send_and_broadcast(
+                                        &graph_inout,
+                                        &mut websocket,
+                                        &GraphRenamedResponse::new(&payload),
+                                        "graph:renamed",
+                                    );
+                                }
+                                Err(err) => {
+                                    log::error!("renaming graph failed: {}", err);
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
+                                            ))
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                }
+                            }
+                        }
+
+                        FBPMessage::Graph(GraphMessage::Switch(payload)) => {
+                            log::info!("got graph:switch message for {}", payload.id);
+                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.id)
+                                .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&GraphErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize graph:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let switch_result = runtime
+                                .write()
+                                .expect("lock poisoned")
+                                .switch_graph(&payload.id);
+                            match switch_result {
+                                Ok(()) => {
+                                    client_capabilities.graph = Some(payload.id.clone());
+                                    log::info!("response: sending graph:switched response");
+                                    send_and_broadcast(
+                                        &graph_inout,
+                                        &mut websocket,
+                                        &GraphSwitchedResponse::new(payload.id),
+                                        "graph:switched",
+                                    );
+                                }
+                                Err(err) => {
+                                    log::error!("switching graph failed: {}", err);
                                     websocket
                                         .send(Message::text(
                                             serde_json::to_string(&GraphErrorResponse::new(
@@ -982,25 +1622,20 @@ impl FlowdServer {
                                         .expect("failed to write message into websocket");
                                     continue;
                                 }
-                                let target_graph =
-                                    match get_graph_by_name(&runtime, &graph, &graph_name) {
-                                        Ok(graph_arc) => graph_arc,
-                                        Err(_err) => {
-                                            websocket
-                                                .send(Message::text(
-                                                    serde_json::to_string(
-                                                        &GraphErrorResponse::new(String::from(
-                                                            "Requested graph not found",
-                                                        )),
-                                                    )
-                                                    .expect(
-                                                        "failed to serialize graph:error response",
-                                                    ),
+                                let target_graph = match get_graph_by_name(&runtime, &graph_name) {
+                                    Ok(graph_arc) => graph_arc,
+                                    Err(_err) => {
+                                        websocket
+                                            .send(Message::text(
+                                                serde_json::to_string(&GraphErrorResponse::new(
+                                                    String::from("Requested graph not found"),
                                                 ))
-                                                .expect("failed to write message into websocket");
-                                            continue;
-                                        }
-                                    };
+                                                .expect("failed to serialize graph:error response"),
+                                            ))
+                                            .expect("failed to write message into websocket");
+                                        continue;
+                                    }
+                                };
                                 let add_node_result = {
                                     let mut graph_write =
                                         target_graph.write().expect("lock poisoned");
@@ -1021,6 +1656,27 @@ impl FlowdServer {
                                 };
                                 match add_node_result {
                                     Ok(response) => {
+                                        let proc_name = response.name.clone();
+                                        let component_name = response.component.clone();
+                                        let components_read =
+                                            components.read().expect("lock poisoned");
+                                        apply_to_running_network(
+                                            &runtime,
+                                            &graph_name,
+                                            &mut websocket,
+                                            |runtime_write| {
+                                                runtime_write
+                                                    .live_add_node(
+                                                        &graph_name,
+                                                        &proc_name,
+                                                        &component_name,
+                                                        &components_read,
+                                                        graph_inout.clone(),
+                                                    )
+                                                    .map(|()| None)
+                                            },
+                                        );
+                                        drop(components_read);
                                         log::info!("response: sending graph:addnode response");
                                         websocket
                                             .send(Message::text(
@@ -1082,27 +1738,54 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let remove_node_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
-                                graph_write.remove_node(payload.graph.clone(), payload.name.clone())
+                                // a subgraph node runs as several processes, found by its component
+                                let component_name = graph_write
+                                    .nodes
+                                    .get(&payload.name)
+                                    .map(|node| node.component.clone())
+                                    .unwrap_or_default();
+                                graph_write
+                                    .remove_node(payload.graph.clone(), payload.name.clone())
+                                    .map(|removed_edges| (component_name, removed_edges))
                             };
                             match remove_node_result {
-                                Ok(removed_edges) => {
+                                Ok((component_name, removed_edges)) => {
+                                    let components_read =
+                                        components.read().expect("lock poisoned");
+                                    apply_to_running_network(
+                                        &runtime,
+                                        &payload.graph,
+                                        &mut websocket,
+                                        |runtime_write| {
+                                            runtime_write
+                                                .live_remove_node(
+                                                    &payload.graph,
+                                                    &payload.name,
+                                                    &component_name,
+                                                    &components_read,
+                                                    &removed_edges,
+                                                    graph_inout.clone(),
+                                                )
+                                                .map(Some)
+                                        },
+                                    );
+                                    drop(components_read);
                                     for removed_edge in removed_edges {
                                         websocket
                                             .send(Message::text(
@@ -1159,21 +1842,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let rename_node_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.rename_node(
@@ -1226,21 +1908,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let change_node_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.change_node(
@@ -1299,21 +1980,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             //TODO optimize clone here
                             let add_edge_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
@@ -1324,6 +2004,37 @@ impl FlowdServer {
                             };
                             match add_edge_result {
                                 Ok(_) => {
+                                    let edge = GraphEdge::from(payload.clone());
+                                    // graph locks are taken before the runtime lock
+                                    let contract = target_graph
+                                        .read()
+                                        .expect("lock poisoned")
+                                        .nodes
+                                        .get(&edge.source.process)
+                                        .and_then(|node| {
+                                            components
+                                                .read()
+                                                .expect("lock poisoned")
+                                                .outport_contract(
+                                                    &node.component,
+                                                    &edge.source.port,
+                                                )
+                                        });
+                                    apply_to_running_network(
+                                        &runtime,
+                                        &payload.graph,
+                                        &mut websocket,
+                                        |runtime_write| {
+                                            runtime_write
+                                                .live_add_edge(
+                                                    &payload.graph,
+                                                    &edge,
+                                                    contract,
+                                                    graph_inout.clone(),
+                                                )
+                                                .map(|()| None)
+                                        },
+                                    );
                                     log::info!("response: sending graph:addedge response");
                                     websocket
                                         .send(Message::text(
@@ -1364,21 +2075,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let remove_edge_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.remove_edge(
@@ -1390,6 +2100,21 @@ impl FlowdServer {
                             match remove_edge_result {
                                 //TODO optimize any way to avoid these clones?
                                 Ok(_) => {
+                                    apply_to_running_network(
+                                        &runtime,
+                                        &payload.graph,
+                                        &mut websocket,
+                                        |runtime_write| {
+                                            runtime_write
+                                                .live_remove_edge(
+                                                    &payload.graph,
+                                                    &payload.src,
+                                                    &payload.tgt,
+                                                    graph_inout.clone(),
+                                                )
+                                                .map(Some)
+                                        },
+                                    );
                                     log::info!("response: sending graph:removeedge response");
                                     websocket
                                         .send(Message::text(
@@ -1432,21 +2157,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let change_edge_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.change_edge(
@@ -1501,27 +2225,44 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let add_initial_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.add_initialip(payload.clone())
                             };
                             match add_initial_result {
                                 Ok(_) => {
+                                    let edge = GraphEdge::from(payload.clone());
+                                    if edge.data.is_some() {
+                                        apply_to_running_network(
+                                            &runtime,
+                                            &payload.graph,
+                                            &mut websocket,
+                                            |runtime_write| {
+                                                runtime_write
+                                                    .live_add_edge(
+                                                        &payload.graph,
+                                                        &edge,
+                                                        None, // IIPs come from no process
+                                                        graph_inout.clone(),
+                                                    )
+                                                    .map(|()| None)
+                                            },
+                                        );
+                                    }
                                     log::info!("response: sending graph:addinitial response");
                                     websocket
                                         .send(Message::text(
@@ -1564,21 +2305,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let remove_initial_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.remove_initialip(payload.clone())
@@ -1631,21 +2371,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let response = GraphAddinportResponse::from_request(payload.clone());
                             let add_inport_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
@@ -1693,21 +2432,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let remove_inport_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.remove_inport(payload.public)
@@ -1756,21 +2494,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             log::info!("response: sending graph:renameinport response");
                             let rename_inport_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
@@ -1818,22 +2555,21 @@ impl FlowdServer {
                                     ))
                                     .expect("failed to write message into websocket");
                                 continue;
-                            }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            }
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let response = GraphAddoutportResponse::from_request(payload.clone());
                             let add_outport_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
@@ -1881,21 +2617,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let remove_outport_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.remove_outport(payload.public.clone())
@@ -1944,21 +2679,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             log::info!("response: sending graph:renameoutport response");
                             let rename_outport_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
@@ -2007,21 +2741,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let add_group_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.add_group(
@@ -2077,21 +2810,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let remove_group_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.remove_group(payload.graph, payload.name)
@@ -2140,21 +2872,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let rename_group_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.rename_group(payload.graph, payload.from, payload.to)
@@ -2203,21 +2934,20 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(err) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&GraphErrorResponse::new(
-                                                    err.to_string(),
-                                                ))
-                                                .expect("failed to serialize graph:error response"),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(err) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&GraphErrorResponse::new(
+                                                err.to_string(),
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
                             let change_group_result = {
                                 let mut graph_write = target_graph.write().expect("lock poisoned");
                                 graph_write.change_group(
@@ -2453,12 +3183,8 @@ impl FlowdServer {
                         // protocol:runtime
                         FBPMessage::Runtime(RuntimeMessage::Packet(payload)) => {
                             log::info!("got runtime:packet message");
-                            if validate_secret(
-                                &runtime,
-                                payload.secret.as_ref(),
-                                &runtime.read().expect("lock poisoned").graph,
-                            )
-                            .is_err()
+                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.graph)
+                                .is_err()
                             {
                                 websocket
                                     .send(Message::text(
@@ -2470,8 +3196,14 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            //TODO or maybe better send this to graph instead of runtime? in future for multi-graph support, yes.
-                            match Runtime::packet(&payload, graph_inout.clone(), runtime.clone()) {
+                            // who sends into the graph inports gets what leaves the outports
+                            subscribe_outports(&graph_inout, &websocket, &payload.graph);
+                            match Runtime::packet(
+                                &payload,
+                                graph_inout.clone(),
+                                runtime.clone(),
+                                components.clone(),
+                            ) {
                                 Ok(_) => {
                                     log::info!("response: sending runtime:packetsent response");
                                     websocket
@@ -2500,6 +3232,56 @@ impl FlowdServer {
                             }
                         }
 
+                        FBPMessage::Runtime(RuntimeMessage::Loglevel(payload)) => {
+                            log::info!("got runtime:loglevel message");
+                            if validate_secret(&runtime, payload.secret.as_ref(), &payload.graph)
+                                .is_err()
+                            {
+                                websocket
+                                    .send(Message::text(
+                                        serde_json::to_string(&RuntimeErrorResponse::new(
+                                            "invalid secret token".to_string(),
+                                        ))
+                                        .expect("failed to serialize runtime:error response"),
+                                    ))
+                                    .expect("failed to write message into websocket");
+                                continue;
+                            }
+                            let result = runtime
+                                .read()
+                                .expect("lock poisoned")
+                                .set_log_level(&payload);
+                            match result {
+                                Ok(level) => {
+                                    log::info!("response: sending runtime:loglevel response");
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&RuntimeLoglevelMessage::new(
+                                                payload.graph,
+                                                payload.node,
+                                                level,
+                                            ))
+                                            .expect(
+                                                "failed to serialize runtime:loglevel response",
+                                            ),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                }
+                                Err(err) => {
+                                    log::error!("runtime.set_log_level() failed: {}", err);
+                                    log::info!("response: sending runtime:error response");
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&RuntimeErrorResponse::new(
+                                                err.to_string(),
+                                            ))
+                                            .expect("failed to serialize runtime:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                }
+                            }
+                        }
+
                         // according to fbp-protocol, this is invalid to be sent from the client (there is no input/packetsent message defined) (TODO clarify with flowbased-devs)
                         //TODO maybe handle this a level higher in list of FBPMessage variants?
                         FBPMessage::Runtime(RuntimeMessage::Packetsent(_payload)) => {
@@ -2533,7 +3315,7 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            if get_graph_by_name(&runtime, &graph, &payload.graph).is_err() {
+                            if get_graph_by_name(&runtime, &payload.graph).is_err() {
                                 websocket
                                     .send(Message::text(
                                         serde_json::to_string(&NetworkErrorResponse::new(
@@ -2597,25 +3379,51 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            let target_graph =
-                                match get_graph_by_name(&runtime, &graph, &payload.graph) {
-                                    Ok(graph_arc) => graph_arc,
-                                    Err(_) => {
-                                        websocket
-                                            .send(Message::text(
-                                                serde_json::to_string(&NetworkErrorResponse::new(
-                                                    String::from("Requested graph not found"),
-                                                    String::from(""),
-                                                    payload.graph,
-                                                ))
-                                                .expect(
-                                                    "failed to serialize network:error response",
-                                                ),
+                            let target_graph = match get_graph_by_name(&runtime, &payload.graph) {
+                                Ok(graph_arc) => graph_arc,
+                                Err(_) => {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(&NetworkErrorResponse::new(
+                                                String::from("Requested graph not found"),
+                                                String::from(""),
+                                                payload.graph,
                                             ))
-                                            .expect("failed to write message into websocket");
-                                        continue;
-                                    }
-                                };
+                                            .expect("failed to serialize network:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                    continue;
+                                }
+                            };
+                            // report every graph problem so that editors can highlight them,
+                            // start() then fails with a network:error
+                            let validation_result = {
+                                let graph_read = target_graph.read().expect("lock poisoned");
+                                let components_read = components.read().expect("lock poisoned");
+                                let runtime_read = runtime.read().expect("lock poisoned");
+                                runtime_read.validate_graph(&graph_read, &components_read)
+                            };
+                            if let Err(errors) = validation_result {
+                                log::info!(
+                                    "response: sending {} graph:error response(s)",
+                                    errors.len()
+                                );
+                                for error in errors.iter() {
+                                    websocket
+                                        .send(Message::text(
+                                            serde_json::to_string(
+                                                &GraphErrorResponse::from_validation(
+                                                    payload.graph.clone(),
+                                                    error,
+                                                ),
+                                            )
+                                            .expect("failed to serialize graph:error response"),
+                                        ))
+                                        .expect("failed to write message into websocket");
+                                }
+                            }
+                            // subscribe first, so that the client sees the outports connect
+                            subscribe_outports(&graph_inout, &websocket, &payload.graph);
                             let start_result = {
                                 let graph_read = target_graph.read().expect("lock poisoned");
                                 let components_read = components.read().expect("lock poisoned");
@@ -2634,7 +3442,7 @@ impl FlowdServer {
                                 Ok(()) => {
                                     let runtime_status = runtime.read().expect("lock poisoned");
                                     let status_payload = NetworkStartedResponsePayload::from(
-                                        runtime_status.status_snapshot(),
+                                        runtime_status.status_snapshot(&payload.graph),
                                     );
                                      log::info!("response: sending network:started response");
                                      websocket
@@ -2646,9 +3454,16 @@ impl FlowdServer {
                                          ))
                                          .expect("failed to write message into websocket");
 
-                                     // Broadcast network:status update to all clients
-                                     let status_message = NetworkStatusMessage::new(NetworkStatusPayload::new(&runtime_status.status_snapshot()));
-                                     broadcast_to_clients(&graph_inout, &status_message, "network:status");
+                                    // Broadcast network:status update to all clients
+                                    let status_message =
+                                        NetworkStatusMessage::new(NetworkStatusPayload::new(
+                                            &runtime_status.status_snapshot(&payload.graph),
+                                        ));
+                                    broadcast_to_clients(
+                                        &graph_inout,
+                                        &status_message,
+                                        "network:status",
+                                    );
 
                                      drop(runtime_status);
 
@@ -2689,7 +3504,7 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            if get_graph_by_name(&runtime, &graph, &payload.graph).is_err() {
+                            if get_graph_by_name(&runtime, &payload.graph).is_err() {
                                 websocket
                                     .send(Message::text(
                                         serde_json::to_string(&NetworkErrorResponse::new(
@@ -2702,33 +3517,24 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            if runtime.read().expect("lock poisoned").status.graph != payload.graph
-                            {
-                                websocket
-                                    .send(Message::text(
-                                        serde_json::to_string(&NetworkErrorResponse::new(
-                                            String::from(
-                                                "requested graph is not the running network",
-                                            ),
-                                            String::from(""),
-                                            payload.graph,
-                                        ))
-                                        .expect("failed to serialize network:error response"),
-                                    ))
-                                    .expect("failed to write message into websocket");
-                                continue;
-                            }
                             // Compatibility: the test suite expects network:stop to ACK quickly even when
                             // network:start has already transitioned the status to running=false.
                             // In that case, avoid the potentially long shutdown path and mark the runtime
                             // as explicitly stopped for this graph.
-                            if !runtime.read().expect("lock poisoned").status.running {
+                            if !runtime
+                                .read()
+                                .expect("lock poisoned")
+                                .graph_status(&payload.graph)
+                                .running
+                            {
                                 let status_snapshot = {
                                     let mut runtime_write = runtime.write().expect("lock poisoned");
-                                    runtime_write.status.graph = payload.graph.clone();
-                                    runtime_write.status.started = false;
-                                    runtime_write.status.running = false;
-                                    runtime_write.status_snapshot()
+                                    let status =
+                                        &mut runtime_write.network_mut(&payload.graph).status;
+                                    status.graph = payload.graph.clone();
+                                    status.started = false;
+                                    status.running = false;
+                                    runtime_write.status_snapshot(&payload.graph)
                                 };
                                 log::info!(
                                     "response: sending network:stopped response (already stopped)"
@@ -2750,10 +3556,11 @@ impl FlowdServer {
                             }
                             let status_snapshot = {
                                 let mut runtime_write = runtime.write().expect("lock poisoned");
-                                runtime_write.status.graph = payload.graph.clone();
-                                runtime_write.status.started = false;
-                                runtime_write.status.running = false;
-                                runtime_write.status_snapshot()
+                                let status = &mut runtime_write.network_mut(&payload.graph).status;
+                                status.graph = payload.graph.clone();
+                                status.started = false;
+                                status.running = false;
+                                runtime_write.status_snapshot(&payload.graph)
                             };
                             log::info!("response: sending network:stop response");
                             websocket
@@ -2772,13 +3579,14 @@ impl FlowdServer {
                             // Complete runtime teardown asynchronously so protocol ACK stays fast.
                             let runtime_clone = runtime.clone();
                             let graph_inout_clone = graph_inout.clone();
+                            let graph_name = payload.graph.clone();
                             thread::Builder::new()
                                 .name("network-stop-async".into())
                                 .spawn(move || {
                                     if let Err(err) = runtime_clone
                                         .write()
                                         .expect("lock poisoned")
-                                        .stop(graph_inout_clone, false)
+                                        .stop(&graph_name, graph_inout_clone, false)
                                     {
                                         log::error!(
                                             "runtime.stop() failed asynchronously: {}",
@@ -2806,7 +3614,7 @@ impl FlowdServer {
                                     .expect("failed to write message into websocket");
                                 continue;
                             }
-                            if get_graph_by_name(&runtime, &graph, &payload.graph).is_err() {
+                            if get_graph_by_name(&runtime, &payload.graph).is_err() {
                                 websocket
                                     .send(Message::text(
                                         serde_json::to_string(&NetworkErrorResponse::new(
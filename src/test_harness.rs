@@ -55,6 +55,7 @@ impl TestHarness {
                     height: Some(72),
                     label: Some(format!("CUT-{}", node_id)),
                     icon: None,
+                    restart: None,
                 },
             )
             .expect("Failed to add component under test");
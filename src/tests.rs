@@ -1,6 +1,8 @@
-    use super::bench_api::{linear_harness_direct, BenchRuntimeHarness};
+    use super::bench_api::{linear_harness_direct, subgraph_harness_direct, BenchRuntimeHarness};
     use super::{
-        Graph, GraphAddinitialRequestPayload, GraphEdge, GraphEdgeMetadata, GraphIIPSpecNetwork,
+        build_component_library, index_ports, ComponentComponentPayload, ComponentLibrary,
+        ComponentPort, EdgeJournal, FbpMessage, Graph, ProcessEdge, ProcessEdgeSink, ProcessEdgeSource,
+        GraphAddinitialRequestPayload, GraphEdge, GraphEdgeMetadata, GraphIIPSpecNetwork,
         GraphNodeMetadata, GraphNodeSpec, GraphNodeSpecNetwork, GraphPort, GraphPortMetadata,
     };
     use std::any::Any;
@@ -78,6 +80,7 @@
                     height: Some(72),
                     label: Some("file".to_string()),
                     icon: None,
+                    restart: None,
                 },
             )
             .expect("failed to add FileReader node");
@@ -93,6 +96,7 @@
                     height: Some(72),
                     label: Some("out".to_string()),
                     icon: None,
+                    restart: None,
                 },
             )
             .expect("failed to add Output node");
@@ -108,6 +112,7 @@
                     height: Some(72),
                     label: Some("drop".to_string()),
                     icon: None,
+                    restart: None,
                 },
             )
             .expect("failed to add Drop node");
@@ -234,6 +239,7 @@
                     height: Some(72),
                     label: Some("source".to_string()),
                     icon: None,
+                    restart: None,
                 },
             )
             .expect("failed to add source Repeat node");
@@ -250,6 +256,7 @@
                     height: Some(72),
                     label: Some("target".to_string()),
                     icon: None,
+                    restart: None,
                 },
             )
             .expect("failed to add target Repeat node");
@@ -320,3 +327,2263 @@
 
         harness.stop().expect("runtime stop failed");
     }
+
+    #[test]
+    fn running_network_can_be_rewired_without_restart() {
+        let harness = linear_harness_direct("hot_graph_mutation");
+        harness.start().expect("runtime failed to start");
+
+        harness
+            .send_data_to_inport("IN", b"one")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 1, Duration::from_secs(3))
+            .expect("did not receive packet through original network");
+
+        // insert a node between RepeatA and RepeatB
+        harness
+            .add_node_live("Repeat", "Middle")
+            .expect("failed to add node to running network");
+        harness
+            .remove_edge_live(("RepeatA", "OUT"), ("RepeatB", "IN"))
+            .expect("failed to remove edge from running network");
+        harness
+            .add_edge_live(("RepeatA", "OUT"), ("Middle", "IN"))
+            .expect("failed to connect RepeatA -> Middle");
+        harness
+            .add_edge_live(("Middle", "OUT"), ("RepeatB", "IN"))
+            .expect("failed to connect Middle -> RepeatB");
+        assert!(harness.is_running(), "network stopped during rewiring");
+
+        harness
+            .send_data_to_inport("IN", b"two")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 1, Duration::from_secs(3))
+            .expect("did not receive packet through inserted node");
+        harness
+            .wait_for_node_work_units_at_least("Middle", 1, Duration::from_secs(3))
+            .expect("inserted node did not process the packet");
+
+        // take it out again
+        harness
+            .remove_node_live("Middle")
+            .expect("failed to remove node from running network");
+        harness
+            .add_edge_live(("RepeatA", "OUT"), ("RepeatB", "IN"))
+            .expect("failed to reconnect RepeatA -> RepeatB");
+        assert!(harness.node_work_units("Middle").is_none());
+
+        harness
+            .send_data_to_inport("IN", b"three")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 1, Duration::from_secs(3))
+            .expect("did not receive packet after removing node");
+
+        harness.assert_outputs_sequence_equal(
+            "OUT",
+            &[b"one".as_ref(), b"two".as_ref(), b"three".as_ref()],
+        );
+        harness.stop().expect("runtime stop failed");
+    }
+
+    #[test]
+    fn graph_runs_as_subgraph_component_inside_other_graph() {
+        let harness = subgraph_harness_direct("subgraph_parent");
+        harness.start().expect("runtime failed to start");
+
+        harness
+            .send_data_to_inport("IN", b"nested")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 1, Duration::from_secs(3))
+            .expect("did not receive packet through subgraph");
+        harness.assert_outputs_sequence_equal("OUT", &[b"nested".as_ref()]);
+
+        // subgraph nodes are inlined into the parent network
+        assert!(harness.node_work_units("Stage/RepeatA").is_some());
+        assert!(harness.node_work_units("Stage/RepeatB").is_some());
+        assert!(harness.node_work_units("Stage").is_none());
+        harness.stop().expect("runtime stop failed");
+    }
+
+    #[test]
+    fn running_subgraph_node_is_removed_with_its_processes() {
+        let harness = subgraph_harness_direct("subgraph_removal");
+        harness.start().expect("runtime failed to start");
+        harness
+            .send_data_to_inport("IN", b"nested")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 1, Duration::from_secs(3))
+            .expect("did not receive packet through subgraph");
+        assert!(harness.node_work_units("Stage/RepeatA").is_some());
+
+        harness
+            .remove_node_live("Stage")
+            .expect("failed to remove subgraph node from running network");
+        assert!(harness.node_work_units("Stage/RepeatA").is_none());
+        assert!(harness.node_work_units("Stage/RepeatB").is_none());
+        assert!(harness.node_work_units("First").is_some());
+        assert!(harness.is_running(), "network stopped while removing node");
+        harness.stop().expect("runtime stop failed");
+    }
+
+    #[test]
+    fn validation_errors_inside_subgraphs_name_the_subgraph_node() {
+        use std::sync::{Arc, RwLock};
+
+        let metadata = || GraphNodeMetadata {
+            x: 0,
+            y: 0,
+            width: None,
+            height: None,
+            label: None,
+            icon: None,
+            restart: None,
+        };
+        let mut inner = Graph::new(
+            "validate_inner".to_string(),
+            "subgraph".to_string(),
+            "test".to_string(),
+        );
+        inner
+            .add_node(
+                "validate_inner".to_string(),
+                "NoSuchComponent".to_string(),
+                "Broken".to_string(),
+                metadata(),
+            )
+            .expect("failed to add node to subgraph");
+        let mut parent = Graph::new(
+            "validate_parent".to_string(),
+            "graph using the subgraph".to_string(),
+            "test".to_string(),
+        );
+        parent
+            .add_node(
+                "validate_parent".to_string(),
+                "validate_inner".to_string(),
+                "Stage".to_string(),
+                metadata(),
+            )
+            .expect("failed to add subgraph node");
+        let mut runtime = super::Runtime::new("validate_parent".to_string());
+        runtime
+            .graphs
+            .add_graph("validate_inner".to_string(), Arc::new(RwLock::new(inner)));
+
+        let components = build_component_library();
+        let errors = runtime
+            .validate_graph(&parent, &components.read().expect("lock poisoned"))
+            .expect_err("graph with unknown component in subgraph must not validate");
+        assert_eq!(errors.len(), 1, "unexpected validation result: {:#?}", errors);
+        assert_eq!(errors[0].node.as_deref(), Some("Stage"));
+        assert_eq!(errors[0].port, None);
+        assert!(errors[0].message.contains("Stage/Broken"));
+    }
+
+    #[test]
+    fn subgraph_containing_itself_is_rejected_on_start() {
+        let harness = subgraph_harness_direct("subgraph_recursive");
+        harness
+            .add_node_live("subgraph_recursive", "Again")
+            .expect("failed to add node to stopped graph");
+
+        let err = harness.start().expect_err("recursive subgraph must not start");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!harness.is_running());
+    }
+
+    #[test]
+    fn graphs_run_side_by_side_and_stop_independently() {
+        let harness = linear_harness_direct("side_by_side_main");
+        harness.start().expect("runtime failed to start main graph");
+        harness
+            .start_linear_graph("side_by_side_other")
+            .expect("runtime failed to start second graph next to running graph");
+        assert!(harness.is_running());
+        assert!(harness.is_graph_running("side_by_side_other"));
+
+        let err = harness
+            .start_linear_graph("side_by_side_other")
+            .expect_err("already running graph must not start twice");
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+        harness
+            .stop_graph("side_by_side_other")
+            .expect("runtime failed to stop second graph");
+        assert!(!harness.is_graph_running("side_by_side_other"));
+        assert!(harness.is_running());
+
+        harness
+            .send_data_to_inport("IN", b"still-alive")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 1, Duration::from_secs(2))
+            .expect("main graph stopped processing after second graph was stopped");
+        harness.assert_outputs_sequence_equal("OUT", &[b"still-alive"]);
+
+        harness.stop().expect("runtime failed to stop main graph");
+        assert!(!harness.is_running());
+    }
+
+    #[test]
+    fn graph_validation_reports_all_problems_before_start() {
+        let port =
+            |name: &str, allowed_type: &str, required: bool, is_arrayport: bool| ComponentPort {
+                name: name.to_string(),
+                allowed_type: allowed_type.to_string(),
+                required,
+                is_arrayport,
+                ..ComponentPort::default()
+            };
+        let components = ComponentLibrary::new(vec![
+            ComponentComponentPayload {
+                name: "Producer".to_string(),
+                in_ports: vec![],
+                out_ports: vec![port("OUT", "string", true, false)],
+                ..ComponentComponentPayload::default()
+            },
+            ComponentComponentPayload {
+                name: "Consumer".to_string(),
+                in_ports: vec![
+                    port("IN", "number", false, false),
+                    port("CONF", "string", true, false),
+                    port("MULTI", "any", false, true),
+                ],
+                out_ports: vec![],
+                ..ComponentComponentPayload::default()
+            },
+        ]);
+
+        let graph_name = "validation";
+        let mut graph = Graph::new(
+            graph_name.to_string(),
+            "graph with several problems".to_string(),
+            "test".to_string(),
+        );
+        for (name, component) in [
+            ("producer", "Producer"),
+            ("consumer", "Consumer"),
+            ("other", "Consumer"),
+            ("mystery", "NoSuchComponent"),
+        ] {
+            graph
+                .add_node(
+                    graph_name.to_string(),
+                    component.to_string(),
+                    name.to_string(),
+                    GraphNodeMetadata {
+                        x: 0,
+                        y: 0,
+                        width: None,
+                        height: None,
+                        label: None,
+                        icon: None,
+                        restart: None,
+                    },
+                )
+                .expect("failed to add node");
+        }
+        let edge = |source: (&str, &str), target: (&str, &str)| GraphEdge {
+            source: GraphNodeSpec {
+                process: source.0.to_string(),
+                port: source.1.to_string(),
+                index: None,
+            },
+            data: None,
+            target: GraphNodeSpec {
+                process: target.0.to_string(),
+                port: target.1.to_string(),
+                index: None,
+            },
+            metadata: GraphEdgeMetadata::new(None, None, None),
+        };
+        graph.edges = vec![
+            // string into number, and second edge on the non-addressable outport
+            edge(("producer", "OUT"), ("consumer", "IN")),
+            edge(("producer", "OUT"), ("other", "MULTI")),
+            // addressable inport may take several edges
+            edge(("producer", "OUT"), ("consumer", "MULTI")),
+            edge(("producer", "NOPE"), ("consumer", "MULTI")),
+            edge(("ghost", "OUT"), ("other", "CONF")),
+        ];
+
+        let errors = graph
+            .validate(&components)
+            .expect_err("graph with problems must not validate");
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        let expected = [
+            "node mystery uses unknown component NoSuchComponent",
+            "type mismatch on edge producer.OUT -> consumer.IN: string is not compatible with number",
+            "connection to nonexistent outport NOPE of node producer (Producer)",
+            "connection to nonexistent node ghost",
+            "3 edges on non-addressable outport producer.OUT",
+            "required inport CONF of node consumer (Consumer) is not connected",
+        ];
+        assert_eq!(messages, expected, "unexpected validation result: {:#?}", errors);
+        assert_eq!(errors[1].node(), Some("consumer"));
+        assert_eq!(errors[1].port(), Some("IN"));
+
+        graph.nodes.remove("mystery");
+        graph.nodes.remove("other");
+        graph.edges = vec![
+            edge(("producer", "OUT"), ("consumer", "MULTI")),
+            GraphEdge {
+                data: Some("conf".to_string()),
+                ..edge(("", ""), ("consumer", "CONF"))
+            },
+        ];
+        assert!(graph.validate(&components).is_ok());
+    }
+
+    #[test]
+    fn addressable_port_elements_keep_their_index() {
+        let ports = index_ports(
+            vec![
+                ("IN".to_string(), Some(3), "three"),
+                ("IN".to_string(), None, "unindexed"),
+                ("IN".to_string(), Some(0), "zero"),
+                ("OUT".to_string(), None, "out"),
+            ],
+            || "disconnected",
+        )
+        .expect("sparse indices must be accepted");
+        assert_eq!(
+            ports.get_vec("IN").expect("IN port missing"),
+            &vec!["zero", "unindexed", "disconnected", "three"]
+        );
+        assert_eq!(ports.get_vec("OUT").expect("OUT port missing"), &vec!["out"]);
+
+        let err = index_ports(
+            vec![
+                ("IN".to_string(), Some(1), "first"),
+                ("IN".to_string(), Some(1), "second"),
+            ],
+            || "disconnected",
+        )
+        .expect_err("index used twice must be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let err = index_ports(
+            vec![("IN".to_string(), Some(usize::MAX), "huge")],
+            || "disconnected",
+        )
+        .expect_err("index beyond the maximum must be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn sparse_array_inport_connection_delivers_packets() {
+        let graph_name = "sparse_array_inport";
+        let mut graph = Graph::new(
+            graph_name.to_string(),
+            "Repeat -> Muxer.IN[2]".to_string(),
+            "test".to_string(),
+        );
+        graph.inports.insert(
+            "IN".to_string(),
+            GraphPort {
+                process: "first".to_string(),
+                port: "IN".to_string(),
+                metadata: GraphPortMetadata { x: 0, y: 0 },
+            },
+        );
+        graph.outports.insert(
+            "OUT".to_string(),
+            GraphPort {
+                process: "mux".to_string(),
+                port: "OUT".to_string(),
+                metadata: GraphPortMetadata { x: 300, y: 0 },
+            },
+        );
+        for (component, name) in [("Repeat", "first"), ("Muxer", "mux")] {
+            graph
+                .add_node(
+                    graph_name.to_string(),
+                    component.to_string(),
+                    name.to_string(),
+                    GraphNodeMetadata {
+                        x: 0,
+                        y: 0,
+                        width: None,
+                        height: None,
+                        label: None,
+                        icon: None,
+                        restart: None,
+                    },
+                )
+                .expect("failed to add node");
+        }
+        graph
+            .add_edge(
+                graph_name.to_string(),
+                GraphEdge {
+                    source: GraphNodeSpec {
+                        process: "first".to_string(),
+                        port: "OUT".to_string(),
+                        index: None,
+                    },
+                    data: None,
+                    target: GraphNodeSpec {
+                        process: "mux".to_string(),
+                        port: "IN".to_string(),
+                        index: Some("2".to_string()),
+                    },
+                    metadata: GraphEdgeMetadata::new(None, None, None),
+                },
+            )
+            .expect("failed to add edge first.OUT -> mux.IN[2]");
+        let components = build_component_library();
+        assert!(graph
+            .validate(&components.read().expect("lock poisoned"))
+            .is_ok());
+
+        let harness = BenchRuntimeHarness::new(graph);
+        harness.start().expect("runtime failed to start");
+        harness
+            .send_data_to_inport("IN", b"indexed")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 1, Duration::from_secs(2))
+            .expect("packet on IN[2] did not reach Muxer output");
+        harness.assert_outputs_sequence_equal("OUT", &[b"indexed"]);
+        harness.stop().expect("runtime failed to stop");
+    }
+
+    fn state_test_dir(name: &str) -> std::path::PathBuf {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock before unix epoch")
+            .as_nanos();
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("flowd-{}-{}-{}", name, std::process::id(), unique));
+        dir
+    }
+
+    #[test]
+    fn persistent_edge_keeps_messages_not_processed() {
+        let dir = state_test_dir("edge-journal");
+        let path = dir.join("edge.journal");
+
+        let (sink, source) = ProcessEdge::new(8);
+        let mut source = ProcessEdgeSource::new(source);
+        let mut edge_sink = ProcessEdgeSink::new(sink, None, None, None);
+        // IIPs are pushed before the journal is enabled and never journaled
+        edge_sink
+            .push(FbpMessage::from_str("iip"))
+            .expect("failed to push IIP");
+        let journal_ack = edge_sink
+            .enable_journal(
+                EdgeJournal::open(&path).expect("failed to open journal"),
+                &mut source,
+            )
+            .expect("failed to enable journal");
+        for data in ["a", "b", "c"] {
+            edge_sink
+                .push(FbpMessage::from_str(data))
+                .expect("failed to push message");
+        }
+        assert_eq!(source.pop().expect("edge empty"), FbpMessage::from_str("iip"));
+        assert_eq!(source.pop().expect("edge empty"), FbpMessage::from_str("a"));
+        journal_ack.ack_taken(); // process() returned
+        // taken out, but process() did not return before the crash
+        assert_eq!(source.pop().expect("edge empty"), FbpMessage::from_str("b"));
+        edge_sink
+            .push(FbpMessage::from_bytes(b"d".to_vec()))
+            .expect("failed to push message");
+        drop(edge_sink); // network stop or crash
+        drop(journal_ack);
+
+        let mut journal = EdgeJournal::open(&path).expect("failed to reopen journal");
+        assert_eq!(
+            journal.take_pending(),
+            vec![
+                FbpMessage::from_str("b"),
+                FbpMessage::from_str("c"),
+                FbpMessage::from_bytes(b"d".to_vec()),
+            ]
+        );
+        drop(journal);
+
+        // replayed messages are delivered first, and once processed they are gone for good
+        let (sink, source) = ProcessEdge::new(8);
+        let mut source = ProcessEdgeSource::new(source);
+        let mut edge_sink = ProcessEdgeSink::new(sink, None, None, None);
+        let journal_ack = edge_sink
+            .enable_journal(
+                EdgeJournal::open(&path).expect("failed to open journal"),
+                &mut source,
+            )
+            .expect("failed to enable journal");
+        edge_sink
+            .push(FbpMessage::from_str("e"))
+            .expect("failed to push message");
+        let mut received = vec![];
+        while let Ok(message) = source.pop() {
+            received.push(message);
+        }
+        assert_eq!(
+            received,
+            vec![
+                FbpMessage::from_str("b"),
+                FbpMessage::from_str("c"),
+                FbpMessage::from_bytes(b"d".to_vec()),
+                FbpMessage::from_str("e"),
+            ]
+        );
+        journal_ack.ack_taken();
+        drop(edge_sink);
+        drop(journal_ack);
+        let journal = EdgeJournal::open(&path).expect("failed to reopen journal");
+        assert_eq!(journal.pending_len(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn persistent_edge_is_acknowledged_when_target_finishes_after_source() {
+        let dir = state_test_dir("edge-journal-finish");
+        let path = dir.join("edge.journal");
+
+        let (sink, source) = ProcessEdge::new(8);
+        let mut source = ProcessEdgeSource::new(source);
+        let mut edge_sink = ProcessEdgeSink::new(sink, None, None, None);
+        let journal_ack = edge_sink
+            .enable_journal(
+                EdgeJournal::open(&path).expect("failed to open journal"),
+                &mut source,
+            )
+            .expect("failed to enable journal");
+        for data in ["a", "b"] {
+            edge_sink
+                .push(FbpMessage::from_str(data))
+                .expect("failed to push message");
+        }
+        drop(edge_sink); // source finished, both messages still in the edge
+
+        while source.pop().is_ok() {}
+        journal_ack.ack_all(); // target finished
+        drop(journal_ack);
+
+        let journal = EdgeJournal::open(&path).expect("failed to reopen journal");
+        assert_eq!(journal.pending_len(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn persistent_edge_replays_journal_on_network_start() {
+        let graph_name = "persistent_edge_replay";
+        let dir = state_test_dir("edge-replay");
+        // left over from a previous run: two messages RepeatB did not take out of the edge
+        let mut journal = EdgeJournal::open(
+            &dir.join(graph_name)
+                .join("RepeatA.OUT-RepeatB.IN.journal"),
+        )
+        .expect("failed to open journal");
+        journal
+            .append(&FbpMessage::from_bytes(b"left".to_vec()))
+            .expect("failed to journal message");
+        journal
+            .append(&FbpMessage::from_bytes(b"over".to_vec()))
+            .expect("failed to journal message");
+        drop(journal);
+
+        let harness = linear_harness_direct(graph_name);
+        harness.set_edge_journal_dir(dir.clone());
+        harness
+            .graph
+            .write()
+            .expect("lock poisoned")
+            .edges
+            .iter_mut()
+            .for_each(|edge| edge.metadata.persistent = Some(true));
+        harness.start().expect("runtime failed to start");
+        harness
+            .send_data_to_inport("IN", b"new")
+            .expect("runtime packet send failed");
+        harness
+            .wait_for_outport_data("OUT", 3, Duration::from_secs(2))
+            .expect("replayed packets did not reach graph outport");
+        harness.assert_outputs_sequence_equal("OUT", &[b"left", b"over", b"new"]);
+        harness.stop().expect("runtime failed to stop");
+
+        // everything was delivered, nothing to replay on the next start
+        let journal = EdgeJournal::open(
+            &dir.join(graph_name)
+                .join("RepeatA.OUT-RepeatB.IN.journal"),
+        )
+        .expect("failed to reopen journal");
+        assert_eq!(journal.pending_len(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn packet_encoding_round_trips_every_message_variant() {
+        use flowd_component_api::{
+            ControlEvent, FbpValue, GraphNodeSpecNetwork as ApiNodeSpec, TraceConnectEventPayload,
+            TraceDataEventPayload, TraceDisconnectEventPayload,
+        };
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        let src = ApiNodeSpec {
+            node: "A".to_string(),
+            port: "OUT".to_string(),
+            index: None,
+        };
+        let tgt = ApiNodeSpec {
+            node: "B".to_string(),
+            port: "IN".to_string(),
+            index: Some("2".to_string()),
+        };
+        let mut map = HashMap::new();
+        map.insert("text".to_string(), FbpValue::Text("x".into()));
+        map.insert("bytes".to_string(), FbpValue::Bytes(vec![0u8, 255].into()));
+        map.insert(
+            "list".to_string(),
+            FbpValue::List(Arc::new(vec![FbpValue::Null, FbpValue::Int(1)])),
+        );
+        let messages = vec![
+            FbpMessage::from_bytes(b"hello".to_vec()),
+            FbpMessage::from_bytes(vec![0xff, 0x00, 0xfe]),
+            FbpMessage::from_bytes(vec![]),
+            FbpMessage::from_str("hello"),
+            FbpMessage::from_str(""),
+            FbpMessage::Value(FbpValue::Null),
+            FbpMessage::Value(FbpValue::Bool(true)),
+            FbpMessage::Value(FbpValue::Int(-5)),
+            FbpMessage::Value(FbpValue::Int(i64::MAX)),
+            FbpMessage::Value(FbpValue::Int(i64::MIN)),
+            FbpMessage::Value(FbpValue::Float(1.5)),
+            FbpMessage::Value(FbpValue::Float(3.0)),
+            FbpMessage::Value(FbpValue::Text("text".into())),
+            FbpMessage::Value(FbpValue::Bytes(b"text".to_vec().into())),
+            FbpMessage::Value(FbpValue::List(Arc::new(vec![]))),
+            FbpMessage::Value(FbpValue::Map(Arc::new(map.clone()))),
+            FbpMessage::Control(ControlEvent::BeginGroup("g".to_string())),
+            FbpMessage::Control(ControlEvent::EndGroup("g".to_string())),
+            FbpMessage::Control(ControlEvent::BeginBracket("b".to_string())),
+            FbpMessage::Control(ControlEvent::EndBracket("b".to_string())),
+            FbpMessage::Control(ControlEvent::Disconnect),
+            FbpMessage::Control(ControlEvent::Drain),
+            FbpMessage::Control(ControlEvent::Discontinuity),
+            FbpMessage::Control(ControlEvent::Custom("c".to_string(), None)),
+            FbpMessage::Control(ControlEvent::Custom(
+                "c".to_string(),
+                Some(FbpValue::Map(Arc::new(map))),
+            )),
+            FbpMessage::TraceData(TraceDataEventPayload {
+                id: "A.OUT-B.IN[2]".to_string(),
+                src: src.clone(),
+                tgt: tgt.clone(),
+                graph: "main".to_string(),
+                data: "hello".to_string(),
+            }),
+            FbpMessage::TraceConnect(TraceConnectEventPayload {
+                id: "A.OUT-B.IN[2]".to_string(),
+                src: src.clone(),
+                tgt: tgt.clone(),
+                graph: "main".to_string(),
+            }),
+            FbpMessage::TraceDisconnect(TraceDisconnectEventPayload {
+                id: "A.OUT-B.IN[2]".to_string(),
+                src,
+                tgt,
+                graph: "main".to_string(),
+            }),
+        ];
+        for message in messages {
+            let encoded = message.encode().expect("failed to encode message");
+            assert_eq!(
+                FbpMessage::decode(&encoded).expect("failed to decode message"),
+                message
+            );
+            // truncated packets are rejected, not misread
+            assert!(FbpMessage::decode(&encoded[..encoded.len() - 1]).is_err());
+        }
+
+        // bytes and text with the same content stay distinguishable
+        assert_ne!(
+            FbpMessage::from_bytes(b"same".to_vec()).encode().unwrap(),
+            FbpMessage::from_str("same").encode().unwrap()
+        );
+
+        // packets of another encoding version are rejected
+        let mut encoded = FbpMessage::from_str("v").encode().unwrap();
+        assert_eq!(encoded[1], flowd_component_api::WIRE_FORMAT_VERSION);
+        encoded[1] = flowd_component_api::WIRE_FORMAT_VERSION + 1;
+        let err = FbpMessage::decode(&encoded).expect_err("decoded packet of unknown version");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn headless_run_returns_once_all_nodes_finished() {
+        let dir = state_test_dir("headless");
+        fs::create_dir_all(&dir).expect("failed to create test dir");
+        let graph_path = dir.join("batch.json");
+        fs::write(
+            &graph_path,
+            r#"{
+                "caseSensitive": true,
+                "properties": {"name": "headless_batch", "environment": {"type": "flowd", "content": ""}, "description": "", "icon": ""},
+                "inports": {}, "outports": {}, "groups": [],
+                "processes": {
+                    "repeat": {"component": "Repeat", "metadata": {"x": 0, "y": 0}},
+                    "output": {"component": "Output", "metadata": {"x": 100, "y": 0}},
+                    "drop": {"component": "Drop", "metadata": {"x": 200, "y": 0}}
+                },
+                "connections": [
+                    {"src": {"process": "", "port": ""}, "data": "hello", "tgt": {"process": "repeat", "port": "IN"}, "metadata": {}},
+                    {"src": {"process": "repeat", "port": "OUT"}, "tgt": {"process": "output", "port": "IN"}, "metadata": {}},
+                    {"src": {"process": "output", "port": "OUT"}, "tgt": {"process": "drop", "port": "IN"}, "metadata": {}}
+                ]
+            }"#,
+        )
+        .expect("failed to write graph file");
+
+        let started = std::time::Instant::now();
+        super::cli::run_graph_file(&graph_path).expect("headless run failed");
+        // well within the watchdog health check interval
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fbp_parser_reads_iips_exports_and_array_ports() {
+        let source = r#"
+            # @name parsed
+            # @description parser test
+            INPORT=split.IN:IN
+            OUTPORT=join.OUT:OUT
+            'mode=packets\'s' -> CONF counter(Count)
+            split(SplitLines) OUT -> IN counter OUT -> IN[1] join(Muxer), idle(Drop)
+            'a' -> IN[0] join
+        "#;
+        let graph = Graph::from_fbp("fallback", source).expect("failed to parse .fbp");
+
+        assert_eq!(graph.properties.name, "parsed");
+        assert_eq!(graph.properties.description, "parser test");
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.nodes["counter"].component, "Count");
+        assert_eq!(graph.nodes["join"].component, "Muxer");
+        assert_eq!(graph.nodes["idle"].component, "Drop");
+        assert_eq!(graph.inports["IN"].process, "split");
+        assert_eq!(graph.outports["OUT"].port, "OUT");
+
+        assert_eq!(graph.edges.len(), 4);
+        assert_eq!(graph.edges[0].data.as_deref(), Some("mode=packets's"));
+        assert_eq!(graph.edges[0].target.process, "counter");
+        assert_eq!(graph.edges[1].source.process, "split");
+        assert_eq!(graph.edges[1].target.process, "counter");
+        assert_eq!(graph.edges[2].source.process, "counter");
+        assert_eq!(graph.edges[2].target.index.as_deref(), Some("1"));
+        assert_eq!(graph.edges[3].target.index.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn fbp_parser_reports_line_and_column() {
+        let err = Graph::from_fbp("broken", "a(Repeat) OUT -> IN b(Drop)\nb OUT IN c")
+            .expect_err("missing arrow must not parse");
+        assert_eq!((err.line, err.column), (2, 7));
+        assert_eq!(err.message, "expected ->");
+
+        let err = Graph::from_fbp("broken", "'data' -> IN a\n")
+            .expect_err("node without component must not parse");
+        assert_eq!((err.line, err.column), (1, 14));
+
+        let err = Graph::from_fbp("broken", "a(Repeat) OUT -> IN b(Drop)\n'x' -> IN b(Repeat)")
+            .expect_err("conflicting components must not parse");
+        assert_eq!((err.line, err.column), (2, 11));
+    }
+
+    #[test]
+    fn fbp_serializer_output_parses_to_the_same_graph() {
+        let source = concat!(
+            "INPORT=a.IN:IN\n",
+            "'line\\n\\'quoted\\'' -> CONF b(Count)\n",
+            "a(Repeat) OUT[2] -> IN b OUT -> IN c(Drop)\n",
+            "lonely(Repeat)\n",
+        );
+        let graph = Graph::from_fbp("roundtrip", source).expect("failed to parse .fbp");
+        assert_eq!(graph.edges[0].data.as_deref(), Some("line\n'quoted'"));
+
+        let serialized = graph.to_fbp().expect("failed to serialize graph");
+        let reparsed = Graph::from_fbp("other", &serialized).expect("failed to parse serialized");
+        assert_eq!(reparsed.properties.name, "roundtrip");
+        assert_eq!(
+            reparsed.to_fbp().expect("failed to serialize graph"),
+            serialized
+        );
+        assert_eq!(reparsed.nodes.len(), 4);
+        assert_eq!(reparsed.nodes["lonely"].component, "Repeat");
+        assert_eq!(reparsed.edges[0].data, graph.edges[0].data);
+        assert_eq!(reparsed.edges[1].source.index.as_deref(), Some("2"));
+        assert_eq!(reparsed.inports["IN"].process, "a");
+    }
+
+    #[test]
+    fn fbp_serializer_rejects_names_the_parser_cannot_read() {
+        let mut graph = Graph::from_fbp("names", "a(Repeat) OUT -> IN b(Drop)\n")
+            .expect("failed to parse .fbp");
+        let node = graph.nodes.remove("b").expect("node b missing");
+        graph.nodes.insert("b c".to_string(), node);
+        graph.edges[0].target.process = "b c".to_string();
+        let err = graph
+            .to_fbp()
+            .expect_err("node name with a space must not be serialized");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("\"b c\""));
+    }
+
+    fn count_graph(graph_name: &str) -> Graph {
+        let mut graph = Graph::new(
+            graph_name.to_string(),
+            "IN -> Count -> OUT".to_string(),
+            "test".to_string(),
+        );
+        graph.inports.insert(
+            "IN".to_string(),
+            GraphPort {
+                process: "count".to_string(),
+                port: "IN".to_string(),
+                metadata: GraphPortMetadata { x: 0, y: 0 },
+            },
+        );
+        graph.outports.insert(
+            "OUT".to_string(),
+            GraphPort {
+                process: "count".to_string(),
+                port: "OUT".to_string(),
+                metadata: GraphPortMetadata { x: 300, y: 0 },
+            },
+        );
+        graph
+            .add_node(
+                graph_name.to_string(),
+                "Count".to_string(),
+                "count".to_string(),
+                GraphNodeMetadata {
+                    x: 0,
+                    y: 0,
+                    width: None,
+                    height: None,
+                    label: None,
+                    icon: None,
+                    restart: None,
+                },
+            )
+            .expect("failed to add node");
+        graph
+            .add_initialip(GraphAddinitialRequestPayload {
+                graph: graph_name.to_string(),
+                metadata: GraphEdgeMetadata::new(None, None, None),
+                src: GraphIIPSpecNetwork {
+                    data: "mode=packets".to_string(),
+                },
+                tgt: GraphNodeSpecNetwork {
+                    node: "count".to_string(),
+                    port: "CONF".to_string(),
+                    index: None,
+                },
+                secret: None,
+            })
+            .expect("failed to add IIP for Count.CONF");
+        graph
+    }
+
+    fn wait_for_count(harness: &BenchRuntimeHarness, expected: &[u8]) {
+        let started = std::time::Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            let _ = harness.wait_for_outport_data("OUT", 1, Duration::from_millis(50));
+            let reported = harness.collect_outputs("OUT");
+            if reported
+                .iter()
+                .any(|message| message.as_text().map(str::as_bytes) == Some(expected))
+            {
+                return;
+            }
+        }
+        panic!(
+            "Count did not report {}",
+            String::from_utf8_lossy(expected)
+        );
+    }
+
+    #[test]
+    fn node_state_is_restored_from_checkpoint_on_network_start() {
+        let graph_name = "checkpoint_restore";
+        let dir = state_test_dir("checkpoint");
+
+        let harness = BenchRuntimeHarness::new(count_graph(graph_name));
+        harness.set_checkpoint_dir(dir.clone());
+        harness.start().expect("runtime failed to start");
+        for _ in 0..3 {
+            harness
+                .send_data_to_inport("IN", b"x")
+                .expect("runtime packet send failed");
+        }
+        wait_for_count(&harness, b"3");
+        assert_eq!(harness.checkpoint().expect("checkpoint failed"), 1);
+        harness.stop().expect("runtime failed to stop");
+        assert!(dir.join("checkpoint_restore.checkpoint").exists());
+
+        // fresh runtime, as after a restart of flowd
+        let harness = BenchRuntimeHarness::new(count_graph(graph_name));
+        harness.set_checkpoint_dir(dir.clone());
+        harness.start().expect("runtime failed to start");
+        for _ in 0..2 {
+            harness
+                .send_data_to_inport("IN", b"x")
+                .expect("runtime packet send failed");
+        }
+        wait_for_count(&harness, b"5");
+        harness.stop().expect("runtime failed to stop");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn checkpoint_without_node_states_removes_stale_checkpoint() {
+        let graph_name = "checkpoint_stale";
+        let dir = state_test_dir("checkpoint-stale");
+
+        let harness = linear_harness_direct(graph_name);
+        harness.set_checkpoint_dir(dir.clone());
+        harness.start().expect("runtime failed to start");
+        // left over from an earlier run of the graph with stateful components
+        let path = dir.join("checkpoint_stale.checkpoint");
+        fs::create_dir_all(&dir).expect("failed to create test dir");
+        fs::write(&path, b"stale").expect("failed to write checkpoint");
+        assert_eq!(harness.checkpoint().expect("checkpoint failed"), 0);
+        assert!(!path.exists());
+        harness.stop().expect("runtime failed to stop");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn write_secrets_file(
+        dir: &std::path::Path,
+        entries: &[(&str, &str, &str)],
+    ) -> std::path::PathBuf {
+        let secrets = entries
+            .iter()
+            .map(|(graph, token, access)| {
+                let digest = super::auth::hash_secret(token)
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<String>();
+                serde_json::json!({"graph": graph, "sha256": digest, "access": access})
+            })
+            .collect::<Vec<_>>();
+        fs::create_dir_all(dir).expect("failed to create test dir");
+        let path = dir.join("secrets.json");
+        fs::write(&path, serde_json::json!({ "secrets": secrets }).to_string())
+            .expect("failed to write secrets file");
+        path
+    }
+
+    #[test]
+    fn secrets_file_grants_access_per_graph() {
+        let dir = state_test_dir("secrets");
+        let path = write_secrets_file(
+            &dir,
+            &[("main", "writer", "ReadWrite"), ("*", "watcher", "ReadOnly")],
+        );
+        let mut runtime = super::Runtime::default();
+        let secrets = super::auth::SecretStore::load(&path).expect("failed to load secrets");
+        runtime.set_secrets(secrets, false);
+        fn check(
+            runtime: &super::Runtime,
+            secret: Option<&str>,
+            graph: &str,
+            access: super::AccessLevel,
+        ) -> Result<(), std::io::Error> {
+            runtime.validate_secret_with_access(secret.map(String::from).as_ref(), graph, access)
+        }
+        use super::AccessLevel::{ReadOnly, ReadWrite};
+
+        assert!(check(&runtime, Some("writer"), "main", ReadWrite).is_ok());
+        assert!(check(&runtime, Some("writer"), "other", ReadOnly).is_err());
+        assert!(check(&runtime, Some("watcher"), "other", ReadOnly).is_ok());
+        let err = check(&runtime, Some("watcher"), "main", ReadWrite)
+            .expect_err("readonly secret allowed a write");
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(check(&runtime, Some("guess"), "main", ReadOnly).is_err());
+        // compatibility mode keeps accepting clients without secret
+        assert!(check(&runtime, Some(""), "main", ReadWrite).is_ok());
+
+        runtime.strict_auth = true;
+        assert!(check(&runtime, None, "main", ReadOnly).is_err());
+        assert!(check(&runtime, Some(""), "main", ReadOnly).is_err());
+        assert!(check(&runtime, Some("writer"), "main", ReadWrite).is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn secrets_file_rejects_plaintext_tokens() {
+        let dir = state_test_dir("secrets-plain");
+        fs::create_dir_all(&dir).expect("failed to create test dir");
+        let path = dir.join("secrets.json");
+        fs::write(
+            &path,
+            r#"{"secrets": [{"graph": "main", "sha256": "my-token", "access": "ReadWrite"}]}"#,
+        )
+        .expect("failed to write secrets file");
+        let err = super::auth::SecretStore::load(&path).expect_err("accepted plaintext token");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn tls_client_stream_clone_writes_while_reading() {
+        use std::io::{Read, Write};
+
+        let dir = state_test_dir("tls");
+        fs::create_dir_all(&dir).expect("failed to create test dir");
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()])
+            .expect("failed to generate certificate");
+        fs::write(dir.join("cert.pem"), certified.cert.pem()).expect("failed to write cert");
+        fs::write(dir.join("key.pem"), certified.key_pair.serialize_pem())
+            .expect("failed to write key");
+        let server_config =
+            super::tls::load_server_config(&dir.join("cert.pem"), &dir.join("key.pem"))
+                .expect("failed to load TLS config");
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().expect("no local address");
+        let server = thread::spawn(move || {
+            let (socket, _) = listener.accept().expect("accept failed");
+            let mut stream = super::tls::ClientStream::accept(socket, Some(&server_config))
+                .expect("TLS handshake failed");
+            let mut broadcast = stream.try_clone().expect("clone failed");
+            let reader = thread::spawn(move || {
+                let mut buf = [0u8; 4];
+                stream.read_exact(&mut buf).expect("read failed");
+                buf
+            });
+            // the handler thread is blocked reading, the clone must still get through
+            broadcast.write_all(b"pong").expect("write from clone failed");
+            broadcast.flush().expect("flush failed");
+            reader.join().expect("reader panicked")
+        });
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(certified.cert.der().clone()).expect("failed to add root");
+        let client_config = rustls::ClientConfig::builder_with_provider(std::sync::Arc::new(
+            rustls::crypto::aws_lc_rs::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .expect("no TLS protocol versions")
+        .with_root_certificates(roots)
+            .with_no_client_auth();
+        let server_name = rustls::pki_types::ServerName::try_from("localhost").expect("bad name");
+        let connection =
+            rustls::ClientConnection::new(std::sync::Arc::new(client_config), server_name)
+                .expect("failed to create client connection");
+        let mut client = rustls::StreamOwned::new(
+            connection,
+            std::net::TcpStream::connect(addr).expect("connect failed"),
+        );
+        let mut buf = [0u8; 4];
+        client.read_exact(&mut buf).expect("client read failed");
+        assert_eq!(&buf, b"pong");
+        client.write_all(b"ping").expect("client write failed");
+        client.flush().expect("client flush failed");
+        assert_eq!(&server.join().expect("server panicked"), b"ping");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn script_source(name: &str, language: &str, code: &str) -> super::ComponentSourcePayload {
+        super::ComponentSourcePayload {
+            name: name.to_owned(),
+            language: language.to_owned(),
+            library: String::from("scratch"),
+            code: code.to_owned(),
+            tests: String::new(),
+        }
+    }
+
+    #[test]
+    fn setsource_registers_and_saves_script_components() {
+        let dir = state_test_dir("setsource");
+        let library = build_component_library();
+        let mut library = library.write().expect("lock poisoned");
+        library.load_scripts(&dir).expect("failed to load scripts");
+
+        // clients may only send scripts if the runtime allows it
+        let err = library
+            .set_script_source(script_source("Shout", "sh", "tr a-z A-Z"))
+            .expect_err("accepted script source without --allow-scripts");
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(!dir.join("Shout.json").exists());
+        library.allow_script_sources(true);
+
+        let component = library
+            .set_script_source(script_source("Shout", "sh", "tr a-z A-Z"))
+            .expect("failed to register script");
+        assert_eq!(component.in_ports[0].name, "IN");
+        assert_eq!(component.out_ports[0].name, "OUT");
+        assert!(library.find("Shout").is_some());
+        let source = library.get_source(String::from("Shout")).expect("no source");
+        assert_eq!(source.code, "tr a-z A-Z");
+        assert!(dir.join("Shout.json").exists());
+
+        let err = library
+            .set_script_source(script_source("Repeat", "sh", "cat"))
+            .expect_err("replaced compiled component");
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        let err = library
+            .set_script_source(script_source("Shout", "cobol", "DISPLAY"))
+            .expect_err("accepted unknown language");
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        let err = library
+            .set_script_source(script_source("../escape", "sh", "cat"))
+            .expect_err("accepted path in name");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        // a fresh library picks the script up again
+        let reloaded = build_component_library();
+        let mut reloaded = reloaded.write().expect("lock poisoned");
+        reloaded.load_scripts(&dir).expect("failed to reload scripts");
+        assert!(reloaded.find("Shout").is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn script_component_runs_once_per_packet() {
+        let dir = state_test_dir("script-run");
+        let result_path = dir.join("result.txt");
+        let library = build_component_library();
+        library
+            .write()
+            .expect("lock poisoned")
+            .load_scripts(&dir.join(super::script::SCRIPT_DIR))
+            .expect("failed to load scripts");
+        library
+            .write()
+            .expect("lock poisoned")
+            .allow_script_sources(true);
+        library
+            .write()
+            .expect("lock poisoned")
+            .set_script_source(script_source(
+                "Shout",
+                "sh",
+                &format!("tr a-z A-Z >> '{}'; echo done", result_path.display()),
+            ))
+            .expect("failed to register script");
+        let graph_path = dir.join("script.json");
+        fs::write(
+            &graph_path,
+            r#"{
+                "caseSensitive": true,
+                "properties": {"name": "script_run", "environment": {"type": "flowd", "content": ""}, "description": "", "icon": ""},
+                "inports": {}, "outports": {}, "groups": [],
+                "processes": {
+                    "shout": {"component": "Shout", "metadata": {"x": 0, "y": 0}},
+                    "drop": {"component": "Drop", "metadata": {"x": 100, "y": 0}}
+                },
+                "connections": [
+                    {"src": {"process": "", "port": ""}, "data": "hello", "tgt": {"process": "shout", "port": "IN"}, "metadata": {}},
+                    {"src": {"process": "shout", "port": "OUT"}, "tgt": {"process": "drop", "port": "IN"}, "metadata": {}}
+                ]
+            }"#,
+        )
+        .expect("failed to write graph file");
+
+        super::cli::run_graph_file(&graph_path).expect("headless run failed");
+        assert_eq!(
+            fs::read_to_string(&result_path).expect("script did not run"),
+            "HELLO"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn script_component_without_source_finishes() {
+        use super::{BudgetClass, Component, NodeContext};
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+
+        let (in_sink, in_source) = ProcessEdge::new(4);
+        let mut in_sink = ProcessEdgeSink::new(in_sink, None, None, None);
+        let (out_sink, out_source) = ProcessEdge::new(4);
+        let mut inports = super::ProcessInports::new();
+        inports.insert(String::from("IN"), in_source.into());
+        let mut outports = super::ProcessOutports::new();
+        outports.insert(
+            String::from("OUT"),
+            ProcessEdgeSink::new(out_sink, None, None, None),
+        );
+        let (_signalsink, signalsource) = mpsc::sync_channel(1);
+        let (watchdog_signalsink, _watchdog_signalsource) = mpsc::sync_channel(1);
+        let mut script = super::script::ScriptComponent::new(
+            inports,
+            outports,
+            signalsource,
+            watchdog_signalsink,
+            super::create_graph_inout_handle(super::create_graph_inout_holder()),
+            None,
+        );
+        in_sink
+            .push(FbpMessage::from_str("data"))
+            .expect("failed to push message");
+        let mut context = NodeContext::new(
+            String::from("Script"),
+            BudgetClass::Normal,
+            Arc::new(AtomicBool::new(false)),
+        );
+        context.remaining_budget = 8;
+        assert!(matches!(
+            script.process(&mut context),
+            super::ProcessResult::Finished
+        ));
+        assert!(out_source.is_abandoned());
+    }
+
+    // plugin "Upper": uppercases text packets from IN and emits them as bytes on OUT, written
+    // against component_api/include/flowd_plugin.h
+    const UPPER_PLUGIN_C: &str = r#"
+        #include <ctype.h>
+        #include <stdlib.h>
+        #include <string.h>
+        #include "flowd_plugin.h"
+
+        uint32_t flowd_plugin_abi_version(void) { return FLOWD_PLUGIN_ABI_VERSION; }
+        const char *flowd_plugin_metadata(void) {
+            return "{\"name\": \"Upper\", \"description\": \"uppercase text\","
+                   " \"inports\": [{\"name\": \"IN\", \"type\": \"string\"}],"
+                   " \"outports\": [{\"name\": \"OUT\"}]}";
+        }
+        void *flowd_plugin_init(void) { return calloc(1, sizeof(int)); }
+        int32_t flowd_plugin_process(void *handle, const char *port, const uint8_t *data,
+                                     size_t len, flowd_emit_fn emit, void *emit_ctx) {
+            /* short text packets only: [1, {"Text": fixstr}] */
+            if (len < 9 || len > 40 || memcmp(data, "\x92\x01\x81\xa4Text", 8) != 0) return -1;
+            size_t text_len = len - 9;
+            /* [1, {"Bytes": bin 8}] */
+            uint8_t out[64] = "\x92\x01\x81\xa5" "Bytes" "\xc4";
+            out[10] = (uint8_t)text_len;
+            for (size_t i = 0; i < text_len; i++) out[11 + i] = toupper(data[9 + i]);
+            emit(emit_ctx, "OUT", out, 11 + text_len);
+            ++*(int *)handle;
+            return 0;
+        }
+        void flowd_plugin_drop(void *handle) { free(handle); }
+    "#;
+
+    fn build_upper_plugin(dir: &std::path::Path) -> std::path::PathBuf {
+        let plugin_dir = dir.join(super::plugin::PLUGIN_DIR);
+        fs::create_dir_all(&plugin_dir).expect("failed to create plugin dir");
+        let source = dir.join("upper.c");
+        fs::write(&source, UPPER_PLUGIN_C).expect("failed to write plugin source");
+        let library = plugin_dir.join(format!("libupper.{}", std::env::consts::DLL_EXTENSION));
+        let status = std::process::Command::new("cc")
+            .args(["-shared", "-fPIC", "-I"])
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/component_api/include"))
+            .arg("-o")
+            .arg(&library)
+            .arg(&source)
+            .status()
+            .expect("failed to run cc");
+        assert!(status.success(), "failed to compile plugin");
+        plugin_dir
+    }
+
+    #[test]
+    fn plugin_component_runs_in_headless_graph() {
+        let dir = state_test_dir("plugin-run");
+        let plugin_dir = build_upper_plugin(&dir);
+
+        // a broken library does not keep the others from loading
+        fs::write(
+            plugin_dir.join(format!("broken.{}", std::env::consts::DLL_EXTENSION)),
+            b"not a library",
+        )
+        .expect("failed to write broken plugin");
+        let library = build_component_library();
+        let mut library = library.write().expect("lock poisoned");
+        library.load_plugins(&plugin_dir).expect("failed to load plugin");
+        let component = library.find("Upper").expect("plugin not in library");
+        assert_eq!(component.in_ports[0].allowed_type, "string");
+        assert_eq!(component.out_ports[0].name, "OUT");
+        library
+            .load_plugins(&plugin_dir)
+            .expect("failed to load plugins again");
+        assert_eq!(
+            library
+                .available
+                .iter()
+                .filter(|component| component.name == "Upper")
+                .count(),
+            1
+        );
+        drop(library);
+
+        let result_path = dir.join("result.txt");
+        let graph_path = dir.join("plugin.json");
+        fs::write(
+            &graph_path,
+            format!(
+                r#"{{
+                "caseSensitive": true,
+                "properties": {{"name": "plugin_run", "environment": {{"type": "flowd", "content": ""}}, "description": "", "icon": ""}},
+                "inports": {{}}, "outports": {{}}, "groups": [],
+                "processes": {{
+                    "upper": {{"component": "Upper", "metadata": {{"x": 0, "y": 0}}}},
+                    "write": {{"component": "FileWriter", "metadata": {{"x": 100, "y": 0}}}}
+                }},
+                "connections": [
+                    {{"src": {{"process": "", "port": ""}}, "data": "hello", "tgt": {{"process": "upper", "port": "IN"}}, "metadata": {{}}}},
+                    {{"src": {{"process": "", "port": ""}}, "data": "{}", "tgt": {{"process": "write", "port": "CONF"}}, "metadata": {{}}}},
+                    {{"src": {{"process": "upper", "port": "OUT"}}, "tgt": {{"process": "write", "port": "IN"}}, "metadata": {{}}}}
+                ]
+            }}"#,
+                result_path.display()
+            ),
+        )
+        .expect("failed to write graph file");
+
+        super::cli::run_graph_file(&graph_path).expect("headless run failed");
+        assert_eq!(
+            fs::read_to_string(&result_path).expect("plugin output not written"),
+            "HELLO"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn clear_payload(graph_name: &str) -> super::GraphClearRequestPayload {
+        super::GraphClearRequestPayload {
+            name: graph_name.to_owned(),
+            label: None,
+            library: None,
+            main: true,
+            icon: None,
+            description: None,
+            scheduler_workers: None,
+            secret: None,
+        }
+    }
+
+    #[test]
+    fn graphs_are_created_switched_renamed_and_deleted() {
+        let dir = state_test_dir("graph-management");
+        let mut runtime = super::Runtime::new(String::from("first"));
+        runtime.set_graph_dir(&dir);
+
+        // graph:clear of an unknown graph replaces the active graph
+        runtime
+            .clear_graph(&clear_payload("second"))
+            .expect("failed to clear graph");
+        assert_eq!(runtime.graphs.list_graphs(), ["second"]);
+        assert_eq!(runtime.graph, "second");
+        // graph:create adds a graph next to the active one
+        let create = |id: &str| {
+            let message = format!(
+                r#"{{"protocol": "graph", "command": "create", "payload": {{"id": "{}"}}}}"#,
+                id
+            );
+            match serde_json::from_str(&message).expect("failed to parse graph:create") {
+                super::FBPMessage::Graph(super::GraphMessage::Create(payload)) => payload,
+                other => panic!("graph:create parsed as {:?}", other),
+            }
+        };
+        runtime
+            .create_graph(&create("first"))
+            .expect("failed to create graph");
+        assert_eq!(runtime.graph, "second");
+        let err = runtime
+            .create_graph(&create("second"))
+            .expect_err("created existing graph");
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        // graph:clear of a known graph keeps the others
+        runtime
+            .clear_graph(&clear_payload("second"))
+            .expect("failed to clear graph");
+        let mut graph_ids = runtime.graphs.list_graphs();
+        graph_ids.sort();
+        assert_eq!(graph_ids, ["first", "second"]);
+        assert_eq!(runtime.graph, "second");
+        runtime.switch_graph("first").expect("failed to switch");
+        assert_eq!(runtime.graphs.get_active_graph_id(), "first");
+        assert!(runtime.switch_graph("missing").is_err());
+
+        fs::create_dir_all(dir.join(super::CHECKPOINT_DIR)).expect("failed to create dir");
+        fs::write(runtime.checkpoint_path("second"), b"{}").expect("failed to write checkpoint");
+        runtime.persist().expect("failed to persist");
+
+        runtime
+            .rename_graph("second", "third")
+            .expect("failed to rename");
+        assert!(runtime.graphs.get_graph("second").is_none());
+        let third = runtime.graphs.get_graph("third").expect("renamed graph missing");
+        assert_eq!(third.read().expect("lock poisoned").properties.name, "third");
+        assert!(runtime.checkpoint_path("third").exists());
+        let err = runtime
+            .rename_graph("third", "first")
+            .expect_err("renamed onto existing graph");
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+        runtime.switch_graph("third").expect("failed to switch");
+        runtime.delete_graph("third").expect("failed to delete");
+        assert_eq!(runtime.graphs.list_graphs(), ["first"]);
+        assert_eq!(runtime.graph, "first");
+        assert!(!runtime.checkpoint_path("third").exists());
+        let persisted = fs::read_to_string(dir.join(super::PERSISTENCE_FILE_NAME))
+            .expect("graph set not persisted");
+        assert!(!persisted.contains("third") && !persisted.contains("second"));
+        assert!(runtime.delete_graph("first").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn outport_subscriptions_end_with_the_client() {
+        let graph_inout = super::create_graph_inout_holder();
+        let mut graph_inout = graph_inout.lock().expect("lock poisoned");
+        let client: std::net::SocketAddr = "127.0.0.1:3569".parse().expect("invalid address");
+        let other: std::net::SocketAddr = "127.0.0.1:3570".parse().expect("invalid address");
+        graph_inout.subscribe_outports("main", client);
+        graph_inout.subscribe_outports("main", other);
+        graph_inout.remove_client(&client);
+        let subscribers = &graph_inout.outport_subscribers["main"];
+        assert!(!subscribers.contains(&client));
+        assert!(subscribers.contains(&other));
+    }
+
+    #[test]
+    fn runtime_packet_payloads_follow_the_port_type() {
+        use super::{packet_from_json, runtime_packet_from_message, ControlEvent, FbpValue};
+        use serde_json::json;
+
+        assert_eq!(
+            packet_from_json(&json!("hi"), "any").unwrap(),
+            FbpMessage::from_str("hi")
+        );
+        assert_eq!(
+            packet_from_json(&json!({"n": 1}), "any").unwrap(),
+            FbpMessage::Value(FbpValue::Map(std::sync::Arc::new(
+                [("n".to_string(), FbpValue::Int(1))].into_iter().collect()
+            )))
+        );
+        assert_eq!(
+            packet_from_json(&json!("hi"), "buffer").unwrap(),
+            FbpMessage::from_bytes(b"hi".to_vec())
+        );
+        assert_eq!(
+            packet_from_json(&json!([1, 2]), "bytes").unwrap(),
+            FbpMessage::from_bytes(vec![1, 2])
+        );
+        assert_eq!(
+            packet_from_json(&json!(3), "int").unwrap(),
+            FbpMessage::Value(FbpValue::Int(3))
+        );
+        assert!(packet_from_json(&json!(3.5), "int").is_err());
+        assert!(packet_from_json(&json!(3), "string").is_err());
+        assert!(packet_from_json(&json!(256), "buffer").is_err());
+
+        let packet = runtime_packet_from_message(
+            "g",
+            "OUT",
+            &FbpMessage::Value(FbpValue::List(std::sync::Arc::new(vec![FbpValue::Bool(true)]))),
+        )
+        .expect("value not forwarded");
+        assert_eq!(packet.payload.typ.as_deref(), Some("array"));
+        assert_eq!(packet.payload.payload, Some(json!([true])));
+        let packet = runtime_packet_from_message(
+            "g",
+            "OUT",
+            &FbpMessage::from_bytes(vec![0xff]),
+        )
+        .expect("bytes not forwarded");
+        assert_eq!(packet.payload.payload, Some(json!([255])));
+        let packet = runtime_packet_from_message(
+            "g",
+            "OUT",
+            &FbpMessage::Control(ControlEvent::EndGroup("batch".to_string())),
+        )
+        .expect("group not forwarded");
+        assert!(matches!(packet.payload.event, super::RuntimePacketEvent::EndGroup));
+        assert_eq!(packet.payload.payload, Some(json!("batch")));
+        assert!(runtime_packet_from_message(
+            "g",
+            "OUT",
+            &FbpMessage::Control(ControlEvent::Drain)
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn runtime_packet_delivers_events_and_reports_backpressure() {
+        use super::{
+            create_graph_inout_holder, ControlEvent, FbpValue, Runtime,
+            RuntimePacketEvent, RuntimePacketRequestPayload,
+        };
+        use serde_json::json;
+        use std::collections::HashMap;
+        use std::io::ErrorKind;
+        use std::sync::{Arc, RwLock};
+
+        let mut graph = Graph::new(
+            "packets".to_string(),
+            "runtime:packet into a typed graph inport".to_string(),
+            "test".to_string(),
+        );
+        graph.inports.insert(
+            "IN".to_string(),
+            GraphPort {
+                process: "count".to_string(),
+                port: "N".to_string(),
+                metadata: GraphPortMetadata { x: 0, y: 0 },
+            },
+        );
+        graph
+            .add_node(
+                "packets".to_string(),
+                "Counter".to_string(),
+                "count".to_string(),
+                GraphNodeMetadata {
+                    x: 40,
+                    y: 80,
+                    width: None,
+                    height: None,
+                    label: None,
+                    icon: None,
+                    restart: None,
+                },
+            )
+            .expect("failed to add node");
+        let components = build_component_library();
+        components.write().expect("lock poisoned").available.push(ComponentComponentPayload {
+            name: "Counter".to_string(),
+            in_ports: vec![ComponentPort {
+                name: "N".to_string(),
+                allowed_type: "int".to_string(),
+                ..ComponentPort::default()
+            }],
+            ..ComponentComponentPayload::default()
+        });
+        let runtime = Arc::new(RwLock::new(Runtime::new("packets".to_string())));
+        runtime
+            .write()
+            .expect("lock poisoned")
+            .graphs
+            .add_graph("packets".to_string(), Arc::new(RwLock::new(graph)));
+        let graph_inout = create_graph_inout_holder();
+        let send = |event: RuntimePacketEvent, payload: Option<serde_json::Value>| {
+            Runtime::packet(
+                &RuntimePacketRequestPayload {
+                    port: "IN".to_string(),
+                    event,
+                    typ: None,
+                    schema: None,
+                    graph: "packets".to_string(),
+                    payload,
+                    secret: None,
+                },
+                graph_inout.clone(),
+                runtime.clone(),
+                components.clone(),
+            )
+        };
+
+        let err = send(RuntimePacketEvent::Data, None).expect_err("data without payload");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = send(RuntimePacketEvent::Data, Some(json!("x"))).expect_err("text for int port");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err = send(RuntimePacketEvent::Data, Some(json!(1))).expect_err("network not running");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        send(RuntimePacketEvent::Connect, None).expect("connect event rejected");
+
+        // stand-in for the running network: the graph inport edge with room for one packet
+        let (sink, mut source) = ProcessEdge::new(1);
+        graph_inout.lock().expect("lock poisoned").inports.insert(
+            "packets".to_string(),
+            HashMap::from([("IN".to_string(), ProcessEdgeSink::new(sink, None, None, None))]),
+        );
+        send(RuntimePacketEvent::BeginGroup, Some(json!("batch"))).expect("begingroup failed");
+        let err = send(RuntimePacketEvent::Data, Some(json!(1))).expect_err("edge is full");
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+        assert_eq!(
+            source.pop().expect("begingroup not delivered"),
+            FbpMessage::Control(ControlEvent::BeginGroup("batch".to_string()))
+        );
+        send(RuntimePacketEvent::Data, Some(json!(1))).expect("data failed");
+        assert_eq!(
+            source.pop().expect("data not delivered"),
+            FbpMessage::Value(FbpValue::Int(1))
+        );
+    }
+
+    #[test]
+    fn metrics_are_exported_in_openmetrics_format() {
+        let harness = linear_harness_direct("metrics_export");
+        harness.start().expect("runtime failed to start");
+        for payload in [b"one".as_ref(), b"two".as_ref()] {
+            harness
+                .send_data_to_inport("IN", payload)
+                .expect("runtime packet send failed");
+        }
+        harness
+            .wait_for_outport_data("OUT", 2, Duration::from_secs(3))
+            .expect("did not receive packets on OUT");
+
+        let metrics = harness.metrics();
+        let network_metrics = harness.network_metrics();
+        harness.stop().expect("runtime stop failed");
+        let sample = |name: &str| -> f64 {
+            metrics
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.trim().parse().ok())
+                .unwrap_or_else(|| panic!("no sample {} in:\n{}", name, metrics))
+        };
+        let node = r#"{graph="metrics_export",node="RepeatB"}"#;
+        let edge = r#"{graph="metrics_export",edge="RepeatA.OUT-RepeatB.IN"}"#;
+        assert!(metrics.contains("# TYPE flowd_node_executions counter\n"));
+        assert!(sample(&format!("flowd_node_executions_total{}", node)) >= 1.0);
+        assert_eq!(sample(&format!("flowd_node_panics_total{}", node)), 0.0);
+        assert_eq!(sample(&format!("flowd_node_finished_total{}", node)), 0.0);
+        assert_eq!(sample(&format!("flowd_edge_pushes_total{}", edge)), 2.0);
+        assert_eq!(sample(&format!("flowd_edge_full_total{}", edge)), 0.0);
+        assert!(sample(&format!("flowd_edge_capacity{}", edge)) > 0.0);
+        // RepeatB took both packets out, occupancy must not stay at the last push
+        assert_eq!(sample(&format!("flowd_edge_occupancy{}", edge)), 0.0);
+        assert!(metrics.ends_with("# EOF\n"));
+
+        // the same counters incl. utilisation per worker reach editors via network:metrics
+        assert_eq!(network_metrics["command"], "metrics");
+        let scheduler = &network_metrics["payload"]["schedulerMetrics"]["metrics_export"];
+        assert!(scheduler["executionsPerNode"]["RepeatB"].as_u64() >= Some(1));
+        let workers = scheduler["workers"].as_array().expect("no worker metrics");
+        assert!(!workers.is_empty());
+        for worker in workers {
+            let utilisation = worker["utilisation"].as_f64().expect("no utilisation");
+            assert!((0.0..=1.0).contains(&utilisation));
+        }
+    }
+
+    #[test]
+    fn log_records_carry_context_and_levels_apply_per_node() {
+        use super::logging::{self, LogContext};
+        use std::sync::Arc;
+
+        let level_request = |graph: &str, node: Option<&str>, level: Option<&str>| {
+            super::RuntimeLoglevelRequestPayload {
+                graph: graph.to_owned(),
+                node: node.map(str::to_owned),
+                level: level.map(str::to_owned),
+                secret: None,
+            }
+        };
+        let runtime = super::Runtime::new(String::from("loglevels"));
+        let default = logging::level_for(None, None);
+        let set = |node: Option<&str>, level: Option<&str>| {
+            runtime
+                .set_log_level(&level_request("loglevels", node, level))
+                .expect("failed to set log level")
+        };
+        assert_eq!(set(None, Some("warn")), log::LevelFilter::Warn);
+        assert_eq!(set(Some("Repeat"), Some("debug")), log::LevelFilter::Debug);
+        assert_eq!(
+            logging::level_for(Some("loglevels"), Some("Other")),
+            log::LevelFilter::Warn
+        );
+        assert_eq!(set(Some("Repeat"), None), log::LevelFilter::Warn);
+        assert_eq!(set(None, None), default);
+        let err = runtime
+            .set_log_level(&level_request("loglevels", None, Some("loud")))
+            .expect_err("accepted unknown level");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let err = runtime
+            .set_log_level(&level_request("missing", None, Some("info")))
+            .expect_err("accepted unknown graph");
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        // contexts nest and are restored when leaving them
+        let node = Arc::new(LogContext::node("loglevels", "Repeat", "Repeat"));
+        {
+            let _node = logging::enter(node.clone());
+            {
+                let _edge = logging::enter(Arc::new(LogContext::edge("loglevels", "A.OUT-B.IN")));
+                assert_eq!(
+                    logging::current_context().and_then(|context| context.edge.clone()),
+                    Some(String::from("A.OUT-B.IN"))
+                );
+            }
+            assert_eq!(logging::current_context(), Some(node.clone()));
+        }
+        assert_eq!(logging::current_context(), None);
+
+        let line = logging::format_json(
+            &log::Record::builder()
+                .args(format_args!("got {} packets", 2))
+                .level(log::Level::Info)
+                .target("flowd_repeat")
+                .build(),
+            Some(&node),
+        );
+        let record: serde_json::Value = serde_json::from_str(&line).expect("not JSON");
+        assert_eq!(record["level"], "INFO");
+        assert_eq!(record["target"], "flowd_repeat");
+        assert_eq!(record["message"], "got 2 packets");
+        assert_eq!(record["graph"], "loglevels");
+        assert_eq!(record["node"], "Repeat");
+        assert_eq!(record["component"], "Repeat");
+        assert!(record.get("edge").is_none());
+        assert!(record["ts"].as_str().is_some_and(|ts| ts.ends_with('Z')));
+    }
+
+    #[test]
+    fn restarted_process_takes_over_connections_and_restart_metadata_validates() {
+        use super::scheduler::{NodeRestart, RestartMode, Scheduler};
+        use super::{create_graph_inout_handle, create_graph_inout_holder, BudgetClass, Runtime};
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+
+        let components = build_component_library();
+        let components = components.read().expect("lock poisoned");
+        let scheduler = Arc::new(Scheduler::new());
+        scheduler.add_node(String::from("Repeat"), BudgetClass::Normal);
+        let graph_inout = create_graph_inout_holder();
+        let (in_sink, in_source) = ProcessEdge::new(4);
+        let mut in_sink = ProcessEdgeSink::new(in_sink, None, None, None);
+        let (out_sink, mut out_source) = ProcessEdge::new(4);
+        let mut inports = super::ProcessInports::new();
+        inports.insert(String::from("IN"), in_source.into());
+        let mut outports = super::ProcessOutports::new();
+        outports.insert(
+            String::from("OUT"),
+            ProcessEdgeSink::new(out_sink, None, None, None),
+        );
+        let (_signalsink, signalsource) = mpsc::sync_channel(1);
+        let (watchdog_signalsink, _watchdog_signalsource) = mpsc::sync_channel(1);
+        let retired = components
+            .instantiate(
+                "Repeat",
+                inports,
+                outports,
+                signalsource,
+                watchdog_signalsink,
+                create_graph_inout_handle(graph_inout.clone()),
+                None,
+            )
+            .expect("failed to instantiate Repeat");
+
+        let mut restarter = Runtime::node_restarter(
+            "restart",
+            "Repeat",
+            &components,
+            "Repeat",
+            Vec::new(),
+            &scheduler,
+            graph_inout,
+        );
+        let restart = NodeRestart {
+            node_id: String::from("Repeat"),
+            panicked: true,
+            restart: 1,
+            max_restarts: 3,
+            backoff: Duration::ZERO,
+        };
+        let mut instance = restarter(retired, &restart).expect("restart failed");
+
+        // the new instance reads and writes the edges of the retired one
+        in_sink
+            .push(FbpMessage::from_str("again"))
+            .expect("failed to push message");
+        let mut context = super::NodeContext::new(
+            String::from("Repeat"),
+            BudgetClass::Normal,
+            Arc::new(AtomicBool::new(false)),
+        );
+        context.remaining_budget = 8;
+        instance.process(&mut context);
+        assert_eq!(
+            out_source.pop().expect("restarted process sent nothing"),
+            FbpMessage::from_str("again")
+        );
+
+        // restart policy in node metadata
+        let mut graph = Graph::new(
+            String::from("restart"),
+            String::from("restart policies"),
+            String::from("test"),
+        );
+        let metadata = serde_json::json!({
+            "x": 0,
+            "y": 0,
+            "restart": { "policy": "on-panic", "maxRestarts": 3, "backoffMs": 20 },
+        });
+        let metadata = super::graph_node_metadata_from_payload(
+            metadata.as_object().expect("metadata is an object"),
+        );
+        let policy = metadata
+            .restart
+            .as_ref()
+            .expect("restart policy not parsed")
+            .policy();
+        assert_eq!(policy.mode, RestartMode::OnPanic);
+        assert_eq!(policy.max_restarts, 3);
+        assert_eq!(policy.backoff, Duration::from_millis(20));
+        assert_eq!(
+            super::graph_node_metadata_to_payload(&metadata)["restart"],
+            serde_json::json!({ "policy": "on-panic", "maxRestarts": 3, "backoffMs": 20 })
+        );
+        for (name, component) in [("Repeat", "Repeat"), ("Reader", "FileReader")] {
+            graph
+                .add_node(
+                    String::from("restart"),
+                    component.to_string(),
+                    name.to_string(),
+                    metadata.clone(),
+                )
+                .expect("failed to add node");
+        }
+        let errors = graph
+            .validate(&components)
+            .expect_err("restart policy on component without reconnect support validated");
+        assert!(errors.iter().any(|error| error.message
+            == "node Reader has a restart policy, but component FileReader does not support reconnect"));
+        assert!(!errors
+            .iter()
+            .any(|error| error.message.starts_with("node Repeat has a restart policy")));
+    }
+
+    #[test]
+    fn configuration_iips_are_validated_against_the_port_schema() {
+        use flowd_component_api::config::{parse_duration, validate_config};
+        use flowd_component_api::{ConfigSchema, ConfigType};
+
+        let schema = ConfigSchema::new()
+            .required(
+                "mode",
+                ConfigType::Enum(vec![String::from("packets"), String::from("sum")]),
+                "",
+            )
+            .with_default("limit", ConfigType::Integer, 10, "")
+            .optional("every", ConfigType::Duration, "")
+            .shorthand("mode");
+        let expected = serde_json::json!({ "mode": "sum", "limit": 3, "every": "2ms" });
+        for iip in [
+            r#"{"mode": "sum", "limit": 3, "every": "2ms"}"#,
+            "mode = \"sum\"\nlimit = 3\nevery = \"2ms\"",
+            "?mode=sum&limit=3&every=2ms",
+            "mode=sum&limit=3&every=2ms",
+        ] {
+            let parsed = schema.parse(iip).expect("valid configuration rejected");
+            assert_eq!(serde_json::Value::Object(parsed), expected, "IIP {}", iip);
+        }
+        let parsed = schema.parse("packets").expect("shorthand rejected");
+        assert_eq!(
+            serde_json::Value::Object(parsed),
+            serde_json::json!({ "mode": "packets", "limit": 10 })
+        );
+        let err = schema
+            .parse("?mode=all&limit=many&every=soon&color=red")
+            .expect_err("invalid configuration accepted");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        for problem in [
+            "field mode must be one of packets|sum",
+            "field limit must be an integer",
+            "field every: invalid duration",
+            "unknown field color, expected one of: mode, limit, every",
+        ] {
+            assert!(err.to_string().contains(problem), "{} not in {}", problem, err);
+        }
+        assert!(schema.parse("{}").is_err_and(|err| err.to_string() == "missing field mode"));
+
+        // the published JSON Schema describes the same configuration
+        let published = schema.to_json_schema().to_string();
+        assert_eq!(
+            ConfigSchema::from_json_schema(&serde_json::from_str(&published).unwrap())
+                .expect("published schema not readable")
+                .to_json_schema(),
+            schema.to_json_schema()
+        );
+        assert!(validate_config(&published, "?mode=packets").is_ok());
+        assert!(validate_config(&published, "?mode=bytes").is_err());
+        assert_eq!(parse_duration("1.5ms"), Ok(Duration::from_micros(1500)));
+        assert_eq!(parse_duration("50"), Ok(Duration::from_micros(50)));
+        assert!(parse_duration("5 parsecs").is_err());
+
+        // graph validation rejects IIPs not matching the CONF schema of Count and Delay
+        let components = build_component_library();
+        let components = components.read().expect("lock poisoned");
+        let graph_name = "config";
+        let mut graph = Graph::new(
+            graph_name.to_string(),
+            "configuration IIPs".to_string(),
+            "test".to_string(),
+        );
+        for (name, component) in [
+            ("count", "Count"),
+            ("delay", "Delay"),
+            ("good_count", "Count"),
+            ("good_delay", "Delay"),
+        ] {
+            graph
+                .add_node(
+                    graph_name.to_string(),
+                    component.to_string(),
+                    name.to_string(),
+                    GraphNodeMetadata::default(),
+                )
+                .expect("failed to add node");
+        }
+        let iip = |process: &str, data: &str| GraphEdge {
+            source: GraphNodeSpec {
+                process: String::new(),
+                port: String::new(),
+                index: None,
+            },
+            data: Some(data.to_string()),
+            target: GraphNodeSpec {
+                process: process.to_string(),
+                port: String::from("CONF"),
+                index: None,
+            },
+            metadata: GraphEdgeMetadata::new(None, None, None),
+        };
+        graph.edges = vec![
+            iip("count", "?mode=bytes"),
+            iip("delay", "?delay=soon"),
+            iip("good_count", r#"{"mode": "sum"}"#),
+            iip("good_delay", "2ms"),
+        ];
+        let errors = graph
+            .validate(&components)
+            .expect_err("invalid configuration validated");
+        let mut messages: Vec<&str> = errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .filter(|message| message.starts_with("invalid IIP"))
+            .collect();
+        messages.sort();
+        assert_eq!(
+            messages,
+            [
+                "invalid IIP for count.CONF: field mode must be one of packets|size|sum, got \"bytes\"",
+                "invalid IIP for delay.CONF: field delay: invalid duration \"soon\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn typed_values_round_trip_through_fbp_values_and_json() {
+        use flowd_component_api::value::{from_value, to_value};
+        use flowd_component_api::FbpValue;
+        use serde::{Deserialize, Serialize};
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        enum Unit {
+            Celsius,
+            Offset(i32),
+            Scaled { factor: f64 },
+            Pair(u8, u8),
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Reading {
+            sensor: String,
+            value: f64,
+            count: u32,
+            unit: Unit,
+            tags: Vec<String>,
+            note: Option<String>,
+            #[serde(with = "raw")]
+            raw: Vec<u8>,
+            by_slot: BTreeMap<u16, bool>,
+        }
+
+        mod raw {
+            pub fn serialize<S: serde::Serializer>(
+                bytes: &[u8],
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(bytes)
+            }
+
+            pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<u8>, D::Error> {
+                let bytes: &[u8] = serde::Deserialize::deserialize(deserializer)?;
+                Ok(bytes.to_vec())
+            }
+        }
+
+        for unit in [
+            Unit::Celsius,
+            Unit::Offset(-3),
+            Unit::Scaled { factor: 0.5 },
+            Unit::Pair(1, 2),
+        ] {
+            let reading = Reading {
+                sensor: String::from("t1"),
+                value: 21.5,
+                count: 7,
+                unit,
+                tags: vec![String::from("kitchen")],
+                note: None,
+                raw: vec![0, 255],
+                by_slot: BTreeMap::from([(3, true), (40000, false)]),
+            };
+            let msg = FbpMessage::from_serialize(&reading).expect("serializing failed");
+            let value = msg.as_value().expect("not a Value message");
+            let FbpValue::Map(fields) = value else {
+                panic!("struct not mapped to Map: {:?}", value);
+            };
+            assert_eq!(fields["count"], FbpValue::Int(7));
+            assert_eq!(fields["note"], FbpValue::Null);
+            assert_eq!(fields["raw"], FbpValue::Bytes(vec![0u8, 255].into()));
+            // the same message survives the binary packet encoding
+            let decoded = FbpMessage::decode(&msg.encode().unwrap()).unwrap();
+            assert_eq!(decoded.deserialize::<Reading>().unwrap(), reading);
+            assert_eq!(from_value::<Reading>(value).unwrap(), reading);
+        }
+
+        // enum representation follows serde_json, so JSON text deserializes the same way
+        assert_eq!(
+            to_value(&Unit::Celsius).unwrap(),
+            FbpValue::Text("Celsius".into())
+        );
+        let json_msg = FbpMessage::from_str(r#"{"Scaled": {"factor": 2.0}}"#);
+        assert_eq!(
+            json_msg.deserialize::<Unit>().unwrap(),
+            Unit::Scaled { factor: 2.0 }
+        );
+        let json = json_msg.deserialize::<serde_json::Value>().unwrap();
+        assert_eq!(
+            from_value::<Unit>(&to_value(&json).unwrap()).unwrap(),
+            Unit::Scaled { factor: 2.0 }
+        );
+
+        // JSON conversion is lossless in both directions or refused
+        let json = serde_json::json!({
+            "int": -5, "float": 1.0, "text": "x", "list": [null, true, 2.5], "map": {"a": {}}
+        });
+        let value = FbpValue::try_from(json.clone()).unwrap();
+        let FbpValue::Map(entries) = &value else {
+            panic!("object not mapped to Map");
+        };
+        assert_eq!(entries["int"], FbpValue::Int(-5));
+        assert_eq!(entries["float"], FbpValue::Float(1.0));
+        assert_eq!(serde_json::Value::try_from(&value).unwrap(), json);
+        assert!(FbpValue::try_from(serde_json::json!(u64::MAX)).is_err());
+        assert!(serde_json::Value::try_from(FbpValue::Float(f64::NAN)).is_err());
+        assert!(serde_json::Value::try_from(FbpValue::Bytes(vec![1u8].into())).is_err());
+
+        // type mismatches are errors, not panics
+        assert!(to_value(&u64::MAX).is_err());
+        assert!(from_value::<u8>(&FbpValue::Int(300)).is_err());
+        assert!(from_value::<(i64, i64)>(&FbpValue::List(vec![FbpValue::Int(1)].into())).is_err());
+        let err = FbpMessage::Value(FbpValue::Text("x".into()))
+            .deserialize::<Reading>()
+            .expect_err("text deserialized as struct");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(FbpMessage::from_str("not json").deserialize::<Unit>().is_err());
+    }
+
+    #[test]
+    fn port_types_are_checked_against_the_type_registry() {
+        use flowd_component_api::types::{CompatibilityResult, RegistryEntry, TypeId};
+        use flowd_component_api::FbpValue;
+        use serde_json::json;
+
+        let reading_v1 = TypeId::parse("sensors/Reading@1").expect("valid TypeId rejected");
+        let reading = TypeId::new("sensors", "Reading", 2);
+        assert_eq!(reading.to_string(), "sensors/Reading@2");
+        for invalid in [
+            "sensors/Reading",
+            "Sensors/Reading@1",
+            "sensors/reading@1",
+            "sensors/Reading@0",
+        ] {
+            assert!(TypeId::parse(invalid).is_err(), "{} parsed as TypeId", invalid);
+        }
+
+        let port = |name: &str, allowed_type: &str| ComponentPort {
+            name: name.to_string(),
+            allowed_type: allowed_type.to_string(),
+            required: false,
+            ..ComponentPort::default()
+        };
+        let component = |name: &str, in_type: &str, out_type: &str| ComponentComponentPayload {
+            name: name.to_string(),
+            in_ports: vec![port("IN", in_type)],
+            out_ports: vec![port("OUT", out_type)],
+            ..ComponentComponentPayload::default()
+        };
+        let components = ComponentLibrary::new(vec![
+            component("Sensor", "any", "sensors/Reading@2"),
+            component("OldSensor", "any", "sensors/Reading@1"),
+            component("Display", "sensors/Reading@2", "string"),
+            component("OldDisplay", "sensors/Reading@1", "any"),
+            component("Counter", "int", "core/Integer@1"),
+            component("Plotter", "number", "sensors/Missing@1"),
+        ])
+        .with_types(vec![
+            RegistryEntry::new(reading_v1.clone(), "reading of a sensor")
+                .with_schema(json!({"type": "object", "required": ["sensor"]})),
+            RegistryEntry::new(reading.clone(), "reading of a sensor with unit")
+                .with_schema(json!({
+                    "type": "object",
+                    "required": ["sensor", "value"],
+                    "properties": {
+                        "sensor": {"type": "string"},
+                        "value": {"type": "number"},
+                        "unit": {"enum": ["C", "F"]},
+                        "history": {"type": "array", "items": {"type": "number"}}
+                    },
+                    "additionalProperties": false
+                }))
+                .compatible_from(reading_v1.clone()),
+        ]);
+        let types = components.types();
+
+        // noflo port types are aliases of the core types
+        let integer = types.resolve("INT").unwrap().expect("alias not resolved");
+        assert_eq!(integer.type_id.to_string(), "core/Integer@1");
+        assert!(types.resolve("my-own-type").unwrap().is_none());
+        assert!(types.resolve("sensors/Missing@1").is_err());
+        // free-form port types may contain a slash as well
+        assert!(types.resolve("image/png").unwrap().is_none());
+        assert!(types.resolve("sensors/Reading").unwrap().is_none());
+        let number = TypeId::new("core", "Number", 1);
+        for (producer, consumer, expected) in [
+            (&reading, &reading, CompatibilityResult::CompatibleExact),
+            (&reading_v1, &reading, CompatibilityResult::CompatibleDeclared),
+            (&reading, &reading_v1, CompatibilityResult::IncompatibleVersion),
+            (&integer.type_id, &number, CompatibilityResult::CompatibleDeclared),
+            (&number, &integer.type_id, CompatibilityResult::IncompatibleType),
+        ] {
+            assert_eq!(types.compatibility(producer, consumer), expected);
+        }
+
+        let graph_name = "typed";
+        let mut graph = Graph::new(
+            graph_name.to_string(),
+            "graph with typed ports".to_string(),
+            "test".to_string(),
+        );
+        for (name, component) in [
+            ("sensor", "Sensor"),
+            ("old_sensor", "OldSensor"),
+            ("display", "Display"),
+            ("old_display", "OldDisplay"),
+            ("counter", "Counter"),
+            ("plotter", "Plotter"),
+        ] {
+            graph
+                .add_node(
+                    graph_name.to_string(),
+                    component.to_string(),
+                    name.to_string(),
+                    GraphNodeMetadata {
+                        x: 0,
+                        y: 0,
+                        width: None,
+                        height: None,
+                        label: None,
+                        icon: None,
+                        restart: None,
+                    },
+                )
+                .expect("failed to add node");
+        }
+        let edge = |source: &str, target: &str| GraphEdge {
+            source: GraphNodeSpec {
+                process: source.to_string(),
+                port: "OUT".to_string(),
+                index: None,
+            },
+            data: None,
+            target: GraphNodeSpec {
+                process: target.to_string(),
+                port: "IN".to_string(),
+                index: None,
+            },
+            metadata: GraphEdgeMetadata::new(None, None, None),
+        };
+        graph.edges = vec![
+            edge("old_sensor", "display"),
+            edge("sensor", "old_display"),
+            edge("display", "counter"),
+            edge("counter", "plotter"),
+            edge("plotter", "sensor"),
+        ];
+        let errors = graph
+            .validate(&components)
+            .expect_err("graph with type mismatches must not validate");
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        let expected = [
+            "type mismatch on edge sensor.OUT -> old_display.IN: \
+             sensors/Reading@2 is not compatible with sensors/Reading@1",
+            "type mismatch on edge display.OUT -> counter.IN: string is not compatible with int",
+            "type mismatch on edge plotter.OUT -> sensor.IN: unknown type sensors/Missing@1",
+        ];
+        assert_eq!(messages, expected, "unexpected validation result: {:#?}", errors);
+
+        // debug builds check what a process sends against the type of its outport
+        let contract = components
+            .outport_contract("Sensor", "out")
+            .expect("no contract for typed outport");
+        assert_eq!(contract.type_id, reading);
+        assert!(components.outport_contract("OldDisplay", "OUT").is_none());
+        // built-in components declare their port types, so their packets are checked as well
+        let builtin = build_component_library();
+        let builtin = builtin.read().expect("lock poisoned");
+        for name in ["Count", "Hasher", "SplitLines"] {
+            let contract = builtin
+                .outport_contract(name, "OUT")
+                .unwrap_or_else(|| panic!("no contract for {}.OUT", name));
+            assert_eq!(contract.type_id.to_string(), "core/String@1");
+        }
+        let packet = |json: serde_json::Value| {
+            FbpMessage::Value(FbpValue::try_from(json).expect("JSON not convertible"))
+        };
+        let valid = json!({"sensor": "t1", "value": 21, "unit": "C", "history": [20.5]});
+        assert!(contract.check(&packet(valid)).is_ok());
+        assert!(contract.check(&FbpMessage::begin_bracket("")).is_ok());
+        for (msg, expected) in [
+            (FbpMessage::from_str("t1"), "expected \"object\", got string"),
+            (packet(json!({"sensor": "t1"})), "missing field value"),
+            (
+                packet(json!({"sensor": "t1", "value": 1, "history": [1.5, "x"]})),
+                "expected \"number\", got string at history[1]",
+            ),
+            (
+                packet(json!({"sensor": "t1", "value": 1, "unit": "K"})),
+                "value not one of [String(\"C\"), String(\"F\")] at unit",
+            ),
+            (
+                packet(json!({"sensor": "t1", "value": 1, "extra": true})),
+                "unknown field extra",
+            ),
+        ] {
+            assert_eq!(
+                contract.check(&msg).unwrap_err(),
+                format!("packet is no sensors/Reading@2: {}", expected)
+            );
+        }
+        // violations are reported, the packet is delivered anyway
+        let (sink, mut source) = ProcessEdge::new(2);
+        let mut sink = ProcessEdgeSink::new(sink, None, Some("display".to_string()), None);
+        sink.check_contract(std::sync::Arc::new(contract));
+        sink.push(FbpMessage::from_str("t1")).expect("push failed");
+        assert_eq!(source.pop().expect("packet not delivered"), FbpMessage::from_str("t1"));
+
+        // editors get the registry over the protocol
+        let response = serde_json::to_value(super::ComponentTypesMessage::new(types))
+            .expect("failed to serialize component:types response");
+        assert_eq!(response["command"], "types");
+        let listed = response["payload"]["types"].as_array().expect("no list of types");
+        let find = |id: &str| listed.iter().find(|entry| entry["id"] == id).cloned();
+        assert_eq!(
+            find("sensors/Reading@2").expect("registered type missing")["compatibleFrom"],
+            json!(["sensors/Reading@1"])
+        );
+        assert_eq!(
+            find("core/String@1").expect("core type missing")["aliases"],
+            json!(["string"])
+        );
+    }
@@ -0,0 +1,183 @@
+// TLS for the management connection
+//
+// A client connection is read by its handler thread and written by the handler as well as by
+// broadcasts to all clients, which use a clone of the connection. For TLS both clones share the
+// session state, and the socket is read without holding the session lock so that writers never
+// wait for the next incoming message.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rustls::{ServerConfig, ServerConnection};
+
+// a client not finishing its TLS handshake must not occupy the handler thread forever
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+// TLS records are at most 16 KiB plus overhead
+const TLS_READ_BUF_SIZE: usize = 18 * 1024;
+
+/// Load certificate chain and private key, both PEM, into a TLS server configuration
+pub fn load_server_config(
+    cert_path: &Path,
+    key_path: &Path,
+) -> std::result::Result<Arc<ServerConfig>, std::io::Error> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(cert_path)?))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("no certificate found in {}", cert_path.display()),
+        ));
+    }
+    let key = rustls_pemfile::private_key(&mut BufReader::new(File::open(key_path)?))?.ok_or_else(
+        || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("no private key found in {}", key_path.display()),
+            )
+        },
+    )?;
+    // explicit provider, other dependencies enable the ring backend of rustls as well,
+    // which leaves no process-wide default
+    let config = ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::aws_lc_rs::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(std::io::Error::other)?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid TLS certificate or key: {}", err),
+        )
+    })?;
+    Ok(Arc::new(config))
+}
+
+/// Connection of a management client, plain or TLS
+#[derive(Debug)]
+pub enum ClientStream {
+    Plain(TcpStream),
+    Tls {
+        socket: TcpStream,
+        session: Arc<Mutex<ServerConnection>>,
+    },
+}
+
+impl ClientStream {
+    /// Wrap an accepted socket, completing the TLS handshake if `tls` is given
+    pub fn accept(
+        socket: TcpStream,
+        tls: Option<&Arc<ServerConfig>>,
+    ) -> std::result::Result<Self, std::io::Error> {
+        let Some(config) = tls else {
+            return Ok(ClientStream::Plain(socket));
+        };
+        let mut socket = socket;
+        let mut session = ServerConnection::new(config.clone()).map_err(std::io::Error::other)?;
+        socket.set_read_timeout(Some(TLS_HANDSHAKE_TIMEOUT))?;
+        while session.is_handshaking() {
+            session.complete_io(&mut socket)?;
+        }
+        socket.set_read_timeout(None)?;
+        Ok(ClientStream::Tls {
+            socket,
+            session: Arc::new(Mutex::new(session)),
+        })
+    }
+
+    /// Second handle on the same connection, for sending from another thread
+    pub fn try_clone(&self) -> std::result::Result<Self, std::io::Error> {
+        match self {
+            ClientStream::Plain(socket) => Ok(ClientStream::Plain(socket.try_clone()?)),
+            ClientStream::Tls { socket, session } => Ok(ClientStream::Tls {
+                socket: socket.try_clone()?,
+                session: session.clone(),
+            }),
+        }
+    }
+
+    fn socket(&self) -> &TcpStream {
+        match self {
+            ClientStream::Plain(socket) => socket,
+            ClientStream::Tls { socket, .. } => socket,
+        }
+    }
+
+    pub fn peer_addr(&self) -> std::result::Result<SocketAddr, std::io::Error> {
+        self.socket().peer_addr()
+    }
+
+    pub fn set_write_timeout(
+        &self,
+        dur: Option<Duration>,
+    ) -> std::result::Result<(), std::io::Error> {
+        self.socket().set_write_timeout(dur)
+    }
+}
+
+impl Read for ClientStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let (socket, session) = match self {
+            ClientStream::Plain(socket) => return socket.read(buf),
+            ClientStream::Tls { socket, session } => (socket, session),
+        };
+        loop {
+            match session.lock().expect("lock poisoned").reader().read(buf) {
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
+                result => return result,
+            }
+            let mut records = [0u8; TLS_READ_BUF_SIZE];
+            let len = socket.read(&mut records)?;
+            if len == 0 {
+                return Ok(0);
+            }
+            let mut session = session.lock().expect("lock poisoned");
+            let mut input = &records[..len];
+            while !input.is_empty() {
+                session.read_tls(&mut input)?;
+                if let Err(err) = session.process_new_packets() {
+                    // let the peer know why the connection ends
+                    let _ = session.write_tls(socket);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+                }
+            }
+            // key updates and alerts
+            while session.wants_write() {
+                session.write_tls(socket)?;
+            }
+        }
+    }
+}
+
+impl Write for ClientStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let (socket, session) = match self {
+            ClientStream::Plain(socket) => return socket.write(buf),
+            ClientStream::Tls { socket, session } => (socket, session),
+        };
+        let mut session = session.lock().expect("lock poisoned");
+        let len = session.writer().write(buf)?;
+        while session.wants_write() {
+            session.write_tls(socket)?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let (socket, session) = match self {
+            ClientStream::Plain(socket) => return socket.flush(),
+            ClientStream::Tls { socket, session } => (socket, session),
+        };
+        let mut session = session.lock().expect("lock poisoned");
+        session.writer().flush()?;
+        while session.wants_write() {
+            session.write_tls(socket)?;
+        }
+        socket.flush()
+    }
+}
@@ -52,7 +52,7 @@ impl RepeatTestHarness {
 
         // Set up inports and outports for the component
         let mut inports = MultiMap::new();
-        inports.insert("IN".to_string(), input_consumer);
+        inports.insert("IN".to_string(), input_consumer.into());
 
         let mut outports = MultiMap::new();
         outports.insert(
@@ -179,7 +179,7 @@ impl HTTPClientTestHarness {
 
         // Set up inports and outports for the component
         let mut inports = MultiMap::new();
-        inports.insert("REQ".to_string(), req_consumer);
+        inports.insert("REQ".to_string(), req_consumer.into());
 
         let mut outports = MultiMap::new();
         outports.insert(
@@ -363,9 +363,9 @@ impl HTTPServerTestHarness {
 
         // Set up inports and outports for the component
         let mut inports = MultiMap::new();
-        inports.insert("CONF".to_string(), conf_consumer);
-        inports.insert("ROUTES".to_string(), routes_consumer);
-        inports.insert("RESP".to_string(), resp_consumer);
+        inports.insert("CONF".to_string(), conf_consumer.into());
+        inports.insert("ROUTES".to_string(), routes_consumer.into());
+        inports.insert("RESP".to_string(), resp_consumer.into());
 
         let mut outports = MultiMap::new();
         outports.insert(
@@ -2178,4 +2178,325 @@ mod tests {
         assert_eq!(message_data_bytes(&outputs1[0]).unwrap(), b"input1");
         assert_eq!(message_data_bytes(&outputs1[1]).unwrap(), b"input2");
     }
+
+    #[test]
+    fn test_derived_components_declare_ports_and_survive_missing_connections() {
+        use flowd_delay::DelayComponent;
+        use flowd_muxer::{Demux3Component, MuxerComponent};
+
+        let metadata = DelayComponent::get_metadata();
+        assert_eq!(metadata.name, "Delay");
+        let ports: Vec<(&str, &str, bool, &str)> = metadata
+            .in_ports
+            .iter()
+            .map(|port| {
+                let (name, allowed_type) = (port.name.as_str(), port.allowed_type.as_str());
+                (
+                    name,
+                    allowed_type,
+                    port.required,
+                    port.value_default.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ports,
+            vec![("CONF", "any", true, "50us"), ("IN", "any", true, "")]
+        );
+        assert!(MuxerComponent::get_metadata().in_ports[0].is_arrayport);
+        assert!(DelayComponent::get_metadata().support_reconnect);
+        assert!(Demux3Component::get_metadata().support_reconnect);
+        let metadata = RepeatComponent::get_metadata();
+        assert!(metadata.support_reconnect);
+        assert_eq!(
+            metadata.out_ports[0].description,
+            "repeated data from IN port"
+        );
+
+        // unconnected ports do not panic on instantiation, a closed IN finishes the process
+        let (signal_sender, signal_receiver) = mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+        let graph_inout: GraphInportOutportHandle = (Arc::new(|_| {}), Arc::new(|_| {}));
+        let mut muxer = MuxerComponent::new(
+            MultiMap::new(),
+            MultiMap::new(),
+            signal_receiver,
+            signal_sender.clone(),
+            graph_inout.clone(),
+            None,
+        );
+        let mut context = NodeContext::new(
+            "test_muxer".to_string(),
+            BudgetClass::Normal,
+            Arc::new(AtomicBool::new(false)),
+        );
+        context.remaining_budget = 32;
+        assert!(matches!(
+            muxer.process(&mut context),
+            ProcessResult::Finished
+        ));
+
+        // config ports of reconnecting components are reconnected as well
+        let (signal_sender, signal_receiver) = mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+        let mut delay = DelayComponent::new(
+            MultiMap::new(),
+            MultiMap::new(),
+            signal_receiver,
+            signal_sender,
+            graph_inout.clone(),
+            None,
+        );
+        let (_conf_producer, conf_consumer) = rtrb::RingBuffer::<MessageBuf>::new(1);
+        assert!(delay
+            .reconnect_inport("CONF", Some(conf_consumer.into()))
+            .expect("reconnect of CONF failed")
+            .is_none());
+        assert!(delay
+            .reconnect_inport("CONF", None)
+            .expect("disconnect of CONF failed")
+            .is_some());
+
+        // derived reconnect and signal handling
+        let (signal_sender, signal_receiver) = mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+        let (pong_sender, pong_receiver) = mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+        let mut repeat = RepeatComponent::new(
+            MultiMap::new(),
+            MultiMap::new(),
+            signal_receiver,
+            pong_sender,
+            graph_inout,
+            None,
+        );
+        let (_input_producer, input_consumer) = rtrb::RingBuffer::<MessageBuf>::new(1);
+        assert!(repeat
+            .reconnect_inport("IN", Some(input_consumer.into()))
+            .expect("reconnect of IN failed")
+            .is_none());
+        let err = repeat
+            .reconnect_outport("NOPE", None)
+            .expect_err("reconnected unknown outport");
+        assert_eq!(err.to_string(), "Repeat has no outport NOPE");
+        signal_sender
+            .try_send(FbpMessage::from_str("ping"))
+            .unwrap();
+        assert!(matches!(
+            repeat.process(&mut context),
+            ProcessResult::NoWork
+        ));
+        assert_eq!(
+            pong_receiver.try_recv().unwrap(),
+            FbpMessage::from_str("pong")
+        );
+        signal_sender
+            .try_send(FbpMessage::from_str("stop"))
+            .unwrap();
+        assert!(matches!(
+            repeat.process(&mut context),
+            ProcessResult::Finished
+        ));
+    }
+
+    #[test]
+    fn test_jsonquery_answers_structured_values_with_structured_values() {
+        use flowd_json::JSONQueryComponent;
+
+        #[derive(serde::Serialize)]
+        struct Reading {
+            sensor: &'static str,
+            celsius: f64,
+        }
+
+        let (mut query_producer, query_consumer) = rtrb::RingBuffer::<MessageBuf>::new(1);
+        let (mut input_producer, input_consumer) = rtrb::RingBuffer::<MessageBuf>::new(4);
+        let (output_producer, mut output_consumer) = rtrb::RingBuffer::<MessageBuf>::new(4);
+        let mut inports = MultiMap::new();
+        inports.insert(String::from("QUERY"), query_consumer.into());
+        inports.insert(String::from("IN"), input_consumer.into());
+        let mut outports = MultiMap::new();
+        outports.insert(
+            String::from("OUT"),
+            ProcessEdgeSink::new(output_producer, None, None, None),
+        );
+        let (signal_sender, signal_receiver) = mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+        let mut component = JSONQueryComponent::new(
+            inports,
+            outports,
+            signal_receiver,
+            signal_sender,
+            (Arc::new(|_| {}), Arc::new(|_| {})),
+            None,
+        );
+        let mut context = NodeContext::new(
+            "test_jsonquery".to_string(),
+            BudgetClass::Normal,
+            Arc::new(AtomicBool::new(false)),
+        );
+        context.remaining_budget = 32;
+
+        query_producer
+            .push(FbpMessage::from_str("{sensor, f: (.celsius * 9 / 5 + 32)}"))
+            .unwrap();
+        component.process(&mut context);
+        let reading = Reading {
+            sensor: "t1",
+            celsius: 20.0,
+        };
+        input_producer
+            .push(FbpMessage::from_serialize(&reading).unwrap())
+            .unwrap();
+        input_producer
+            .push(FbpMessage::from_str(r#"{"sensor": "t2", "celsius": 0}"#))
+            .unwrap();
+        component.process(&mut context);
+
+        let structured = output_consumer
+            .pop()
+            .expect("no output for the Value packet");
+        let result: serde_json::Value = structured.deserialize().unwrap();
+        assert!(structured.as_value().is_some());
+        assert_eq!(result, serde_json::json!({ "sensor": "t1", "f": 68.0 }));
+        let text = output_consumer
+            .pop()
+            .expect("no output for the JSON packet");
+        assert!(text.as_value().is_none());
+        assert_eq!(message_text_lossy(&text), r#"{"sensor":"t2","f":32.0}"#);
+    }
+
+    #[test]
+    fn test_splitlines_brackets_lines_and_count_reports_per_bracket() {
+        use flowd_count::CountComponent;
+        use flowd_splitlines::SplitLinesComponent;
+
+        fn run<C: Component>(inputs: Vec<(&str, Vec<FbpMessage>)>, budget: u32) -> Vec<FbpMessage> {
+            let mut inports = MultiMap::new();
+            for (port, messages) in inputs {
+                let (mut producer, consumer) = rtrb::RingBuffer::<MessageBuf>::new(16);
+                for message in messages {
+                    producer.push(message).unwrap();
+                }
+                // dropping the producer closes the input after these messages
+                inports.insert(String::from(port), consumer.into());
+            }
+            let (output_producer, mut output_consumer) = rtrb::RingBuffer::<MessageBuf>::new(16);
+            let mut outports = MultiMap::new();
+            outports.insert(
+                String::from("OUT"),
+                ProcessEdgeSink::new(output_producer, None, None, None),
+            );
+            let (signal_sender, signal_receiver) = mpsc::sync_channel(PROCESSEDGE_SIGNAL_BUFSIZE);
+            let mut component = C::new(
+                inports,
+                outports,
+                signal_receiver,
+                signal_sender,
+                (Arc::new(|_| {}), Arc::new(|_| {})),
+                None,
+            );
+            let mut context = NodeContext::new(
+                "test_brackets".to_string(),
+                BudgetClass::Normal,
+                Arc::new(AtomicBool::new(false)),
+            );
+            for _ in 0..16 {
+                context.remaining_budget = budget;
+                if matches!(component.process(&mut context), ProcessResult::Finished) {
+                    break;
+                }
+            }
+            std::iter::from_fn(|| output_consumer.pop().ok()).collect()
+        }
+
+        let group = |name: &str| FbpMessage::Control(ControlEvent::BeginGroup(name.to_owned()));
+        let end_group = |name: &str| FbpMessage::Control(ControlEvent::EndGroup(name.to_owned()));
+        let input = vec![
+            group("file"),
+            FbpMessage::from_str("a\nb"),
+            FbpMessage::from_str("c"),
+            end_group("file"),
+        ];
+        // brackets can be turned off for consumers not handling control packets
+        let plain = run::<SplitLinesComponent>(
+            vec![
+                ("CONF", vec![FbpMessage::from_str("brackets=false")]),
+                ("IN", input.clone()),
+            ],
+            3,
+        );
+        assert_eq!(
+            plain,
+            vec![
+                group("file"),
+                FbpMessage::from_str("a"),
+                FbpMessage::from_str("b"),
+                FbpMessage::from_str("c"),
+                end_group("file"),
+            ]
+        );
+        let lines = run::<SplitLinesComponent>(vec![("IN", input)], 3);
+        assert_eq!(
+            lines,
+            vec![
+                group("file"),
+                FbpMessage::begin_bracket(""),
+                FbpMessage::from_str("a"),
+                FbpMessage::from_str("b"),
+                FbpMessage::end_bracket(""),
+                FbpMessage::begin_bracket(""),
+                FbpMessage::from_str("c"),
+                FbpMessage::end_bracket(""),
+                end_group("file"),
+            ]
+        );
+        let mut tracker = BracketTracker::new();
+        let depths: Vec<usize> = lines
+            .iter()
+            .map(|msg| {
+                tracker.observe(msg);
+                tracker.depth()
+            })
+            .collect();
+        assert_eq!(depths, vec![1, 2, 2, 2, 1, 2, 2, 1, 0]);
+        assert_eq!(
+            tracker.observe(&FbpMessage::end_bracket("")),
+            None,
+            "unmatched closing bracket must be ignored"
+        );
+
+        // each bracket is reported before it closes, the stream total at the end
+        let counts = run::<CountComponent>(
+            vec![
+                (
+                    "CONF",
+                    vec![FbpMessage::from_str("?mode=packets&scope=bracket")],
+                ),
+                ("IN", lines.clone()),
+            ],
+            32,
+        );
+        assert_eq!(
+            counts,
+            vec![
+                group("file"),
+                FbpMessage::begin_bracket(""),
+                FbpMessage::from_str("2"),
+                FbpMessage::end_bracket(""),
+                FbpMessage::begin_bracket(""),
+                FbpMessage::from_str("1"),
+                FbpMessage::end_bracket(""),
+                FbpMessage::from_str("3"),
+                end_group("file"),
+                FbpMessage::from_str("3"),
+            ]
+        );
+
+        // without scope, brackets are neither counted nor forwarded
+        let counts = run::<CountComponent>(
+            vec![
+                ("CONF", vec![FbpMessage::from_str("packets")]),
+                ("IN", lines),
+            ],
+            32,
+        );
+        assert_eq!(counts.last(), Some(&FbpMessage::from_str("3")));
+        assert!(counts.iter().all(|msg| msg.as_control().is_none()));
+    }
 }
@@ -52,6 +52,55 @@ impl flowd_component_api::Component for PanicComponent {
     }
 }
 
+/// Counts its process() calls in a shared counter and finishes after a fixed count.
+struct CountingComponent {
+    executions: Arc<AtomicUsize>,
+    remaining: u32,
+}
+
+impl CountingComponent {
+    fn new(executions: Arc<AtomicUsize>, remaining: u32) -> Self {
+        CountingComponent {
+            executions,
+            remaining,
+        }
+    }
+}
+
+impl flowd_component_api::Component for CountingComponent {
+    fn new(
+        _inports: flowd_component_api::ProcessInports,
+        _outports: flowd_component_api::ProcessOutports,
+        _signals_in: flowd_component_api::ProcessSignalSource,
+        _signals_out: flowd_component_api::ProcessSignalSink,
+        _graph_inout: flowd_component_api::GraphInportOutportHandle,
+        _scheduler_waker: Option<flowd_component_api::SchedulerWaker>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        CountingComponent::new(Arc::new(AtomicUsize::new(0)), 1)
+    }
+
+    fn process(
+        &mut self,
+        context: &mut flowd_component_api::NodeContext,
+    ) -> flowd_component_api::ProcessResult {
+        self.executions.fetch_add(1, Ordering::SeqCst);
+        if self.remaining == 0 {
+            return flowd_component_api::ProcessResult::Finished;
+        }
+        self.remaining -= 1;
+        // keep being scheduled through the ready flag, not only via DidWork
+        context.signal_ready();
+        flowd_component_api::ProcessResult::DidWork(1)
+    }
+
+    fn get_metadata() -> flowd_component_api::ComponentComponentPayload {
+        flowd_component_api::ComponentComponentPayload::default()
+    }
+}
+
 impl MockComponent {
     fn new(should_work: bool) -> Self {
         MockComponent {
@@ -289,4 +338,106 @@ mod tests {
             .join()
             .expect("second scheduler should stop cleanly");
     }
+
+    #[test]
+    fn test_multi_worker_scheduler_runs_every_node_to_completion() {
+        // process() never runs concurrently with itself by construction: the worker takes the
+        // component out of the scheduler while executing it. What can go wrong is losing or
+        // duplicating executions while wakeups for an executing node arrive from outside.
+        const WORKERS: usize = 4;
+        const NODES: usize = 8;
+        const RUNS: u32 = 200;
+        let scheduler = Arc::new(Scheduler::with_workers(WORKERS));
+        assert_eq!(scheduler.worker_count(), WORKERS);
+
+        let node_ids: Vec<String> = (0..NODES).map(|i| format!("counter_{}", i)).collect();
+        let mut executions = Vec::new();
+        for node_id in &node_ids {
+            let counter = Arc::new(AtomicUsize::new(0));
+            scheduler.add_node(node_id.clone(), flowd_component_api::BudgetClass::Heavy);
+            scheduler.add_component(
+                Box::new(CountingComponent::new(Arc::clone(&counter), RUNS)),
+                node_id.clone(),
+            );
+            executions.push(counter);
+        }
+        // hammer wakeups from outside while workers execute
+        for node_id in &node_ids {
+            scheduler.signal_ready(node_id);
+        }
+        let waker_scheduler = Arc::clone(&scheduler);
+        let waker_nodes = node_ids.clone();
+        let waker = std::thread::spawn(move || {
+            for _ in 0..200 {
+                for node_id in &waker_nodes {
+                    waker_scheduler.signal_ready(node_id);
+                }
+                std::thread::sleep(Duration::from_micros(100));
+            }
+        });
+
+        let runner = Arc::clone(&scheduler);
+        let handle = std::thread::spawn(move || runner.run());
+        waker.join().expect("waker thread should join");
+
+        // all nodes finish on their own, which lets every worker exit
+        let started = std::time::Instant::now();
+        while !handle.is_finished() && started.elapsed() < Duration::from_secs(10) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(
+            handle.is_finished(),
+            "multi-worker scheduler should exit after all nodes finished"
+        );
+        handle.join().expect("scheduler should stop cleanly");
+
+        let metrics = scheduler.metrics_snapshot();
+        for (node_id, counter) in node_ids.iter().zip(&executions) {
+            assert_eq!(
+                counter.load(Ordering::SeqCst),
+                RUNS as usize + 1,
+                "node {} should run until it finished, and not after",
+                node_id
+            );
+            // the final call returning Finished did no work and is not counted as execution
+            assert_eq!(
+                metrics.executions_per_node.get(node_id).copied(),
+                Some(RUNS as u64),
+                "scheduler should account every execution of node {}",
+                node_id
+            );
+        }
+        assert_eq!(metrics.workers.len(), WORKERS);
+        let total_executions: u64 = metrics.workers.iter().map(|w| w.executions).sum();
+        assert_eq!(total_executions, NODES as u64 * RUNS as u64);
+        for worker in &metrics.workers {
+            assert!((0.0..=1.0).contains(&worker.utilisation));
+        }
+    }
+
+    #[test]
+    fn test_multi_worker_scheduler_stops_all_workers() {
+        let scheduler = Arc::new(Scheduler::with_workers(3));
+        let node_id = "busy_node".to_string();
+        scheduler.add_node(node_id.clone(), flowd_component_api::BudgetClass::Normal);
+        scheduler.add_component(Box::new(MockComponent::new(true)), node_id.clone());
+        scheduler.signal_ready(&node_id);
+
+        let runner = Arc::clone(&scheduler);
+        let handle = std::thread::spawn(move || runner.run());
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!handle.is_finished(), "scheduler should keep running");
+
+        scheduler.stop();
+        handle.join().expect("all scheduler workers should join");
+        assert!(
+            scheduler
+                .metrics_snapshot()
+                .executions_per_node
+                .get(&node_id)
+                .copied()
+                .unwrap_or_default()
+                > 0
+        );
+    }
 }